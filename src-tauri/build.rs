@@ -1,3 +1,10 @@
 fn main() {
-    tauri_build::build();
+    // A headless build (no `desktop` feature) leaves out the window plugins
+    // the capabilities grant, and has no window to grant anything to.
+    let attributes = if std::env::var_os("CARGO_FEATURE_DESKTOP").is_some() {
+        tauri_build::Attributes::new()
+    } else {
+        tauri_build::Attributes::new().capabilities_path_pattern("./capabilities/headless/*")
+    };
+    tauri_build::try_build(attributes).expect("failed to run tauri-build");
 }
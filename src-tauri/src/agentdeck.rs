@@ -1,15 +1,18 @@
+use crate::backend::{self, CreateSessionParams};
 use crate::command::{expand_tilde, new_command};
 use crate::orca_db::OrcaDb;
+use crate::ssh::{self, SshHost};
 use rusqlite::Connection;
 use std::path::{Path, PathBuf};
 use tauri::{Emitter, State};
 
 use crate::claude_logs::{self, AttentionStatus};
-use crate::models::{AttentionCounts, Group, Session, VersionCheck};
+use crate::models::{AttentionCounts, Group, Session, ToolProfile, VersionCheck};
+use crate::webhooks;
 
 const SUPPORTED_VERSION: &str = "0.19.19";
 
-fn db_path() -> Result<PathBuf, String> {
+pub fn db_path() -> Result<PathBuf, String> {
     let home = dirs::home_dir().ok_or("Could not find home directory")?;
     Ok(home.join(".agent-deck/profiles/default/state.db"))
 }
@@ -53,6 +56,11 @@ pub fn check_agent_deck_version() -> Result<VersionCheck, String> {
 
 #[tauri::command]
 pub fn get_groups(orca_db: State<'_, OrcaDb>) -> Result<Vec<Group>, String> {
+    get_groups_impl(&orca_db)
+}
+
+/// Read groups from the agent-deck DB and merge in Orca's group settings.
+pub fn get_groups_impl(orca_db: &OrcaDb) -> Result<Vec<Group>, String> {
     log::debug!("get_groups");
     let conn = open_db_readonly()?;
 
@@ -153,6 +161,14 @@ pub fn get_groups(orca_db: State<'_, OrcaDb>) -> Result<Vec<Group>, String> {
     Ok(groups)
 }
 
+/// Find a group by path or name.
+pub fn find_group(orca_db: &OrcaDb, query: &str) -> Result<Group, String> {
+    get_groups_impl(orca_db)?
+        .into_iter()
+        .find(|g| g.path == query || g.name == query)
+        .ok_or_else(|| format!("Group not found: {query}"))
+}
+
 #[tauri::command]
 #[allow(clippy::too_many_arguments)]
 pub fn update_group_settings(
@@ -192,9 +208,22 @@ pub fn get_sessions(
     group_path: Option<String>,
 ) -> Result<Vec<Session>, String> {
     log::debug!("get_sessions: group_path={group_path:?}");
+    let sessions = match (ssh::host_for(&orca_db, group_path.as_deref())?, &group_path) {
+        (Some(host), Some(group_path)) => backend::ssh::list_sessions(&host, &orca_db, group_path)?,
+        _ => list_sessions_with_prompts(&orca_db, group_path.as_deref())?,
+    };
+    log::debug!("get_sessions: found {} sessions", sessions.len());
+    Ok(sessions)
+}
+
+/// Read sessions from the agent-deck DB and merge in prompts from Orca's DB.
+pub fn list_sessions_with_prompts(
+    orca_db: &OrcaDb,
+    group_path: Option<&str>,
+) -> Result<Vec<Session>, String> {
     let conn = open_db_readonly()?;
 
-    let mut sessions = query_sessions(&conn, group_path.as_deref())?;
+    let mut sessions = query_sessions(&conn, group_path)?;
 
     let prompts = orca_db.get_all_prompts().unwrap_or_default();
     for session in &mut sessions {
@@ -203,18 +232,47 @@ pub fn get_sessions(
         }
     }
 
-    log::debug!("get_sessions: found {} sessions", sessions.len());
     Ok(sessions)
 }
 
+/// Look up a single session by ID from the agent-deck DB.
+pub fn get_session_by_id(orca_db: &OrcaDb, session_id: &str) -> Result<Session, String> {
+    let conn = open_db_readonly()?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, title, project_path, group_path, sort_order, status, tmux_session, \
+             created_at, last_accessed, worktree_path, worktree_repo, worktree_branch, tool_data, \
+             tool FROM instances WHERE id = ?1",
+        )
+        .map_err(|e| e.to_string())?;
+    let mut sessions = stmt
+        .query_map([session_id], map_session_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    fix_last_accessed(&mut sessions);
+
+    let mut session = sessions
+        .pop()
+        .ok_or_else(|| format!("Session not found: {session_id}"))?;
+    session.prompt = orca_db
+        .get_all_prompts()
+        .unwrap_or_default()
+        .remove(session_id);
+    Ok(session)
+}
+
+/// The `instances` columns read into a [`Session`], in [`map_session_row`]'s order.
+pub(crate) const SESSION_COLUMNS: &str =
+    "id, title, project_path, group_path, sort_order, status, tmux_session, \
+     created_at, last_accessed, worktree_path, worktree_repo, worktree_branch, tool_data, tool";
+
 fn query_sessions(conn: &Connection, group_path: Option<&str>) -> Result<Vec<Session>, String> {
-    let columns = "id, title, project_path, group_path, sort_order, status, tmux_session, \
-                    created_at, last_accessed, worktree_path, worktree_repo, worktree_branch, tool_data";
     let sql = match group_path {
-        Some(_) => {
-            format!("SELECT {columns} FROM instances WHERE group_path = ?1 ORDER BY sort_order")
-        }
-        None => format!("SELECT {columns} FROM instances ORDER BY group_path, sort_order"),
+        Some(_) => format!(
+            "SELECT {SESSION_COLUMNS} FROM instances WHERE group_path = ?1 ORDER BY sort_order"
+        ),
+        None => format!("SELECT {SESSION_COLUMNS} FROM instances ORDER BY group_path, sort_order"),
     };
     let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
     let rows = match group_path {
@@ -414,7 +472,7 @@ fn run_agent_deck_add(args: &[String]) -> Result<String, String> {
 }
 
 /// Parse a session ID from agent-deck output (JSON or fallback text format).
-fn parse_session_id(stdout: &str) -> Result<String, String> {
+pub(crate) fn parse_session_id(stdout: &str) -> Result<String, String> {
     // Try JSON output first (normal creation).
     // agent-deck may prefix the JSON with text like "Created worktree at: ...",
     // so find the first '{' and parse from there.
@@ -463,10 +521,30 @@ fn start_agent_deck_session(session_id: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Stop a running session via `agent-deck session stop`.
+pub(crate) fn stop_agent_deck_session(session_id: &str) -> Result<(), String> {
+    log::info!("agent-deck session stop {session_id}");
+    let output = new_command("agent-deck")
+        .args(["session", "stop", session_id])
+        .output()
+        .map_err(|e| format!("Failed to stop session: {e}"))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        log::error!(
+            "agent-deck session stop failed (exit {}): {}",
+            output.status,
+            stderr.trim()
+        );
+        return Err(format!("agent-deck session stop failed: {}", stderr.trim()));
+    }
+    Ok(())
+}
+
 #[allow(clippy::too_many_arguments)]
 #[tauri::command]
 pub fn create_session(
-    app: tauri::AppHandle,
+    app: crate::AppHandle,
     orca_db: State<'_, OrcaDb>,
     creation_id: String,
     project_path: String,
@@ -480,29 +558,41 @@ pub fn create_session(
     components: Option<Vec<String>>,
 ) -> Result<(), String> {
     let orca_db = orca_db.inner().clone();
+    let host = ssh::host_for_group(&orca_db, &group)?;
     // Spawn the work to a background thread and return immediately
     std::thread::spawn(move || {
-        match create_session_impl(
-            project_path,
-            group,
-            title,
-            tool,
-            worktree_branch,
-            new_branch,
-            start,
-            prompt.clone(),
-            components,
-            &orca_db,
-        ) {
+        let created = match host {
+            Some(host) => backend::ssh::create_session(
+                &host,
+                &orca_db,
+                CreateSessionParams {
+                    group_path: group,
+                    project_path,
+                    title,
+                    tool,
+                    worktree_branch,
+                    new_branch,
+                    start,
+                    prompt: prompt.clone(),
+                    components,
+                },
+            ),
+            None => create_session_impl(
+                project_path,
+                group,
+                title,
+                tool,
+                worktree_branch,
+                new_branch,
+                start,
+                prompt.clone(),
+                components,
+                &orca_db,
+            ),
+        };
+        match created {
             Ok(session_id) => {
-                // Store prompt in Orca's DB
-                if let Some(ref prompt_text) = prompt {
-                    if !prompt_text.trim().is_empty() {
-                        if let Err(e) = orca_db.store_prompt(&session_id, prompt_text) {
-                            log::error!("Failed to store prompt for {session_id}: {e}");
-                        }
-                    }
-                }
+                store_session_prompt(&orca_db, &session_id, prompt.as_deref());
                 let _ = app.emit(
                     "session-created",
                     serde_json::json!({
@@ -525,8 +615,19 @@ pub fn create_session(
     Ok(())
 }
 
+/// Store the creation prompt for a session in Orca's DB (best-effort).
+pub fn store_session_prompt(orca_db: &OrcaDb, session_id: &str, prompt: Option<&str>) {
+    if let Some(prompt_text) = prompt {
+        if !prompt_text.trim().is_empty() {
+            if let Err(e) = orca_db.store_prompt(session_id, prompt_text) {
+                log::error!("Failed to store prompt for {session_id}: {e}");
+            }
+        }
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
-fn create_session_impl(
+pub fn create_session_impl(
     project_path: String,
     group: String,
     title: String,
@@ -581,6 +682,8 @@ fn create_session_impl(
         effective_path = wt_str.clone();
     }
 
+    let is_claude = tool_name == "claude";
+
     // Build agent-deck add arguments
     let mut args = vec![
         "add".to_string(),
@@ -613,6 +716,18 @@ fn create_session_impl(
         update_session_worktree(session_id.clone(), wt_path, wt_repo, wt_branch)?;
     }
 
+    // Install attention hooks before Claude starts so its first events are seen.
+    if is_claude {
+        match get_session_by_id(orca_db, &session_id) {
+            Ok(session) => {
+                if let Err(e) = crate::hooks::install_hooks(&session.project_path) {
+                    log::warn!("Failed to install attention hooks for {session_id}: {e}");
+                }
+            }
+            Err(e) => log::warn!("Failed to look up new session {session_id}: {e}"),
+        }
+    }
+
     // Optionally start the session immediately
     if start.unwrap_or(false) {
         start_agent_deck_session(&session_id)?;
@@ -623,9 +738,9 @@ fn create_session_impl(
             if !prompt_text.trim().is_empty() {
                 let sid = session_id.clone();
                 let pt = prompt_text.clone();
-                let tool_name = tool.clone().unwrap_or_else(|| "claude".to_string());
+                let profile = crate::tool_profile::profile_for(tool.as_deref().unwrap_or("claude"));
                 std::thread::spawn(move || {
-                    if let Err(e) = send_prompt_to_session(&sid, &pt, &tool_name) {
+                    if let Err(e) = send_prompt_to_session(&sid, &pt, &profile) {
                         log::error!("Failed to send prompt to session {sid}: {e}");
                     }
                 });
@@ -637,16 +752,62 @@ fn create_session_impl(
 }
 
 #[tauri::command]
-pub fn restart_session(session_id: String) -> Result<(), String> {
-    start_agent_deck_session(&session_id)
+pub fn restart_session(
+    orca_db: State<'_, OrcaDb>,
+    session_id: String,
+    group_path: Option<String>,
+) -> Result<(), String> {
+    match ssh::host_for(&orca_db, group_path.as_deref())? {
+        Some(host) => host
+            .agent_deck(&["session", "start", &session_id])
+            .map(|_| ()),
+        None => start_agent_deck_session(&session_id),
+    }
 }
 
 #[tauri::command]
 pub fn remove_session(orca_db: State<'_, OrcaDb>, session_id: String) -> Result<(), String> {
+    remove_session_impl(&orca_db, &session_id)
+}
+
+/// Project path of a session, read before removing it so its attention
+/// hooks can be cleaned up afterwards.
+fn session_project_path(session_id: &str) -> Option<String> {
+    let conn = open_db_readonly().ok()?;
+    conn.query_row(
+        "SELECT project_path FROM instances WHERE id = ?1",
+        [session_id],
+        |row| row.get(0),
+    )
+    .ok()
+}
+
+/// Remove Orca's attention hooks from a project path once no remaining
+/// session uses it.
+fn remove_unused_hooks(conn: &Connection, project_path: &str) {
+    let in_use = conn
+        .query_row(
+            "SELECT COUNT(*) FROM instances WHERE project_path = ?1",
+            [project_path],
+            |row| row.get::<_, i64>(0),
+        )
+        .map_or(true, |count| count > 0);
+    if in_use {
+        return;
+    }
+    if let Err(e) = crate::hooks::remove_hooks(project_path) {
+        log::warn!("Failed to remove attention hooks from {project_path}: {e}");
+    }
+}
+
+/// Remove a session via agent-deck, falling back to direct DB deletion.
+pub fn remove_session_impl(orca_db: &OrcaDb, session_id: &str) -> Result<(), String> {
+    let project_path = session_project_path(session_id);
+
     // Try agent-deck remove first
     log::info!("agent-deck remove {session_id}");
     let remove_result = new_command("agent-deck")
-        .args(["remove", &session_id])
+        .args(["remove", session_id])
         .output();
     match &remove_result {
         Ok(output) if output.status.success() => {
@@ -669,11 +830,14 @@ pub fn remove_session(orca_db: State<'_, OrcaDb>, session_id: String) -> Result<
     // DB deletion if the session still exists.
     let conn = open_db()?;
 
-    conn.execute("DELETE FROM instances WHERE id = ?1", [&session_id])
+    conn.execute("DELETE FROM instances WHERE id = ?1", [session_id])
         .map_err(|e| format!("Failed to delete session: {e}"))?;
+    if let Some(ref path) = project_path {
+        remove_unused_hooks(&conn, path);
+    }
 
     // Clean up Orca's own data for this session
-    if let Err(e) = orca_db.delete_session_data(&session_id) {
+    if let Err(e) = orca_db.delete_session_data(session_id) {
         log::error!("Failed to clean up Orca session data for {session_id}: {e}");
     }
 
@@ -682,14 +846,37 @@ pub fn remove_session(orca_db: State<'_, OrcaDb>, session_id: String) -> Result<
 
 #[tauri::command]
 pub fn remove_session_background(
-    app: tauri::AppHandle,
+    app: crate::AppHandle,
     orca_db: State<'_, OrcaDb>,
     session_id: String,
     repo_path: Option<String>,
     worktree_path: Option<String>,
+    group_path: Option<String>,
 ) -> Result<(), String> {
     let orca_db = orca_db.inner().clone();
+    if let Some(host) = ssh::host_for(&orca_db, group_path.as_deref())? {
+        // The host's agent-deck removes the session and its worktree.
+        std::thread::spawn(move || {
+            match backend::ssh::delete_session(&host, &orca_db, &session_id) {
+                Ok(()) => {
+                    let _ = app.emit(
+                        "session-removed",
+                        serde_json::json!({ "session_id": session_id }),
+                    );
+                }
+                Err(e) => {
+                    let _ = app.emit(
+                        "session-removal-failed",
+                        serde_json::json!({ "session_id": session_id, "error": e }),
+                    );
+                }
+            }
+        });
+        return Ok(());
+    }
     std::thread::spawn(move || {
+        let project_path = session_project_path(&session_id);
+
         // Best-effort worktree removal
         if let (Some(ref repo), Some(ref wt)) = (&repo_path, &worktree_path) {
             if let Err(e) = crate::git::remove_worktree_sync(repo, wt) {
@@ -737,6 +924,9 @@ pub fn remove_session_background(
             );
             return;
         }
+        if let Some(ref path) = project_path {
+            remove_unused_hooks(&conn, path);
+        }
 
         if let Err(e) = orca_db.delete_session_data(&session_id) {
             log::error!("Failed to clean up Orca session data for {session_id}: {e}");
@@ -751,14 +941,20 @@ pub fn remove_session_background(
 }
 
 #[tauri::command]
-pub fn get_attention_counts() -> Result<AttentionCounts, String> {
+pub fn get_attention_counts(orca_db: State<'_, OrcaDb>) -> Result<AttentionCounts, String> {
+    get_attention_counts_impl(&orca_db)
+}
+
+pub fn get_attention_counts_impl(orca_db: &OrcaDb) -> Result<AttentionCounts, String> {
     let conn = open_db_readonly()?;
+    let thresholds = orca_db.get_all_attention_thresholds().unwrap_or_default();
 
     // Fetch candidate sessions and refine with JSONL analysis.
     // Include sessions with a live tmux session — their DB status may be stale.
     let mut stmt = conn
         .prepare(
-            "SELECT id, project_path, group_path, status, tool_data, tmux_session FROM instances \
+            "SELECT id, project_path, group_path, status, tool_data, tmux_session, tool \
+             FROM instances \
              WHERE status IN ('waiting', 'error') \
              OR (tmux_session IS NOT NULL AND tmux_session != '')",
         )
@@ -770,35 +966,44 @@ pub fn get_attention_counts() -> Result<AttentionCounts, String> {
     let rows = stmt
         .query_map([], |row| {
             let tool_data_str: String = row.get(4)?;
-            let claude_session_id = serde_json::from_str::<serde_json::Value>(&tool_data_str)
+            let tool: String = row.get::<_, Option<String>>(6)?.unwrap_or_default();
+            let id_key = if tool == "opencode" {
+                "opencode_session_id"
+            } else {
+                "claude_session_id"
+            };
+            let tool_session_id = serde_json::from_str::<serde_json::Value>(&tool_data_str)
                 .ok()
-                .and_then(|v| v.get("claude_session_id")?.as_str().map(String::from));
+                .and_then(|v| v.get(id_key)?.as_str().map(String::from));
             Ok((
                 row.get::<_, String>(1)?, // project_path
                 row.get::<_, String>(2)?, // group_path
                 row.get::<_, String>(3)?, // status
-                claude_session_id,
+                tool_session_id,
                 row.get::<_, Option<String>>(5)?, // tmux_session
+                tool,
             ))
         })
         .map_err(|e| e.to_string())?;
 
     let mut candidate_count = 0u32;
     for row in rows {
-        let (project_path, group_path, status, claude_session_id, tmux_session) =
+        let (project_path, group_path, status, tool_session_id, tmux_session, tool) =
             row.map_err(|e| e.to_string())?;
         candidate_count += 1;
 
         let attention = claude_logs::compute_attention(
             &project_path,
-            claude_session_id.as_deref(),
+            tool_session_id.as_deref(),
             &status,
             tmux_session.as_deref(),
+            &tool,
+            &thresholds.get(&group_path).cloned().unwrap_or_default(),
         );
 
         let refined_status = match attention {
-            AttentionStatus::NeedsInput => "waiting",
-            AttentionStatus::Error => "error",
+            AttentionStatus::AwaitingPermission | AttentionStatus::NeedsInput => "waiting",
+            AttentionStatus::RateLimited | AttentionStatus::Error => "error",
             _ => continue, // skip non-actionable sessions
         };
 
@@ -818,14 +1023,29 @@ pub fn get_attention_counts() -> Result<AttentionCounts, String> {
 
 #[tauri::command]
 pub fn get_attention_sessions(orca_db: State<'_, OrcaDb>) -> Result<Vec<Session>, String> {
+    get_attention_sessions_impl(&orca_db)
+}
+
+/// Sessions that need action, most urgent first.
+pub fn get_attention_sessions_impl(orca_db: &OrcaDb) -> Result<Vec<Session>, String> {
+    Ok(get_attention_sessions_with_status(orca_db)?
+        .into_iter()
+        .map(|(_, s)| s)
+        .collect())
+}
+
+/// Sessions that need action with their attention status, most urgent first.
+pub fn get_attention_sessions_with_status(
+    orca_db: &OrcaDb,
+) -> Result<Vec<(AttentionStatus, Session)>, String> {
     let conn = open_db_readonly()?;
 
     // Include sessions with a live tmux session — their DB status may be stale.
     let mut stmt = conn
         .prepare(
             "SELECT id, title, project_path, group_path, sort_order, status, tmux_session, \
-             created_at, last_accessed, worktree_path, worktree_repo, worktree_branch, tool_data \
-             FROM instances \
+             created_at, last_accessed, worktree_path, worktree_repo, worktree_branch, tool_data, \
+             tool FROM instances \
              WHERE status IN ('waiting', 'error') \
              OR (tmux_session IS NOT NULL AND tmux_session != '') \
              ORDER BY group_path, sort_order",
@@ -840,11 +1060,13 @@ pub fn get_attention_sessions(orca_db: State<'_, OrcaDb>) -> Result<Vec<Session>
     fix_last_accessed(&mut candidates);
 
     let prompts = orca_db.get_all_prompts().unwrap_or_default();
+    let thresholds = orca_db.get_all_attention_thresholds().unwrap_or_default();
 
-    // Refine using JSONL/tmux analysis — only keep sessions that truly need attention
-    let result = candidates
+    // Refine using JSONL/tmux analysis — only keep sessions that truly need
+    // attention, most urgent first (the sort is stable, so group order holds)
+    let mut result: Vec<(AttentionStatus, Session)> = candidates
         .into_iter()
-        .filter(|s| {
+        .filter_map(|mut s| {
             let tmux = if s.tmux_session.is_empty() {
                 None
             } else {
@@ -852,22 +1074,22 @@ pub fn get_attention_sessions(orca_db: State<'_, OrcaDb>) -> Result<Vec<Session>
             };
             let attention = claude_logs::compute_attention(
                 &s.project_path,
-                s.claude_session_id.as_deref(),
+                s.tool_session_id(),
                 &s.status,
                 tmux,
+                &s.tool,
+                &thresholds.get(&s.group_path).cloned().unwrap_or_default(),
             );
-            matches!(
-                attention,
-                AttentionStatus::NeedsInput | AttentionStatus::Error
-            )
-        })
-        .map(|mut s| {
+            if !attention.needs_action() {
+                return None;
+            }
             if let Some(prompt) = prompts.get(&s.id) {
                 s.prompt = Some(prompt.clone());
             }
-            s
+            Some((attention, s))
         })
         .collect();
+    result.sort_by_key(|(attention, _)| attention.priority());
 
     Ok(result)
 }
@@ -1029,22 +1251,7 @@ pub fn rename_session(session_id: String, new_title: String) -> Result<(), Strin
 }
 
 fn map_session_row(row: &rusqlite::Row) -> rusqlite::Result<Session> {
-    let tool_data_str: String = row.get(12)?;
-    let tool_data = serde_json::from_str::<serde_json::Value>(&tool_data_str).ok();
-    let claude_session_id = tool_data
-        .as_ref()
-        .and_then(|v| v.get("claude_session_id")?.as_str().map(String::from));
-    let pr_url = tool_data
-        .as_ref()
-        .and_then(|v| v.get("pr_url")?.as_str().map(String::from));
-    let pr_number = tool_data
-        .as_ref()
-        .and_then(|v| v.get("pr_number")?.as_u64());
-    let pr_state = tool_data
-        .as_ref()
-        .and_then(|v| v.get("pr_state")?.as_str().map(String::from));
-
-    Ok(Session {
+    let mut session = Session {
         id: row.get(0)?,
         title: row.get(1)?,
         project_path: row.get(2)?,
@@ -1057,12 +1264,60 @@ fn map_session_row(row: &rusqlite::Row) -> rusqlite::Result<Session> {
         worktree_path: row.get(9)?,
         worktree_repo: row.get(10)?,
         worktree_branch: row.get(11)?,
-        claude_session_id,
+        tool: row.get::<_, Option<String>>(13)?.unwrap_or_default(),
+        claude_session_id: None,
+        opencode_session_id: None,
         prompt: None, // populated by caller from Orca DB
-        pr_url,
-        pr_number,
-        pr_state,
-    })
+        pr_url: None,
+        pr_number: None,
+        pr_state: None,
+    };
+    apply_tool_data(&mut session, &row.get::<_, String>(12)?);
+    Ok(session)
+}
+
+/// A session from a row of `sqlite3 -json` output for [`SESSION_COLUMNS`],
+/// as read from another machine's agent-deck DB.
+pub(crate) fn session_from_json(row: &serde_json::Value) -> Session {
+    let text = |key: &str| row[key].as_str().unwrap_or_default().to_string();
+    let mut session = Session {
+        id: text("id"),
+        title: text("title"),
+        project_path: text("project_path"),
+        group_path: text("group_path"),
+        sort_order: row["sort_order"].as_i64().unwrap_or_default() as i32,
+        status: text("status"),
+        tmux_session: text("tmux_session"),
+        created_at: row["created_at"].as_i64().unwrap_or_default(),
+        last_accessed: row["last_accessed"].as_i64().unwrap_or_default(),
+        worktree_path: text("worktree_path"),
+        worktree_repo: text("worktree_repo"),
+        worktree_branch: text("worktree_branch"),
+        tool: text("tool"),
+        claude_session_id: None,
+        opencode_session_id: None,
+        prompt: None,
+        pr_url: None,
+        pr_number: None,
+        pr_state: None,
+    };
+    apply_tool_data(&mut session, &text("tool_data"));
+    session
+}
+
+/// Fill in the fields agent-deck keeps in the `tool_data` JSON column.
+fn apply_tool_data(session: &mut Session, tool_data: &str) {
+    let Ok(tool_data) = serde_json::from_str::<serde_json::Value>(tool_data) else {
+        return;
+    };
+    let text = |key: &str| tool_data.get(key)?.as_str().map(String::from);
+    session.claude_session_id = text("claude_session_id");
+    session.opencode_session_id = text("opencode_session_id");
+    session.pr_url = text("pr_url");
+    session.pr_number = tool_data
+        .get("pr_number")
+        .and_then(serde_json::Value::as_u64);
+    session.pr_state = text("pr_state");
 }
 
 /// Fix up `last_accessed` for sessions where agent-deck stores a bogus value
@@ -1086,9 +1341,11 @@ fn fix_last_accessed(sessions: &mut [Session]) {
     }
 }
 
-/// Store PR info in the session's tool_data JSON.
+/// Store PR info in the session's tool_data JSON, firing the `pr.created`
+/// and `pr.merged` webhooks when the PR is new or newly merged.
 #[tauri::command]
 pub fn store_session_pr_info(
+    orca_db: State<'_, OrcaDb>,
     session_id: String,
     pr_url: String,
     pr_number: u64,
@@ -1097,17 +1354,24 @@ pub fn store_session_pr_info(
     log::info!("store_session_pr_info: session_id={session_id}, pr_number={pr_number}, pr_state={pr_state}");
     let conn = open_db()?;
 
-    let current: String = conn
+    let (current, title, group_path): (String, String, String) = conn
         .query_row(
-            "SELECT tool_data FROM instances WHERE id = ?1",
+            "SELECT tool_data, title, group_path FROM instances WHERE id = ?1",
             [&session_id],
-            |row| row.get(0),
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
         )
         .map_err(|e| format!("Failed to read tool_data for {session_id}: {e}"))?;
 
     let mut data: serde_json::Value =
         serde_json::from_str(&current).unwrap_or(serde_json::json!({}));
-    data["pr_url"] = serde_json::Value::String(pr_url);
+    let event = if data["pr_number"].as_u64() != Some(pr_number) {
+        Some("pr.created")
+    } else if pr_state == "MERGED" && data["pr_state"] != "MERGED" {
+        Some("pr.merged")
+    } else {
+        None
+    };
+    data["pr_url"] = serde_json::Value::String(pr_url.clone());
     data["pr_number"] = serde_json::json!(pr_number);
     data["pr_state"] = serde_json::Value::String(pr_state);
 
@@ -1118,11 +1382,21 @@ pub fn store_session_pr_info(
     )
     .map_err(|e| format!("Failed to update tool_data for {session_id}: {e}"))?;
 
+    if let Some(event) = event {
+        webhooks::dispatch(
+            &orca_db,
+            webhooks::WebhookEvent {
+                pr_url: Some(pr_url),
+                pr_number: Some(pr_number),
+                ..webhooks::WebhookEvent::new(event, &session_id, &title, &group_path)
+            },
+        );
+    }
     Ok(())
 }
 
 /// Look up the tmux session name for a given session ID from the agent-deck DB.
-fn get_tmux_session_name(session_id: &str) -> Result<String, String> {
+pub fn get_tmux_session_name(session_id: &str) -> Result<String, String> {
     let conn = open_db_readonly()?;
 
     conn.query_row(
@@ -1133,56 +1407,48 @@ fn get_tmux_session_name(session_id: &str) -> Result<String, String> {
     .map_err(|e| format!("Failed to get tmux session name for {session_id}: {e}"))
 }
 
-/// Check if Opencode has fully started by looking for its prompt indicator
-fn is_opencode_ready(content: &str) -> bool {
-    // Opencode's TUI shows "tab agents" in the UI when it's fully loaded
-    // This is a reliable indicator that Opencode is ready to accept input
-    content.contains("tab agents")
-}
-
 /// Send a prompt to a session's tmux session. Waits for the tmux session
 /// to exist and for the AI to start rendering before sending.
 /// Runs on a background thread — must not block the UI.
-fn send_prompt_to_session(session_id: &str, prompt: &str, tool: &str) -> Result<(), String> {
+pub fn send_prompt_to_session(
+    session_id: &str,
+    prompt: &str,
+    profile: &ToolProfile,
+) -> Result<(), String> {
     let tmux_name = get_tmux_session_name(session_id)?;
     log::info!(
-        "Sending prompt to tmux session '{tmux_name}' for session {session_id} (tool={tool})"
+        "Sending prompt to tmux session '{tmux_name}' for session {session_id} (tool={})",
+        profile.name
     );
+    send_prompt_to_tmux(None, &tmux_name, prompt, profile)
+}
 
+/// Wait for the tool in a tmux session (local, or on `host`) to be ready,
+/// then paste and submit the prompt. Blocks for up to 30s.
+pub(crate) fn send_prompt_to_tmux(
+    host: Option<&SshHost>,
+    tmux_name: &str,
+    prompt: &str,
+    profile: &ToolProfile,
+) -> Result<(), String> {
+    let tool = &profile.name;
     let max_attempts = 100;
     let delay = std::time::Duration::from_millis(300);
     let mut session_ready = false;
 
-    // Wait for the tmux session to exist and for the tool's input prompt to
-    // be ready.  For Claude we look for the actual input prompt (❯ / >);
-    // for Opencode we look for its own prompt indicator.
+    // Wait for the tmux session to exist and for the tool's profile to
+    // report it ready (e.g. Claude's ❯ prompt, OpenCode's "tab agents").
     for attempt in 0..max_attempts {
-        let capture = new_command("tmux")
-            .args(["capture-pane", "-t", &tmux_name, "-p"])
-            .output();
-        match capture {
-            Ok(output) if output.status.success() => {
-                let content = String::from_utf8_lossy(&output.stdout);
-
-                if tool == "opencode" && is_opencode_ready(&content) {
-                    log::debug!(
-                        "Opencode ready in tmux session '{tmux_name}' after {} attempts",
-                        attempt + 1
-                    );
-                    session_ready = true;
-                    break;
-                }
-
-                if tool != "opencode" && crate::tmux::pane_shows_input_prompt(&content) {
-                    log::debug!(
-                        "{tool} input prompt ready in tmux session '{tmux_name}' after {} attempts",
-                        attempt + 1
-                    );
-                    session_ready = true;
-                    break;
-                }
+        // None while the tmux session doesn't exist yet
+        if let Some(content) = crate::tmux::capture_pane(host, tmux_name, None) {
+            if profile.is_ready(&content) {
+                log::debug!(
+                    "{tool} ready in tmux session '{tmux_name}' after {} attempts",
+                    attempt + 1
+                );
+                session_ready = true;
+                break;
             }
-            _ => {} // tmux session doesn't exist yet or command failed
         }
         if attempt < max_attempts - 1 {
             std::thread::sleep(delay);
@@ -1198,7 +1464,7 @@ fn send_prompt_to_session(session_id: &str, prompt: &str, tool: &str) -> Result<
 
     // Use bracketed paste + Enter — same mechanism as paste_to_tmux_pane
     // with submit=true, which is known to work reliably.
-    crate::tmux::paste_and_submit(&tmux_name, prompt)?;
+    crate::tmux::paste_and_submit_on(host, tmux_name, prompt)?;
 
     log::debug!("Prompt sent successfully to tmux session '{tmux_name}'");
     Ok(())
@@ -1260,6 +1526,22 @@ pub fn set_dismissed(
 mod tests {
     use super::*;
 
+    #[test]
+    fn session_from_sqlite_json_row() {
+        let row = serde_json::json!({
+            "id": "abc", "title": "t", "project_path": "/r", "group_path": "/r",
+            "sort_order": 2, "status": "running", "tmux_session": "agentdeck_t",
+            "created_at": 10, "last_accessed": 20, "worktree_path": "",
+            "worktree_repo": "", "worktree_branch": "", "tool": "claude",
+            "tool_data": r#"{"claude_session_id":"c-1"}"#
+        });
+        let session = session_from_json(&row);
+        assert_eq!(session.id, "abc");
+        assert_eq!(session.sort_order, 2);
+        assert_eq!(session.tmux_session, "agentdeck_t");
+        assert_eq!(session.claude_session_id.as_deref(), Some("c-1"));
+    }
+
     #[test]
     fn parse_session_id_json() {
         let output = r#"{"id":"abc-123","title":"test"}"#;
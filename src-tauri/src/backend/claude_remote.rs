@@ -0,0 +1,196 @@
+//! Claude remote backend: a single Claude Code agent behind an AgentAPI server.
+//!
+//! AgentAPI exposes exactly one conversation per server, so the group has a
+//! single synthetic session with a fixed ID.
+
+use super::{remote_session, truncate_for_card, AgentBackend, BackendConfig, CreateSessionParams};
+use crate::claude_logs::{AttentionStatus, SessionSummary};
+use crate::claude_remote::{self, CrMessage};
+use crate::models::Session;
+use crate::remote_common::SseHandles;
+use crate::transcript::{self, TranscriptEntry};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+const SESSION_ID: &str = "agentapi";
+
+pub struct ClaudeRemoteBackend {
+    group_path: String,
+    server_url: String,
+    token: String,
+}
+
+impl ClaudeRemoteBackend {
+    pub fn create(config: BackendConfig) -> Result<Arc<dyn AgentBackend>, String> {
+        Ok(Arc::new(Self {
+            server_url: config.require_server_url()?,
+            token: config.token.unwrap_or_default(),
+            group_path: config.group_path,
+        }))
+    }
+
+    async fn status(&self) -> Result<String, String> {
+        Ok(
+            claude_remote::cr_get_status(self.server_url.clone(), self.token.clone())
+                .await?
+                .status,
+        )
+    }
+
+    async fn messages(&self) -> Result<Vec<CrMessage>, String> {
+        claude_remote::cr_get_messages(self.server_url.clone(), self.token.clone()).await
+    }
+}
+
+/// AgentAPI reports "running" while the agent works and "stable" when it is
+/// waiting at its prompt. A stable agent only needs input once it has replied.
+fn attention_from_status(status: &str, messages: &[CrMessage]) -> AttentionStatus {
+    match status {
+        "running" => AttentionStatus::Running,
+        "stable" if messages.iter().any(|m| m.role == "agent") => AttentionStatus::NeedsInput,
+        "stable" => AttentionStatus::Idle,
+        _ => AttentionStatus::Unknown,
+    }
+}
+
+#[async_trait]
+impl AgentBackend for ClaudeRemoteBackend {
+    async fn list_sessions(&self) -> Result<Vec<Session>, String> {
+        let status = self.status().await?;
+        Ok(vec![remote_session(
+            SESSION_ID.to_string(),
+            "Claude Remote".to_string(),
+            &self.group_path,
+            status,
+            0,
+            0,
+        )])
+    }
+
+    async fn create_session(&self, params: CreateSessionParams) -> Result<String, String> {
+        if let Some(prompt) = params.prompt.filter(|p| !p.trim().is_empty()) {
+            self.send_message(SESSION_ID, &prompt).await?;
+        }
+        Ok(SESSION_ID.to_string())
+    }
+
+    async fn delete_session(&self, _session_id: &str) -> Result<(), String> {
+        Err("Claude remote sessions are owned by the AgentAPI server and cannot be deleted".into())
+    }
+
+    async fn send_message(&self, _session_id: &str, text: &str) -> Result<(), String> {
+        claude_remote::cr_send_message(
+            self.server_url.clone(),
+            self.token.clone(),
+            text.to_string(),
+        )
+        .await
+    }
+
+    async fn subscribe_events(
+        &self,
+        app: &crate::AppHandle,
+        handles: &SseHandles,
+    ) -> Result<(), String> {
+        claude_remote::subscribe_events(app, handles, &self.server_url, &self.token).await
+    }
+
+    async fn get_summary(&self, _session_id: &str) -> Result<SessionSummary, String> {
+        let status = self.status().await?;
+        let messages = self.messages().await?;
+
+        let initial_prompt = messages
+            .iter()
+            .filter(|m| m.role == "user")
+            .find_map(|m| truncate_for_card(&m.content));
+        let last_text = messages
+            .iter()
+            .rev()
+            .filter(|m| m.role == "agent")
+            .find_map(|m| truncate_for_card(&m.content));
+
+        Ok(SessionSummary {
+            summary: None,
+            initial_prompt,
+            attention: attention_from_status(&status, &messages),
+            last_tool: None,
+            last_text,
+            subagents: Vec::new(),
+        })
+    }
+
+    async fn get_attention(&self, _session_id: &str) -> Result<AttentionStatus, String> {
+        let status = self.status().await?;
+        let messages = self.messages().await?;
+        Ok(attention_from_status(&status, &messages))
+    }
+
+    async fn get_transcript(&self, _session_id: &str) -> Result<Vec<TranscriptEntry>, String> {
+        Ok(self
+            .messages()
+            .await?
+            .iter()
+            .map(|m| {
+                transcript::remote_entry(
+                    &m.id,
+                    &m.role,
+                    &m.msg_type,
+                    &serde_json::Value::String(m.content.clone()),
+                    m.tool_name.as_deref(),
+                    m.tool_id.as_deref(),
+                    None,
+                )
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(role: &str) -> CrMessage {
+        CrMessage {
+            id: "cr-0".into(),
+            role: role.into(),
+            msg_type: "text".into(),
+            content: "hi".into(),
+            tool_name: None,
+            tool_id: None,
+            timestamp: None,
+            session_id: String::new(),
+        }
+    }
+
+    #[test]
+    fn running_is_running() {
+        assert!(matches!(
+            attention_from_status("running", &[]),
+            AttentionStatus::Running
+        ));
+    }
+
+    #[test]
+    fn stable_without_reply_is_idle() {
+        assert!(matches!(
+            attention_from_status("stable", &[message("user")]),
+            AttentionStatus::Idle
+        ));
+    }
+
+    #[test]
+    fn stable_after_reply_needs_input() {
+        assert!(matches!(
+            attention_from_status("stable", &[message("user"), message("agent")]),
+            AttentionStatus::NeedsInput
+        ));
+    }
+
+    #[test]
+    fn unknown_status() {
+        assert!(matches!(
+            attention_from_status("offline", &[]),
+            AttentionStatus::Unknown
+        ));
+    }
+}
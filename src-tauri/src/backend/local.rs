@@ -0,0 +1,136 @@
+//! Local backend: agent-deck sessions in tmux, with Claude JSONL logs for summaries.
+
+use super::{spawn_blocking, AgentBackend, BackendConfig, CreateSessionParams};
+use crate::agentdeck;
+use crate::claude_logs::{self, AttentionStatus, SessionSummary};
+use crate::models::Session;
+use crate::orca_db::OrcaDb;
+use crate::remote_common::SseHandles;
+use crate::transcript::{self, TranscriptEntry};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+pub struct LocalBackend {
+    group_path: String,
+    orca_db: OrcaDb,
+}
+
+impl LocalBackend {
+    pub fn create(config: BackendConfig) -> Result<Arc<dyn AgentBackend>, String> {
+        Ok(Arc::new(Self {
+            group_path: config.group_path,
+            orca_db: config.orca_db,
+        }))
+    }
+}
+
+fn tmux_name(session: &Session) -> Option<String> {
+    if session.tmux_session.is_empty() {
+        None
+    } else {
+        Some(session.tmux_session.clone())
+    }
+}
+
+#[async_trait]
+impl AgentBackend for LocalBackend {
+    async fn list_sessions(&self) -> Result<Vec<Session>, String> {
+        let orca_db = self.orca_db.clone();
+        let group_path = self.group_path.clone();
+        spawn_blocking(move || agentdeck::list_sessions_with_prompts(&orca_db, Some(&group_path)))
+            .await
+    }
+
+    async fn create_session(&self, params: CreateSessionParams) -> Result<String, String> {
+        let orca_db = self.orca_db.clone();
+        spawn_blocking(move || {
+            let prompt = params.prompt.clone();
+            let session_id = agentdeck::create_session_impl(
+                params.project_path,
+                params.group_path,
+                params.title,
+                params.tool,
+                params.worktree_branch,
+                params.new_branch,
+                params.start,
+                params.prompt,
+                params.components,
+                &orca_db,
+            )?;
+            agentdeck::store_session_prompt(&orca_db, &session_id, prompt.as_deref());
+            Ok(session_id)
+        })
+        .await
+    }
+
+    async fn delete_session(&self, session_id: &str) -> Result<(), String> {
+        let orca_db = self.orca_db.clone();
+        let session_id = session_id.to_string();
+        spawn_blocking(move || agentdeck::remove_session_impl(&orca_db, &session_id)).await
+    }
+
+    async fn send_message(&self, session_id: &str, text: &str) -> Result<(), String> {
+        let session_id = session_id.to_string();
+        let text = text.to_string();
+        spawn_blocking(move || {
+            let tmux = agentdeck::get_tmux_session_name(&session_id)?;
+            crate::tmux::paste_and_submit(&tmux, &text)
+        })
+        .await
+    }
+
+    async fn subscribe_events(
+        &self,
+        _app: &crate::AppHandle,
+        _handles: &SseHandles,
+    ) -> Result<(), String> {
+        // Local session changes are pushed by the filesystem watcher (see `watcher.rs`).
+        Ok(())
+    }
+
+    async fn get_summary(&self, session_id: &str) -> Result<SessionSummary, String> {
+        let orca_db = self.orca_db.clone();
+        let group_path = self.group_path.clone();
+        let session_id = session_id.to_string();
+        spawn_blocking(move || {
+            let session = agentdeck::get_session_by_id(&orca_db, &session_id)?;
+            let thresholds = orca_db.attention_thresholds_for(Some(&group_path));
+            Ok(claude_logs::summary_for_session(&session, &thresholds))
+        })
+        .await
+    }
+
+    async fn get_attention(&self, session_id: &str) -> Result<AttentionStatus, String> {
+        let orca_db = self.orca_db.clone();
+        let group_path = self.group_path.clone();
+        let session_id = session_id.to_string();
+        spawn_blocking(move || {
+            let session = agentdeck::get_session_by_id(&orca_db, &session_id)?;
+            let tmux = tmux_name(&session);
+            Ok(claude_logs::compute_attention(
+                &session.project_path,
+                session.tool_session_id(),
+                &session.status,
+                tmux.as_deref(),
+                &session.tool,
+                &orca_db.attention_thresholds_for(Some(&group_path)),
+            ))
+        })
+        .await
+    }
+
+    async fn get_transcript(&self, session_id: &str) -> Result<Vec<TranscriptEntry>, String> {
+        let orca_db = self.orca_db.clone();
+        let session_id = session_id.to_string();
+        spawn_blocking(move || {
+            let session = agentdeck::get_session_by_id(&orca_db, &session_id)?;
+            let csid = session
+                .claude_session_id
+                .ok_or_else(|| format!("Session {session_id} has no Claude transcript"))?;
+            let path = claude_logs::find_jsonl_path(&session.project_path, &csid)
+                .ok_or_else(|| format!("No transcript found for session {session_id}"))?;
+            transcript::read_all(&path)
+        })
+        .await
+    }
+}
@@ -0,0 +1,397 @@
+//! Unified session backend abstraction.
+//!
+//! Each group picks a backend via `group_settings.backend`. The frontend lists
+//! a group's sessions, and creates, messages and deletes remote sessions,
+//! through the same `backend_*` Tauri commands, and the registry below
+//! resolves the group's setting to an `AgentBackend` implementation. Adding a
+//! new backend means adding one module and one registry entry.
+
+mod claude_remote;
+mod local;
+mod opencode_remote;
+pub(crate) mod ssh;
+
+use crate::claude_logs::{AttentionStatus, SessionSummary};
+use crate::models::Session;
+use crate::orca_db::OrcaDb;
+use crate::remote_common::SseHandles;
+use crate::transcript::TranscriptEntry;
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::sync::Arc;
+use tauri::State;
+
+/// Parameters for creating a session, shared by all backends.
+///
+/// Remote backends only use `title` and `prompt`; the rest describe the
+/// agent-deck setup (local, or on the host of the `ssh` backend).
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateSessionParams {
+    pub group_path: String,
+    pub project_path: String,
+    pub title: String,
+    #[serde(default)]
+    pub tool: Option<String>,
+    #[serde(default)]
+    pub worktree_branch: Option<String>,
+    #[serde(default)]
+    pub new_branch: bool,
+    #[serde(default)]
+    pub start: Option<bool>,
+    #[serde(default)]
+    pub prompt: Option<String>,
+    #[serde(default)]
+    pub components: Option<Vec<String>>,
+}
+
+/// Everything a backend factory needs to know about the group it serves.
+#[derive(Clone)]
+pub struct BackendConfig {
+    pub group_path: String,
+    pub server_url: Option<String>,
+    pub token: Option<String>,
+    pub orca_db: OrcaDb,
+}
+
+impl BackendConfig {
+    /// The server URL, or an error naming the group if none is configured.
+    fn require_server_url(&self) -> Result<String, String> {
+        self.server_url
+            .clone()
+            .filter(|u| !u.is_empty())
+            .ok_or_else(|| format!("No server URL configured for group {}", self.group_path))
+    }
+}
+
+#[async_trait]
+pub trait AgentBackend: Send + Sync {
+    /// List the sessions belonging to this backend's group.
+    async fn list_sessions(&self) -> Result<Vec<Session>, String>;
+
+    /// Create a session, returning its ID.
+    async fn create_session(&self, params: CreateSessionParams) -> Result<String, String>;
+
+    async fn delete_session(&self, session_id: &str) -> Result<(), String>;
+
+    /// Send a user message (prompt) to a running session.
+    async fn send_message(&self, session_id: &str, text: &str) -> Result<(), String>;
+
+    /// Start pushing live updates to the frontend as Tauri events.
+    /// Backends without a push channel return `Ok(())` and rely on polling.
+    async fn subscribe_events(
+        &self,
+        app: &crate::AppHandle,
+        handles: &SseHandles,
+    ) -> Result<(), String>;
+
+    async fn get_summary(&self, session_id: &str) -> Result<SessionSummary, String>;
+
+    async fn get_attention(&self, session_id: &str) -> Result<AttentionStatus, String>;
+
+    /// The session's full conversation, oldest first.
+    async fn get_transcript(&self, session_id: &str) -> Result<Vec<TranscriptEntry>, String>;
+}
+
+type BackendFactory = fn(BackendConfig) -> Result<Arc<dyn AgentBackend>, String>;
+
+/// All known backends, keyed by the `backend` value stored in `group_settings`.
+const REGISTRY: &[(&str, BackendFactory)] = &[
+    ("local", local::LocalBackend::create),
+    (
+        "opencode-remote",
+        opencode_remote::OpenCodeRemoteBackend::create,
+    ),
+    ("claude-remote", claude_remote::ClaudeRemoteBackend::create),
+    ("ssh", ssh::SshBackend::create),
+];
+
+fn find_factory(backend: &str) -> Option<BackendFactory> {
+    REGISTRY
+        .iter()
+        .find(|(name, _)| *name == backend)
+        .map(|(_, factory)| *factory)
+}
+
+/// Resolve the backend configured for a group.
+pub fn backend_for_group(
+    orca_db: &OrcaDb,
+    group_path: &str,
+) -> Result<Arc<dyn AgentBackend>, String> {
+    let backend = orca_db.get_group_backend(group_path)?;
+    let factory = find_factory(&backend)
+        .ok_or_else(|| format!("Unknown backend '{backend}' for group {group_path}"))?;
+    let (server_url, token) = orca_db.resolve_server_credentials(group_path)?;
+    log::debug!("backend_for_group: group={group_path}, backend={backend}");
+    factory(BackendConfig {
+        group_path: group_path.to_string(),
+        server_url,
+        token,
+        orca_db: orca_db.clone(),
+    })
+}
+
+/// Build a `Session` for a backend that has no agent-deck row (no tmux, no worktree).
+fn remote_session(
+    id: String,
+    title: String,
+    group_path: &str,
+    status: String,
+    created_at: i64,
+    last_accessed: i64,
+) -> Session {
+    Session {
+        id,
+        title,
+        project_path: String::new(),
+        group_path: group_path.to_string(),
+        sort_order: 0,
+        status,
+        tmux_session: String::new(),
+        created_at,
+        last_accessed,
+        worktree_path: String::new(),
+        worktree_repo: String::new(),
+        worktree_branch: String::new(),
+        tool: String::new(),
+        claude_session_id: None,
+        opencode_session_id: None,
+        prompt: None,
+        pr_url: None,
+        pr_number: None,
+        pr_state: None,
+    }
+}
+
+// --- Sync access for the automation API, MCP server and CLI ---
+
+/// Run a backend call from a plain thread (API and MCP requests, the CLI).
+pub fn block_on<T>(future: impl std::future::Future<Output = T>) -> T {
+    tauri::async_runtime::block_on(future)
+}
+
+/// Group paths with their backends.
+pub type GroupBackends = Vec<(String, Arc<dyn AgentBackend>)>;
+
+/// The backends of groups whose sessions aren't in the local agent-deck DB.
+/// A group whose backend can't be set up is logged and skipped.
+pub fn non_local_backends(orca_db: &OrcaDb) -> Result<GroupBackends, String> {
+    let mut backends = Vec::new();
+    for (group_path, settings) in orca_db.get_all_group_settings()? {
+        if settings.backend == "local" {
+            continue;
+        }
+        match backend_for_group(orca_db, &group_path) {
+            Ok(backend) => backends.push((group_path, backend)),
+            Err(e) => log::warn!("Skipping group {group_path}: {e}"),
+        }
+    }
+    Ok(backends)
+}
+
+/// A group's sessions through its backend, or with no group every session:
+/// the local agent-deck DB's plus those of each SSH and remote group.
+/// Unreachable groups are logged and left out of the full list.
+pub fn list_sessions_blocking(
+    orca_db: &OrcaDb,
+    group_path: Option<&str>,
+) -> Result<Vec<Session>, String> {
+    if let Some(group_path) = group_path {
+        return block_on(backend_for_group(orca_db, group_path)?.list_sessions());
+    }
+    let mut sessions = crate::agentdeck::list_sessions_with_prompts(orca_db, None)?;
+    for (group_path, backend) in non_local_backends(orca_db)? {
+        match block_on(backend.list_sessions()) {
+            Ok(group_sessions) => sessions.extend(group_sessions),
+            Err(e) => log::warn!("Failed to list sessions of {group_path}: {e}"),
+        }
+    }
+    Ok(sessions)
+}
+
+/// A session and its group's backend: a local agent-deck session, or one
+/// listed by an SSH or remote group's backend.
+pub fn find_session_blocking(
+    orca_db: &OrcaDb,
+    session_id: &str,
+) -> Result<(Session, Arc<dyn AgentBackend>), String> {
+    if let Ok(session) = crate::agentdeck::get_session_by_id(orca_db, session_id) {
+        let backend = backend_for_group(orca_db, &session.group_path)?;
+        return Ok((session, backend));
+    }
+    for (_, backend) in non_local_backends(orca_db)? {
+        let Ok(sessions) = block_on(backend.list_sessions()) else {
+            continue;
+        };
+        if let Some(session) = sessions.into_iter().find(|s| s.id == session_id) {
+            return Ok((session, backend));
+        }
+    }
+    Err(format!("Session not found: {session_id}"))
+}
+
+/// agent-deck, tmux, git and JSONL access all block, so run them off the async runtime.
+async fn spawn_blocking<F, T>(f: F) -> Result<T, String>
+where
+    F: FnOnce() -> Result<T, String> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| format!("Task failed: {e}"))?
+}
+
+/// Truncate text to the ~200 chars shown on session cards.
+fn truncate_for_card(text: &str) -> Option<String> {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        None
+    } else {
+        Some(trimmed.chars().take(200).collect())
+    }
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub async fn backend_list_sessions(
+    orca_db: State<'_, OrcaDb>,
+    group_path: String,
+) -> Result<Vec<Session>, String> {
+    backend_for_group(&orca_db, &group_path)?
+        .list_sessions()
+        .await
+}
+
+#[tauri::command]
+pub async fn backend_create_session(
+    orca_db: State<'_, OrcaDb>,
+    params: CreateSessionParams,
+) -> Result<String, String> {
+    backend_for_group(&orca_db, &params.group_path)?
+        .create_session(params)
+        .await
+}
+
+#[tauri::command]
+pub async fn backend_delete_session(
+    orca_db: State<'_, OrcaDb>,
+    group_path: String,
+    session_id: String,
+) -> Result<(), String> {
+    backend_for_group(&orca_db, &group_path)?
+        .delete_session(&session_id)
+        .await
+}
+
+#[tauri::command]
+pub async fn backend_send_message(
+    orca_db: State<'_, OrcaDb>,
+    group_path: String,
+    session_id: String,
+    text: String,
+) -> Result<(), String> {
+    backend_for_group(&orca_db, &group_path)?
+        .send_message(&session_id, &text)
+        .await
+}
+
+#[tauri::command]
+pub async fn backend_subscribe_events(
+    app: crate::AppHandle,
+    orca_db: State<'_, OrcaDb>,
+    handles: State<'_, SseHandles>,
+    group_path: String,
+) -> Result<(), String> {
+    backend_for_group(&orca_db, &group_path)?
+        .subscribe_events(&app, &handles)
+        .await
+}
+
+#[tauri::command]
+pub async fn backend_get_summary(
+    orca_db: State<'_, OrcaDb>,
+    group_path: String,
+    session_id: String,
+) -> Result<SessionSummary, String> {
+    backend_for_group(&orca_db, &group_path)?
+        .get_summary(&session_id)
+        .await
+}
+
+#[tauri::command]
+pub async fn backend_get_attention(
+    orca_db: State<'_, OrcaDb>,
+    group_path: String,
+    session_id: String,
+) -> Result<AttentionStatus, String> {
+    backend_for_group(&orca_db, &group_path)?
+        .get_attention(&session_id)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> (OrcaDb, tempfile::TempDir) {
+        let tmp = tempfile::tempdir().expect("failed to create temp dir");
+        let db = OrcaDb::init(tmp.path()).expect("init failed");
+        (db, tmp)
+    }
+
+    #[test]
+    fn registry_covers_known_backends() {
+        for name in ["local", "opencode-remote", "claude-remote", "ssh"] {
+            assert!(find_factory(name).is_some(), "missing backend {name}");
+        }
+        assert!(find_factory("carrier-pigeon").is_none());
+    }
+
+    #[test]
+    fn registry_names_are_unique() {
+        let mut names: Vec<&str> = REGISTRY.iter().map(|(name, _)| *name).collect();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), REGISTRY.len());
+    }
+
+    #[test]
+    fn group_without_settings_resolves_to_local() {
+        let (db, _tmp) = setup();
+        assert!(backend_for_group(&db, "/no/settings").is_ok());
+    }
+
+    #[test]
+    fn remote_backend_requires_server_url() {
+        let (db, _tmp) = setup();
+        db.update_group_settings(
+            "/repo",
+            true,
+            "merge",
+            None,
+            2,
+            "opencode-remote",
+            None,
+            None,
+        )
+        .expect("update failed");
+        let err = backend_for_group(&db, "/repo").err().expect("should fail");
+        assert!(err.contains("No server URL"));
+    }
+
+    #[test]
+    fn unknown_backend_is_error() {
+        let (db, _tmp) = setup();
+        db.update_group_settings("/repo", true, "merge", None, 2, "bogus", None, None)
+            .expect("update failed");
+        let err = backend_for_group(&db, "/repo").err().expect("should fail");
+        assert!(err.contains("Unknown backend 'bogus'"));
+    }
+
+    #[test]
+    fn truncate_for_card_limits_length() {
+        assert_eq!(truncate_for_card("  "), None);
+        assert_eq!(truncate_for_card(" hi "), Some("hi".to_string()));
+        assert_eq!(truncate_for_card(&"x".repeat(300)).unwrap().len(), 200);
+    }
+}
@@ -0,0 +1,241 @@
+//! OpenCode remote backend: REST + SSE against an `opencode serve` instance.
+
+use super::{remote_session, truncate_for_card, AgentBackend, BackendConfig, CreateSessionParams};
+use crate::claude_logs::{AttentionStatus, SessionSummary};
+use crate::dates;
+use crate::models::Session;
+use crate::opencode_remote::{self, OcMessage, OcSession};
+use crate::remote_common::SseHandles;
+use crate::transcript::{self, TranscriptEntry};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+pub struct OpenCodeRemoteBackend {
+    group_path: String,
+    server_url: String,
+    password: String,
+}
+
+impl OpenCodeRemoteBackend {
+    pub fn create(config: BackendConfig) -> Result<Arc<dyn AgentBackend>, String> {
+        Ok(Arc::new(Self {
+            server_url: config.require_server_url()?,
+            password: config.token.unwrap_or_default(),
+            group_path: config.group_path,
+        }))
+    }
+
+    async fn find_session(&self, session_id: &str) -> Result<OcSession, String> {
+        opencode_remote::oc_list_sessions(self.server_url.clone(), self.password.clone())
+            .await?
+            .into_iter()
+            .find(|s| s.id == session_id)
+            .ok_or_else(|| format!("Session not found on server: {session_id}"))
+    }
+}
+
+/// Map an OpenCode session status onto Orca's attention model.
+fn attention_from_status(status: &str) -> AttentionStatus {
+    match status {
+        "running" | "busy" => AttentionStatus::Running,
+        "permission" => AttentionStatus::AwaitingPermission,
+        "waiting" | "question" => AttentionStatus::NeedsInput,
+        "error" => AttentionStatus::Error,
+        "idle" | "" => AttentionStatus::Idle,
+        _ => AttentionStatus::Unknown,
+    }
+}
+
+/// OpenCode timestamps are milliseconds; Orca sessions use seconds.
+fn millis_to_secs(ts: Option<i64>) -> i64 {
+    ts.map(|ms| ms / 1000).unwrap_or(0)
+}
+
+fn to_session(oc: OcSession, group_path: &str) -> Session {
+    remote_session(
+        oc.id,
+        oc.title,
+        group_path,
+        oc.status,
+        millis_to_secs(oc.created_at),
+        millis_to_secs(oc.updated_at.or(oc.created_at)),
+    )
+}
+
+fn message_text(msg: &OcMessage) -> Option<String> {
+    match &msg.content {
+        serde_json::Value::String(s) => truncate_for_card(s),
+        _ => None,
+    }
+}
+
+#[async_trait]
+impl AgentBackend for OpenCodeRemoteBackend {
+    async fn list_sessions(&self) -> Result<Vec<Session>, String> {
+        let sessions =
+            opencode_remote::oc_list_sessions(self.server_url.clone(), self.password.clone())
+                .await?;
+        Ok(sessions
+            .into_iter()
+            .map(|s| to_session(s, &self.group_path))
+            .collect())
+    }
+
+    async fn create_session(&self, params: CreateSessionParams) -> Result<String, String> {
+        let session = opencode_remote::oc_create_session(
+            self.server_url.clone(),
+            self.password.clone(),
+            Some(params.title),
+            params.prompt.filter(|p| !p.trim().is_empty()),
+        )
+        .await?;
+        Ok(session.id)
+    }
+
+    async fn delete_session(&self, session_id: &str) -> Result<(), String> {
+        opencode_remote::oc_delete_session(
+            self.server_url.clone(),
+            self.password.clone(),
+            session_id.to_string(),
+        )
+        .await
+    }
+
+    async fn send_message(&self, session_id: &str, text: &str) -> Result<(), String> {
+        opencode_remote::oc_send_message(
+            self.server_url.clone(),
+            self.password.clone(),
+            session_id.to_string(),
+            text.to_string(),
+        )
+        .await
+    }
+
+    async fn subscribe_events(
+        &self,
+        app: &crate::AppHandle,
+        handles: &SseHandles,
+    ) -> Result<(), String> {
+        opencode_remote::subscribe_events(app, handles, &self.server_url, &self.password).await
+    }
+
+    async fn get_summary(&self, session_id: &str) -> Result<SessionSummary, String> {
+        let session = self.find_session(session_id).await?;
+        let messages = opencode_remote::oc_get_messages(
+            self.server_url.clone(),
+            self.password.clone(),
+            session_id.to_string(),
+        )
+        .await?;
+
+        let initial_prompt = messages
+            .iter()
+            .filter(|m| m.role == "user")
+            .find_map(message_text);
+        let last_text = messages
+            .iter()
+            .rev()
+            .filter(|m| m.role == "assistant")
+            .find_map(message_text);
+        let last_tool = messages.iter().rev().find_map(|m| m.tool_name.clone());
+
+        Ok(SessionSummary {
+            summary: session.summary,
+            initial_prompt,
+            attention: attention_from_status(&session.status),
+            last_tool,
+            last_text,
+            subagents: Vec::new(),
+        })
+    }
+
+    async fn get_attention(&self, session_id: &str) -> Result<AttentionStatus, String> {
+        let session = self.find_session(session_id).await?;
+        Ok(attention_from_status(&session.status))
+    }
+
+    async fn get_transcript(&self, session_id: &str) -> Result<Vec<TranscriptEntry>, String> {
+        let messages = opencode_remote::oc_get_messages(
+            self.server_url.clone(),
+            self.password.clone(),
+            session_id.to_string(),
+        )
+        .await?;
+        Ok(messages
+            .iter()
+            .map(|m| {
+                transcript::remote_entry(
+                    &m.id,
+                    &m.role,
+                    &m.msg_type,
+                    &m.content,
+                    m.tool_name.as_deref(),
+                    m.tool_id.as_deref(),
+                    m.timestamp
+                        .map(|ms| dates::utc_timestamp(millis_to_secs(Some(ms)) as u64)),
+                )
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_mapping() {
+        assert!(matches!(
+            attention_from_status("running"),
+            AttentionStatus::Running
+        ));
+        assert!(matches!(
+            attention_from_status("permission"),
+            AttentionStatus::AwaitingPermission
+        ));
+        assert!(matches!(
+            attention_from_status("error"),
+            AttentionStatus::Error
+        ));
+        assert!(matches!(attention_from_status(""), AttentionStatus::Idle));
+        assert!(matches!(
+            attention_from_status("something-new"),
+            AttentionStatus::Unknown
+        ));
+    }
+
+    #[test]
+    fn to_session_converts_millis() {
+        let oc = OcSession {
+            id: "oc-1".into(),
+            title: "Fix bug".into(),
+            status: "idle".into(),
+            summary: None,
+            created_at: Some(1_700_000_000_000),
+            updated_at: None,
+        };
+        let session = to_session(oc, "remote-group");
+        assert_eq!(session.id, "oc-1");
+        assert_eq!(session.group_path, "remote-group");
+        assert_eq!(session.created_at, 1_700_000_000);
+        assert_eq!(session.last_accessed, 1_700_000_000);
+        assert!(session.tmux_session.is_empty());
+    }
+
+    #[test]
+    fn message_text_only_uses_string_content() {
+        let mut msg = OcMessage {
+            id: "m1".into(),
+            role: "assistant".into(),
+            msg_type: "text".into(),
+            content: serde_json::json!("Done!"),
+            tool_name: None,
+            tool_id: None,
+            timestamp: None,
+            session_id: "oc-1".into(),
+        };
+        assert_eq!(message_text(&msg), Some("Done!".to_string()));
+        msg.content = serde_json::json!({"parts": []});
+        assert_eq!(message_text(&msg), None);
+    }
+}
@@ -0,0 +1,467 @@
+//! SSH backend: agent-deck sessions on another machine (see [`crate::ssh`]).
+//!
+//! The local backend's model, one hop away. Sessions are read from the
+//! remote agent-deck DB with `sqlite3 -json`, created and removed with the
+//! remote `agent-deck`, and driven through the remote tmux. Claude
+//! transcripts are mirrored into Orca's cache, fetching only the bytes
+//! appended since the last read, and parsed like local ones.
+//!
+//! Not carried over: bare-repo and scripted worktrees (agent-deck's own `-w`
+//! is used), Claude Code hooks (their events stay on the remote host, so
+//! attention comes from transcripts and panes) and OpenCode transcripts.
+
+use super::{spawn_blocking, AgentBackend, BackendConfig, CreateSessionParams};
+use crate::agentdeck;
+use crate::claude_logs::{self, AttentionStatus, SessionSummary};
+use crate::command::shell_quote;
+use crate::models::{AttentionThresholds, Session};
+use crate::orca_db::OrcaDb;
+use crate::remote_common::SseHandles;
+use crate::ssh::SshHost;
+use crate::tmux;
+use crate::tool_profile;
+use crate::transcript::{self, TranscriptEntry};
+use async_trait::async_trait;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+pub struct SshBackend {
+    group_path: String,
+    host: SshHost,
+    orca_db: OrcaDb,
+}
+
+impl SshBackend {
+    pub fn create(config: BackendConfig) -> Result<Arc<dyn AgentBackend>, String> {
+        Ok(Arc::new(Self {
+            host: SshHost::parse(&config.require_server_url()?)?,
+            group_path: config.group_path,
+            orca_db: config.orca_db,
+        }))
+    }
+
+    /// Run `f` with clones of the backend's state on the blocking pool.
+    async fn blocking<F, T>(&self, f: F) -> Result<T, String>
+    where
+        F: FnOnce(&SshHost, &OrcaDb, &str) -> Result<T, String> + Send + 'static,
+        T: Send + 'static,
+    {
+        let host = self.host.clone();
+        let orca_db = self.orca_db.clone();
+        let group_path = self.group_path.clone();
+        spawn_blocking(move || f(&host, &orca_db, &group_path)).await
+    }
+}
+
+fn sql_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Sessions from the remote agent-deck DB matching a SQL condition.
+fn query_sessions(host: &SshHost, condition: &str) -> Result<Vec<Session>, String> {
+    let sql = format!(
+        "SELECT {} FROM instances WHERE {condition} ORDER BY sort_order",
+        agentdeck::SESSION_COLUMNS
+    );
+    let output = host.run(&["sqlite3", "-readonly", "-json", &host.agent_deck_db(), &sql])?;
+    // sqlite3 prints nothing at all for an empty result.
+    if output.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let rows: Vec<serde_json::Value> = serde_json::from_str(&output)
+        .map_err(|e| format!("Unexpected sqlite3 output from {}: {e}", host.destination()))?;
+    Ok(rows.iter().map(agentdeck::session_from_json).collect())
+}
+
+fn get_session(host: &SshHost, session_id: &str) -> Result<Session, String> {
+    query_sessions(host, &format!("id = {}", sql_quote(session_id)))?
+        .pop()
+        .ok_or_else(|| format!("Session not found on {}: {session_id}", host.destination()))
+}
+
+fn tmux_name(session: &Session) -> Result<&str, String> {
+    if session.tmux_session.is_empty() {
+        Err(format!("Session {} has no tmux session", session.id))
+    } else {
+        Ok(&session.tmux_session)
+    }
+}
+
+/// Characters other than these become `_` in cache directory names.
+fn cache_key(destination: &str) -> String {
+    destination
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || "@.-".contains(c) {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Leading bytes of a transcript compared to tell an appended file from a
+/// replaced one, as in `jsonl_cache`.
+const FINGERPRINT_BYTES: u64 = 256;
+
+/// The shell script printing a Claude transcript's size on a line, its first
+/// [`FINGERPRINT_BYTES`] and then everything from byte `offset` on, looking
+/// in the project's directory or, for worktrees, one it prefixes (like
+/// [`claude_logs::find_jsonl_path`]). Exits 1 if there is none.
+fn tail_transcript_script(project_path: &str, claude_session_id: &str, offset: u64) -> String {
+    let dir = shell_quote(&project_path.replace('/', "-"));
+    let file = shell_quote(&format!("{claude_session_id}.jsonl"));
+    format!(
+        "for f in .claude/projects/{dir}/{file} .claude/projects/{dir}*/{file}; do \
+         if [ -f \"$f\" ]; then wc -c < \"$f\"; head -c {FINGERPRINT_BYTES} \"$f\"; \
+         exec tail -c +{} \"$f\"; fi; done; exit 1",
+        offset + 1
+    )
+}
+
+/// The output of [`tail_transcript_script`].
+#[derive(Debug, PartialEq, Eq)]
+struct TranscriptTail {
+    len: u64,
+    fingerprint: Vec<u8>,
+    appended: Vec<u8>,
+}
+
+fn parse_transcript_tail(output: &[u8]) -> Option<TranscriptTail> {
+    let newline = output.iter().position(|b| *b == b'\n')?;
+    // BSD wc pads the count with spaces.
+    let len: u64 = std::str::from_utf8(&output[..newline])
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    let rest = &output[newline + 1..];
+    let (fingerprint, appended) =
+        rest.split_at(rest.len().min(len.min(FINGERPRINT_BYTES) as usize));
+    Some(TranscriptTail {
+        len,
+        fingerprint: fingerprint.to_vec(),
+        appended: appended.to_vec(),
+    })
+}
+
+fn fetch_transcript_tail(
+    host: &SshHost,
+    project_path: &str,
+    claude_session_id: &str,
+    offset: u64,
+) -> Option<TranscriptTail> {
+    let script = tail_transcript_script(project_path, claude_session_id, offset);
+    match host.run_bytes(&["sh", "-c", &script]) {
+        Ok(output) => parse_transcript_tail(&output),
+        Err(e) => {
+            log::debug!(
+                "No transcript {claude_session_id} on {}: {e}",
+                host.destination()
+            );
+            None
+        }
+    }
+}
+
+/// Whether the host's transcript continues the local copy, which holds its
+/// first `offset` bytes: it hasn't shrunk, and starts the same way.
+fn continues_mirror(tail: &TranscriptTail, offset: u64, local_fingerprint: &[u8]) -> bool {
+    tail.len >= offset && tail.fingerprint.starts_with(local_fingerprint)
+}
+
+/// A local copy of a Claude transcript on the host, brought up to date by
+/// appending what was written there since the last call. A transcript that
+/// was truncated or replaced there (`/clear`, a resume that rewrites it) is
+/// fetched again from the start. None if there is no transcript (yet).
+fn mirror_transcript(
+    host: &SshHost,
+    project_path: &str,
+    claude_session_id: &str,
+) -> Option<PathBuf> {
+    let dir = dirs::cache_dir()?
+        .join("dk.beaufour.orca/ssh-transcripts")
+        .join(cache_key(host.destination()));
+    let path = dir.join(format!("{claude_session_id}.jsonl"));
+    let offset = std::fs::metadata(&path).map_or(0, |m| m.len());
+
+    let Some(mut tail) = fetch_transcript_tail(host, project_path, claude_session_id, offset)
+    else {
+        return (offset > 0).then_some(path);
+    };
+    let mut local_fingerprint = Vec::new();
+    if offset > 0 {
+        if let Ok(file) = std::fs::File::open(&path) {
+            let _ = file
+                .take(FINGERPRINT_BYTES)
+                .read_to_end(&mut local_fingerprint);
+        }
+    }
+    let append = continues_mirror(&tail, offset, &local_fingerprint);
+    if !append {
+        log::debug!(
+            "Transcript {claude_session_id} on {} was rewritten; fetching it again",
+            host.destination()
+        );
+        tail = fetch_transcript_tail(host, project_path, claude_session_id, 0)?;
+    }
+    if append && tail.appended.is_empty() {
+        return Some(path);
+    }
+    let written = std::fs::create_dir_all(&dir).and_then(|()| {
+        std::fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(append)
+            .truncate(!append)
+            .open(&path)?
+            .write_all(&tail.appended)
+    });
+    if let Err(e) = written {
+        log::warn!("Failed to cache transcript {}: {e}", path.display());
+        return None;
+    }
+    Some(path)
+}
+
+/// [`claude_logs::session_summary`] for a session on the host.
+pub(crate) fn session_summary(
+    host: &SshHost,
+    project_path: &str,
+    claude_session_id: Option<&str>,
+    agentdeck_status: &str,
+    tmux_session: Option<&str>,
+    tool: &str,
+    thresholds: &AttentionThresholds,
+) -> SessionSummary {
+    let transcript = claude_session_id.and_then(|csid| mirror_transcript(host, project_path, csid));
+    claude_logs::summary_from_log(
+        transcript.as_deref(),
+        claude_session_id,
+        agentdeck_status,
+        tmux_session.filter(|t| !t.is_empty()),
+        Some(host),
+        tool,
+        thresholds,
+    )
+}
+
+/// The group's sessions on the host, with their prompts from Orca's DB.
+pub(crate) fn list_sessions(
+    host: &SshHost,
+    orca_db: &OrcaDb,
+    group_path: &str,
+) -> Result<Vec<Session>, String> {
+    let mut sessions = query_sessions(host, &format!("group_path = {}", sql_quote(group_path)))?;
+    let prompts = orca_db.get_all_prompts().unwrap_or_default();
+    for session in &mut sessions {
+        session.prompt = prompts.get(&session.id).cloned();
+    }
+    Ok(sessions)
+}
+
+/// Create a session with the host's agent-deck, in a new worktree when
+/// `params.worktree_branch` is set, returning its ID.
+pub(crate) fn create_session(
+    host: &SshHost,
+    orca_db: &OrcaDb,
+    params: CreateSessionParams,
+) -> Result<String, String> {
+    let tool = params.tool.unwrap_or_else(|| "claude".to_string());
+    let mut argv = vec![
+        "add",
+        &params.project_path,
+        "-g",
+        &params.group_path,
+        "-t",
+        &params.title,
+        "-c",
+        &tool,
+        "-json",
+    ];
+    if let Some(branch) = &params.worktree_branch {
+        argv.extend(["-w", branch]);
+        if params.new_branch {
+            argv.push("-b");
+        }
+    }
+    let session_id = agentdeck::parse_session_id(host.agent_deck(&argv)?.trim())?;
+    agentdeck::store_session_prompt(orca_db, &session_id, params.prompt.as_deref());
+
+    if params.start.unwrap_or(false) {
+        host.agent_deck(&["session", "start", &session_id])?;
+        if let Some(prompt) = params.prompt.filter(|p| !p.trim().is_empty()) {
+            let host = host.clone();
+            let sid = session_id.clone();
+            let profile = tool_profile::profile_for(&tool);
+            // Waiting for the tool to start can take a while.
+            std::thread::spawn(move || {
+                let sent = get_session(&host, &sid).and_then(|session| {
+                    agentdeck::send_prompt_to_tmux(
+                        Some(&host),
+                        tmux_name(&session)?,
+                        &prompt,
+                        &profile,
+                    )
+                });
+                if let Err(e) = sent {
+                    log::error!("Failed to send prompt to session {sid}: {e}");
+                }
+            });
+        }
+    }
+    Ok(session_id)
+}
+
+/// Remove a session with the host's agent-deck, and its data in Orca's DB.
+pub(crate) fn delete_session(
+    host: &SshHost,
+    orca_db: &OrcaDb,
+    session_id: &str,
+) -> Result<(), String> {
+    host.agent_deck(&["remove", session_id])?;
+    if let Err(e) = orca_db.delete_session_data(session_id) {
+        log::error!("Failed to clean up Orca session data for {session_id}: {e}");
+    }
+    Ok(())
+}
+
+fn summarize(host: &SshHost, orca_db: &OrcaDb, session: &Session) -> SessionSummary {
+    session_summary(
+        host,
+        &session.project_path,
+        session.claude_session_id.as_deref(),
+        &session.status,
+        Some(&session.tmux_session),
+        &session.tool,
+        &orca_db.attention_thresholds_for(Some(&session.group_path)),
+    )
+}
+
+#[async_trait]
+impl AgentBackend for SshBackend {
+    async fn list_sessions(&self) -> Result<Vec<Session>, String> {
+        self.blocking(list_sessions).await
+    }
+
+    async fn create_session(&self, params: CreateSessionParams) -> Result<String, String> {
+        self.blocking(move |host, orca_db, _| create_session(host, orca_db, params))
+            .await
+    }
+
+    async fn delete_session(&self, session_id: &str) -> Result<(), String> {
+        let session_id = session_id.to_string();
+        self.blocking(move |host, orca_db, _| delete_session(host, orca_db, &session_id))
+            .await
+    }
+
+    async fn send_message(&self, session_id: &str, text: &str) -> Result<(), String> {
+        let session_id = session_id.to_string();
+        let text = text.to_string();
+        self.blocking(move |host, _, _| {
+            let session = get_session(host, &session_id)?;
+            tmux::paste_and_submit_on(Some(host), tmux_name(&session)?, &text)
+        })
+        .await
+    }
+
+    async fn subscribe_events(
+        &self,
+        _app: &crate::AppHandle,
+        _handles: &SseHandles,
+    ) -> Result<(), String> {
+        // The filesystem watcher (see `watcher.rs`) only sees this machine, so
+        // SSH sessions are refreshed by the frontend's periodic refetch.
+        Ok(())
+    }
+
+    async fn get_summary(&self, session_id: &str) -> Result<SessionSummary, String> {
+        let session_id = session_id.to_string();
+        self.blocking(move |host, orca_db, _| {
+            let session = get_session(host, &session_id)?;
+            Ok(summarize(host, orca_db, &session))
+        })
+        .await
+    }
+
+    async fn get_attention(&self, session_id: &str) -> Result<AttentionStatus, String> {
+        Ok(self.get_summary(session_id).await?.attention)
+    }
+
+    async fn get_transcript(&self, session_id: &str) -> Result<Vec<TranscriptEntry>, String> {
+        let session_id = session_id.to_string();
+        self.blocking(move |host, _, _| {
+            let session = get_session(host, &session_id)?;
+            let path = session
+                .claude_session_id
+                .as_deref()
+                .and_then(|csid| mirror_transcript(host, &session.project_path, csid))
+                .ok_or_else(|| format!("No transcript found for session {session_id}"))?;
+            transcript::read_all(&path)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_sql_and_cache_keys() {
+        assert_eq!(sql_quote("it's"), "'it''s'");
+        assert_eq!(cache_key("dev@devbox.lan"), "dev@devbox.lan");
+        assert_eq!(cache_key("a/../b c"), "a_.._b_c");
+    }
+
+    #[test]
+    fn tails_transcripts_from_the_next_byte() {
+        let script = tail_transcript_script("/home/me/repo", "abc", 0);
+        assert!(script.contains(".claude/projects/'-home-me-repo'/'abc.jsonl'"));
+        assert!(script.contains(".claude/projects/'-home-me-repo'*/'abc.jsonl'"));
+        assert!(script.contains("wc -c < "));
+        assert!(script.contains("head -c 256 "));
+        assert!(script.contains("tail -c +1 "));
+        assert!(tail_transcript_script("/r", "abc", 4096).contains("tail -c +4097 "));
+    }
+
+    #[test]
+    fn parses_transcript_tails() {
+        let tail = parse_transcript_tail(b"   11\n{\"a\":1}\nnew\n").unwrap();
+        assert_eq!(tail.len, 11);
+        assert_eq!(tail.fingerprint, b"{\"a\":1}\nnew");
+        assert_eq!(tail.appended, b"\n");
+
+        let long = "x".repeat(300);
+        let output = format!("300\n{}{}", &long[..256], &long[200..]);
+        let tail = parse_transcript_tail(output.as_bytes()).unwrap();
+        assert_eq!(tail.fingerprint.len(), 256);
+        assert_eq!(tail.appended.len(), 100);
+
+        assert_eq!(parse_transcript_tail(b"no count"), None);
+    }
+
+    #[test]
+    fn refetches_shrunk_or_replaced_transcripts() {
+        let tail = |len: u64, fingerprint: &[u8]| TranscriptTail {
+            len,
+            fingerprint: fingerprint.to_vec(),
+            appended: Vec::new(),
+        };
+        assert!(continues_mirror(
+            &tail(10, b"{\"a\":1}\n{\""),
+            8,
+            b"{\"a\":1}\n"
+        ));
+        assert!(continues_mirror(&tail(0, b""), 0, b""));
+        // Truncated below what we have, or a different file
+        assert!(!continues_mirror(&tail(4, b"{\"a\""), 8, b"{\"a\":1}\n"));
+        assert!(!continues_mirror(
+            &tail(20, b"{\"b\":2}\n..."),
+            8,
+            b"{\"a\":1}\n"
+        ));
+    }
+}
@@ -0,0 +1,440 @@
+//! Per-group daily budgets (tokens, dollars, agent runtime).
+//!
+//! Budgets live in `group_settings`. A background monitor checks them once a
+//! minute against today's usage (UTC days, same as the usage report) and:
+//!
+//! - emits `budget-warning` once a limit crosses `warn_percent`
+//! - emits `budget-exhausted` at 100%, then applies the group's action:
+//!   `"interrupt"` sends Escape to running sessions' tmux panes, `"stop"`
+//!   stops them via `agent-deck session stop`, `"warn"` does nothing more.
+//!
+//! Each event fires at most once per group, day and limit, but the action is
+//! re-applied on every check while a limit stays exhausted, so sessions
+//! resumed or started after the first check are interrupted or stopped too.
+//! Events also go to the automation API's event stream (see
+//! [`crate::local_api`]).
+
+use crate::agentdeck;
+use crate::claude_logs::{self, AttentionStatus};
+use crate::dates::today;
+use crate::local_api;
+use crate::models::{AttentionThresholds, GroupBudget, Session};
+use crate::orca_db::OrcaDb;
+use crate::tmux;
+use crate::usage;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+use tauri::{Emitter, State};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const ACTIONS: &[&str] = &["warn", "interrupt", "stop"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetKind {
+    Tokens,
+    Usd,
+    Runtime,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetLevel {
+    Ok,
+    Warning,
+    Exhausted,
+}
+
+/// Today's consumption for a group.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BudgetUsage {
+    pub tokens: u64,
+    pub cost_usd: f64,
+    pub runtime_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetCheck {
+    pub kind: BudgetKind,
+    pub used: f64,
+    pub limit: f64,
+    pub percent: f64,
+    pub level: BudgetLevel,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetStatus {
+    pub group_path: String,
+    pub day: String,
+    pub budget: GroupBudget,
+    pub usage: BudgetUsage,
+    pub checks: Vec<BudgetCheck>,
+}
+
+/// Payload of `budget-warning` and `budget-exhausted`.
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetEvent {
+    pub group_path: String,
+    pub check: BudgetCheck,
+    pub action: String,
+    /// Sessions the action was applied to (exhausted events only).
+    pub affected_sessions: Vec<String>,
+}
+
+/// Compare usage against each limit the budget sets.
+fn evaluate(budget: &GroupBudget, usage: &BudgetUsage) -> Vec<BudgetCheck> {
+    let limits = [
+        (
+            BudgetKind::Tokens,
+            budget.max_tokens.map(|v| v as f64),
+            usage.tokens as f64,
+        ),
+        (BudgetKind::Usd, budget.max_usd, usage.cost_usd),
+        (
+            BudgetKind::Runtime,
+            budget.max_runtime_mins.map(|v| v as f64 * 60.0),
+            usage.runtime_secs as f64,
+        ),
+    ];
+    limits
+        .into_iter()
+        .filter_map(|(kind, limit, used)| {
+            let limit = limit?;
+            let percent = if limit > 0.0 {
+                used / limit * 100.0
+            } else {
+                100.0
+            };
+            let level = if percent >= 100.0 {
+                BudgetLevel::Exhausted
+            } else if percent >= f64::from(budget.warn_percent) {
+                BudgetLevel::Warning
+            } else {
+                BudgetLevel::Ok
+            };
+            Some(BudgetCheck {
+                kind,
+                used,
+                limit,
+                percent,
+                level,
+            })
+        })
+        .collect()
+}
+
+fn validate(budget: &GroupBudget) -> Result<(), String> {
+    if !ACTIONS.contains(&budget.action.as_str()) {
+        return Err(format!(
+            "Invalid budget action: '{}' (expected one of {})",
+            budget.action,
+            ACTIONS.join(", ")
+        ));
+    }
+    if budget.warn_percent == 0 || budget.warn_percent > 100 {
+        return Err("warn_percent must be between 1 and 100".to_string());
+    }
+    if budget.max_usd.is_some_and(|v| !v.is_finite() || v < 0.0) {
+        return Err("max_usd must be a non-negative number".to_string());
+    }
+    Ok(())
+}
+
+fn is_running(session: &Session, thresholds: &AttentionThresholds) -> bool {
+    let tmux = (!session.tmux_session.is_empty()).then_some(session.tmux_session.as_str());
+    claude_logs::compute_attention(
+        &session.project_path,
+        session.tool_session_id(),
+        &session.status,
+        tmux,
+        &session.tool,
+        thresholds,
+    ) == AttentionStatus::Running
+}
+
+/// Apply the exhausted action to a group's running sessions, returning the
+/// IDs of sessions it was applied to.
+fn enforce(action: &str, running: &[&Session]) -> Vec<String> {
+    let mut affected = Vec::new();
+    for session in running {
+        let result = match action {
+            "interrupt" if !session.tmux_session.is_empty() => {
+                tmux::send_escape(&session.tmux_session)
+            }
+            "stop" => agentdeck::stop_agent_deck_session(&session.id),
+            _ => continue,
+        };
+        match result {
+            Ok(()) => affected.push(session.id.clone()),
+            Err(e) => log::warn!("Budget {action} failed for session {}: {e}", session.id),
+        }
+    }
+    affected
+}
+
+fn budget_statuses(
+    orca_db: &OrcaDb,
+    budgets: HashMap<String, GroupBudget>,
+    day: &str,
+) -> Result<Vec<BudgetStatus>, String> {
+    let mut totals = usage::group_totals(orca_db, day)?;
+    let runtime = orca_db.get_group_runtime(day)?;
+    let mut statuses: Vec<BudgetStatus> = budgets
+        .into_iter()
+        .map(|(group_path, budget)| {
+            let totals = totals.remove(&group_path).unwrap_or_default();
+            let usage = BudgetUsage {
+                tokens: totals.tokens.total(),
+                cost_usd: totals.cost_usd,
+                runtime_secs: runtime.get(&group_path).copied().unwrap_or(0),
+            };
+            BudgetStatus {
+                checks: evaluate(&budget, &usage),
+                group_path,
+                day: day.to_string(),
+                budget,
+                usage,
+            }
+        })
+        .collect();
+    statuses.sort_by(|a, b| a.group_path.cmp(&b.group_path));
+    Ok(statuses)
+}
+
+/// Monitor state carried between checks.
+struct Monitor {
+    last_check: Instant,
+    /// (group, day, kind, level) events already emitted.
+    notified: HashSet<(String, String, BudgetKind, BudgetLevel)>,
+}
+
+impl Monitor {
+    fn tick(&mut self, app: Option<&crate::AppHandle>, orca_db: &OrcaDb) -> Result<(), String> {
+        let elapsed = self.last_check.elapsed().as_secs();
+        self.last_check = Instant::now();
+
+        let budgets = orca_db.get_all_group_budgets()?;
+        if budgets.is_empty() {
+            return Ok(());
+        }
+        let day = today();
+        self.notified.retain(|(_, d, _, _)| *d == day);
+
+        let sessions = agentdeck::list_sessions_with_prompts(orca_db, None)?;
+        let thresholds = orca_db.get_all_attention_thresholds()?;
+        let mut running: HashMap<&str, Vec<&Session>> = HashMap::new();
+        for session in &sessions {
+            if budgets.contains_key(&session.group_path)
+                && is_running(
+                    session,
+                    &thresholds
+                        .get(&session.group_path)
+                        .cloned()
+                        .unwrap_or_default(),
+                )
+            {
+                running
+                    .entry(session.group_path.as_str())
+                    .or_default()
+                    .push(session);
+            }
+        }
+        for (group_path, group_sessions) in &running {
+            let secs = elapsed * group_sessions.len() as u64;
+            orca_db.add_group_runtime(group_path, &day, secs)?;
+        }
+
+        usage::refresh_usage(orca_db)?;
+        for status in budget_statuses(orca_db, budgets, &day)? {
+            let affected = if status
+                .checks
+                .iter()
+                .any(|check| check.level == BudgetLevel::Exhausted)
+            {
+                let group_running = running
+                    .get(status.group_path.as_str())
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+                enforce(&status.budget.action, group_running)
+            } else {
+                Vec::new()
+            };
+            if !affected.is_empty() {
+                log::info!(
+                    "Budget {} applied to {} session(s) in {}",
+                    status.budget.action,
+                    affected.len(),
+                    status.group_path
+                );
+            }
+            for check in status.checks {
+                if check.level == BudgetLevel::Ok {
+                    continue;
+                }
+                let key = (
+                    status.group_path.clone(),
+                    day.clone(),
+                    check.kind,
+                    check.level,
+                );
+                if !self.notified.insert(key) {
+                    continue;
+                }
+
+                let (event, affected_sessions) = if check.level == BudgetLevel::Exhausted {
+                    ("budget-exhausted", affected.clone())
+                } else {
+                    ("budget-warning", Vec::new())
+                };
+                log::info!(
+                    "{event}: group {} {:?} at {:.0}%",
+                    status.group_path,
+                    check.kind,
+                    check.percent
+                );
+                let payload = BudgetEvent {
+                    group_path: status.group_path.clone(),
+                    check,
+                    action: status.budget.action.clone(),
+                    affected_sessions,
+                };
+                if let Some(app) = app {
+                    let _ = app.emit(event, &payload);
+                }
+                local_api::publish(event, &payload);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Start the budget monitor in a background thread. `app` is None in the
+/// headless daemon.
+pub fn start(app: Option<crate::AppHandle>, orca_db: OrcaDb) {
+    std::thread::spawn(move || {
+        let mut monitor = Monitor {
+            last_check: Instant::now(),
+            notified: HashSet::new(),
+        };
+        loop {
+            std::thread::sleep(CHECK_INTERVAL);
+            if let Err(e) = monitor.tick(app.as_ref(), &orca_db) {
+                log::warn!("Budget monitor check failed: {e}");
+            }
+        }
+    });
+}
+
+// ── Commands ─────────────────────────────────────────────────────────
+
+#[tauri::command]
+pub fn get_group_budget(
+    orca_db: State<'_, OrcaDb>,
+    group_path: String,
+) -> Result<Option<GroupBudget>, String> {
+    orca_db.get_group_budget(&group_path)
+}
+
+/// Set a group's budget; `None` (or a budget with no limits) clears it.
+#[tauri::command]
+pub fn set_group_budget(
+    orca_db: State<'_, OrcaDb>,
+    group_path: String,
+    budget: Option<GroupBudget>,
+) -> Result<(), String> {
+    if let Some(ref budget) = budget {
+        validate(budget)?;
+    }
+    orca_db.set_group_budget(&group_path, budget.as_ref())
+}
+
+/// Today's usage against every group budget.
+#[tauri::command]
+pub async fn get_budget_status(orca_db: State<'_, OrcaDb>) -> Result<Vec<BudgetStatus>, String> {
+    let orca_db = orca_db.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        usage::refresh_usage(&orca_db)?;
+        let budgets = orca_db.get_all_group_budgets()?;
+        budget_statuses(&orca_db, budgets, &today())
+    })
+    .await
+    .map_err(|e| format!("Task failed: {e}"))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn budget() -> GroupBudget {
+        GroupBudget {
+            max_tokens: Some(1000),
+            max_usd: None,
+            max_runtime_mins: Some(10),
+            warn_percent: 80,
+            action: "interrupt".into(),
+        }
+    }
+
+    #[test]
+    fn evaluate_checks_only_set_limits() {
+        let checks = evaluate(&budget(), &BudgetUsage::default());
+        let kinds: Vec<_> = checks.iter().map(|c| c.kind).collect();
+        assert_eq!(kinds, vec![BudgetKind::Tokens, BudgetKind::Runtime]);
+        assert!(checks.iter().all(|c| c.level == BudgetLevel::Ok));
+    }
+
+    #[test]
+    fn evaluate_levels() {
+        let usage = BudgetUsage {
+            tokens: 850,
+            cost_usd: 0.0,
+            runtime_secs: 600,
+        };
+        let checks = evaluate(&budget(), &usage);
+        assert_eq!(checks[0].level, BudgetLevel::Warning);
+        assert!((checks[0].percent - 85.0).abs() < 1e-9);
+        assert_eq!(checks[1].level, BudgetLevel::Exhausted);
+        assert!((checks[1].limit - 600.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn zero_limit_is_exhausted() {
+        let zero = GroupBudget {
+            max_usd: Some(0.0),
+            ..budget()
+        };
+        let checks = evaluate(&zero, &BudgetUsage::default());
+        assert_eq!(checks[1].kind, BudgetKind::Usd);
+        assert_eq!(checks[1].level, BudgetLevel::Exhausted);
+    }
+
+    #[test]
+    fn validate_rejects_bad_input() {
+        assert!(validate(&budget()).is_ok());
+        let bad_action = GroupBudget {
+            action: "explode".into(),
+            ..budget()
+        };
+        assert!(validate(&bad_action).is_err());
+        let bad_pct = GroupBudget {
+            warn_percent: 0,
+            ..budget()
+        };
+        assert!(validate(&bad_pct).is_err());
+    }
+
+    #[test]
+    fn statuses_combine_usage_and_runtime() {
+        let tmp = tempfile::tempdir().expect("failed to create temp dir");
+        let db = OrcaDb::init(tmp.path()).expect("init failed");
+        db.set_group_budget("/repo", Some(&budget())).unwrap();
+        db.add_group_runtime("/repo", "2025-01-01", 540).unwrap();
+
+        let statuses =
+            budget_statuses(&db, db.get_all_group_budgets().unwrap(), "2025-01-01").unwrap();
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].usage.runtime_secs, 540);
+        assert_eq!(statuses[0].checks[1].level, BudgetLevel::Warning);
+    }
+}
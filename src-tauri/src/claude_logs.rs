@@ -1,8 +1,15 @@
 use crate::command::expand_tilde;
+use crate::dates::parse_timestamp;
+use crate::jsonl_cache;
+use crate::models::{AttentionThresholds, Session, ToolProfile};
+use crate::orca_db::OrcaDb;
+use crate::ssh::SshHost;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs::File;
-use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
+use tauri::State;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionSummary {
@@ -11,12 +18,43 @@ pub struct SessionSummary {
     pub attention: AttentionStatus,
     pub last_tool: Option<String>,
     pub last_text: Option<String>,
+    /// Subagents (Task tool) started by the session, oldest first.
+    #[serde(default)]
+    pub subagents: Vec<Subagent>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubagentStatus {
+    Running,
+    Completed,
+    Failed,
+}
+
+/// A subagent spawned through the `Task` tool. Its own messages are the
+/// sidechain entries of the transcript.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Subagent {
+    pub tool_use_id: String,
+    pub description: Option<String>,
+    pub subagent_type: Option<String>,
+    pub status: SubagentStatus,
+    pub started_at: Option<String>,
+    /// Most recent tool the subagent used.
+    pub last_tool: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum AttentionStatus {
     NeedsInput,
+    /// Waiting for the user to approve a tool call (as opposed to answering
+    /// a question or giving the next prompt).
+    AwaitingPermission,
+    /// The last API call hit a rate or usage limit.
+    RateLimited,
+    /// The tool is compacting its context.
+    Compacting,
     Error,
     Running,
     Idle,
@@ -24,7 +62,33 @@ pub enum AttentionStatus {
     Unknown,
 }
 
-fn claude_projects_dir() -> Option<PathBuf> {
+impl AttentionStatus {
+    /// Whether the session is blocked on the user, shown in "Needs Action".
+    pub fn needs_action(&self) -> bool {
+        matches!(
+            self,
+            Self::AwaitingPermission | Self::NeedsInput | Self::RateLimited | Self::Error
+        )
+    }
+
+    /// Sort key for "Needs Action": lower is more urgent. A permission prompt
+    /// blocks work outright; a rate limit clears on its own.
+    pub fn priority(&self) -> u8 {
+        match self {
+            Self::AwaitingPermission => 0,
+            Self::NeedsInput => 1,
+            Self::Error => 2,
+            Self::RateLimited => 3,
+            Self::Compacting => 4,
+            Self::Running => 5,
+            Self::Idle => 6,
+            Self::Stale => 7,
+            Self::Unknown => 8,
+        }
+    }
+}
+
+pub fn claude_projects_dir() -> Option<PathBuf> {
     Some(dirs::home_dir()?.join(".claude/projects"))
 }
 
@@ -73,56 +137,8 @@ pub fn find_jsonl_path(project_path: &str, claude_session_id: &str) -> Option<Pa
     None
 }
 
-/// Read the last N bytes of a file and parse JSONL lines from it
-fn read_tail_lines(path: &Path, max_bytes: u64) -> Vec<serde_json::Value> {
-    let Ok(file) = File::open(path) else {
-        log::warn!("read_tail_lines: failed to open {}", path.display());
-        return vec![];
-    };
-
-    let Ok(metadata) = file.metadata() else {
-        log::warn!(
-            "read_tail_lines: failed to read metadata for {}",
-            path.display()
-        );
-        return vec![];
-    };
-
-    let file_size = metadata.len();
-    let seek_pos = file_size.saturating_sub(max_bytes);
-    log::debug!(
-        "read_tail_lines: file_size={file_size}, seek_pos={seek_pos} for {}",
-        path.display()
-    );
-
-    let mut reader = BufReader::new(file);
-    if reader.seek(SeekFrom::Start(seek_pos)).is_err() {
-        return vec![];
-    }
-
-    // If we seeked to the middle, skip the first partial line
-    if seek_pos > 0 {
-        let mut discard = String::new();
-        let _ = reader.read_line(&mut discard);
-    }
-
-    let mut lines = Vec::new();
-    for line in reader.lines() {
-        let Ok(line) = line else {
-            continue;
-        };
-        if line.trim().is_empty() {
-            continue;
-        }
-        if let Ok(val) = serde_json::from_str::<serde_json::Value>(&line) {
-            lines.push(val);
-        }
-    }
-    lines
-}
-
 /// Read the first N bytes of a file and parse JSONL lines from it
-fn read_head_lines(path: &Path, max_bytes: u64) -> Vec<serde_json::Value> {
+pub fn read_head_lines(path: &Path, max_bytes: u64) -> Vec<serde_json::Value> {
     let Ok(file) = File::open(path) else {
         return vec![];
     };
@@ -149,199 +165,365 @@ fn read_head_lines(path: &Path, max_bytes: u64) -> Vec<serde_json::Value> {
     lines
 }
 
-fn extract_initial_prompt(lines: &[serde_json::Value]) -> Option<String> {
-    for line in lines.iter() {
-        let msg = line.get("message").unwrap_or(line);
-        if msg.get("role").and_then(|v| v.as_str()) != Some("user") {
-            continue;
-        }
-        if let Some(content) = msg.get("content").and_then(|v| v.as_array()) {
-            for item in content {
-                if item.get("type").and_then(|v| v.as_str()) == Some("text") {
-                    let text = item.get("text").and_then(|v| v.as_str()).unwrap_or("");
-                    let trimmed = text.trim();
-                    if !trimmed.is_empty() {
-                        let truncated: String = trimmed.chars().take(200).collect();
-                        return Some(truncated);
-                    }
-                }
-            }
-        }
-    }
-    None
+/// Tools that always stop for the user, regardless of auto-approve settings.
+const USER_FACING_TOOLS: &[&str] = &["AskUserQuestion", "ExitPlanMode", "EnterPlanMode"];
+/// Text of API error messages that mean a rate or usage limit was hit.
+const RATE_LIMIT_MARKERS: &[&str] = &["429", "rate limit", "rate_limit", "usage limit"];
+/// Finished subagents beyond this many are dropped from the summary.
+const MAX_SUBAGENTS: usize = 20;
+
+/// Everything Orca derives from a JSONL transcript, built up one line at a
+/// time so `jsonl_cache` only has to feed it newly appended lines.
+#[derive(Debug, Clone, Default)]
+pub struct LogState {
+    /// Most recent `type=summary` entry.
+    pub summary: Option<String>,
+    /// First user text message (truncated to ~200 chars).
+    pub initial_prompt: Option<String>,
+    /// First tool used by the most recent assistant message that used one.
+    pub last_tool: Option<String>,
+    /// Most recent assistant text (truncated to ~200 chars).
+    pub last_text: Option<String>,
+    /// Any user/assistant message seen.
+    has_conversation: bool,
+    /// Any assistant message seen.
+    has_assistant: bool,
+    /// The latest user/assistant message is an assistant turn calling one of
+    /// `USER_FACING_TOOLS`.
+    awaiting_user_tool: bool,
+    /// The latest user/assistant message is an API error for a rate or
+    /// usage limit.
+    rate_limited: bool,
+    /// `timestamp` of the last line (unix seconds), if it had one.
+    last_timestamp: Option<f64>,
+    /// Subagents started by the main thread, oldest first.
+    pub subagents: Vec<Subagent>,
 }
 
-fn extract_summary(lines: &[serde_json::Value]) -> Option<String> {
-    // Look for type=summary entries (most recent wins)
-    for line in lines.iter().rev() {
-        if line.get("type").and_then(|v| v.as_str()) == Some("summary") {
-            if let Some(s) = line.get("summary").and_then(|v| v.as_str()) {
-                return Some(s.to_string());
-            }
-        }
+/// Claude Code records failed API calls as synthetic assistant messages
+/// flagged `isApiErrorMessage`; rate and usage limits show up as 429s or
+/// "usage limit reached".
+fn is_rate_limit_error(line: &serde_json::Value, content: &[serde_json::Value]) -> bool {
+    let error = line.get("error").and_then(|v| v.as_str());
+    if error == Some("rate_limit") {
+        return true;
     }
-    None
+    if error.is_none()
+        && line
+            .get("isApiErrorMessage")
+            .and_then(serde_json::Value::as_bool)
+            != Some(true)
+    {
+        return false;
+    }
+    first_text(content).is_some_and(|text| {
+        let text = text.to_lowercase();
+        RATE_LIMIT_MARKERS.iter().any(|m| text.contains(m))
+    })
 }
 
-fn extract_attention(lines: &[serde_json::Value], agentdeck_status: &str) -> AttentionStatus {
-    // For all statuses (including "running" and "waiting"), refine using JSONL
-    let relevant: Vec<&serde_json::Value> = lines
-        .iter()
-        .filter(|l| {
-            let t = l.get("type").and_then(|v| v.as_str()).unwrap_or("");
-            t == "assistant" || t == "user"
-        })
-        .collect();
-
-    // Agent-deck "waiting" means the CLI is at a prompt, but only flag as
-    // NeedsInput if there has been an actual conversation (assistant messages).
-    // A fresh session with no assistant messages is just the initial prompt — Idle.
-    if agentdeck_status == "waiting" {
-        let has_assistant = relevant.iter().any(|entry| {
-            let msg = entry.get("message").unwrap_or(entry);
-            msg.get("role").and_then(|v| v.as_str()) == Some("assistant")
-        });
-        if has_assistant {
-            return AttentionStatus::NeedsInput;
+fn first_text(content: &[serde_json::Value]) -> Option<String> {
+    content.iter().find_map(|item| {
+        if item.get("type").and_then(|v| v.as_str()) != Some("text") {
+            return None;
+        }
+        let text = item.get("text").and_then(|v| v.as_str()).unwrap_or("");
+        let trimmed = text.trim();
+        if trimmed.is_empty() {
+            None
         } else {
-            return AttentionStatus::Idle;
+            Some(trimmed.chars().take(200).collect())
         }
-    }
+    })
+}
 
-    if relevant.is_empty() {
-        // No JSONL data — trust agent-deck status
-        return match agentdeck_status {
-            "running" => AttentionStatus::Running,
-            "error" => AttentionStatus::Error,
-            _ => AttentionStatus::Unknown,
-        };
+impl LogState {
+    pub fn from_lines(lines: &[serde_json::Value]) -> Self {
+        let mut state = Self::default();
+        for line in lines {
+            state.push_line(line);
+        }
+        state
     }
 
-    let last = relevant.last().unwrap();
-    let msg = last.get("message").unwrap_or(last);
-    let role = msg.get("role").and_then(|v| v.as_str()).unwrap_or("");
-    let content = msg.get("content").and_then(|v| v.as_array());
+    /// Fold one parsed JSONL line into the state.
+    pub fn push_line(&mut self, line: &serde_json::Value) {
+        self.last_timestamp = line
+            .get("timestamp")
+            .and_then(|v| v.as_f64().or_else(|| v.as_str().and_then(parse_timestamp)));
 
-    if role == "assistant" {
-        if let Some(content_arr) = content {
-            for item in content_arr {
-                if item.get("type").and_then(|v| v.as_str()) == Some("tool_use") {
-                    let name = item.get("name").and_then(|v| v.as_str()).unwrap_or("");
+        let line_type = line.get("type").and_then(|v| v.as_str()).unwrap_or("");
+        if line_type == "summary" {
+            if let Some(s) = line.get("summary").and_then(|v| v.as_str()) {
+                self.summary = Some(s.to_string());
+            }
+        }
 
-                    // Explicit user-facing prompts — these tools always
-                    // require user interaction regardless of auto-approve
-                    // settings.
-                    if name == "AskUserQuestion"
-                        || name == "ExitPlanMode"
-                        || name == "EnterPlanMode"
-                    {
-                        return AttentionStatus::NeedsInput;
-                    }
+        let msg = line.get("message").unwrap_or(line);
+        let role = msg.get("role").and_then(|v| v.as_str()).unwrap_or("");
+        let content = msg
+            .get("content")
+            .and_then(|v| v.as_array())
+            .map_or(&[][..], Vec::as_slice);
+
+        // Subagent traffic only updates its subagent, never the main thread's
+        // prompt, last tool or attention.
+        if line.get("isSidechain").and_then(serde_json::Value::as_bool) == Some(true) {
+            if role == "assistant" {
+                self.push_sidechain_tools(content);
+            }
+            return;
+        }
+        self.track_subagents(role, content, line);
+
+        if line_type == "assistant" || line_type == "user" {
+            self.has_conversation = true;
+            self.has_assistant |= role == "assistant";
+            self.awaiting_user_tool = role == "assistant"
+                && content.iter().any(|item| {
+                    item.get("type").and_then(|v| v.as_str()) == Some("tool_use")
+                        && item
+                            .get("name")
+                            .and_then(|v| v.as_str())
+                            .is_some_and(|name| USER_FACING_TOOLS.contains(&name))
+                });
+            self.rate_limited = role == "assistant" && is_rate_limit_error(line, content);
+        }
 
-                    // NOTE: We do NOT flag generic tool_use-without-result as
-                    // NeedsInput.  A missing tool_result can mean either
-                    // "waiting for user approval" or "tool currently executing"
-                    // — we can't tell which from JSONL alone.  False "Needs
-                    // Input" during every tool execution is worse than showing
-                    // "Running" during an actual permission prompt.
+        match role {
+            "user" if self.initial_prompt.is_none() => {
+                self.initial_prompt = first_text(content);
+            }
+            "assistant" => {
+                if let Some(text) = first_text(content) {
+                    self.last_text = Some(text);
+                }
+                if let Some(tool) = content
+                    .iter()
+                    .find(|item| item.get("type").and_then(|v| v.as_str()) == Some("tool_use"))
+                {
+                    self.last_tool = tool.get("name").and_then(|v| v.as_str()).map(String::from);
                 }
             }
+            _ => {}
         }
     }
 
-    // NOTE: We intentionally do NOT check tool_result is_error here.
-    // The is_error flag on tool_results covers normal workflow events like
-    // rejected tool calls, rejected plans (ExitPlanMode), and failed bash
-    // commands — none of which are session-level errors.  If the session
-    // truly errored out, agent-deck will report "error" status and the
-    // catch-all below handles it.
-
-    // Check staleness based on timestamp
-    if let Some(ts) = lines
-        .last()
-        .and_then(|l| l.get("timestamp").and_then(serde_json::Value::as_f64))
-    {
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs_f64();
-        if now - ts > 3600.0 {
-            return AttentionStatus::Stale;
+    /// Start subagents for `Task` calls and finish them on their results.
+    fn track_subagents(
+        &mut self,
+        role: &str,
+        content: &[serde_json::Value],
+        line: &serde_json::Value,
+    ) {
+        for item in content {
+            match (role, item.get("type").and_then(|v| v.as_str())) {
+                ("assistant", Some("tool_use"))
+                    if item.get("name").and_then(|v| v.as_str()) == Some("Task") =>
+                {
+                    let input = item.get("input");
+                    let input_str = |key: &str| {
+                        input
+                            .and_then(|i| i.get(key))
+                            .and_then(|v| v.as_str())
+                            .map(String::from)
+                    };
+                    self.subagents.push(Subagent {
+                        tool_use_id: item
+                            .get("id")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("")
+                            .to_string(),
+                        description: input_str("description"),
+                        subagent_type: input_str("subagent_type"),
+                        status: SubagentStatus::Running,
+                        started_at: line
+                            .get("timestamp")
+                            .and_then(|v| v.as_str())
+                            .map(String::from),
+                        last_tool: None,
+                    });
+                    if self.subagents.len() > MAX_SUBAGENTS {
+                        if let Some(pos) = self
+                            .subagents
+                            .iter()
+                            .position(|a| a.status != SubagentStatus::Running)
+                        {
+                            self.subagents.remove(pos);
+                        }
+                    }
+                }
+                ("user", Some("tool_result")) => {
+                    let id = item.get("tool_use_id").and_then(|v| v.as_str());
+                    let is_error = item
+                        .get("is_error")
+                        .and_then(serde_json::Value::as_bool)
+                        .unwrap_or(false);
+                    if let Some(agent) = self
+                        .subagents
+                        .iter_mut()
+                        .find(|a| Some(a.tool_use_id.as_str()) == id)
+                    {
+                        agent.status = if is_error {
+                            SubagentStatus::Failed
+                        } else {
+                            SubagentStatus::Completed
+                        };
+                    }
+                }
+                _ => {}
+            }
         }
     }
 
-    if agentdeck_status == "running" {
-        return AttentionStatus::Running;
+    /// Record a sidechain assistant turn's tool against the newest running
+    /// subagent (sidechain lines don't say which Task call they belong to).
+    fn push_sidechain_tools(&mut self, content: &[serde_json::Value]) {
+        let Some(tool) = content
+            .iter()
+            .filter(|item| item.get("type").and_then(|v| v.as_str()) == Some("tool_use"))
+            .find_map(|item| item.get("name").and_then(|v| v.as_str()))
+        else {
+            return;
+        };
+        if let Some(agent) = self
+            .subagents
+            .iter_mut()
+            .rev()
+            .find(|a| a.status == SubagentStatus::Running)
+        {
+            agent.last_tool = Some(tool.to_string());
+        }
     }
 
-    if agentdeck_status == "error" {
-        return AttentionStatus::Error;
+    fn has_running_subagents(&self) -> bool {
+        self.subagents
+            .iter()
+            .any(|a| a.status == SubagentStatus::Running)
     }
 
-    AttentionStatus::Idle
-}
-
-fn extract_last_text(lines: &[serde_json::Value]) -> Option<String> {
-    for line in lines.iter().rev() {
-        let msg = line.get("message").unwrap_or(line);
-        if msg.get("role").and_then(|v| v.as_str()) != Some("assistant") {
-            continue;
+    /// Derive the attention status, using agent-deck's status as the primary
+    /// signal and the transcript to refine it.
+    pub fn attention(
+        &self,
+        agentdeck_status: &str,
+        thresholds: &AttentionThresholds,
+    ) -> AttentionStatus {
+        // Agent-deck "waiting" means the CLI is at a prompt, but only flag as
+        // NeedsInput if there has been an actual conversation (assistant messages).
+        // A fresh session with no assistant messages is just the initial prompt — Idle.
+        if agentdeck_status == "waiting" {
+            return if self.has_assistant {
+                AttentionStatus::NeedsInput
+            } else {
+                AttentionStatus::Idle
+            };
         }
-        if let Some(content) = msg.get("content").and_then(|v| v.as_array()) {
-            for item in content {
-                if item.get("type").and_then(|v| v.as_str()) == Some("text") {
-                    let text = item.get("text").and_then(|v| v.as_str()).unwrap_or("");
-                    let trimmed = text.trim();
-                    if !trimmed.is_empty() {
-                        // Return first ~200 chars
-                        let truncated: String = trimmed.chars().take(200).collect();
-                        return Some(truncated);
-                    }
-                }
-            }
+
+        if !self.has_conversation {
+            // No JSONL data — trust agent-deck status
+            return match agentdeck_status {
+                "running" => AttentionStatus::Running,
+                "error" => AttentionStatus::Error,
+                _ => AttentionStatus::Unknown,
+            };
         }
-    }
-    None
-}
 
-fn extract_last_tool(lines: &[serde_json::Value]) -> Option<String> {
-    for line in lines.iter().rev() {
-        let msg = line.get("message").unwrap_or(line);
-        if msg.get("role").and_then(|v| v.as_str()) != Some("assistant") {
-            continue;
+        // NOTE: We do NOT flag generic tool_use-without-result as NeedsInput.
+        // A missing tool_result can mean either "waiting for user approval" or
+        // "tool currently executing" — we can't tell which from JSONL alone.
+        // False "Needs Input" during every tool execution is worse than showing
+        // "Running" during an actual permission prompt.
+        if self.awaiting_user_tool {
+            return AttentionStatus::NeedsInput;
         }
-        if let Some(content) = msg.get("content").and_then(|v| v.as_array()) {
-            for item in content {
-                if item.get("type").and_then(|v| v.as_str()) == Some("tool_use") {
-                    return item.get("name").and_then(|v| v.as_str()).map(String::from);
-                }
+        if self.rate_limited {
+            return AttentionStatus::RateLimited;
+        }
+
+        // NOTE: We intentionally do NOT check tool_result is_error here.
+        // The is_error flag on tool_results covers normal workflow events like
+        // rejected tool calls, rejected plans (ExitPlanMode), and failed bash
+        // commands — none of which are session-level errors.  If the session
+        // truly errored out, agent-deck will report "error" status and the
+        // catch-all below handles it.
+
+        // Check staleness (and a lagging "running") based on timestamp
+        if let Some(ts) = self.last_timestamp {
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs_f64();
+            let age = now - ts;
+            if age > thresholds.stale_after_secs as f64 {
+                return AttentionStatus::Stale;
+            }
+            if agentdeck_status == "running"
+                && !self.has_running_subagents()
+                && thresholds
+                    .idle_after_secs
+                    .is_some_and(|idle| age > idle as f64)
+            {
+                return AttentionStatus::Idle;
             }
         }
+
+        match agentdeck_status {
+            "running" => AttentionStatus::Running,
+            "error" => AttentionStatus::Error,
+            // The main thread is blocked on a Task call while its subagent works.
+            _ if self.has_running_subagents() => AttentionStatus::Running,
+            _ => AttentionStatus::Idle,
+        }
     }
-    None
 }
 
 /// Compute just the attention status for a session (lightweight — skips summary/tool extraction).
+///
+/// `session_id` is the tool's own session ID: Claude's, or OpenCode's for
+/// `tool == "opencode"` (whose storage is read by `opencode_logs`). A recent
+/// Claude Code hook event wins; otherwise the transcript and tmux heuristics
+/// decide.
 pub fn compute_attention(
     project_path: &str,
-    claude_session_id: Option<&str>,
+    session_id: Option<&str>,
     agentdeck_status: &str,
     tmux_session: Option<&str>,
+    tool: &str,
+    thresholds: &AttentionThresholds,
 ) -> AttentionStatus {
     log::debug!(
-        "compute_attention: session_id={claude_session_id:?}, agentdeck_status={agentdeck_status}, tmux={tmux_session:?}"
+        "compute_attention: session_id={session_id:?}, agentdeck_status={agentdeck_status}, tmux={tmux_session:?}, tool={tool}"
     );
+    let profile = crate::tool_profile::profile_for(tool);
+
+    if tool == "opencode" {
+        if let Some(state) = crate::opencode_logs::read_state(project_path, session_id) {
+            return refine_with_tmux(
+                state.attention(agentdeck_status, thresholds),
+                tmux_session,
+                &profile,
+            );
+        }
+    }
+    let claude_session_id = session_id.filter(|_| tool != "opencode");
 
     let Some(claude_session_id) = claude_session_id else {
         let attention = match agentdeck_status {
             "running" => AttentionStatus::Running,
             "waiting" => AttentionStatus::Idle,
             "error" => AttentionStatus::Error,
+            "idle" => AttentionStatus::Idle,
             _ => AttentionStatus::Unknown,
         };
-        return refine_with_tmux(attention, tmux_session);
+        return refine_with_tmux(attention, tmux_session, &profile);
     };
 
+    if let Some(attention) = hook_attention(claude_session_id, agentdeck_status) {
+        log::debug!("compute_attention: hook signal={attention:?}");
+        return attention;
+    }
+
     let Some(jsonl_path) = find_jsonl_path(project_path, claude_session_id) else {
         let attention = match agentdeck_status {
             "running" => AttentionStatus::Running,
@@ -350,20 +532,61 @@ pub fn compute_attention(
             "idle" => AttentionStatus::Idle,
             _ => AttentionStatus::Unknown,
         };
-        return refine_with_tmux(attention, tmux_session);
+        return refine_with_tmux(attention, tmux_session, &profile);
     };
 
-    let lines = read_tail_lines(&jsonl_path, 256 * 1024);
-    let result = refine_with_tmux(extract_attention(&lines, agentdeck_status), tmux_session);
+    let state = jsonl_cache::read_log_state(&jsonl_path);
+    let result = refine_with_tmux(
+        state.attention(agentdeck_status, thresholds),
+        tmux_session,
+        &profile,
+    );
     log::debug!("compute_attention: result={result:?}");
     result
 }
 
+/// The latest assistant text of a local session, from its transcript.
+pub fn session_last_text(session: &Session) -> Option<String> {
+    if session.tool == "opencode" {
+        return crate::opencode_logs::read_state(
+            &session.project_path,
+            session.opencode_session_id.as_deref(),
+        )?
+        .last_text;
+    }
+    let csid = session.claude_session_id.as_deref()?;
+    let path = find_jsonl_path(&session.project_path, csid)?;
+    jsonl_cache::read_log_state(&path).last_text
+}
+
+/// Attention from a recent Claude Code hook event. Hooks are authoritative
+/// when present, but agent-deck still owns the error state.
+fn hook_attention(claude_session_id: &str, agentdeck_status: &str) -> Option<AttentionStatus> {
+    if agentdeck_status == "error" {
+        return None;
+    }
+    crate::hooks::hook_attention(claude_session_id)
+}
+
 /// Refine attention status by checking the tmux session.
 ///
-/// - `Running` + tmux shows input prompt → `NeedsInput`
+/// - `Running`/`Unknown` + tmux shows the tool's input or permission prompt → `NeedsInput`
 /// - `Idle` + tmux session alive → `NeedsInput` (DB status is stale; session is actually active)
-fn refine_with_tmux(attention: AttentionStatus, tmux_session: Option<&str>) -> AttentionStatus {
+fn refine_with_tmux(
+    attention: AttentionStatus,
+    tmux_session: Option<&str>,
+    profile: &ToolProfile,
+) -> AttentionStatus {
+    refine_with_tmux_on(None, attention, tmux_session, profile)
+}
+
+/// [`refine_with_tmux`] for a tmux session on `host` (None: this machine).
+fn refine_with_tmux_on(
+    host: Option<&SshHost>,
+    attention: AttentionStatus,
+    tmux_session: Option<&str>,
+    profile: &ToolProfile,
+) -> AttentionStatus {
     let Some(ts) = tmux_session else {
         return attention;
     };
@@ -372,35 +595,71 @@ fn refine_with_tmux(attention: AttentionStatus, tmux_session: Option<&str>) -> A
     }
 
     match attention {
-        AttentionStatus::Running => {
-            if crate::tmux::is_waiting_for_input(ts) {
-                log::debug!("refine_with_tmux: tmux check upgraded Running -> NeedsInput for {ts}");
-                return AttentionStatus::NeedsInput;
+        AttentionStatus::Running | AttentionStatus::Unknown => {
+            match crate::tmux::pane_attention_on(host, ts, profile) {
+                Some(pane) => {
+                    log::debug!(
+                        "refine_with_tmux: pane upgraded {attention:?} -> {pane:?} for {ts}"
+                    );
+                    pane
+                }
+                None => attention,
             }
         }
-        AttentionStatus::Idle => {
-            if crate::tmux::is_tmux_session_alive(ts) {
-                log::debug!(
-                    "refine_with_tmux: tmux session alive, upgraded Idle -> NeedsInput for {ts}"
-                );
-                return AttentionStatus::NeedsInput;
-            }
+        // The transcript can't tell a question from a permission prompt.
+        AttentionStatus::NeedsInput
+            if crate::tmux::pane_attention_on(host, ts, profile)
+                == Some(AttentionStatus::AwaitingPermission) =>
+        {
+            AttentionStatus::AwaitingPermission
         }
-        _ => {}
+        AttentionStatus::Idle if crate::tmux::is_tmux_session_alive(host, ts) => {
+            log::debug!(
+                "refine_with_tmux: tmux session alive, upgraded Idle -> NeedsInput for {ts}"
+            );
+            AttentionStatus::NeedsInput
+        }
+        _ => attention,
     }
-    attention
 }
 
-#[tauri::command]
-pub fn get_session_summary(
+/// Summary and attention for a Claude session's transcript.
+pub fn session_summary(
     project_path: String,
     claude_session_id: String,
     agentdeck_status: String,
     tmux_session: Option<String>,
+    thresholds: &AttentionThresholds,
+) -> SessionSummary {
+    let jsonl_path = find_jsonl_path(&project_path, &claude_session_id);
+    summary_from_log(
+        jsonl_path.as_deref(),
+        Some(&claude_session_id),
+        &agentdeck_status,
+        tmux_session.as_deref(),
+        None,
+        "claude",
+        thresholds,
+    )
+}
+
+/// Summary and attention from a Claude transcript at `jsonl_path` (None if
+/// there is none yet, or the tool isn't Claude). `host` is where the tmux
+/// session runs when it isn't this machine.
+pub(crate) fn summary_from_log(
+    jsonl_path: Option<&Path>,
+    claude_session_id: Option<&str>,
+    agentdeck_status: &str,
+    tmux_session: Option<&str>,
+    host: Option<&SshHost>,
+    tool: &str,
+    thresholds: &AttentionThresholds,
 ) -> SessionSummary {
-    let Some(jsonl_path) = find_jsonl_path(&project_path, &claude_session_id) else {
-        log::debug!("get_session_summary: no JSONL for session {claude_session_id}, using agentdeck_status={agentdeck_status}");
-        let attention = match agentdeck_status.as_str() {
+    let hook = claude_session_id.and_then(|csid| hook_attention(csid, agentdeck_status));
+    let profile = crate::tool_profile::profile_for(tool);
+    let Some(jsonl_path) = jsonl_path else {
+        log::debug!("get_session_summary: no JSONL for session {claude_session_id:?}, using agentdeck_status={agentdeck_status}");
+        let attention = match agentdeck_status {
             "running" => AttentionStatus::Running,
             // No JSONL file means no conversation yet — just the initial prompt
             "waiting" => AttentionStatus::Idle,
@@ -411,9 +670,11 @@ pub fn get_session_summary(
         return SessionSummary {
             summary: None,
             initial_prompt: None,
-            attention: refine_with_tmux(attention, tmux_session.as_deref()),
+            attention: hook
+                .unwrap_or_else(|| refine_with_tmux_on(host, attention, tmux_session, &profile)),
             last_tool: None,
             last_text: None,
+            subagents: Vec::new(),
         };
     };
 
@@ -422,26 +683,521 @@ pub fn get_session_summary(
         jsonl_path.display()
     );
 
-    // Read last 256KB of the file
-    let lines = read_tail_lines(&jsonl_path, 256 * 1024);
-    let attention = extract_attention(&lines, &agentdeck_status);
-    let final_attention = refine_with_tmux(attention, tmux_session.as_deref());
+    let state = jsonl_cache::read_log_state(jsonl_path);
+    let final_attention = hook.unwrap_or_else(|| {
+        refine_with_tmux_on(
+            host,
+            state.attention(agentdeck_status, thresholds),
+            tmux_session,
+            &profile,
+        )
+    });
 
     log::debug!(
-        "get_session_summary: attention={final_attention:?} for session {claude_session_id}"
+        "get_session_summary: attention={final_attention:?} for session {claude_session_id:?}"
     );
 
-    // Read initial prompt from the head of the file
-    let head_lines = read_head_lines(&jsonl_path, 32 * 1024);
-    let initial_prompt = extract_initial_prompt(&head_lines);
-
     SessionSummary {
-        summary: extract_summary(&lines),
-        initial_prompt,
+        summary: state.summary,
+        initial_prompt: state.initial_prompt,
         attention: final_attention,
-        last_tool: extract_last_tool(&lines),
-        last_text: extract_last_text(&lines),
+        last_tool: state.last_tool,
+        last_text: state.last_text,
+        subagents: state.subagents,
+    }
+}
+
+/// Summary of any local session, from the transcript of its tool.
+pub fn summary_for_session(session: &Session, thresholds: &AttentionThresholds) -> SessionSummary {
+    let tmux = if session.tmux_session.is_empty() {
+        None
+    } else {
+        Some(session.tmux_session.clone())
+    };
+    if session.tool == "opencode" {
+        return crate::opencode_logs::session_summary(
+            &session.project_path,
+            session.opencode_session_id.as_deref(),
+            &session.status,
+            tmux.as_deref(),
+            thresholds,
+        );
+    }
+    match &session.claude_session_id {
+        Some(csid) => session_summary(
+            session.project_path.clone(),
+            csid.clone(),
+            session.status.clone(),
+            tmux,
+            thresholds,
+        ),
+        None => SessionSummary {
+            summary: None,
+            initial_prompt: None,
+            attention: compute_attention(
+                &session.project_path,
+                None,
+                &session.status,
+                tmux.as_deref(),
+                &session.tool,
+                thresholds,
+            ),
+            last_tool: None,
+            last_text: None,
+            subagents: Vec::new(),
+        },
+    }
+}
+
+#[tauri::command]
+pub fn get_session_summary(
+    orca_db: State<'_, OrcaDb>,
+    project_path: String,
+    claude_session_id: String,
+    agentdeck_status: String,
+    tmux_session: Option<String>,
+    group_path: Option<String>,
+) -> SessionSummary {
+    let thresholds = orca_db.attention_thresholds_for(group_path.as_deref());
+    match crate::ssh::host_for(&orca_db, group_path.as_deref()) {
+        Ok(Some(host)) => {
+            return crate::backend::ssh::session_summary(
+                &host,
+                &project_path,
+                Some(claude_session_id.as_str()).filter(|csid| !csid.is_empty()),
+                &agentdeck_status,
+                tmux_session.as_deref(),
+                "claude",
+                &thresholds,
+            )
+        }
+        Ok(None) => {}
+        Err(e) => log::warn!("get_session_summary: {e}"),
     }
+    session_summary(
+        project_path,
+        claude_session_id,
+        agentdeck_status,
+        tmux_session,
+        &thresholds,
+    )
+}
+
+fn validate_thresholds(thresholds: &AttentionThresholds) -> Result<(), String> {
+    if thresholds.stale_after_secs == 0 {
+        return Err("stale_after_secs must be greater than 0".to_string());
+    }
+    if let Some(idle) = thresholds.idle_after_secs {
+        if idle == 0 || idle >= thresholds.stale_after_secs {
+            return Err("idle_after_secs must be between 1 and stale_after_secs".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// A group's attention thresholds (the defaults if it has none set).
+#[tauri::command]
+pub fn get_attention_thresholds(
+    orca_db: State<'_, OrcaDb>,
+    group_path: String,
+) -> AttentionThresholds {
+    orca_db.attention_thresholds_for(Some(&group_path))
+}
+
+/// Set a group's attention thresholds; `None` restores the defaults.
+#[tauri::command]
+pub fn set_attention_thresholds(
+    orca_db: State<'_, OrcaDb>,
+    group_path: String,
+    thresholds: Option<AttentionThresholds>,
+) -> Result<(), String> {
+    if let Some(ref thresholds) = thresholds {
+        validate_thresholds(thresholds)?;
+    }
+    orca_db.set_attention_thresholds(&group_path, thresholds.as_ref())
+}
+
+// ── File timeline ───────────────────────────────────────────────────
+
+/// Edits, patches and reasons can be long; cap what we ship to the UI.
+const MAX_TIMELINE_TEXT_CHARS: usize = 4_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileAction {
+    Read,
+    Write,
+    Edit,
+    Delete,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FileEdit {
+    pub old_string: String,
+    pub new_string: String,
+}
+
+/// One tool call touching one file.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileTouch {
+    /// Path as the tool saw it (usually absolute).
+    pub path: String,
+    /// Path relative to the session's project, if it lies inside it.
+    pub relative_path: Option<String>,
+    pub action: FileAction,
+    pub tool: String,
+    pub tool_use_id: String,
+    pub timestamp: Option<String>,
+    /// Replacements made by `Edit` / `MultiEdit`.
+    pub edits: Vec<FileEdit>,
+    /// The command, for files touched by `Bash`.
+    pub command: Option<String>,
+    /// The assistant text that preceded the call, i.e. why it was made.
+    pub reason: Option<String>,
+    /// The tool result was an error (the change may not have happened).
+    pub failed: bool,
+    /// Made by a subagent (Task tool).
+    pub is_sidechain: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileSummary {
+    pub path: String,
+    pub relative_path: Option<String>,
+    pub reads: u32,
+    pub writes: u32,
+    pub edits: u32,
+    pub deletes: u32,
+    pub first_touched: Option<String>,
+    pub last_touched: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FileTimeline {
+    /// Oldest first.
+    pub events: Vec<FileTouch>,
+    /// One entry per file, most recently touched first.
+    pub files: Vec<FileSummary>,
+}
+
+fn cap_text(text: &str) -> String {
+    text.chars().take(MAX_TIMELINE_TEXT_CHARS).collect()
+}
+
+fn relative_to(path: &str, project_path: &str) -> Option<String> {
+    if !path.starts_with('/') {
+        return Some(path.trim_start_matches("./").to_string());
+    }
+    let root = project_path.trim_end_matches('/');
+    path.strip_prefix(root)?.strip_prefix('/').map(String::from)
+}
+
+/// Split a shell command into simple commands on `&&`, `||`, `;`, `|` and
+/// newlines, and on a background `&` unless it belongs to a redirection
+/// (`2>&1`, `<&3`, `&>out`). Quoting is only handled well enough for paths.
+fn shell_segments(command: &str) -> Vec<Vec<String>> {
+    let mut segments = Vec::new();
+    let mut segment = String::new();
+    let mut chars = command.chars().peekable();
+    let mut prev = None;
+    while let Some(c) = chars.next() {
+        let separator = match c {
+            '\n' | ';' => true,
+            // `||`, and `|&` (pipe stderr too)
+            '|' => {
+                chars.next_if(|next| matches!(next, '|' | '&'));
+                true
+            }
+            '&' if chars.next_if_eq(&'&').is_some() => true,
+            '&' => !matches!(prev, Some('>' | '<')) && chars.peek() != Some(&'>'),
+            _ => false,
+        };
+        if separator {
+            segments.push(std::mem::take(&mut segment));
+        } else {
+            segment.push(c);
+        }
+        prev = Some(c);
+    }
+    segments.push(segment);
+    segments
+        .iter()
+        .map(|segment| {
+            segment
+                .split_whitespace()
+                .map(|word| word.trim_matches(|c| c == '"' || c == '\'').to_string())
+                .filter(|word| !word.is_empty())
+                .collect::<Vec<_>>()
+        })
+        .filter(|words| !words.is_empty())
+        .collect()
+}
+
+/// Best-effort file operations in a `Bash` command: `rm`, `touch`, `cp`,
+/// `mv`, `tee`, in-place `sed`/`perl` and output redirections.
+fn bash_file_touches(command: &str) -> Vec<(FileAction, String)> {
+    let mut touches = Vec::new();
+    for words in shell_segments(command) {
+        let mut args: Vec<&str> = Vec::new();
+        let mut words_iter = words.iter().map(String::as_str).peekable();
+        while let Some(word) = words_iter.next() {
+            // Redirections: `> out`, `>> out`, `>out`, `&> out`; skip fd dups
+            // like 2>&1.
+            let redirect = word.strip_prefix('&').unwrap_or(word);
+            if let Some(target) = redirect
+                .strip_prefix(">>")
+                .or_else(|| redirect.strip_prefix('>'))
+            {
+                let target = if target.is_empty() {
+                    words_iter.next().unwrap_or("")
+                } else {
+                    target
+                };
+                if !target.is_empty() && target != "/dev/null" {
+                    touches.push((FileAction::Write, target.to_string()));
+                }
+                continue;
+            }
+            if word.ends_with('>') || word.contains(">&") {
+                continue;
+            }
+            args.push(word);
+        }
+
+        // Skip `sudo` and leading VAR=value assignments.
+        let mut args = args
+            .into_iter()
+            .skip_while(|w| *w == "sudo" || w.contains('='));
+        let Some(program) = args.next() else {
+            continue;
+        };
+        let flags: Vec<&str> = args.clone().filter(|a| a.starts_with('-')).collect();
+        let operands: Vec<&str> = args.filter(|a| !a.starts_with('-')).collect();
+        let in_place = flags
+            .iter()
+            .any(|f| f.starts_with("-i") || *f == "--in-place");
+
+        let mut push = |action: FileAction, paths: &[&str]| {
+            touches.extend(paths.iter().map(|p| (action, (*p).to_string())));
+        };
+        match (program, operands.split_last()) {
+            ("rm", _) => push(FileAction::Delete, &operands),
+            ("touch" | "tee", _) => push(FileAction::Write, &operands),
+            ("cp", Some((dest, _))) => push(FileAction::Write, &[dest]),
+            ("mv", Some((dest, sources))) => {
+                push(FileAction::Delete, sources);
+                push(FileAction::Write, &[dest]);
+            }
+            // The first operand is the script.
+            ("sed" | "perl", _) if in_place && operands.len() > 1 => {
+                push(FileAction::Edit, &operands[1..]);
+            }
+            _ => {}
+        }
+    }
+    touches
+}
+
+/// Files a tool call touches, with the action and any edits.
+fn tool_file_touches(
+    name: &str,
+    input: &serde_json::Value,
+) -> Vec<(FileAction, String, Vec<FileEdit>)> {
+    let str_field = |key: &str| input.get(key).and_then(|v| v.as_str()).map(String::from);
+    let edit = |value: &serde_json::Value| FileEdit {
+        old_string: cap_text(
+            value
+                .get("old_string")
+                .and_then(|v| v.as_str())
+                .unwrap_or(""),
+        ),
+        new_string: cap_text(
+            value
+                .get("new_string")
+                .and_then(|v| v.as_str())
+                .unwrap_or(""),
+        ),
+    };
+    match name {
+        "Read" => str_field("file_path")
+            .map(|p| vec![(FileAction::Read, p, Vec::new())])
+            .unwrap_or_default(),
+        "Write" => str_field("file_path")
+            .map(|p| vec![(FileAction::Write, p, Vec::new())])
+            .unwrap_or_default(),
+        "Edit" => str_field("file_path")
+            .map(|p| vec![(FileAction::Edit, p, vec![edit(input)])])
+            .unwrap_or_default(),
+        "MultiEdit" => str_field("file_path")
+            .map(|p| {
+                let edits = input
+                    .get("edits")
+                    .and_then(|v| v.as_array())
+                    .map(|items| items.iter().map(edit).collect())
+                    .unwrap_or_default();
+                vec![(FileAction::Edit, p, edits)]
+            })
+            .unwrap_or_default(),
+        "NotebookEdit" => str_field("notebook_path")
+            .map(|p| vec![(FileAction::Edit, p, Vec::new())])
+            .unwrap_or_default(),
+        "Bash" => str_field("command")
+            .map(|command| {
+                bash_file_touches(&command)
+                    .into_iter()
+                    .map(|(action, path)| (action, path, Vec::new()))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+/// Build the file timeline from transcript lines.
+fn build_file_timeline(lines: &[serde_json::Value], project_path: &str) -> FileTimeline {
+    let mut events: Vec<FileTouch> = Vec::new();
+    let mut reason: Option<String> = None;
+
+    for line in lines {
+        let line_type = line.get("type").and_then(|v| v.as_str()).unwrap_or("");
+        if line_type != "assistant" && line_type != "user" {
+            continue;
+        }
+        let msg = line.get("message").unwrap_or(line);
+        let Some(content) = msg.get("content").and_then(|v| v.as_array()) else {
+            // A plain user prompt starts a new line of reasoning.
+            reason = None;
+            continue;
+        };
+        let timestamp = line
+            .get("timestamp")
+            .and_then(|v| v.as_str())
+            .map(String::from);
+        let is_sidechain = line
+            .get("isSidechain")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false);
+
+        for item in content {
+            match item.get("type").and_then(|v| v.as_str()) {
+                Some("text") if line_type == "assistant" => {
+                    let text = item.get("text").and_then(|v| v.as_str()).unwrap_or("");
+                    if !text.trim().is_empty() {
+                        reason = Some(cap_text(text.trim()));
+                    }
+                }
+                Some("text") => reason = None,
+                Some("tool_use") => {
+                    let name = item.get("name").and_then(|v| v.as_str()).unwrap_or("");
+                    let input = item.get("input").unwrap_or(&serde_json::Value::Null);
+                    let tool_use_id = item.get("id").and_then(|v| v.as_str()).unwrap_or("");
+                    for (action, path, edits) in tool_file_touches(name, input) {
+                        events.push(FileTouch {
+                            relative_path: relative_to(&path, project_path),
+                            path,
+                            action,
+                            tool: name.to_string(),
+                            tool_use_id: tool_use_id.to_string(),
+                            timestamp: timestamp.clone(),
+                            edits,
+                            command: (name == "Bash")
+                                .then(|| input.get("command").and_then(|v| v.as_str()))
+                                .flatten()
+                                .map(cap_text),
+                            reason: reason.clone(),
+                            failed: false,
+                            is_sidechain,
+                        });
+                    }
+                }
+                Some("tool_result") => {
+                    let is_error = item
+                        .get("is_error")
+                        .and_then(serde_json::Value::as_bool)
+                        .unwrap_or(false);
+                    let id = item.get("tool_use_id").and_then(|v| v.as_str());
+                    if is_error {
+                        for event in events.iter_mut().rev() {
+                            if Some(event.tool_use_id.as_str()) == id {
+                                event.failed = true;
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // (index of the latest event, summary), with a path -> position lookup
+    let mut files: Vec<(usize, FileSummary)> = Vec::new();
+    let mut positions: HashMap<&str, usize> = HashMap::new();
+    for (index, event) in events.iter().enumerate() {
+        let pos = match positions.get(event.path.as_str()) {
+            Some(&pos) => pos,
+            None => {
+                positions.insert(&event.path, files.len());
+                files.push((
+                    index,
+                    FileSummary {
+                        path: event.path.clone(),
+                        relative_path: event.relative_path.clone(),
+                        reads: 0,
+                        writes: 0,
+                        edits: 0,
+                        deletes: 0,
+                        first_touched: event.timestamp.clone(),
+                        last_touched: None,
+                    },
+                ));
+                files.len() - 1
+            }
+        };
+        let (last_index, summary) = &mut files[pos];
+        *last_index = index;
+        summary.last_touched = event.timestamp.clone();
+        match event.action {
+            FileAction::Read => summary.reads += 1,
+            FileAction::Write => summary.writes += 1,
+            FileAction::Edit => summary.edits += 1,
+            FileAction::Delete => summary.deletes += 1,
+        }
+    }
+    files.sort_by_key(|(last_index, _)| std::cmp::Reverse(*last_index));
+
+    FileTimeline {
+        events,
+        files: files.into_iter().map(|(_, f)| f).collect(),
+    }
+}
+
+fn read_all_lines(path: &Path) -> Result<Vec<serde_json::Value>, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {e}", path.display()))?;
+    Ok(BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect())
+}
+
+/// Files a session read, wrote, edited or deleted, in order, with the tool
+/// and the assistant's reasoning for each change.
+#[tauri::command]
+pub async fn get_session_file_timeline(
+    project_path: String,
+    claude_session_id: String,
+) -> Result<FileTimeline, String> {
+    tokio::task::spawn_blocking(move || {
+        let path = find_jsonl_path(&project_path, &claude_session_id)
+            .ok_or_else(|| format!("No transcript found for session {claude_session_id}"))?;
+        let lines = read_all_lines(&path)?;
+        Ok(build_file_timeline(
+            &lines,
+            &expand_tilde(&project_path).to_string_lossy(),
+        ))
+    })
+    .await
+    .map_err(|e| format!("Task failed: {e}"))?
 }
 
 #[cfg(test)]
@@ -449,6 +1205,22 @@ mod tests {
     use super::*;
     use serde_json::json;
 
+    fn extract_summary(lines: &[serde_json::Value]) -> Option<String> {
+        LogState::from_lines(lines).summary
+    }
+
+    fn extract_attention(lines: &[serde_json::Value], agentdeck_status: &str) -> AttentionStatus {
+        LogState::from_lines(lines).attention(agentdeck_status, &AttentionThresholds::default())
+    }
+
+    fn extract_last_text(lines: &[serde_json::Value]) -> Option<String> {
+        LogState::from_lines(lines).last_text
+    }
+
+    fn extract_last_tool(lines: &[serde_json::Value]) -> Option<String> {
+        LogState::from_lines(lines).last_tool
+    }
+
     // ── extract_summary ──
 
     #[test]
@@ -656,6 +1428,97 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn attention_thresholds_are_configurable() {
+        let ts = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs_f64()
+            - 600.0; // 10 minutes ago
+        let state = LogState::from_lines(&[
+            json!({
+                "type": "assistant",
+                "message": {"role": "assistant", "content": [{"type": "text", "text": "working"}]}
+            }),
+            json!({"type": "result", "timestamp": ts}),
+        ]);
+        let defaults = AttentionThresholds::default();
+        assert_eq!(
+            state.attention("running", &defaults),
+            AttentionStatus::Running
+        );
+
+        let idle = AttentionThresholds {
+            stale_after_secs: 3600,
+            idle_after_secs: Some(300),
+        };
+        assert_eq!(state.attention("running", &idle), AttentionStatus::Idle);
+
+        let stale = AttentionThresholds {
+            stale_after_secs: 300,
+            idle_after_secs: None,
+        };
+        assert_eq!(state.attention("running", &stale), AttentionStatus::Stale);
+    }
+
+    #[test]
+    fn attention_iso_timestamps_count_for_staleness() {
+        let lines = vec![json!({
+            "type": "assistant",
+            "timestamp": "2020-01-01T00:00:00.000Z",
+            "message": {"role": "assistant", "content": [{"type": "text", "text": "hi"}]}
+        })];
+        assert_eq!(extract_attention(&lines, "running"), AttentionStatus::Stale);
+    }
+
+    #[test]
+    fn attention_rate_limited_until_next_message() {
+        let mut lines = vec![json!({
+            "type": "assistant",
+            "isApiErrorMessage": true,
+            "message": {"role": "assistant", "content": [
+                {"type": "text", "text": "API Error: 429 {\"type\":\"rate_limit_error\"}"}
+            ]}
+        })];
+        assert_eq!(
+            extract_attention(&lines, "idle"),
+            AttentionStatus::RateLimited
+        );
+
+        lines.push(json!({
+            "type": "user",
+            "message": {"role": "user", "content": "try again"}
+        }));
+        assert_eq!(extract_attention(&lines, "idle"), AttentionStatus::Idle);
+    }
+
+    #[test]
+    fn attention_other_api_errors_are_not_rate_limits() {
+        let lines = vec![json!({
+            "type": "assistant",
+            "isApiErrorMessage": true,
+            "message": {"role": "assistant", "content": [
+                {"type": "text", "text": "API Error: 500 Internal server error"}
+            ]}
+        })];
+        assert_eq!(extract_attention(&lines, "idle"), AttentionStatus::Idle);
+    }
+
+    #[test]
+    fn priority_puts_permission_prompts_first() {
+        let mut statuses = [
+            AttentionStatus::RateLimited,
+            AttentionStatus::NeedsInput,
+            AttentionStatus::AwaitingPermission,
+            AttentionStatus::Error,
+        ];
+        statuses.sort_by_key(AttentionStatus::priority);
+        assert_eq!(statuses[0], AttentionStatus::AwaitingPermission);
+        assert_eq!(statuses[3], AttentionStatus::RateLimited);
+        assert!(statuses.iter().all(AttentionStatus::needs_action));
+        assert!(!AttentionStatus::Compacting.needs_action());
+    }
+
     // ── extract_last_text ──
 
     #[test]
@@ -725,26 +1588,88 @@ mod tests {
         assert_eq!(extract_last_tool(&lines), None);
     }
 
+    // ── LogState ──
+
+    #[test]
+    fn initial_prompt_is_first_user_text() {
+        let lines = vec![
+            json!({"type": "user", "message": {"role": "user", "content": [{"type": "text", "text": "  "}]}}),
+            json!({"type": "user", "message": {"role": "user", "content": [{"type": "text", "text": "Fix the bug"}]}}),
+            json!({"type": "user", "message": {"role": "user", "content": [{"type": "text", "text": "And the tests"}]}}),
+        ];
+        assert_eq!(
+            LogState::from_lines(&lines).initial_prompt,
+            Some("Fix the bug".into())
+        );
+    }
+
+    #[test]
+    fn user_facing_tool_cleared_by_later_message() {
+        let mut state = LogState::from_lines(&[json!({
+            "type": "assistant",
+            "message": {"role": "assistant", "content": [{"type": "tool_use", "name": "AskUserQuestion"}]}
+        })]);
+        assert!(matches!(
+            state.attention("running", &AttentionThresholds::default()),
+            AttentionStatus::NeedsInput
+        ));
+
+        state.push_line(&json!({
+            "type": "user",
+            "message": {"role": "user", "content": [{"type": "tool_result", "content": "yes"}]}
+        }));
+        assert!(matches!(
+            state.attention("running", &AttentionThresholds::default()),
+            AttentionStatus::Running
+        ));
+    }
+
+    #[test]
+    fn push_line_matches_from_lines() {
+        let lines = vec![
+            json!({"type": "user", "message": {"role": "user", "content": [{"type": "text", "text": "Go"}]}}),
+            json!({"type": "assistant", "message": {"role": "assistant", "content": [{"type": "text", "text": "On it"}, {"type": "tool_use", "name": "Bash"}]}}),
+            json!({"type": "summary", "summary": "Working"}),
+        ];
+        let mut incremental = LogState::default();
+        for line in &lines {
+            incremental.push_line(line);
+        }
+        let batch = LogState::from_lines(&lines);
+        assert_eq!(incremental.summary, batch.summary);
+        assert_eq!(incremental.initial_prompt, batch.initial_prompt);
+        assert_eq!(incremental.last_tool, Some("Bash".into()));
+        assert_eq!(incremental.last_text, Some("On it".into()));
+    }
+
     // ── refine_with_tmux ──
 
+    fn claude() -> ToolProfile {
+        crate::tool_profile::profile_for("claude")
+    }
+
     #[test]
     fn refine_with_tmux_no_session() {
         // No tmux session — status unchanged
-        let result = refine_with_tmux(AttentionStatus::Idle, None);
+        let result = refine_with_tmux(AttentionStatus::Idle, None, &claude());
         assert!(matches!(result, AttentionStatus::Idle));
     }
 
     #[test]
     fn refine_with_tmux_empty_session() {
         // Empty tmux session string — status unchanged
-        let result = refine_with_tmux(AttentionStatus::Idle, Some(""));
+        let result = refine_with_tmux(AttentionStatus::Idle, Some(""), &claude());
         assert!(matches!(result, AttentionStatus::Idle));
     }
 
     #[test]
     fn refine_with_tmux_nonexistent_session_idle() {
         // Non-existent tmux session — is_tmux_session_alive returns false, stays Idle
-        let result = refine_with_tmux(AttentionStatus::Idle, Some("nonexistent-session-xyz-999"));
+        let result = refine_with_tmux(
+            AttentionStatus::Idle,
+            Some("nonexistent-session-xyz-999"),
+            &claude(),
+        );
         assert!(matches!(result, AttentionStatus::Idle));
     }
 
@@ -754,6 +1679,7 @@ mod tests {
         let result = refine_with_tmux(
             AttentionStatus::Running,
             Some("nonexistent-session-xyz-999"),
+            &claude(),
         );
         assert!(matches!(result, AttentionStatus::Running));
     }
@@ -762,20 +1688,226 @@ mod tests {
     fn refine_with_tmux_preserves_other_statuses() {
         // Error, Stale, NeedsInput, Unknown should pass through unchanged
         assert!(matches!(
-            refine_with_tmux(AttentionStatus::Error, Some("any")),
+            refine_with_tmux(AttentionStatus::Error, Some("any"), &claude()),
             AttentionStatus::Error
         ));
         assert!(matches!(
-            refine_with_tmux(AttentionStatus::Stale, Some("any")),
+            refine_with_tmux(AttentionStatus::Stale, Some("any"), &claude()),
             AttentionStatus::Stale
         ));
         assert!(matches!(
-            refine_with_tmux(AttentionStatus::NeedsInput, Some("any")),
+            refine_with_tmux(AttentionStatus::NeedsInput, Some("any"), &claude()),
             AttentionStatus::NeedsInput
         ));
         assert!(matches!(
-            refine_with_tmux(AttentionStatus::Unknown, Some("any")),
+            refine_with_tmux(AttentionStatus::Unknown, Some("any"), &claude()),
             AttentionStatus::Unknown
         ));
     }
+
+    // ── file timeline ──
+
+    #[test]
+    fn bash_touches_common_file_ops() {
+        let touches = bash_file_touches("rm -f a.txt && mv old.rs new.rs; echo hi > out.log 2>&1");
+        assert_eq!(
+            touches,
+            vec![
+                (FileAction::Delete, "a.txt".to_string()),
+                (FileAction::Delete, "old.rs".to_string()),
+                (FileAction::Write, "new.rs".to_string()),
+                (FileAction::Write, "out.log".to_string()),
+            ]
+        );
+        assert_eq!(
+            bash_file_touches("sed -i 's/a/b/' src/lib.rs | cat"),
+            vec![(FileAction::Edit, "src/lib.rs".to_string())]
+        );
+        assert!(bash_file_touches("cargo test > /dev/null").is_empty());
+        assert!(bash_file_touches("sed 's/a/b/' src/lib.rs").is_empty());
+        assert_eq!(
+            bash_file_touches("make &> build.log & touch done.txt"),
+            vec![
+                (FileAction::Write, "build.log".to_string()),
+                (FileAction::Write, "done.txt".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn shell_segments_keep_redirections_whole() {
+        assert_eq!(
+            shell_segments("cargo build 2>&1 | tee out.log"),
+            vec![vec!["cargo", "build", "2>&1"], vec!["tee", "out.log"]]
+        );
+        assert_eq!(
+            shell_segments("a && b || c; d |& e\nf & g >&2"),
+            vec![
+                vec!["a"],
+                vec!["b"],
+                vec!["c"],
+                vec!["d"],
+                vec!["e"],
+                vec!["f"],
+                vec!["g", ">&2"],
+            ]
+        );
+        assert_eq!(
+            shell_segments("run &>> all.log"),
+            vec![vec!["run", "&>>", "all.log"]]
+        );
+    }
+
+    #[test]
+    fn relative_to_project() {
+        assert_eq!(
+            relative_to("/src/app/billing/invoice.rs", "/src/app/"),
+            Some("billing/invoice.rs".into())
+        );
+        assert_eq!(relative_to("/src/application/x.rs", "/src/app"), None);
+        assert_eq!(relative_to("./x.rs", "/src/app"), Some("x.rs".into()));
+    }
+
+    #[test]
+    fn file_timeline_tracks_edits_reasons_and_failures() {
+        let lines = vec![
+            json!({"type": "user", "message": {"role": "user", "content": "Fix invoices"}}),
+            json!({"type": "assistant", "timestamp": "2025-01-01T10:00:00Z", "message": {"role": "assistant", "content": [
+                {"type": "text", "text": "Rounding is wrong, fixing it"},
+                {"type": "tool_use", "id": "t1", "name": "Read", "input": {"file_path": "/src/app/invoice.rs"}},
+                {"type": "tool_use", "id": "t2", "name": "MultiEdit", "input": {
+                    "file_path": "/src/app/invoice.rs",
+                    "edits": [{"old_string": "round()", "new_string": "round_half_even()"}]
+                }}
+            ]}}),
+            json!({"type": "user", "message": {"role": "user", "content": [
+                {"type": "tool_result", "tool_use_id": "t2", "is_error": true, "content": "no match"}
+            ]}}),
+            json!({"type": "assistant", "timestamp": "2025-01-01T10:01:00Z", "isSidechain": true, "message": {"role": "assistant", "content": [
+                {"type": "tool_use", "id": "t3", "name": "Write", "input": {"file_path": "/src/app/notes.md", "content": "x"}}
+            ]}}),
+        ];
+        let timeline = build_file_timeline(&lines, "/src/app");
+        assert_eq!(timeline.events.len(), 3);
+
+        let edit = &timeline.events[1];
+        assert_eq!(edit.action, FileAction::Edit);
+        assert_eq!(edit.tool, "MultiEdit");
+        assert_eq!(edit.relative_path.as_deref(), Some("invoice.rs"));
+        assert_eq!(edit.reason.as_deref(), Some("Rounding is wrong, fixing it"));
+        assert_eq!(edit.edits[0].new_string, "round_half_even()");
+        assert!(edit.failed);
+        assert!(!timeline.events[0].failed);
+
+        let write = &timeline.events[2];
+        assert!(write.is_sidechain);
+        assert_eq!(write.timestamp.as_deref(), Some("2025-01-01T10:01:00Z"));
+
+        // Most recently touched first
+        assert_eq!(timeline.files.len(), 2);
+        assert_eq!(timeline.files[0].path, "/src/app/notes.md");
+        let invoice = &timeline.files[1];
+        assert_eq!((invoice.reads, invoice.edits), (1, 1));
+        assert_eq!(
+            invoice.first_touched.as_deref(),
+            Some("2025-01-01T10:00:00Z")
+        );
+    }
+
+    // ── subagents ──
+
+    fn task_call(id: &str, description: &str) -> serde_json::Value {
+        json!({"type": "assistant", "timestamp": "2025-01-01T10:00:00Z", "message": {"role": "assistant", "content": [
+            {"type": "tool_use", "id": id, "name": "Task",
+             "input": {"description": description, "subagent_type": "general-purpose", "prompt": "..."}}
+        ]}})
+    }
+
+    fn sidechain_tool(name: &str) -> serde_json::Value {
+        json!({"type": "assistant", "isSidechain": true, "message": {"role": "assistant", "content": [
+            {"type": "tool_use", "id": "s1", "name": name, "input": {}}
+        ]}})
+    }
+
+    #[test]
+    fn subagent_tracks_status_and_tools() {
+        let lines = vec![
+            json!({"type": "user", "message": {"role": "user", "content": [{"type": "text", "text": "Audit the code"}]}}),
+            task_call("t1", "Find call sites"),
+            json!({"type": "user", "isSidechain": true, "message": {"role": "user", "content": [{"type": "text", "text": "Subagent prompt"}]}}),
+            sidechain_tool("Grep"),
+        ];
+        let state = LogState::from_lines(&lines);
+        assert_eq!(state.initial_prompt, Some("Audit the code".into()));
+        assert_eq!(state.last_tool, Some("Task".into()));
+        assert_eq!(state.subagents.len(), 1);
+        let agent = &state.subagents[0];
+        assert_eq!(agent.description.as_deref(), Some("Find call sites"));
+        assert_eq!(agent.subagent_type.as_deref(), Some("general-purpose"));
+        assert_eq!(agent.status, SubagentStatus::Running);
+        assert_eq!(agent.last_tool.as_deref(), Some("Grep"));
+        assert_eq!(agent.started_at.as_deref(), Some("2025-01-01T10:00:00Z"));
+        // agent-deck may report idle while the main thread waits on Task
+        assert_eq!(
+            state.attention("idle", &AttentionThresholds::default()),
+            AttentionStatus::Running
+        );
+
+        let mut lines = lines;
+        lines.push(
+            json!({"type": "user", "message": {"role": "user", "content": [
+                {"type": "tool_result", "tool_use_id": "t1", "content": "done"}
+            ]}}),
+        );
+        let state = LogState::from_lines(&lines);
+        assert_eq!(state.subagents[0].status, SubagentStatus::Completed);
+        assert_eq!(
+            state.attention("idle", &AttentionThresholds::default()),
+            AttentionStatus::Idle
+        );
+    }
+
+    #[test]
+    fn sidechain_does_not_set_main_last_tool_or_text() {
+        let lines = vec![
+            json!({"type": "assistant", "message": {"role": "assistant", "content": [{"type": "text", "text": "Delegating"}]}}),
+            task_call("t1", "Search"),
+            json!({"type": "assistant", "isSidechain": true, "message": {"role": "assistant", "content": [
+                {"type": "text", "text": "Subagent chatter"},
+                {"type": "tool_use", "id": "s1", "name": "AskUserQuestion", "input": {}}
+            ]}}),
+        ];
+        let state = LogState::from_lines(&lines);
+        assert_eq!(state.last_text, Some("Delegating".into()));
+        assert_eq!(state.last_tool, Some("Task".into()));
+        assert_eq!(
+            state.attention("running", &AttentionThresholds::default()),
+            AttentionStatus::Running
+        );
+    }
+
+    #[test]
+    fn failed_subagent_and_cap() {
+        let mut lines: Vec<_> = (0..MAX_SUBAGENTS + 5)
+            .flat_map(|i| {
+                let id = format!("t{i}");
+                [
+                    task_call(&id, "job"),
+                    json!({"type": "user", "message": {"role": "user", "content": [
+                        {"type": "tool_result", "tool_use_id": id, "is_error": i == 0, "content": "x"}
+                    ]}}),
+                ]
+            })
+            .collect();
+        lines.insert(1, sidechain_tool("Read"));
+        let state = LogState::from_lines(&lines[..2]);
+        assert_eq!(state.subagents[0].last_tool.as_deref(), Some("Read"));
+
+        let state = LogState::from_lines(&lines);
+        assert_eq!(state.subagents.len(), MAX_SUBAGENTS);
+        assert!(state
+            .subagents
+            .iter()
+            .all(|a| a.status == SubagentStatus::Completed));
+    }
 }
@@ -128,12 +128,22 @@ pub async fn cr_get_status(server_url: String, token: String) -> Result<CrStatus
 
 #[tauri::command]
 pub async fn cr_subscribe_events(
-    app: tauri::AppHandle,
+    app: crate::AppHandle,
     handles: tauri::State<'_, remote_common::SseHandles>,
     server_url: String,
     token: String,
 ) -> Result<(), String> {
-    let client = build_client(&token)?;
-    let url = format!("{}/events", remote_common::normalize_url(&server_url));
-    remote_common::subscribe_sse(&app, &handles, &client, &url, "cr-event").await
+    subscribe_events(&app, &handles, &server_url, &token).await
+}
+
+/// Start streaming AgentAPI's SSE feed as "cr-event" Tauri events.
+pub async fn subscribe_events(
+    app: &crate::AppHandle,
+    handles: &remote_common::SseHandles,
+    server_url: &str,
+    token: &str,
+) -> Result<(), String> {
+    let client = build_client(token)?;
+    let url = format!("{}/events", remote_common::normalize_url(server_url));
+    remote_common::subscribe_sse(app, handles, &client, &url, "cr-event").await
 }
@@ -0,0 +1,595 @@
+//! `orca-cli`, the command-line companion to the app.
+//!
+//! Runs the same agent-deck, git and tmux code as the app's commands against
+//! the same databases, so sessions created here show up in the app (and the
+//! other way round). The app doesn't need to be running. Sessions go through
+//! their group's backend, and git runs on the group's SSH host if it has one.
+
+use crate::agentdeck;
+use crate::backend::{self, backend_for_group, CreateSessionParams};
+use crate::claude_logs::AttentionStatus;
+use crate::git;
+use crate::hooks;
+use crate::mcp;
+use crate::models::Session;
+use crate::orca_db::OrcaDb;
+use crate::ssh;
+use crate::tool_profile;
+use serde::Serialize;
+use std::io::Read;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: orca-cli <command> [options]
+
+Commands:
+  list [--group GROUP] [--json]
+      List sessions.
+  new --group GROUP [--branch BRANCH] [--title TITLE] [--prompt TEXT]
+      [--tool TOOL] [--path DIR] [--existing-branch] [--no-start] [--json]
+      Create a session, in a new worktree for BRANCH when given, and send
+      it the prompt once the tool is ready.
+  send SESSION TEXT
+      Send TEXT to the session as a prompt.
+  attention [--json]
+      List sessions that need action, most urgent first.
+  diff SESSION
+      Print the session's changes against the default branch.
+  merge SESSION [--remove]
+      Rebase the session's branch onto the default branch and merge it.
+      --remove deletes the session and its worktree afterwards.
+  mcp
+      Serve MCP over stdin/stdout, for agents that orchestrate Orca
+      (e.g. `claude mcp add orca -- orca-cli mcp`).
+
+GROUP is a group path or name. SESSION is a session ID, a unique ID prefix
+or a unique title. Use - as TEXT to read it from stdin.
+
+The command is orca-cli rather than orca, which is the app's own binary.";
+
+/// Flags that take a value; all others are switches.
+const VALUE_FLAGS: &[&str] = &["group", "branch", "title", "prompt", "tool", "path"];
+const SWITCHES: &[&str] = &["json", "no-start", "existing-branch", "remove", "help"];
+
+enum CliError {
+    Usage(String),
+    Failed(String),
+}
+
+impl From<String> for CliError {
+    fn from(message: String) -> Self {
+        CliError::Failed(message)
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Args {
+    command: Option<String>,
+    positional: Vec<String>,
+    flags: Vec<(String, Option<String>)>,
+}
+
+impl Args {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Args, String> {
+        let mut parsed = Args::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let Some(flag) = arg.strip_prefix("--") else {
+                if parsed.command.is_none() {
+                    parsed.command = Some(arg);
+                } else {
+                    parsed.positional.push(arg);
+                }
+                continue;
+            };
+            let (name, inline) = match flag.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (flag, None),
+            };
+            if VALUE_FLAGS.contains(&name) {
+                let value = match inline {
+                    Some(value) => value,
+                    None => args
+                        .next()
+                        .ok_or_else(|| format!("--{name} needs a value"))?,
+                };
+                parsed.flags.push((name.to_string(), Some(value)));
+            } else if SWITCHES.contains(&name) && inline.is_none() {
+                parsed.flags.push((name.to_string(), None));
+            } else {
+                return Err(format!("Unknown option: --{flag}"));
+            }
+        }
+        Ok(parsed)
+    }
+
+    fn value(&self, name: &str) -> Option<&str> {
+        self.flags
+            .iter()
+            .rev()
+            .find(|(n, _)| n == name)
+            .and_then(|(_, v)| v.as_deref())
+    }
+
+    fn switch(&self, name: &str) -> bool {
+        self.flags.iter().any(|(n, _)| n == name)
+    }
+
+    fn expect_positional(&self, count: usize) -> Result<(), CliError> {
+        if self.positional.len() == count {
+            Ok(())
+        } else {
+            Err(CliError::Usage(format!(
+                "expected {count} argument(s), got {}",
+                self.positional.len()
+            )))
+        }
+    }
+}
+
+/// Entry point for the `orca-cli` binary.
+pub fn main() -> ExitCode {
+    crate::command::init_path();
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => return usage_error(&e),
+    };
+    if args.switch("help") || args.command.as_deref().map_or(true, |c| c == "help") {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(CliError::Usage(message)) => usage_error(&message),
+        Err(CliError::Failed(message)) => {
+            eprintln!("orca-cli: {message}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn usage_error(message: &str) -> ExitCode {
+    eprintln!("orca-cli: {message}\n\n{USAGE}");
+    ExitCode::from(2)
+}
+
+fn open_db() -> Result<OrcaDb, String> {
+    let data_dir = crate::data_dir().ok_or("Cannot determine Orca's data directory")?;
+    let orca_db = OrcaDb::init(&data_dir)?;
+    tool_profile::reload(&orca_db)?;
+    hooks::load(&data_dir, &orca_db)?;
+    if let Err(e) = ssh::init(&data_dir) {
+        log::warn!("{e}");
+    }
+    Ok(orca_db)
+}
+
+fn run(args: &Args) -> Result<(), CliError> {
+    let command = args.command.as_deref().unwrap_or_default();
+    if !["list", "new", "send", "attention", "diff", "merge", "mcp"].contains(&command) {
+        return Err(CliError::Usage(format!("unknown command '{command}'")));
+    }
+    let orca_db = open_db()?;
+    match command {
+        "list" => list(&orca_db, args),
+        "new" => new_session(&orca_db, args),
+        "send" => send(&orca_db, args),
+        "attention" => attention(&orca_db, args),
+        "diff" => diff(&orca_db, args),
+        "mcp" => Ok(mcp::serve_stdio(&orca_db)?),
+        _ => merge(&orca_db, args),
+    }
+}
+
+/// `-` reads the text from stdin.
+fn text_arg(text: &str) -> Result<String, String> {
+    if text != "-" {
+        return Ok(text.to_string());
+    }
+    let mut input = String::new();
+    std::io::stdin()
+        .read_to_string(&mut input)
+        .map_err(|e| format!("Failed to read stdin: {e}"))?;
+    Ok(input.trim_end().to_string())
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    println!("{json}");
+    Ok(())
+}
+
+/// Print rows as left-aligned columns, the last one unpadded.
+fn print_table(rows: &[Vec<String>]) {
+    let columns = rows.first().map_or(0, Vec::len);
+    let widths: Vec<usize> = (0..columns)
+        .map(|i| rows.iter().map(|r| r[i].chars().count()).max().unwrap_or(0))
+        .collect();
+    for row in rows {
+        let mut line = String::new();
+        for (i, cell) in row.iter().enumerate() {
+            if i + 1 == columns {
+                line.push_str(cell);
+            } else {
+                line.push_str(&format!("{cell:<width$}  ", width = widths[i]));
+            }
+        }
+        println!("{}", line.trim_end());
+    }
+}
+
+fn short_id(id: &str) -> String {
+    id.chars().take(8).collect()
+}
+
+fn attention_name(attention: &AttentionStatus) -> String {
+    serde_json::to_value(attention)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// Find a session by ID, unique ID prefix or unique title.
+fn find_session<'a>(sessions: &'a [Session], query: &str) -> Result<&'a Session, String> {
+    if let Some(session) = sessions.iter().find(|s| s.id == query) {
+        return Ok(session);
+    }
+    let mut matches: Vec<&Session> = sessions
+        .iter()
+        .filter(|s| s.id.starts_with(query))
+        .collect();
+    if matches.is_empty() {
+        matches = sessions.iter().filter(|s| s.title == query).collect();
+    }
+    match matches.as_slice() {
+        [session] => Ok(session),
+        [] => Err(format!("No session matches '{query}'")),
+        several => Err(format!(
+            "'{query}' matches {} sessions: {}",
+            several.len(),
+            several
+                .iter()
+                .map(|s| format!("{} ({})", short_id(&s.id), s.title))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+/// Find a session in any group, SSH and remote-server groups included.
+fn resolve_session(orca_db: &OrcaDb, query: &str) -> Result<Session, String> {
+    let sessions = backend::list_sessions_blocking(orca_db, None)?;
+    find_session(&sessions, query).cloned()
+}
+
+/// A session's worktree and the repo it belongs to.
+fn worktree_of(session: &Session) -> Result<(String, String), String> {
+    if session.worktree_path.is_empty() || session.worktree_branch.is_empty() {
+        return Err(format!("Session '{}' is not in a worktree", session.title));
+    }
+    let repo = if session.worktree_repo.is_empty() {
+        session.project_path.clone()
+    } else {
+        session.worktree_repo.clone()
+    };
+    Ok((session.worktree_path.clone(), repo))
+}
+
+fn list(orca_db: &OrcaDb, args: &Args) -> Result<(), CliError> {
+    args.expect_positional(0)?;
+    let group = match args.value("group") {
+        Some(group) => Some(agentdeck::find_group(orca_db, group)?.path),
+        None => None,
+    };
+    let sessions = backend::list_sessions_blocking(orca_db, group.as_deref())?;
+    if args.switch("json") {
+        return Ok(print_json(&sessions)?);
+    }
+    let mut rows = vec![vec![
+        "ID".to_string(),
+        "STATUS".to_string(),
+        "GROUP".to_string(),
+        "BRANCH".to_string(),
+        "TITLE".to_string(),
+    ]];
+    rows.extend(sessions.iter().map(|s| {
+        vec![
+            short_id(&s.id),
+            s.status.clone(),
+            s.group_path.clone(),
+            s.worktree_branch.clone(),
+            s.title.clone(),
+        ]
+    }));
+    print_table(&rows);
+    Ok(())
+}
+
+fn new_session(orca_db: &OrcaDb, args: &Args) -> Result<(), CliError> {
+    args.expect_positional(0)?;
+    let group_query = args
+        .value("group")
+        .ok_or_else(|| CliError::Usage("new needs --group".to_string()))?;
+    let group = agentdeck::find_group(orca_db, group_query)?;
+    let project_path = match args.value("path") {
+        Some(path) => path.to_string(),
+        None if group.default_path.is_empty() => {
+            return Err(format!("Group '{}' has no default path; pass --path", group.name).into())
+        }
+        None => group.default_path.clone(),
+    };
+    let branch = args.value("branch").map(str::to_string);
+    let title = args
+        .value("title")
+        .map(str::to_string)
+        .or_else(|| branch.clone())
+        .unwrap_or_else(|| "session".to_string());
+    let tool = args.value("tool").map(str::to_string);
+    let prompt = args.value("prompt").map(text_arg).transpose()?;
+    let start = !args.switch("no-start");
+
+    let backend = backend_for_group(orca_db, &group.path)?;
+    // agent-deck backends (local and SSH) send the prompt from a background
+    // thread that would die with this process, so it's sent below instead.
+    let uses_tmux = matches!(
+        orca_db.get_group_backend(&group.path)?.as_str(),
+        "local" | "ssh"
+    );
+    let session_id = backend::block_on(backend.create_session(CreateSessionParams {
+        group_path: group.path.clone(),
+        project_path,
+        title,
+        tool: tool.clone(),
+        new_branch: branch.is_some() && !args.switch("existing-branch"),
+        worktree_branch: branch,
+        start: Some(start),
+        prompt: if uses_tmux { None } else { prompt.clone() },
+        components: None,
+    }))?;
+    let session = backend::block_on(backend.list_sessions())?
+        .into_iter()
+        .find(|s| s.id == session_id)
+        .ok_or_else(|| format!("Created session {session_id}, but it isn't listed"))?;
+    if uses_tmux {
+        agentdeck::store_session_prompt(orca_db, &session_id, prompt.as_deref());
+        if let Some(prompt) = prompt.filter(|p| start && !p.trim().is_empty()) {
+            let host = ssh::host_for(orca_db, Some(&group.path))?;
+            let profile = tool_profile::profile_for(tool.as_deref().unwrap_or("claude"));
+            agentdeck::send_prompt_to_tmux(
+                host.as_ref(),
+                &session.tmux_session,
+                &prompt,
+                &profile,
+            )?;
+        }
+    }
+
+    if args.switch("json") {
+        print_json(&session)?;
+    } else {
+        println!("{session_id}");
+    }
+    Ok(())
+}
+
+fn send(orca_db: &OrcaDb, args: &Args) -> Result<(), CliError> {
+    args.expect_positional(2)?;
+    let session = resolve_session(orca_db, &args.positional[0])?;
+    let text = text_arg(&args.positional[1])?;
+    if text.trim().is_empty() {
+        return Err(CliError::Usage("TEXT must not be empty".to_string()));
+    }
+    let backend_name = orca_db.get_group_backend(&session.group_path)?;
+    if matches!(backend_name.as_str(), "local" | "ssh") && session.tmux_session.is_empty() {
+        return Err(format!(
+            "Session '{}' has no tmux session; start it first",
+            session.title
+        )
+        .into());
+    }
+    let backend = backend_for_group(orca_db, &session.group_path)?;
+    backend::block_on(backend.send_message(&session.id, &text))?;
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct AttentionEntry<'a> {
+    attention: &'a AttentionStatus,
+    #[serde(flatten)]
+    session: &'a Session,
+}
+
+fn attention(orca_db: &OrcaDb, args: &Args) -> Result<(), CliError> {
+    args.expect_positional(0)?;
+    let sessions = agentdeck::get_attention_sessions_with_status(orca_db)?;
+    if args.switch("json") {
+        let entries: Vec<AttentionEntry> = sessions
+            .iter()
+            .map(|(attention, session)| AttentionEntry { attention, session })
+            .collect();
+        return Ok(print_json(&entries)?);
+    }
+    let mut rows = vec![vec![
+        "ID".to_string(),
+        "ATTENTION".to_string(),
+        "GROUP".to_string(),
+        "TITLE".to_string(),
+    ]];
+    rows.extend(sessions.iter().map(|(attention, s)| {
+        vec![
+            short_id(&s.id),
+            attention_name(attention),
+            s.group_path.clone(),
+            s.title.clone(),
+        ]
+    }));
+    print_table(&rows);
+    Ok(())
+}
+
+fn diff(orca_db: &OrcaDb, args: &Args) -> Result<(), CliError> {
+    args.expect_positional(1)?;
+    let session = resolve_session(orca_db, &args.positional[0])?;
+    let (worktree, _) = worktree_of(&session)?;
+    let host = ssh::host_for(orca_db, Some(&session.group_path))?;
+    print!("{}", git::get_branch_diff_on(host.as_ref(), &worktree)?);
+    Ok(())
+}
+
+/// The app's merge flow: rebase onto the local default branch, then merge in
+/// the default branch's worktree with the group's merge strategy.
+fn merge(orca_db: &OrcaDb, args: &Args) -> Result<(), CliError> {
+    args.expect_positional(1)?;
+    let session = resolve_session(orca_db, &args.positional[0])?;
+    let (worktree, repo) = worktree_of(&session)?;
+    let branch = &session.worktree_branch;
+    let host = ssh::host_for(orca_db, Some(&session.group_path))?;
+    let host = host.as_ref();
+    let target = git::get_default_branch_on(host, &repo)?;
+
+    let rebase = git::rebase_branch_on(host, &worktree, &target, false)?;
+    if !rebase.success {
+        return Err(format!(
+            "Rebasing {branch} onto {target} hit conflicts:\n{}\n\nResolve them in {worktree} \
+             and run `git rebase --continue`, or `git rebase --abort`.",
+            rebase.conflict_message.unwrap_or_default()
+        )
+        .into());
+    }
+    let workflow = orca_db
+        .get_all_group_settings()?
+        .remove(&session.group_path)
+        .map(|settings| settings.merge_workflow)
+        .unwrap_or_default();
+    let strategy = git::MergeStrategy::from_workflow(&workflow);
+    let message = (strategy == git::MergeStrategy::Squash).then(|| {
+        let commits =
+            git::branch_commit_subjects_on(host, &repo, &target, branch).unwrap_or_default();
+        let commits: Vec<&str> = commits.iter().map(String::as_str).collect();
+        git::squash_message(branch, session.prompt.as_deref(), None, &commits)
+    });
+    let result = git::try_merge_branch_on(
+        host,
+        &repo,
+        branch,
+        Some(&target),
+        strategy,
+        message.as_deref(),
+    )?;
+    if !result.success && !result.conflicted {
+        return Err(format!(
+            "Merging {branch} into {target} failed and was rolled back:\n{}",
+            result.conflict_message.unwrap_or_default()
+        )
+        .into());
+    }
+    if !result.success {
+        return Err(format!(
+            "Merging {branch} into {target} hit conflicts:\n{}\n\nResolve them in {} and \
+             commit, or run `git merge --abort` there.",
+            result.conflict_message.unwrap_or_default(),
+            result.main_worktree_path
+        )
+        .into());
+    }
+    println!("Merged {branch} into {target}");
+
+    if args.switch("remove") {
+        git::remove_worktree_on(host, &repo, &worktree)?;
+        let backend = backend_for_group(orca_db, &session.group_path)?;
+        backend::block_on(backend.delete_session(&session.id))?;
+        println!("Removed session '{}' and its worktree", session.title);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, String> {
+        Args::parse(args.iter().map(|a| (*a).to_string()))
+    }
+
+    fn session(id: &str, title: &str) -> Session {
+        Session {
+            id: id.into(),
+            title: title.into(),
+            project_path: "/repo".into(),
+            group_path: "repo".into(),
+            sort_order: 0,
+            status: "idle".into(),
+            tmux_session: String::new(),
+            created_at: 0,
+            last_accessed: 0,
+            worktree_path: String::new(),
+            worktree_repo: String::new(),
+            worktree_branch: String::new(),
+            tool: "claude".into(),
+            claude_session_id: None,
+            opencode_session_id: None,
+            prompt: None,
+            pr_url: None,
+            pr_number: None,
+            pr_state: None,
+        }
+    }
+
+    #[test]
+    fn parses_commands_flags_and_switches() {
+        let args = parse(&[
+            "new",
+            "--group",
+            "orca",
+            "--branch=fix-login",
+            "--prompt",
+            "Fix the --help flag",
+            "--no-start",
+        ])
+        .unwrap();
+        assert_eq!(args.command.as_deref(), Some("new"));
+        assert_eq!(args.value("group"), Some("orca"));
+        assert_eq!(args.value("branch"), Some("fix-login"));
+        assert_eq!(args.value("prompt"), Some("Fix the --help flag"));
+        assert!(args.switch("no-start"));
+        assert!(!args.switch("json"));
+
+        let send = parse(&["send", "abc", "hello there"]).unwrap();
+        assert_eq!(send.positional, ["abc", "hello there"]);
+
+        assert!(parse(&["new", "--group"]).is_err());
+        assert!(parse(&["list", "--verbose"]).is_err());
+        assert!(parse(&["list", "--json=yes"]).is_err());
+    }
+
+    #[test]
+    fn finds_sessions_by_id_prefix_or_title() {
+        let sessions = [
+            session("a1b2c3", "fix-login"),
+            session("a1ffff", "docs"),
+            session("b00000", "a1"),
+        ];
+        assert_eq!(
+            find_session(&sessions, "a1b2c3").unwrap().title,
+            "fix-login"
+        );
+        assert_eq!(find_session(&sessions, "a1b").unwrap().title, "fix-login");
+        assert_eq!(find_session(&sessions, "docs").unwrap().id, "a1ffff");
+        // An ID prefix wins over a title.
+        let ambiguous = find_session(&sessions, "a1").unwrap_err();
+        assert!(ambiguous.contains("matches 2 sessions"), "{ambiguous}");
+        assert!(find_session(&sessions, "zzz").is_err());
+    }
+
+    #[test]
+    fn worktree_of_falls_back_to_the_project_path() {
+        let mut s = session("a", "plain");
+        assert!(worktree_of(&s).is_err());
+        s.worktree_path = "/repo/fix".into();
+        s.worktree_branch = "fix".into();
+        assert_eq!(
+            worktree_of(&s).unwrap(),
+            ("/repo/fix".to_string(), "/repo".to_string())
+        );
+    }
+}
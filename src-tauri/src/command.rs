@@ -1,6 +1,7 @@
+use crate::ssh::SshHost;
 use serde::Serialize;
 use std::path::PathBuf;
-use std::process::Command;
+use std::process::{Command, Output};
 
 #[derive(Serialize)]
 pub struct PrerequisiteStatus {
@@ -9,30 +10,42 @@ pub struct PrerequisiteStatus {
     pub required: bool,
 }
 
+fn binary_found(binary: &str, args: &[String]) -> bool {
+    Command::new(binary)
+        .args(args)
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
 /// Check whether external binaries that Orca depends on are available.
+///
+/// Agent CLIs come from the tool profile registry: required tools are always
+/// checked, and so are tools the user added. Optional built-in tools
+/// (Codex, Aider, ...) are only used when a session asks for them.
 #[tauri::command]
 pub fn check_prerequisites() -> Vec<PrerequisiteStatus> {
     let checks: &[(&str, &[&str], bool)] = &[
-        ("claude", &["--version"], true),
         ("agent-deck", &["version"], true),
         ("tmux", &["-V"], true),
         ("git", &["--version"], false),
         ("gh", &["--version"], false),
     ];
-
-    checks
-        .iter()
-        .map(|(name, args, required)| {
-            let found = Command::new(name)
-                .args(*args)
-                .output()
-                .map(|o| o.status.success())
-                .unwrap_or(false);
-            PrerequisiteStatus {
-                name: name.to_string(),
-                found,
-                required: *required,
-            }
+    let tools = crate::tool_profile::profiles()
+        .into_iter()
+        .filter(|p| p.required || !p.builtin)
+        .map(|p| (p.binary, p.version_args, p.required));
+    let core = checks.iter().map(|(name, args, required)| {
+        let args: Vec<String> = args.iter().map(|a| (*a).to_string()).collect();
+        ((*name).to_string(), args, *required)
+    });
+
+    tools
+        .chain(core)
+        .map(|(name, args, required)| PrerequisiteStatus {
+            found: binary_found(&name, &args),
+            name,
+            required,
         })
         .collect()
 }
@@ -47,6 +60,11 @@ pub fn expand_tilde(path: &str) -> PathBuf {
     PathBuf::from(path)
 }
 
+/// Quote a string for a POSIX shell.
+pub fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
 /// Inherit the user's shell PATH for macOS GUI apps.
 ///
 /// GUI apps on macOS don't inherit the shell PATH, so commands like
@@ -82,15 +100,48 @@ pub fn new_command(program: &str) -> Command {
 
 /// Run a command in a directory, returning stdout on success or an error on non-zero exit.
 pub fn run_cmd(program: &str, cwd: &str, args: &[&str]) -> Result<String, String> {
-    let expanded = expand_tilde(cwd);
-    let cwd_str = expanded.to_string_lossy();
-    log::info!("{program} {} (cwd: {cwd_str})", args.join(" "));
+    run_cmd_on(None, program, cwd, args)
+}
 
-    let output = new_command(program)
-        .current_dir(cwd_str.as_ref())
-        .args(args)
-        .output()
-        .map_err(|e| format!("Failed to run {program}: {e}"))?;
+/// Run `program` in `cwd`, here or on an SSH host.
+pub(crate) fn output_on(
+    host: Option<&SshHost>,
+    program: &str,
+    cwd: &str,
+    args: &[&str],
+) -> Result<Output, String> {
+    let output = match host {
+        Some(host) => {
+            log::info!(
+                "{program} {} (cwd: {}:{cwd})",
+                args.join(" "),
+                host.destination()
+            );
+            let mut argv = vec![program];
+            argv.extend_from_slice(args);
+            host.command(Some(cwd), &argv).output()
+        }
+        None => {
+            let expanded = expand_tilde(cwd);
+            let cwd_str = expanded.to_string_lossy();
+            log::info!("{program} {} (cwd: {cwd_str})", args.join(" "));
+            new_command(program)
+                .current_dir(cwd_str.as_ref())
+                .args(args)
+                .output()
+        }
+    };
+    output.map_err(|e| format!("Failed to run {program}: {e}"))
+}
+
+/// [`run_cmd`] on an SSH host when `host` is set.
+pub fn run_cmd_on(
+    host: Option<&SshHost>,
+    program: &str,
+    cwd: &str,
+    args: &[&str],
+) -> Result<String, String> {
+    let output = output_on(host, program, cwd, args)?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -112,18 +163,15 @@ pub fn run_cmd(program: &str, cwd: &str, args: &[&str]) -> Result<String, String
     Ok(stdout)
 }
 
-/// Run a command in a directory, returning (stdout, success) without treating non-zero exit as error.
-pub fn run_cmd_status(program: &str, cwd: &str, args: &[&str]) -> Result<(String, bool), String> {
-    let expanded = expand_tilde(cwd);
-    let cwd_str = expanded.to_string_lossy();
-    log::info!("{program} {} (cwd: {cwd_str})", args.join(" "));
-
-    let output = new_command(program)
-        .current_dir(cwd_str.as_ref())
-        .args(args)
-        .output()
-        .map_err(|e| format!("Failed to run {program}: {e}"))?;
-
+/// Run a command in a directory, here or on an SSH host when `host` is set,
+/// returning (stdout, success) without treating non-zero exit as error.
+pub fn run_cmd_status_on(
+    host: Option<&SshHost>,
+    program: &str,
+    cwd: &str,
+    args: &[&str],
+) -> Result<(String, bool), String> {
+    let output = output_on(host, program, cwd, args)?;
     let stdout = String::from_utf8_lossy(&output.stdout).to_string();
     Ok((stdout, output.status.success()))
 }
@@ -0,0 +1,624 @@
+//! Merge and rebase conflicts: what conflicted, and helpers to resolve it.
+//!
+//! `get_conflicts` reads the unmerged index entries of a worktree (the base,
+//! ours and theirs stages of `git ls-files -u`) and the conflict markers in
+//! the working-tree files, so small conflicts can be resolved in the app, and
+//! bigger ones described precisely to a fresh agent session. During a rebase
+//! git's "ours" is the branch being rebased onto and "theirs" the commit
+//! being replayed; during a merge "ours" is the checked-out branch.
+//!
+//! Like the git commands, these take the session's `group_path` and work on
+//! the group's SSH host when it has one.
+
+use crate::command::{run_cmd_on, run_cmd_status_on};
+use crate::git::{git_combined_output_on, spawn_git, MergeResult, RebaseResult};
+use crate::orca_db::OrcaDb;
+use crate::ssh::{self, SshHost};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictOperation {
+    Merge,
+    Rebase,
+    CherryPick,
+    /// Unmerged files with nothing in progress, as a conflicted
+    /// `merge --squash` leaves them.
+    Other,
+    /// No conflicts and no operation in progress.
+    None,
+}
+
+/// One conflicted region of a file, between `<<<<<<<` and `>>>>>>>`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConflictHunk {
+    /// 1-based lines of the opening and closing markers.
+    pub start_line: u32,
+    pub end_line: u32,
+    pub ours: String,
+    /// The common ancestor's text, with `merge.conflictStyle=diff3`.
+    pub base: Option<String>,
+    pub theirs: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConflictFile {
+    pub path: String,
+    /// Each side's content; None where the file doesn't exist (added on one
+    /// side, or deleted on the other) and for binary files.
+    pub base: Option<String>,
+    pub ours: Option<String>,
+    pub theirs: Option<String>,
+    /// The working-tree file, with conflict markers.
+    pub content: Option<String>,
+    pub binary: bool,
+    pub hunks: Vec<ConflictHunk>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConflictReport {
+    pub operation: ConflictOperation,
+    pub files: Vec<ConflictFile>,
+    /// The conflicts as a prompt for an agent session.
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResolveStrategy {
+    Ours,
+    Theirs,
+    /// The given content, written to the file.
+    Manual,
+}
+
+/// An unmerged path with the blob of each stage present (1 base, 2 ours,
+/// 3 theirs).
+#[derive(Debug, Default, PartialEq, Eq)]
+struct UnmergedPath {
+    path: String,
+    stages: [Option<String>; 3],
+}
+
+/// Parse `git ls-files -u -z`: `<mode> <sha> <stage>\t<path>` records.
+fn parse_unmerged(output: &str) -> Vec<UnmergedPath> {
+    let mut paths: Vec<UnmergedPath> = Vec::new();
+    for record in output.split('\0').filter(|r| !r.is_empty()) {
+        let Some((meta, path)) = record.split_once('\t') else {
+            continue;
+        };
+        let fields: Vec<&str> = meta.split(' ').collect();
+        let (Some(sha), Some(stage)) = (fields.get(1), fields.get(2)) else {
+            continue;
+        };
+        let Some(slot) = stage.parse::<usize>().ok().filter(|s| (1..=3).contains(s)) else {
+            continue;
+        };
+        if paths.last().map(|p| p.path.as_str()) != Some(path) {
+            paths.push(UnmergedPath {
+                path: path.to_string(),
+                ..UnmergedPath::default()
+            });
+        }
+        if let Some(entry) = paths.last_mut() {
+            entry.stages[slot - 1] = Some((*sha).to_string());
+        }
+    }
+    paths
+}
+
+/// A conflict marker line: seven marker characters, then a space or nothing.
+fn is_marker(line: &str, marker: char) -> bool {
+    let rest = line.trim_end_matches(['\r', '\n']);
+    rest.len() >= 7
+        && rest.chars().take(7).all(|c| c == marker)
+        && rest[7..].chars().next().map_or(true, |c| c == ' ')
+}
+
+/// The conflicted regions of a file with conflict markers.
+fn parse_hunks(content: &str) -> Vec<ConflictHunk> {
+    enum Section {
+        Outside,
+        Ours,
+        Base,
+        Theirs,
+    }
+    let mut hunks = Vec::new();
+    let mut section = Section::Outside;
+    let mut current = ConflictHunk {
+        start_line: 0,
+        end_line: 0,
+        ours: String::new(),
+        base: None,
+        theirs: String::new(),
+    };
+    for (i, line) in content.split_inclusive('\n').enumerate() {
+        let number = i as u32 + 1;
+        match section {
+            Section::Outside if is_marker(line, '<') => {
+                current = ConflictHunk {
+                    start_line: number,
+                    end_line: number,
+                    ours: String::new(),
+                    base: None,
+                    theirs: String::new(),
+                };
+                section = Section::Ours;
+            }
+            Section::Outside => {}
+            Section::Ours | Section::Base if is_marker(line, '=') => section = Section::Theirs,
+            Section::Ours if is_marker(line, '|') => {
+                current.base = Some(String::new());
+                section = Section::Base;
+            }
+            Section::Ours => current.ours.push_str(line),
+            Section::Base => {
+                if let Some(base) = current.base.as_mut() {
+                    base.push_str(line);
+                }
+            }
+            Section::Theirs if is_marker(line, '>') => {
+                current.end_line = number;
+                hunks.push(current.clone());
+                section = Section::Outside;
+            }
+            Section::Theirs => current.theirs.push_str(line),
+        }
+    }
+    hunks
+}
+
+/// Whether `name` (e.g. `MERGE_HEAD`) exists in the worktree's git dir.
+fn git_path_exists(
+    host: Option<&SshHost>,
+    worktree_path: &str,
+    name: &str,
+) -> Result<bool, String> {
+    let path = run_cmd_on(
+        host,
+        "git",
+        worktree_path,
+        &["rev-parse", "--git-path", name],
+    )?;
+    let path = path.trim();
+    match host {
+        Some(_) => Ok(run_cmd_status_on(host, "test", worktree_path, &["-e", path])?.1),
+        None => Ok(Path::new(worktree_path).join(path).exists()),
+    }
+}
+
+fn current_operation(
+    host: Option<&SshHost>,
+    worktree_path: &str,
+    conflicted: bool,
+) -> Result<ConflictOperation, String> {
+    let exists = |name: &str| git_path_exists(host, worktree_path, name);
+    let operation = if exists("rebase-merge")? || exists("rebase-apply")? {
+        ConflictOperation::Rebase
+    } else if exists("MERGE_HEAD")? {
+        ConflictOperation::Merge
+    } else if exists("CHERRY_PICK_HEAD")? {
+        ConflictOperation::CherryPick
+    } else if conflicted {
+        ConflictOperation::Other
+    } else {
+        ConflictOperation::None
+    };
+    Ok(operation)
+}
+
+/// A blob's text; None for binary content.
+fn read_blob(
+    host: Option<&SshHost>,
+    worktree_path: &str,
+    sha: &str,
+) -> Result<Option<String>, String> {
+    let content = run_cmd_on(host, "git", worktree_path, &["cat-file", "blob", sha])?;
+    Ok(Some(content).filter(|c| !c.contains('\0')))
+}
+
+/// A working-tree file's text; None when it is missing.
+fn read_worktree_file(host: Option<&SshHost>, worktree_path: &str, file: &str) -> Option<String> {
+    match host {
+        Some(_) => run_cmd_on(host, "cat", worktree_path, &["--", file]).ok(),
+        None => std::fs::read(Path::new(worktree_path).join(file))
+            .ok()
+            .map(|bytes| String::from_utf8_lossy(&bytes).to_string()),
+    }
+}
+
+fn write_worktree_file(
+    host: Option<&SshHost>,
+    worktree_path: &str,
+    file: &str,
+    content: &str,
+) -> Result<(), String> {
+    match host {
+        Some(host) => host
+            .run_with_input(
+                worktree_path,
+                &["sh", "-c", "cat > \"$1\"", "sh", file],
+                content.as_bytes(),
+            )
+            .map(|_| ()),
+        None => std::fs::write(Path::new(worktree_path).join(file), content)
+            .map_err(|e| format!("Failed to write {file}: {e}")),
+    }
+}
+
+fn line_range(hunk: &ConflictHunk) -> String {
+    format!("{}-{}", hunk.start_line, hunk.end_line)
+}
+
+/// What happened to a file, for the description.
+fn file_summary(file: &ConflictFile, stages: &[Option<String>; 3]) -> String {
+    let [base, ours, theirs] = stages;
+    if ours.is_none() {
+        return "deleted on our side, changed on theirs".to_string();
+    }
+    if theirs.is_none() {
+        return "changed on our side, deleted on theirs".to_string();
+    }
+    if file.binary {
+        return "binary, changed on both sides".to_string();
+    }
+    let added = if base.is_none() {
+        "added on both sides, "
+    } else {
+        ""
+    };
+    let ranges: Vec<String> = file.hunks.iter().map(line_range).collect();
+    match ranges.len() {
+        0 => format!("{added}no conflict markers left; review and stage it"),
+        1 => format!("{added}1 conflict, at lines {}", ranges[0]),
+        n => format!("{added}{n} conflicts, at lines {}", ranges.join(", ")),
+    }
+}
+
+fn describe(
+    worktree_path: &str,
+    operation: ConflictOperation,
+    files: &[(ConflictFile, String)],
+) -> String {
+    let (what, next) = match operation {
+        ConflictOperation::Rebase => (
+            "A `git rebase`",
+            "Resolve each conflict and stage the file with `git add`, then run \
+             `git -c core.editor=true rebase --continue`. If it stops on more conflicts, \
+             repeat. During a rebase \"ours\" is the branch being rebased onto and \"theirs\" \
+             the commit being replayed. Do NOT use `git merge`.",
+        ),
+        ConflictOperation::Merge => (
+            "A `git merge`",
+            "Resolve each conflict and stage the file with `git add`, then commit the merge \
+             with `git commit --no-edit`.",
+        ),
+        ConflictOperation::CherryPick => (
+            "A `git cherry-pick`",
+            "Resolve each conflict and stage the file with `git add`, then run \
+             `git -c core.editor=true cherry-pick --continue`.",
+        ),
+        ConflictOperation::Other | ConflictOperation::None => (
+            "A git operation",
+            "Resolve each conflict and stage the file with `git add`, then commit.",
+        ),
+    };
+    if files.is_empty() {
+        return format!("There are no conflicts left in {worktree_path}.");
+    }
+    let count = if files.len() == 1 {
+        "1 file".to_string()
+    } else {
+        format!("{} files", files.len())
+    };
+    let mut description = format!("{what} in {worktree_path} stopped on conflicts in {count}:\n");
+    for (file, summary) in files {
+        description.push_str(&format!("\n- `{}`: {summary}", file.path));
+    }
+    description.push_str("\n\n");
+    description.push_str(next);
+    description
+}
+
+fn unmerged_on(host: Option<&SshHost>, worktree_path: &str) -> Result<Vec<UnmergedPath>, String> {
+    let output = run_cmd_on(host, "git", worktree_path, &["ls-files", "-u", "-z"])?;
+    Ok(parse_unmerged(&output))
+}
+
+/// The conflicts in a worktree, here or on an SSH host.
+pub fn get_conflicts_on(
+    host: Option<&SshHost>,
+    worktree_path: &str,
+) -> Result<ConflictReport, String> {
+    let unmerged = unmerged_on(host, worktree_path)?;
+    let operation = current_operation(host, worktree_path, !unmerged.is_empty())?;
+    let mut files = Vec::new();
+    for entry in unmerged {
+        let mut sides = [None, None, None];
+        let mut binary = false;
+        for (side, sha) in sides.iter_mut().zip(&entry.stages) {
+            if let Some(sha) = sha {
+                *side = read_blob(host, worktree_path, sha)?;
+                binary |= side.is_none();
+            }
+        }
+        let content = if binary {
+            None
+        } else {
+            read_worktree_file(host, worktree_path, &entry.path)
+        };
+        let [base, ours, theirs] = sides;
+        let file = ConflictFile {
+            hunks: content.as_deref().map(parse_hunks).unwrap_or_default(),
+            path: entry.path,
+            base,
+            ours,
+            theirs,
+            content,
+            binary,
+        };
+        let summary = file_summary(&file, &entry.stages);
+        files.push((file, summary));
+    }
+    Ok(ConflictReport {
+        operation,
+        description: describe(worktree_path, operation, &files),
+        files: files.into_iter().map(|(file, _)| file).collect(),
+    })
+}
+
+/// Resolve and stage one file of a worktree, here or on an SSH host. Only
+/// files with unmerged entries can be resolved, and manual content must be
+/// free of conflict markers.
+pub fn resolve_conflict_on(
+    host: Option<&SshHost>,
+    worktree_path: &str,
+    file: &str,
+    strategy: ResolveStrategy,
+    content: Option<&str>,
+) -> Result<(), String> {
+    let entry = unmerged_on(host, worktree_path)?
+        .into_iter()
+        .find(|entry| entry.path == file)
+        .ok_or_else(|| format!("{file} has no unresolved conflict"))?;
+    let git = |args: &[&str]| run_cmd_on(host, "git", worktree_path, args);
+    let (side, stage) = match strategy {
+        ResolveStrategy::Ours => ("--ours", &entry.stages[1]),
+        ResolveStrategy::Theirs => ("--theirs", &entry.stages[2]),
+        ResolveStrategy::Manual => {
+            let content = content.ok_or("Manual resolution needs the file's content")?;
+            if content
+                .lines()
+                .any(|line| ['<', '=', '>'].iter().any(|&m| is_marker(line, m)))
+            {
+                return Err(format!("{file} still has conflict markers"));
+            }
+            write_worktree_file(host, worktree_path, file, content)?;
+            git(&["add", "--", file])?;
+            return Ok(());
+        }
+    };
+    // A side without the file deleted it there: take the deletion.
+    if stage.is_some() {
+        git(&["checkout", side, "--", file])?;
+        git(&["add", "--", file])?;
+    } else {
+        git(&["rm", "-q", "--", file])?;
+    }
+    Ok(())
+}
+
+// --- Tauri Commands ---
+
+/// The conflicted files of a worktree, with each side's content and the
+/// conflict regions of the working-tree file.
+#[tauri::command]
+pub async fn get_conflicts(
+    orca_db: tauri::State<'_, OrcaDb>,
+    worktree_path: String,
+    group_path: Option<String>,
+) -> Result<ConflictReport, String> {
+    let orca_db = orca_db.inner().clone();
+    spawn_git(move || {
+        let host = ssh::host_for(&orca_db, group_path.as_deref())?;
+        get_conflicts_on(host.as_ref(), &worktree_path)
+    })
+    .await
+}
+
+/// Resolve one file with a side's version or the given content, and stage it.
+#[tauri::command]
+pub async fn resolve_conflict(
+    orca_db: tauri::State<'_, OrcaDb>,
+    worktree_path: String,
+    file: String,
+    strategy: ResolveStrategy,
+    content: Option<String>,
+    group_path: Option<String>,
+) -> Result<(), String> {
+    let orca_db = orca_db.inner().clone();
+    spawn_git(move || {
+        let host = ssh::host_for(&orca_db, group_path.as_deref())?;
+        resolve_conflict_on(
+            host.as_ref(),
+            &worktree_path,
+            &file,
+            strategy,
+            content.as_deref(),
+        )
+    })
+    .await
+}
+
+/// `git rebase --continue` once the conflicts are resolved. Stopping on the
+/// next commit's conflicts is reported like `rebase_branch` does.
+#[tauri::command]
+pub async fn continue_rebase(
+    orca_db: tauri::State<'_, OrcaDb>,
+    worktree_path: String,
+    group_path: Option<String>,
+) -> Result<RebaseResult, String> {
+    let orca_db = orca_db.inner().clone();
+    spawn_git(move || {
+        let host = ssh::host_for(&orca_db, group_path.as_deref())?;
+        let (success, message) = git_combined_output_on(
+            host.as_ref(),
+            &worktree_path,
+            &["-c", "core.editor=true", "rebase", "--continue"],
+        )?;
+        Ok(RebaseResult {
+            success,
+            conflict_message: (!success).then_some(message),
+        })
+    })
+    .await
+}
+
+/// Commit a merge (or a squash) once the conflicts are resolved, with
+/// `message` or git's prepared one.
+#[tauri::command]
+pub async fn continue_merge(
+    orca_db: tauri::State<'_, OrcaDb>,
+    worktree_path: String,
+    message: Option<String>,
+    group_path: Option<String>,
+) -> Result<MergeResult, String> {
+    let orca_db = orca_db.inner().clone();
+    spawn_git(move || {
+        let host = ssh::host_for(&orca_db, group_path.as_deref())?;
+        let args = match message.as_deref().map(str::trim).filter(|m| !m.is_empty()) {
+            Some(message) => vec!["commit", "-m", message],
+            None => vec!["commit", "--no-edit"],
+        };
+        let (success, output) = git_combined_output_on(host.as_ref(), &worktree_path, &args)?;
+        Ok(MergeResult {
+            success,
+            main_worktree_path: worktree_path,
+            conflict_message: (!success).then_some(output),
+            conflicted: !success,
+        })
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_unmerged_entries() {
+        let output = [
+            "100644 aaa 1\tsrc/a.rs",
+            "100644 bbb 2\tsrc/a.rs",
+            "100644 ccc 3\tsrc/a.rs",
+            "100644 ddd 1\tgone.txt",
+            "100644 eee 3\tgone.txt",
+            "",
+        ]
+        .join("\0");
+        let paths = parse_unmerged(&output);
+        assert_eq!(paths.len(), 2);
+        assert_eq!(paths[0].path, "src/a.rs");
+        assert_eq!(
+            paths[0].stages,
+            [Some("aaa".into()), Some("bbb".into()), Some("ccc".into())]
+        );
+        assert_eq!(
+            paths[1].stages,
+            [Some("ddd".into()), None, Some("eee".into())]
+        );
+    }
+
+    #[test]
+    fn parses_conflict_hunks() {
+        let content = "keep\n<<<<<<< HEAD\nours\n||||||| base\nbase\n=======\ntheirs\n\
+                       >>>>>>> feature\nmiddle\n<<<<<<< HEAD\n=======\nadded\n>>>>>>> feature\n\
+                       ======= not a marker\n";
+        let hunks = parse_hunks(content);
+        assert_eq!(hunks.len(), 2);
+        assert_eq!((hunks[0].start_line, hunks[0].end_line), (2, 8));
+        assert_eq!(hunks[0].ours, "ours\n");
+        assert_eq!(hunks[0].base.as_deref(), Some("base\n"));
+        assert_eq!(hunks[0].theirs, "theirs\n");
+        assert_eq!((hunks[1].start_line, hunks[1].end_line), (10, 13));
+        assert_eq!(hunks[1].ours, "");
+        assert_eq!(hunks[1].base, None);
+        assert_eq!(hunks[1].theirs, "added\n");
+        assert!(!is_marker("========", '='));
+    }
+
+    fn git(dir: &Path, args: &[&str]) {
+        let output = std::process::Command::new("git")
+            .args(["-c", "user.name=t", "-c", "user.email=t@t"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .expect("git");
+        assert!(output.status.success(), "git {args:?}: {output:?}");
+    }
+
+    #[test]
+    fn reports_and_resolves_merge_conflicts() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        git(dir, &["init", "-q", "-b", "main"]);
+        git(dir, &["config", "user.name", "t"]);
+        git(dir, &["config", "user.email", "t@t"]);
+        std::fs::write(dir.join("a.txt"), "one\ntwo\n").unwrap();
+        std::fs::write(dir.join("b.txt"), "b\n").unwrap();
+        git(dir, &["add", "."]);
+        git(dir, &["commit", "-qm", "init"]);
+        git(dir, &["checkout", "-qb", "feature"]);
+        std::fs::write(dir.join("a.txt"), "one\nfeature\n").unwrap();
+        std::fs::write(dir.join("b.txt"), "feature b\n").unwrap();
+        git(dir, &["commit", "-qam", "feature"]);
+        git(dir, &["checkout", "-q", "main"]);
+        std::fs::write(dir.join("a.txt"), "one\nmain\n").unwrap();
+        git(dir, &["rm", "-q", "b.txt"]);
+        git(dir, &["commit", "-qam", "main"]);
+        let path = dir.to_str().unwrap();
+
+        assert_eq!(
+            get_conflicts_on(None, path).unwrap().operation,
+            ConflictOperation::None
+        );
+        let (_, merged) = run_cmd_status_on(None, "git", path, &["merge", "feature"]).unwrap();
+        assert!(!merged);
+
+        let report = get_conflicts_on(None, path).unwrap();
+        assert_eq!(report.operation, ConflictOperation::Merge);
+        let paths: Vec<&str> = report.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, ["a.txt", "b.txt"]);
+        let a = &report.files[0];
+        assert_eq!(a.base.as_deref(), Some("one\ntwo\n"));
+        assert_eq!(a.ours.as_deref(), Some("one\nmain\n"));
+        assert_eq!(a.theirs.as_deref(), Some("one\nfeature\n"));
+        assert_eq!(a.hunks.len(), 1);
+        assert_eq!(a.hunks[0].ours, "main\n");
+        assert_eq!(report.files[1].ours, None);
+        assert!(report
+            .description
+            .contains("- `a.txt`: 1 conflict, at lines 2-6"));
+        assert!(report
+            .description
+            .contains("- `b.txt`: deleted on our side, changed on theirs"));
+
+        let resolve =
+            |file, strategy, content| resolve_conflict_on(None, path, file, strategy, content);
+        assert!(resolve("../a.txt", ResolveStrategy::Ours, None).is_err());
+        assert!(resolve("c.txt", ResolveStrategy::Theirs, None).is_err());
+        let err = resolve("a.txt", ResolveStrategy::Manual, a.content.as_deref()).unwrap_err();
+        assert!(err.contains("conflict markers"), "{err}");
+        resolve("a.txt", ResolveStrategy::Manual, Some("one\nboth\n")).unwrap();
+        resolve("b.txt", ResolveStrategy::Ours, None).unwrap();
+        let report = get_conflicts_on(None, path).unwrap();
+        assert!(report.files.is_empty());
+        assert_eq!(report.operation, ConflictOperation::Merge);
+        assert!(!dir.join("b.txt").exists());
+        assert_eq!(
+            std::fs::read_to_string(dir.join("a.txt")).unwrap(),
+            "one\nboth\n"
+        );
+    }
+}
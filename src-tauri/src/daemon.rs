@@ -0,0 +1,260 @@
+//! `orca-daemon`, Orca without a window.
+//!
+//! Runs the app's background machinery on a machine with no display: Orca's
+//! DB, the session watcher (attention tracking, webhooks), the budget monitor
+//! and the local automation API, which is always on here. Desktop
+//! notifications and frontend events are skipped. Scripts and agents (MCP on
+//! `/mcp`) reach it through the API, from other machines over an SSH tunnel,
+//! until it gets SIGINT or SIGTERM. It won't start while the desktop app (or
+//! another daemon) runs on the same data directory.
+
+use crate::agentdeck;
+use crate::budget;
+use crate::hooks;
+use crate::instance_lock;
+use crate::local_api;
+use crate::models::ApiSettings;
+use crate::orca_db::OrcaDb;
+use crate::ssh;
+use crate::tool_profile;
+use crate::watcher;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: orca-daemon [--port PORT | --socket PATH] [--verbose]
+
+Runs Orca headless: tracks sessions and attention, fires webhooks, enforces
+budgets and serves the automation API (including MCP on /mcp) until
+interrupted. The API's address and token are written to api.json in Orca's
+data directory.
+
+Options:
+  --port PORT     Listen on 127.0.0.1:PORT (default: the saved API settings)
+  --socket PATH   Listen on a Unix socket instead
+  --verbose       Log debug messages";
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Options {
+    port: Option<u16>,
+    socket: Option<String>,
+    verbose: bool,
+}
+
+/// Parse the command line; None means `--help`.
+fn parse_options(args: impl IntoIterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut options = Options::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let (name, inline) = match arg.split_once('=') {
+            Some((name, value)) => (name.to_string(), Some(value.to_string())),
+            None => (arg, None),
+        };
+        let mut value = || {
+            inline
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("{name} needs a value"))
+        };
+        match name.as_str() {
+            "--port" => {
+                let port = value()?;
+                options.port = Some(
+                    port.parse()
+                        .ok()
+                        .filter(|p| *p != 0)
+                        .ok_or_else(|| format!("Invalid port: {port}"))?,
+                );
+            }
+            "--socket" => options.socket = Some(value()?),
+            "--verbose" if inline.is_none() => options.verbose = true,
+            "--help" | "-h" | "help" => return Ok(None),
+            _ => return Err(format!("Unknown option: {name}")),
+        }
+    }
+    if options.port.is_some() && options.socket.is_some() {
+        return Err("--port and --socket are mutually exclusive".to_string());
+    }
+    Ok(Some(options))
+}
+
+/// The saved API settings, enabled, with the command-line overrides.
+fn api_settings(saved: ApiSettings, options: &Options) -> ApiSettings {
+    let mut settings = ApiSettings {
+        enabled: true,
+        ..saved
+    };
+    if let Some(port) = options.port {
+        settings.transport = "tcp".to_string();
+        settings.port = port;
+    }
+    if let Some(socket) = &options.socket {
+        settings.transport = "unix".to_string();
+        settings.socket_path = Some(socket.clone());
+    }
+    settings
+}
+
+/// Plain stderr logging; journald or the terminal adds timestamps.
+struct StderrLogger;
+
+impl log::Log for StderrLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("[{}] {}", record.level(), record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: StderrLogger = StderrLogger;
+
+/// Entry point for the `orca-daemon` binary.
+pub fn main() -> ExitCode {
+    let options = match parse_options(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("orca-daemon: {e}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(if options.verbose {
+            log::LevelFilter::Debug
+        } else {
+            log::LevelFilter::Info
+        });
+    }
+    crate::command::init_path();
+    match run(&options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            log::error!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(options: &Options) -> Result<(), String> {
+    let data_dir = crate::data_dir().ok_or("Cannot determine Orca's data directory")?;
+    let _lock = instance_lock::acquire(&data_dir)?;
+    let orca_db = OrcaDb::init(&data_dir)?;
+    if let Err(e) = tool_profile::reload(&orca_db) {
+        log::warn!("Failed to load tool profiles: {e}");
+    }
+    if let Err(e) = hooks::init(&data_dir, &orca_db) {
+        log::warn!("Claude Code hook events unavailable: {e}");
+    }
+    if let Err(e) = ssh::init(&data_dir) {
+        log::warn!("{e}");
+    }
+    match agentdeck::check_agent_deck_version() {
+        Ok(version) => log::info!(
+            "agent-deck {} (Orca supports {})",
+            version.installed,
+            version.supported
+        ),
+        Err(e) => log::warn!("{e}"),
+    }
+
+    let settings = api_settings(orca_db.get_api_settings()?, options);
+    let endpoint = local_api::start(&settings, &orca_db, &data_dir)?;
+    watcher::start(None, orca_db.clone());
+    budget::start(None, orca_db.clone());
+    log::info!(
+        "Orca daemon ready: API on {endpoint}, token in {}",
+        data_dir.join(local_api::INFO_FILE).display()
+    );
+
+    let signal = tauri::async_runtime::block_on(wait_for_signal());
+    log::info!("Received {signal}, shutting down");
+    local_api::stop();
+    Ok(())
+}
+
+/// Wait for Ctrl-C, or SIGTERM from systemd and friends.
+async fn wait_for_signal() -> &'static str {
+    #[cfg(unix)]
+    {
+        use futures::future::{select, Either};
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let Ok(mut terminate) = signal(SignalKind::terminate()) else {
+            let _ = tokio::signal::ctrl_c().await;
+            return "SIGINT";
+        };
+        let interrupted = Box::pin(tokio::signal::ctrl_c());
+        let terminated = Box::pin(terminate.recv());
+        // The losing future borrows `terminate`, so don't keep the result
+        // around as the block's value
+        let interrupt = matches!(select(interrupted, terminated).await, Either::Left(_));
+        if interrupt {
+            "SIGINT"
+        } else {
+            "SIGTERM"
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+        "Ctrl-C"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Option<Options>, String> {
+        parse_options(args.iter().map(ToString::to_string))
+    }
+
+    #[test]
+    fn parses_options() {
+        assert_eq!(parse(&[]), Ok(Some(Options::default())));
+        assert_eq!(
+            parse(&["--port", "8123", "--verbose"]),
+            Ok(Some(Options {
+                port: Some(8123),
+                verbose: true,
+                ..Options::default()
+            }))
+        );
+        assert_eq!(
+            parse(&["--socket=/tmp/orca.sock"]).unwrap().unwrap().socket,
+            Some("/tmp/orca.sock".to_string())
+        );
+        assert_eq!(parse(&["--help"]), Ok(None));
+        assert!(parse(&["--port", "0"]).is_err());
+        assert!(parse(&["--port"]).is_err());
+        assert!(parse(&["--port", "1", "--socket", "x"]).is_err());
+        assert!(parse(&["--bind", "0.0.0.0"]).is_err());
+    }
+
+    #[test]
+    fn overrides_enable_the_api() {
+        let saved = ApiSettings::default();
+        assert!(!saved.enabled);
+
+        let settings = api_settings(saved.clone(), &Options::default());
+        assert!(settings.enabled);
+        assert_eq!(settings.transport, saved.transport);
+        assert_eq!(settings.port, saved.port);
+
+        let options = Options {
+            socket: Some("/run/orca.sock".to_string()),
+            ..Options::default()
+        };
+        let settings = api_settings(saved, &options);
+        assert_eq!(settings.transport, "unix");
+        assert_eq!(settings.socket_path.as_deref(), Some("/run/orca.sock"));
+    }
+}
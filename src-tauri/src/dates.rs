@@ -0,0 +1,127 @@
+//! UTC date formatting and parsing without pulling in a date/time crate.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Split a unix timestamp into a UTC (year, month, day).
+fn civil_date(unix_secs: u64) -> (i64, i64, i64) {
+    // Days-to-civil conversion (Howard Hinnant's algorithm).
+    let z = (unix_secs / 86_400) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Format a unix timestamp as a UTC `YYYY-MM-DD` day.
+pub fn utc_day(unix_secs: u64) -> String {
+    let (year, month, day) = civil_date(unix_secs);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Format a unix timestamp as RFC 3339 UTC (`YYYY-MM-DDTHH:MM:SSZ`), the
+/// same shape Claude Code writes in its transcripts.
+pub fn utc_timestamp(unix_secs: u64) -> String {
+    let secs = unix_secs % 86_400;
+    format!(
+        "{}T{:02}:{:02}:{:02}Z",
+        utc_day(unix_secs),
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+/// Days since the unix epoch for a civil date (inverse of `civil_date`).
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Parse an RFC 3339 timestamp (`2025-01-01T10:00:00.123Z` or with a
+/// `±HH:MM` offset) into unix seconds.
+pub fn parse_timestamp(text: &str) -> Option<f64> {
+    let num = |s: &str| s.parse::<i64>().ok();
+    let (date, time) = text.split_once(&['T', ' '][..])?;
+    let mut date_parts = date.splitn(3, '-');
+    let (year, month, day) = (
+        num(date_parts.next()?)?,
+        num(date_parts.next()?)?,
+        num(date_parts.next()?)?,
+    );
+
+    let (clock, offset_secs) = if let Some(clock) = time.strip_suffix(&['Z', 'z'][..]) {
+        (clock, 0)
+    } else {
+        let split = time.rfind(&['+', '-'][..])?;
+        let (clock, offset) = time.split_at(split);
+        let sign = if offset.starts_with('-') { -1 } else { 1 };
+        let (hours, mins) = offset[1..].split_once(':')?;
+        (clock, sign * (num(hours)? * 3600 + num(mins)? * 60))
+    };
+    let mut clock_parts = clock.splitn(3, ':');
+    let (hour, minute) = (num(clock_parts.next()?)?, num(clock_parts.next()?)?);
+    let seconds: f64 = clock_parts.next()?.parse().ok()?;
+
+    let days = days_from_civil(year, month, day);
+    let whole = days * 86_400 + hour * 3600 + minute * 60 - offset_secs;
+    Some(whole as f64 + seconds)
+}
+
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Today's UTC day.
+pub fn today() -> String {
+    utc_day(now_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utc_day_formats_dates() {
+        assert_eq!(utc_day(0), "1970-01-01");
+        assert_eq!(utc_day(951_782_400), "2000-02-29");
+        assert_eq!(utc_day(1_735_689_599), "2024-12-31");
+        assert_eq!(utc_day(1_735_689_600), "2025-01-01");
+    }
+
+    #[test]
+    fn utc_timestamp_includes_time() {
+        assert_eq!(utc_timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(utc_timestamp(1_735_689_599), "2024-12-31T23:59:59Z");
+        assert_eq!(utc_timestamp(1_700_000_000), "2023-11-14T22:13:20Z");
+    }
+
+    #[test]
+    fn parse_timestamp_round_trips() {
+        for secs in [0, 951_782_400, 1_735_689_599, 1_700_000_000] {
+            assert_eq!(parse_timestamp(&utc_timestamp(secs)), Some(secs as f64));
+        }
+        assert_eq!(
+            parse_timestamp("2025-01-01T00:00:00.250Z"),
+            Some(1_735_689_600.25)
+        );
+        assert_eq!(
+            parse_timestamp("2025-01-01T02:00:00+02:00"),
+            Some(1_735_689_600.0)
+        );
+        assert_eq!(parse_timestamp("2025-01-01"), None);
+        assert_eq!(parse_timestamp("yesterday"), None);
+    }
+}
@@ -0,0 +1,731 @@
+//! Structured worktree diffs for the diff viewer.
+//!
+//! `get_branch_diff_v2` lists the changed files with their status and line
+//! counts, without any diff text, so a huge diff costs one `git diff --raw
+//! --numstat`. The viewer then fetches the hunks of the files it shows with
+//! `get_branch_diff_file`, passing back the list's `base` so both describe
+//! the same diff even if HEAD moves in between.
+//!
+//! Two scopes: `committed` is the branch's commits against the default branch
+//! (like `git::get_branch_diff`), `uncommitted` the working tree, staged or
+//! not, against HEAD, including untracked files.
+
+use crate::command::{run_cmd_on, run_cmd_status_on};
+use crate::git::{get_default_branch_on, spawn_git};
+use crate::orca_db::OrcaDb;
+use crate::ssh::{self, SshHost};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Lines of a single file's diff returned before it is cut off.
+const MAX_FILE_LINES: usize = 20_000;
+/// Untracked paths counted per `git grep`, keeping its command line short.
+const UNTRACKED_BATCH: usize = 500;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffScope {
+    #[default]
+    Committed,
+    Uncommitted,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct DiffOptions {
+    pub scope: DiffScope,
+    /// `git diff -w`: ignore all whitespace changes.
+    pub ignore_whitespace: bool,
+    /// Mark the changed words of each line (hunks only).
+    pub word_diff: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileStatus {
+    Added,
+    Modified,
+    Deleted,
+    Renamed,
+    Copied,
+    TypeChanged,
+    Untracked,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DiffFile {
+    pub path: String,
+    /// The path before a rename or copy.
+    pub old_path: Option<String>,
+    pub status: FileStatus,
+    pub binary: bool,
+    pub additions: u32,
+    pub deletions: u32,
+    /// Blob SHAs on each side; None for a side the file doesn't exist on,
+    /// and for the working-tree side of uncommitted changes.
+    pub old_blob: Option<String>,
+    pub new_blob: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BranchDiff {
+    pub scope: DiffScope,
+    /// The commit the changes are against: the merge base with the default
+    /// branch for `committed`, HEAD for `uncommitted`.
+    pub base: String,
+    pub files: Vec<DiffFile>,
+    pub additions: u32,
+    pub deletions: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffLineKind {
+    Addition,
+    Deletion,
+    Context,
+}
+
+/// A run of text within a word-diffed line.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DiffSegment {
+    #[serde(rename = "type")]
+    pub kind: DiffLineKind,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DiffLine {
+    #[serde(rename = "type")]
+    pub kind: DiffLineKind,
+    /// The line with its `+`/`-`/space prefix, as in a unified diff.
+    pub content: String,
+    pub old_line: Option<u32>,
+    pub new_line: Option<u32>,
+    /// With `word_diff`, the line split into unchanged and changed words. A
+    /// line with both is a `context` line whose segments carry the changes.
+    pub segments: Option<Vec<DiffSegment>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DiffHunk {
+    pub header: String,
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+    pub lines: Vec<DiffLine>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileDiff {
+    pub path: String,
+    pub binary: bool,
+    pub hunks: Vec<DiffHunk>,
+    /// The diff had more than [`MAX_FILE_LINES`] lines; the rest is left out.
+    pub truncated: bool,
+}
+
+fn git(host: Option<&SshHost>, worktree_path: &str, args: &[&str]) -> Result<String, String> {
+    run_cmd_on(host, "git", worktree_path, args)
+}
+
+/// The commit a diff of `scope` is taken against.
+fn resolve_base(
+    host: Option<&SshHost>,
+    worktree_path: &str,
+    scope: DiffScope,
+) -> Result<String, String> {
+    let base = match scope {
+        DiffScope::Committed => {
+            // The fork point, as in `git diff <default>...HEAD`.
+            let default_branch = get_default_branch_on(host, worktree_path)?;
+            git(
+                host,
+                worktree_path,
+                &["merge-base", &default_branch, "HEAD"],
+            )?
+        }
+        DiffScope::Uncommitted => git(host, worktree_path, &["rev-parse", "HEAD"])?,
+    };
+    Ok(base.trim().to_string())
+}
+
+/// The `git diff` arguments selecting the scope's changes from `base`.
+fn range_args(scope: DiffScope, base: &str) -> Vec<&str> {
+    match scope {
+        DiffScope::Committed => vec![base, "HEAD"],
+        DiffScope::Uncommitted => vec![base],
+    }
+}
+
+/// The changed files, with stats, of the worktree's diff in `options.scope`.
+pub fn list_files(
+    host: Option<&SshHost>,
+    worktree_path: &str,
+    options: &DiffOptions,
+) -> Result<BranchDiff, String> {
+    let base = resolve_base(host, worktree_path, options.scope)?;
+    let mut args = vec!["diff", "--raw", "--numstat", "-z", "-M", "--no-abbrev"];
+    if options.ignore_whitespace {
+        args.push("-w");
+    }
+    args.extend(range_args(options.scope, &base));
+    let mut files = parse_raw_numstat(&git(host, worktree_path, &args)?);
+
+    if options.scope == DiffScope::Uncommitted {
+        let untracked = git(
+            host,
+            worktree_path,
+            &["ls-files", "--others", "--exclude-standard", "-z"],
+        )?;
+        let paths: Vec<&str> = untracked.split('\0').filter(|p| !p.is_empty()).collect();
+        files.extend(untracked_files(host, worktree_path, &paths));
+    }
+
+    Ok(BranchDiff {
+        scope: options.scope,
+        base,
+        additions: files.iter().map(|f| f.additions).sum(),
+        deletions: files.iter().map(|f| f.deletions).sum(),
+        files,
+    })
+}
+
+/// Untracked files as additions. `git grep -c ''` counts the lines of a
+/// whole batch of files in one process; with `-I` it skips binary files, so a
+/// file counted only without it is binary and one counted by neither is empty.
+fn untracked_files(host: Option<&SshHost>, worktree_path: &str, paths: &[&str]) -> Vec<DiffFile> {
+    let mut text = HashMap::new();
+    let mut all = HashMap::new();
+    for batch in paths.chunks(UNTRACKED_BATCH) {
+        text.extend(grep_line_counts(host, worktree_path, batch, true));
+        all.extend(grep_line_counts(host, worktree_path, batch, false));
+    }
+    paths
+        .iter()
+        .map(|path| DiffFile {
+            path: path.to_string(),
+            old_path: None,
+            status: FileStatus::Untracked,
+            binary: !text.contains_key(*path) && all.contains_key(*path),
+            additions: text.get(*path).copied().unwrap_or(0),
+            deletions: 0,
+            old_blob: None,
+            new_blob: None,
+        })
+        .collect()
+}
+
+/// Line counts of the files in `paths` that have any lines. git grep exits
+/// non-zero when nothing matches, so a failure just counts nothing.
+fn grep_line_counts(
+    host: Option<&SshHost>,
+    worktree_path: &str,
+    paths: &[&str],
+    skip_binary: bool,
+) -> HashMap<String, u32> {
+    let mut args = vec!["grep", "--untracked", "-c", "-z"];
+    if skip_binary {
+        args.push("-I");
+    }
+    args.extend(["-e", "", "--"]);
+    args.extend(paths);
+    run_cmd_status_on(host, "git", worktree_path, &args)
+        .map(|(output, _)| parse_grep_counts(&output))
+        .unwrap_or_default()
+}
+
+/// Parse `git grep -c -z` output: `<path>\0<count>\n` per file.
+fn parse_grep_counts(output: &str) -> HashMap<String, u32> {
+    let mut counts = HashMap::new();
+    let mut fields = output.split('\0');
+    let mut path = fields.next().unwrap_or_default();
+    for field in fields {
+        let (count, next) = field.split_once('\n').unwrap_or((field, ""));
+        if let Ok(count) = count.parse() {
+            counts.insert(path.to_string(), count);
+        }
+        path = next;
+    }
+    counts
+}
+
+/// One file's hunks from a diff listed by [`list_files`] against `base`.
+pub fn file_hunks(
+    host: Option<&SshHost>,
+    worktree_path: &str,
+    base: &str,
+    file: &DiffFileRequest,
+    options: &DiffOptions,
+) -> Result<FileDiff, String> {
+    let mut args = vec!["diff", "--no-color", "--no-ext-diff", "-M"];
+    if options.ignore_whitespace {
+        args.push("-w");
+    }
+    if options.word_diff {
+        args.push("--word-diff=porcelain");
+    }
+    let output = if file.untracked {
+        args.push("--no-index");
+        args.extend(["--", "/dev/null", &file.path]);
+        run_cmd_status_on(host, "git", worktree_path, &args)?.0
+    } else {
+        args.extend(range_args(options.scope, base));
+        args.push("--");
+        if let Some(old_path) = &file.old_path {
+            args.push(old_path);
+        }
+        args.push(&file.path);
+        git(host, worktree_path, &args)?
+    };
+    Ok(parse_file_diff(&file.path, &output, options.word_diff))
+}
+
+/// Which file [`file_hunks`] should diff.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DiffFileRequest {
+    pub path: String,
+    #[serde(default)]
+    pub old_path: Option<String>,
+    #[serde(default)]
+    pub untracked: bool,
+}
+
+fn file_status(letter: char) -> FileStatus {
+    match letter {
+        'A' => FileStatus::Added,
+        'D' => FileStatus::Deleted,
+        'R' => FileStatus::Renamed,
+        'C' => FileStatus::Copied,
+        'T' => FileStatus::TypeChanged,
+        _ => FileStatus::Modified,
+    }
+}
+
+/// A blob SHA from `--raw`, or None for the all-zero "no blob" (missing
+/// side, or working tree).
+fn blob(sha: &str) -> Option<String> {
+    (!sha.is_empty() && sha.chars().any(|c| c != '0')).then(|| sha.to_string())
+}
+
+/// Parse `git diff --raw --numstat -z` output: the raw records of all files,
+/// then their numstat records in the same order.
+fn parse_raw_numstat(output: &str) -> Vec<DiffFile> {
+    let mut files: Vec<DiffFile> = Vec::new();
+    let mut stats = 0;
+    let mut tokens = output.split('\0');
+    while let Some(token) = tokens.next() {
+        if let Some(meta) = token.strip_prefix(':') {
+            // `:old_mode new_mode old_sha new_sha STATUS`, then the path(s).
+            let fields: Vec<&str> = meta.split(' ').collect();
+            let letter = fields.get(4).and_then(|s| s.chars().next()).unwrap_or('M');
+            let status = file_status(letter);
+            let first = tokens.next().unwrap_or_default().to_string();
+            let (path, old_path) = match status {
+                FileStatus::Renamed | FileStatus::Copied => {
+                    (tokens.next().unwrap_or_default().to_string(), Some(first))
+                }
+                _ => (first, None),
+            };
+            files.push(DiffFile {
+                path,
+                old_path,
+                status,
+                binary: false,
+                additions: 0,
+                deletions: 0,
+                old_blob: fields.get(2).and_then(|s| blob(s)),
+                new_blob: fields.get(3).and_then(|s| blob(s)),
+            });
+        } else if !token.is_empty() {
+            // `added\tdeleted\tpath`, or `added\tdeleted\t` followed by the
+            // old and new paths for renames.
+            let mut fields = token.splitn(3, '\t');
+            let additions = fields.next().unwrap_or("-");
+            let deletions = fields.next().unwrap_or("-");
+            if fields.next().unwrap_or_default().is_empty() {
+                tokens.next();
+                tokens.next();
+            }
+            if let Some(file) = files.get_mut(stats) {
+                file.binary = additions == "-";
+                file.additions = additions.parse().unwrap_or(0);
+                file.deletions = deletions.parse().unwrap_or(0);
+            }
+            stats += 1;
+        }
+    }
+    files
+}
+
+/// Parse `@@ -a,b +c,d @@ ...` into (a, b, c, d); a missing count is 1.
+fn parse_hunk_header(header: &str) -> Option<(u32, u32, u32, u32)> {
+    let ranges = header.strip_prefix("@@ ")?.split(" @@").next()?;
+    let (old, new) = ranges.split_once(' ')?;
+    let range = |r: &str| -> Option<(u32, u32)> {
+        match r.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((r.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_lines) = range(old.strip_prefix('-')?)?;
+    let (new_start, new_lines) = range(new.strip_prefix('+')?)?;
+    Some((old_start, old_lines, new_start, new_lines))
+}
+
+fn line_kind(prefix: char) -> Option<DiffLineKind> {
+    match prefix {
+        '+' => Some(DiffLineKind::Addition),
+        '-' => Some(DiffLineKind::Deletion),
+        ' ' => Some(DiffLineKind::Context),
+        _ => None,
+    }
+}
+
+/// Parse one file's unified diff, plain or `--word-diff=porcelain`.
+fn parse_file_diff(path: &str, output: &str, word_diff: bool) -> FileDiff {
+    let mut diff = FileDiff {
+        path: path.to_string(),
+        binary: false,
+        hunks: Vec::new(),
+        truncated: false,
+    };
+    let mut old_line = 0;
+    let mut new_line = 0;
+    // Word-diff segments of the line being assembled, until its `~`.
+    let mut segments: Vec<DiffSegment> = Vec::new();
+
+    for (count, line) in output.lines().enumerate() {
+        if count >= MAX_FILE_LINES {
+            diff.truncated = true;
+            break;
+        }
+        if line.starts_with("@@ ") {
+            if let Some((old_start, old_lines, new_start, new_lines)) = parse_hunk_header(line) {
+                old_line = old_start;
+                new_line = new_start;
+                diff.hunks.push(DiffHunk {
+                    header: line.to_string(),
+                    old_start,
+                    old_lines,
+                    new_start,
+                    new_lines,
+                    lines: Vec::new(),
+                });
+            }
+            continue;
+        }
+        let Some(hunk) = diff.hunks.last_mut() else {
+            if line.starts_with("Binary files ") {
+                diff.binary = true;
+            }
+            continue;
+        };
+        if word_diff && line == "~" {
+            // End of a word-diffed line.
+            hunk.lines.push(word_diff_line(
+                std::mem::take(&mut segments),
+                &mut old_line,
+                &mut new_line,
+            ));
+            continue;
+        }
+        let mut chars = line.chars();
+        let Some(kind) = chars.next().and_then(line_kind) else {
+            // `\ No newline at end of file`
+            continue;
+        };
+        if word_diff {
+            segments.push(DiffSegment {
+                kind,
+                text: chars.as_str().to_string(),
+            });
+            continue;
+        }
+        let (old, new) = match kind {
+            DiffLineKind::Addition => (None, Some(new_line)),
+            DiffLineKind::Deletion => (Some(old_line), None),
+            DiffLineKind::Context => (Some(old_line), Some(new_line)),
+        };
+        if old.is_some() {
+            old_line += 1;
+        }
+        if new.is_some() {
+            new_line += 1;
+        }
+        hunk.lines.push(DiffLine {
+            kind,
+            content: line.to_string(),
+            old_line: old,
+            new_line: new,
+            segments: None,
+        });
+    }
+    diff
+}
+
+/// A word-diffed line from its segments: an addition or deletion if every
+/// segment is one, otherwise context (with the changes in the segments).
+fn word_diff_line(segments: Vec<DiffSegment>, old_line: &mut u32, new_line: &mut u32) -> DiffLine {
+    let all = |kind| !segments.is_empty() && segments.iter().all(|s| s.kind == kind);
+    let kind = if all(DiffLineKind::Addition) {
+        DiffLineKind::Addition
+    } else if all(DiffLineKind::Deletion) {
+        DiffLineKind::Deletion
+    } else {
+        DiffLineKind::Context
+    };
+    let (old, new) = match kind {
+        DiffLineKind::Addition => (None, Some(*new_line)),
+        DiffLineKind::Deletion => (Some(*old_line), None),
+        DiffLineKind::Context => (Some(*old_line), Some(*new_line)),
+    };
+    if old.is_some() {
+        *old_line += 1;
+    }
+    if new.is_some() {
+        *new_line += 1;
+    }
+    let prefix = match kind {
+        DiffLineKind::Addition => '+',
+        DiffLineKind::Deletion => '-',
+        DiffLineKind::Context => ' ',
+    };
+    // The plain text of the line on the side it belongs to.
+    let text: String = segments
+        .iter()
+        .filter(|s| match kind {
+            DiffLineKind::Deletion => true,
+            _ => s.kind != DiffLineKind::Deletion,
+        })
+        .map(|s| s.text.as_str())
+        .collect();
+    DiffLine {
+        kind,
+        content: format!("{prefix}{text}"),
+        old_line: old,
+        new_line: new,
+        segments: Some(segments),
+    }
+}
+
+// --- Tauri Commands ---
+
+/// The changed files of a worktree. `group_path` locates the worktree on the
+/// group's SSH host, if it has one.
+#[tauri::command]
+pub async fn get_branch_diff_v2(
+    orca_db: tauri::State<'_, OrcaDb>,
+    worktree_path: String,
+    options: Option<DiffOptions>,
+    group_path: Option<String>,
+) -> Result<BranchDiff, String> {
+    let orca_db = orca_db.inner().clone();
+    spawn_git(move || {
+        let host = ssh::host_for(&orca_db, group_path.as_deref())?;
+        list_files(host.as_ref(), &worktree_path, &options.unwrap_or_default())
+    })
+    .await
+}
+
+/// One file's hunks, against the `base` returned by `get_branch_diff_v2`.
+#[tauri::command]
+pub async fn get_branch_diff_file(
+    orca_db: tauri::State<'_, OrcaDb>,
+    worktree_path: String,
+    base: String,
+    file: DiffFileRequest,
+    options: Option<DiffOptions>,
+    group_path: Option<String>,
+) -> Result<FileDiff, String> {
+    let orca_db = orca_db.inner().clone();
+    spawn_git(move || {
+        let host = ssh::host_for(&orca_db, group_path.as_deref())?;
+        file_hunks(
+            host.as_ref(),
+            &worktree_path,
+            &base,
+            &file,
+            &options.unwrap_or_default(),
+        )
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    #[test]
+    fn parses_raw_and_numstat_records() {
+        let sha = |c: char| c.to_string().repeat(40);
+        let zero = sha('0');
+        let output = format!(
+            ":100644 100644 {} {} M\0src/a.rs\0\
+             :000000 100644 {zero} {} A\0new.png\0\
+             :100644 100644 {} {} R087\0old/b.rs\0new/b.rs\0\
+             3\t1\tsrc/a.rs\0-\t-\tnew.png\0\
+             2\t2\t\0old/b.rs\0new/b.rs\0",
+            sha('a'),
+            sha('b'),
+            sha('c'),
+            sha('d'),
+            sha('e'),
+        );
+        let files = parse_raw_numstat(&output);
+        assert_eq!(files.len(), 3);
+
+        assert_eq!(files[0].path, "src/a.rs");
+        assert_eq!(files[0].status, FileStatus::Modified);
+        assert_eq!((files[0].additions, files[0].deletions), (3, 1));
+        assert_eq!(files[0].old_blob, Some(sha('a')));
+
+        assert_eq!(files[1].status, FileStatus::Added);
+        assert!(files[1].binary);
+        assert_eq!(files[1].old_blob, None);
+
+        assert_eq!(files[2].status, FileStatus::Renamed);
+        assert_eq!(files[2].path, "new/b.rs");
+        assert_eq!(files[2].old_path.as_deref(), Some("old/b.rs"));
+        assert_eq!((files[2].additions, files[2].deletions), (2, 2));
+    }
+
+    #[test]
+    fn parses_grep_counts() {
+        let counts = parse_grep_counts("a.txt\x003\nsub/with\nnewline\x0012\n");
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts["a.txt"], 3);
+        assert_eq!(counts["sub/with\nnewline"], 12);
+        assert!(parse_grep_counts("").is_empty());
+    }
+
+    #[test]
+    fn parses_hunks_with_line_numbers() {
+        let output = "\
+diff --git a/f b/f
+--- a/f
++++ b/f
+@@ -10,3 +10,3 @@ fn main() {
+ keep
+-old
++new
+ keep
+\\ No newline at end of file
+@@ -40 +40,2 @@
++added
+ tail
+";
+        let diff = parse_file_diff("f", output, false);
+        assert!(!diff.binary);
+        assert_eq!(diff.hunks.len(), 2);
+        let hunk = &diff.hunks[0];
+        assert_eq!(
+            (hunk.old_start, hunk.old_lines, hunk.new_start),
+            (10, 3, 10)
+        );
+        assert_eq!(hunk.lines.len(), 4);
+        assert_eq!(hunk.lines[1].kind, DiffLineKind::Deletion);
+        assert_eq!(
+            (hunk.lines[1].old_line, hunk.lines[1].new_line),
+            (Some(11), None)
+        );
+        assert_eq!(
+            (hunk.lines[2].old_line, hunk.lines[2].new_line),
+            (None, Some(11))
+        );
+        assert_eq!(
+            (hunk.lines[3].old_line, hunk.lines[3].new_line),
+            (Some(12), Some(12))
+        );
+        assert_eq!(diff.hunks[1].old_lines, 1);
+        assert_eq!(diff.hunks[1].lines[1].new_line, Some(41));
+    }
+
+    #[test]
+    fn parses_word_diff_porcelain() {
+        // As printed by git: a context segment keeps its trailing space.
+        let output = "@@ -1,2 +1,3 @@\n let x = \n-1;\n+2;\n~\n+brand new line\n~\n end\n~\n";
+        let diff = parse_file_diff("f", output, true);
+        let lines = &diff.hunks[0].lines;
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].kind, DiffLineKind::Context);
+        assert_eq!(lines[0].content, " let x = 2;");
+        assert_eq!(lines[0].segments.as_ref().unwrap().len(), 3);
+        assert_eq!(lines[1].kind, DiffLineKind::Addition);
+        assert_eq!((lines[1].old_line, lines[1].new_line), (None, Some(2)));
+        assert_eq!((lines[2].old_line, lines[2].new_line), (Some(2), Some(3)));
+    }
+
+    #[test]
+    fn marks_binary_files() {
+        let diff = parse_file_diff("x.png", "Binary files a/x.png and b/x.png differ\n", false);
+        assert!(diff.binary);
+        assert!(diff.hunks.is_empty());
+    }
+
+    fn run_git(dir: &std::path::Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(["-c", "user.name=t", "-c", "user.email=t@t"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .expect("git");
+        assert!(status.status.success(), "git {args:?}: {status:?}");
+    }
+
+    #[test]
+    fn separates_committed_and_uncommitted_changes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        run_git(dir, &["init", "-q", "-b", "main"]);
+        std::fs::write(dir.join("a.txt"), "one\ntwo\n").unwrap();
+        run_git(dir, &["add", "."]);
+        run_git(dir, &["commit", "-qm", "init"]);
+        run_git(dir, &["checkout", "-qb", "feature"]);
+        std::fs::write(dir.join("a.txt"), "one\n2\n").unwrap();
+        run_git(dir, &["commit", "-qam", "change"]);
+        std::fs::write(dir.join("a.txt"), "one\n2\nthree\n").unwrap();
+        std::fs::write(dir.join("notes.md"), "x\ny\n").unwrap();
+        std::fs::write(dir.join("pic.bin"), b"\x00\x01\n\x02").unwrap();
+        std::fs::write(dir.join("z empty"), "").unwrap();
+        let path = dir.to_str().unwrap();
+
+        let committed = list_files(None, path, &DiffOptions::default()).unwrap();
+        assert_eq!(committed.files.len(), 1);
+        assert_eq!((committed.additions, committed.deletions), (1, 1));
+
+        let options = DiffOptions {
+            scope: DiffScope::Uncommitted,
+            ..DiffOptions::default()
+        };
+        let uncommitted = list_files(None, path, &options).unwrap();
+        let paths: Vec<&str> = uncommitted.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, ["a.txt", "notes.md", "pic.bin", "z empty"]);
+        assert_eq!(uncommitted.files[1].status, FileStatus::Untracked);
+        assert_eq!(uncommitted.files[1].additions, 2);
+        assert!(!uncommitted.files[1].binary);
+        assert!(uncommitted.files[2].binary);
+        assert_eq!(
+            (uncommitted.files[3].additions, uncommitted.files[3].binary),
+            (0, false)
+        );
+
+        let request = DiffFileRequest {
+            path: "a.txt".to_string(),
+            old_path: None,
+            untracked: false,
+        };
+        let hunks = file_hunks(None, path, &uncommitted.base, &request, &options).unwrap();
+        assert_eq!(hunks.hunks[0].lines.last().unwrap().content, "+three");
+
+        let request = DiffFileRequest {
+            path: "notes.md".to_string(),
+            old_path: None,
+            untracked: true,
+        };
+        let hunks = file_hunks(None, path, &uncommitted.base, &request, &options).unwrap();
+        assert_eq!(hunks.hunks[0].lines.len(), 2);
+    }
+}
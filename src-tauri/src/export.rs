@@ -0,0 +1,603 @@
+//! Export a session's transcript as Markdown, HTML or a JSON bundle.
+//!
+//! The transcript comes from the group's backend (local JSONL, OpenCode or
+//! AgentAPI), so every backend exports the same way. Markdown and HTML are
+//! self-contained documents with thinking, tool calls and tool output
+//! collapsed into `<details>` blocks, suitable for pasting into a PR or
+//! incident write-up. The JSON bundle carries the session metadata (branch,
+//! PR, prompt) alongside the typed entries.
+
+use crate::backend::backend_for_group;
+use crate::dates;
+use crate::models::Session;
+use crate::orca_db::OrcaDb;
+use crate::transcript::{TranscriptBlock, TranscriptEntry};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write;
+use tauri::State;
+
+/// Bumped when the JSON bundle layout changes incompatibly.
+const BUNDLE_VERSION: u32 = 1;
+const TOOL_HINT_CHARS: usize = 80;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Markdown,
+    Html,
+    Json,
+}
+
+impl ExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Html => "html",
+            ExportFormat::Json => "json",
+        }
+    }
+
+    fn mime_type(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "text/markdown",
+            ExportFormat::Html => "text/html",
+            ExportFormat::Json => "application/json",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportBundle {
+    pub version: u32,
+    /// Unix seconds.
+    pub exported_at: i64,
+    pub backend: String,
+    pub session: Session,
+    pub entries: Vec<TranscriptEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptExport {
+    /// Suggested file name, derived from the session title.
+    pub file_name: String,
+    pub mime_type: String,
+    pub content: String,
+}
+
+// ── Helpers ──────────────────────────────────────────────────────────
+
+fn slugify(title: &str) -> String {
+    let mut slug = String::new();
+    for c in title.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "transcript".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+/// A code fence longer than any backtick run inside `text`.
+fn fence(text: &str) -> String {
+    let mut longest = 0;
+    let mut run = 0;
+    for c in text.chars() {
+        if c == '`' {
+            run += 1;
+            longest = longest.max(run);
+        } else {
+            run = 0;
+        }
+    }
+    "`".repeat((longest + 1).max(3))
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// A one-line hint for a collapsed tool call, e.g. the file or command.
+fn tool_hint(input: &serde_json::Value) -> Option<String> {
+    let value = [
+        "file_path",
+        "command",
+        "pattern",
+        "path",
+        "url",
+        "description",
+    ]
+    .iter()
+    .find_map(|key| input.get(key)?.as_str())?;
+    let line = value.lines().next().unwrap_or("").trim();
+    if line.chars().count() > TOOL_HINT_CHARS {
+        Some(format!(
+            "{}…",
+            line.chars().take(TOOL_HINT_CHARS).collect::<String>()
+        ))
+    } else {
+        Some(line.to_string())
+    }
+}
+
+fn tool_summary(name: &str, input: &serde_json::Value) -> String {
+    match tool_hint(input) {
+        Some(hint) if !hint.is_empty() => format!("Tool: {name} — {hint}"),
+        _ => format!("Tool: {name}"),
+    }
+}
+
+fn result_summary(name: Option<&str>, is_error: bool) -> String {
+    let mut summary = match name {
+        Some(name) => format!("Result: {name}"),
+        None => "Result".to_string(),
+    };
+    if is_error {
+        summary.push_str(" (error)");
+    }
+    summary
+}
+
+/// Tool names by tool_use id, so results can be labelled.
+fn tool_names(entries: &[TranscriptEntry]) -> HashMap<&str, &str> {
+    entries
+        .iter()
+        .flat_map(|e| &e.blocks)
+        .filter_map(|b| match b {
+            TranscriptBlock::ToolUse { id, name, .. } => Some((id.as_str(), name.as_str())),
+            _ => None,
+        })
+        .collect()
+}
+
+fn role_heading(entry: &TranscriptEntry) -> String {
+    let mut heading = match entry.role.as_str() {
+        "user" => "User".to_string(),
+        "assistant" => "Assistant".to_string(),
+        other => {
+            let mut chars = other.chars();
+            chars
+                .next()
+                .map(|c| c.to_uppercase().chain(chars).collect())
+                .unwrap_or_default()
+        }
+    };
+    if entry.is_sidechain {
+        heading.push_str(" (subagent)");
+    }
+    heading
+}
+
+/// Tool results come back as "user" turns; render them under the turn that
+/// made the call rather than as a new user message.
+fn is_tool_result_only(entry: &TranscriptEntry) -> bool {
+    entry
+        .blocks
+        .iter()
+        .all(|b| matches!(b, TranscriptBlock::ToolResult { .. }))
+}
+
+fn pretty_json(value: &serde_json::Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
+
+// ── Markdown ─────────────────────────────────────────────────────────
+
+fn markdown_details(out: &mut String, summary: &str, body: &str) {
+    let _ = write!(
+        out,
+        "<details>\n<summary>{}</summary>\n\n{body}\n\n</details>\n\n",
+        escape_html(summary)
+    );
+}
+
+fn markdown_code(lang: &str, text: &str) -> String {
+    let fence = fence(text);
+    format!("{fence}{lang}\n{text}\n{fence}")
+}
+
+fn render_markdown(bundle: &ExportBundle) -> String {
+    let session = &bundle.session;
+    let mut out = String::new();
+    let _ = writeln!(out, "# {}\n", session.title);
+    let _ = writeln!(out, "- **Session:** `{}`", session.id);
+    let _ = writeln!(out, "- **Backend:** {}", bundle.backend);
+    if !session.project_path.is_empty() {
+        let _ = writeln!(out, "- **Project:** `{}`", session.project_path);
+    }
+    if !session.worktree_branch.is_empty() {
+        let _ = writeln!(out, "- **Branch:** `{}`", session.worktree_branch);
+    }
+    if let Some(url) = &session.pr_url {
+        let label = session
+            .pr_number
+            .map_or_else(|| url.clone(), |n| format!("#{n}"));
+        let state = session
+            .pr_state
+            .as_deref()
+            .map(|s| format!(" ({s})"))
+            .unwrap_or_default();
+        let _ = writeln!(out, "- **PR:** [{label}]({url}){state}");
+    }
+    out.push('\n');
+
+    if let Some(prompt) = session.prompt.as_deref().filter(|p| !p.trim().is_empty()) {
+        out.push_str("## Prompt\n\n");
+        for line in prompt.lines() {
+            let _ = writeln!(out, "> {line}");
+        }
+        out.push('\n');
+    }
+
+    out.push_str("## Transcript\n\n");
+    let names = tool_names(&bundle.entries);
+    for entry in &bundle.entries {
+        if !is_tool_result_only(entry) {
+            let _ = write!(out, "### {}", role_heading(entry));
+            if let Some(ts) = &entry.timestamp {
+                let _ = write!(out, " · {ts}");
+            }
+            out.push_str("\n\n");
+        }
+        for block in &entry.blocks {
+            match block {
+                TranscriptBlock::Text { text } => {
+                    let _ = writeln!(out, "{}\n", text.trim_end());
+                }
+                TranscriptBlock::Thinking { text } => {
+                    markdown_details(&mut out, "Thinking", text.trim_end());
+                }
+                TranscriptBlock::ToolUse { name, input, .. } => {
+                    markdown_details(
+                        &mut out,
+                        &tool_summary(name, input),
+                        &markdown_code("json", &pretty_json(input)),
+                    );
+                }
+                TranscriptBlock::ToolResult {
+                    tool_use_id,
+                    output,
+                    is_error,
+                    truncated,
+                } => {
+                    let mut body = markdown_code("", output.trim_end());
+                    if *truncated {
+                        body.push_str("\n\n_Output truncated._");
+                    }
+                    let name = names.get(tool_use_id.as_str()).copied();
+                    markdown_details(&mut out, &result_summary(name, *is_error), &body);
+                }
+                TranscriptBlock::Image => out.push_str("_[image]_\n\n"),
+            }
+        }
+    }
+    out
+}
+
+// ── HTML ─────────────────────────────────────────────────────────────
+
+const HTML_STYLE: &str = "\
+body{font:14px/1.5 -apple-system,BlinkMacSystemFont,'Segoe UI',sans-serif;max-width:960px;margin:2em auto;padding:0 1em;color:#1f2328}\
+h1{font-size:1.6em}dl{display:grid;grid-template-columns:max-content 1fr;gap:.2em 1em}dt{font-weight:600}dd{margin:0}\
+blockquote{border-left:3px solid #d0d7de;margin:0;padding:0 1em;color:#57606a;white-space:pre-wrap}\
+.entry{border-top:1px solid #d0d7de;padding:.5em 0}.role{font-weight:600;margin:.5em 0}.role time{font-weight:400;color:#57606a;margin-left:.5em}\
+.assistant .role{color:#0969da}.user .role{color:#1a7f37}.text{white-space:pre-wrap}\
+details{background:#f6f8fa;border:1px solid #d0d7de;border-radius:6px;margin:.5em 0;padding:.3em .8em}\
+summary{cursor:pointer;font-family:ui-monospace,monospace;font-size:.9em}details.error summary{color:#cf222e}\
+pre{overflow-x:auto;white-space:pre-wrap;font-size:.85em}";
+
+fn html_details(out: &mut String, class: &str, summary: &str, body: &str) {
+    let _ = writeln!(
+        out,
+        "<details class=\"{class}\"><summary>{}</summary>{body}</details>",
+        escape_html(summary)
+    );
+}
+
+fn html_pre(text: &str) -> String {
+    format!("<pre>{}</pre>", escape_html(text))
+}
+
+fn render_html(bundle: &ExportBundle) -> String {
+    let session = &bundle.session;
+    let title = escape_html(&session.title);
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{title}</title>\n<style>{HTML_STYLE}</style>\n</head>\n<body>\n<h1>{title}</h1>\n<dl>\n"
+    );
+    let mut meta = |label: &str, value: String| {
+        let _ = writeln!(out, "<dt>{label}</dt><dd>{value}</dd>");
+    };
+    meta(
+        "Session",
+        format!("<code>{}</code>", escape_html(&session.id)),
+    );
+    meta("Backend", escape_html(&bundle.backend));
+    if !session.project_path.is_empty() {
+        meta(
+            "Project",
+            format!("<code>{}</code>", escape_html(&session.project_path)),
+        );
+    }
+    if !session.worktree_branch.is_empty() {
+        meta(
+            "Branch",
+            format!("<code>{}</code>", escape_html(&session.worktree_branch)),
+        );
+    }
+    if let Some(url) = &session.pr_url {
+        let label = session
+            .pr_number
+            .map_or_else(|| url.clone(), |n| format!("#{n}"));
+        let state = session
+            .pr_state
+            .as_deref()
+            .map(|s| format!(" ({})", escape_html(s)))
+            .unwrap_or_default();
+        meta(
+            "PR",
+            format!(
+                "<a href=\"{}\">{}</a>{state}",
+                escape_html(url),
+                escape_html(&label)
+            ),
+        );
+    }
+    out.push_str("</dl>\n");
+
+    if let Some(prompt) = session.prompt.as_deref().filter(|p| !p.trim().is_empty()) {
+        let _ = writeln!(
+            out,
+            "<h2>Prompt</h2>\n<blockquote>{}</blockquote>",
+            escape_html(prompt)
+        );
+    }
+
+    out.push_str("<h2>Transcript</h2>\n");
+    let names = tool_names(&bundle.entries);
+    for entry in &bundle.entries {
+        let _ = writeln!(
+            out,
+            "<section class=\"entry {}\">",
+            escape_html(&entry.role)
+        );
+        if !is_tool_result_only(entry) {
+            let _ = write!(
+                out,
+                "<div class=\"role\">{}",
+                escape_html(&role_heading(entry))
+            );
+            if let Some(ts) = &entry.timestamp {
+                let _ = write!(out, "<time>{}</time>", escape_html(ts));
+            }
+            out.push_str("</div>\n");
+        }
+        for block in &entry.blocks {
+            match block {
+                TranscriptBlock::Text { text } => {
+                    let _ = writeln!(out, "<div class=\"text\">{}</div>", escape_html(text));
+                }
+                TranscriptBlock::Thinking { text } => {
+                    html_details(&mut out, "thinking", "Thinking", &html_pre(text));
+                }
+                TranscriptBlock::ToolUse { name, input, .. } => {
+                    html_details(
+                        &mut out,
+                        "tool",
+                        &tool_summary(name, input),
+                        &html_pre(&pretty_json(input)),
+                    );
+                }
+                TranscriptBlock::ToolResult {
+                    tool_use_id,
+                    output,
+                    is_error,
+                    truncated,
+                } => {
+                    let mut body = html_pre(output);
+                    if *truncated {
+                        body.push_str("<p><em>Output truncated.</em></p>");
+                    }
+                    let name = names.get(tool_use_id.as_str()).copied();
+                    let class = if *is_error { "result error" } else { "result" };
+                    html_details(&mut out, class, &result_summary(name, *is_error), &body);
+                }
+                TranscriptBlock::Image => out.push_str("<p><em>[image]</em></p>\n"),
+            }
+        }
+        out.push_str("</section>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+// ── Export ───────────────────────────────────────────────────────────
+
+fn render(bundle: &ExportBundle, format: ExportFormat) -> Result<TranscriptExport, String> {
+    let content = match format {
+        ExportFormat::Markdown => render_markdown(bundle),
+        ExportFormat::Html => render_html(bundle),
+        ExportFormat::Json => serde_json::to_string_pretty(bundle)
+            .map_err(|e| format!("Failed to serialize transcript: {e}"))?,
+    };
+    Ok(TranscriptExport {
+        file_name: format!("{}.{}", slugify(&bundle.session.title), format.extension()),
+        mime_type: format.mime_type().to_string(),
+        content,
+    })
+}
+
+/// Export a session's transcript. If `output_path` is given the document is
+/// also written there.
+#[tauri::command]
+pub async fn export_session_transcript(
+    orca_db: State<'_, OrcaDb>,
+    group_path: String,
+    session_id: String,
+    format: ExportFormat,
+    output_path: Option<String>,
+) -> Result<TranscriptExport, String> {
+    let backend = backend_for_group(&orca_db, &group_path)?;
+    let session = backend
+        .list_sessions()
+        .await?
+        .into_iter()
+        .find(|s| s.id == session_id)
+        .ok_or_else(|| format!("Session not found: {session_id}"))?;
+    let entries = backend.get_transcript(&session_id).await?;
+
+    let bundle = ExportBundle {
+        version: BUNDLE_VERSION,
+        exported_at: dates::now_secs() as i64,
+        backend: orca_db.get_group_backend(&group_path)?,
+        session,
+        entries,
+    };
+    let export = render(&bundle, format)?;
+    if let Some(path) = output_path {
+        std::fs::write(&path, &export.content)
+            .map_err(|e| format!("Failed to write {path}: {e}"))?;
+        log::info!("Exported transcript for {session_id} to {path}");
+    }
+    Ok(export)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn bundle() -> ExportBundle {
+        ExportBundle {
+            version: BUNDLE_VERSION,
+            exported_at: 0,
+            backend: "local".into(),
+            session: Session {
+                id: "s1".into(),
+                title: "Fix <login> bug".into(),
+                project_path: "/src/app".into(),
+                group_path: "/src/app".into(),
+                sort_order: 0,
+                status: "idle".into(),
+                tmux_session: String::new(),
+                created_at: 0,
+                last_accessed: 0,
+                worktree_path: String::new(),
+                worktree_repo: String::new(),
+                worktree_branch: "fix-login".into(),
+                tool: "claude".into(),
+                claude_session_id: None,
+                opencode_session_id: None,
+                prompt: Some("Fix the login bug".into()),
+                pr_url: Some("https://github.com/o/r/pull/7".into()),
+                pr_number: Some(7),
+                pr_state: Some("open".into()),
+            },
+            entries: vec![
+                TranscriptEntry {
+                    uuid: None,
+                    parent_uuid: None,
+                    role: "assistant".into(),
+                    timestamp: None,
+                    model: None,
+                    is_sidechain: false,
+                    agent_id: None,
+                    blocks: vec![
+                        TranscriptBlock::Text {
+                            text: "Reading the handler".into(),
+                        },
+                        TranscriptBlock::ToolUse {
+                            id: "t1".into(),
+                            name: "Read".into(),
+                            input: json!({"file_path": "/src/app/login.rs"}),
+                        },
+                    ],
+                },
+                TranscriptEntry {
+                    uuid: None,
+                    parent_uuid: None,
+                    role: "user".into(),
+                    timestamp: None,
+                    model: None,
+                    is_sidechain: false,
+                    agent_id: None,
+                    blocks: vec![TranscriptBlock::ToolResult {
+                        tool_use_id: "t1".into(),
+                        output: "fn login() {}\n```".into(),
+                        is_error: false,
+                        truncated: false,
+                    }],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn markdown_collapses_tool_calls() {
+        let md = render_markdown(&bundle());
+        assert!(md.starts_with("# Fix <login> bug\n"));
+        assert!(md.contains("- **Branch:** `fix-login`"));
+        assert!(md.contains("- **PR:** [#7](https://github.com/o/r/pull/7) (open)"));
+        assert!(md.contains("> Fix the login bug"));
+        assert!(md.contains("<summary>Tool: Read — /src/app/login.rs</summary>"));
+        assert!(md.contains("<summary>Result: Read</summary>"));
+        // Output containing ``` gets a longer fence
+        assert!(md.contains("````\nfn login() {}\n```\n````"));
+        // Tool results don't start a new "User" turn
+        assert!(!md.contains("### User"));
+    }
+
+    #[test]
+    fn html_is_escaped_and_self_contained() {
+        let html = render_html(&bundle());
+        assert!(html.contains("<title>Fix &lt;login&gt; bug</title>"));
+        assert!(html.contains("<style>"));
+        assert!(html.contains("<details class=\"tool\"><summary>Tool: Read"));
+        assert!(html.contains("<a href=\"https://github.com/o/r/pull/7\">#7</a> (open)"));
+        assert!(!html.contains("<login>"));
+    }
+
+    #[test]
+    fn json_bundle_includes_session_metadata() {
+        let export = render(&bundle(), ExportFormat::Json).expect("render failed");
+        assert_eq!(export.file_name, "fix-login-bug.json");
+        assert_eq!(export.mime_type, "application/json");
+        let value: serde_json::Value = serde_json::from_str(&export.content).expect("json");
+        assert_eq!(value["version"], 1);
+        assert_eq!(value["session"]["worktree_branch"], "fix-login");
+        assert_eq!(value["session"]["prompt"], "Fix the login bug");
+        assert_eq!(value["entries"][0]["blocks"][1]["type"], "tool_use");
+    }
+
+    #[test]
+    fn slugify_handles_symbols() {
+        assert_eq!(slugify("Fix <login> bug!"), "fix-login-bug");
+        assert_eq!(slugify("***"), "transcript");
+    }
+
+    #[test]
+    fn tool_hint_truncates_long_commands() {
+        let hint = tool_hint(&json!({"command": "x".repeat(200)})).expect("hint");
+        assert_eq!(hint.chars().count(), TOOL_HINT_CHARS + 1);
+        assert_eq!(tool_hint(&json!({"other": 1})), None);
+    }
+}
@@ -1,9 +1,11 @@
-use crate::command::{expand_tilde, new_command, run_cmd, run_cmd_status};
+use crate::command::{expand_tilde, new_command, output_on, run_cmd_on, run_cmd_status_on};
+use crate::orca_db::OrcaDb;
+use crate::ssh::{self, SshHost};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
 /// Run a blocking git closure on the tokio blocking thread pool so it never blocks the IPC handler.
-async fn spawn_git<F, T>(f: F) -> Result<T, String>
+pub(crate) async fn spawn_git<F, T>(f: F) -> Result<T, String>
 where
     F: FnOnce() -> Result<T, String> + Send + 'static,
     T: Send + 'static,
@@ -22,11 +24,20 @@ pub struct Worktree {
 }
 
 fn run_git(repo_path: &str, args: &[&str]) -> Result<String, String> {
-    run_cmd("git", repo_path, args)
+    run_git_on(None, repo_path, args)
 }
 
-fn run_git_status(repo_path: &str, args: &[&str]) -> Result<(String, bool), String> {
-    run_cmd_status("git", repo_path, args)
+/// [`run_git`] on an SSH host when `host` is set.
+fn run_git_on(host: Option<&SshHost>, repo_path: &str, args: &[&str]) -> Result<String, String> {
+    run_cmd_on(host, "git", repo_path, args)
+}
+
+fn run_git_status_on(
+    host: Option<&SshHost>,
+    repo_path: &str,
+    args: &[&str],
+) -> Result<(String, bool), String> {
+    run_cmd_status_on(host, "git", repo_path, args)
 }
 
 /// Parse the porcelain output of `git worktree list --porcelain` into Worktree structs.
@@ -80,16 +91,16 @@ pub fn parse_worktree_list(output: &str) -> Vec<Worktree> {
     worktrees
 }
 
-fn list_worktrees_sync(repo_path: &str) -> Result<Vec<Worktree>, String> {
+fn list_worktrees_on(host: Option<&SshHost>, repo_path: &str) -> Result<Vec<Worktree>, String> {
     // Find the actual git dir - might be a worktree itself, so go up to find .bare or .git
-    let effective_repo = find_repo_root(repo_path)?;
-    let output = run_git(&effective_repo, &["worktree", "list", "--porcelain"])?;
+    let effective_repo = find_repo_root_on(host, repo_path)?;
+    let output = run_git_on(host, &effective_repo, &["worktree", "list", "--porcelain"])?;
     Ok(parse_worktree_list(&output))
 }
 
 #[tauri::command]
 pub async fn list_worktrees(repo_path: String) -> Result<Vec<Worktree>, String> {
-    spawn_git(move || list_worktrees_sync(&repo_path)).await
+    spawn_git(move || list_worktrees_on(None, &repo_path)).await
 }
 
 #[tauri::command]
@@ -128,16 +139,26 @@ pub async fn add_worktree(repo_path: String, branch: String) -> Result<String, S
 
 /// Remove a worktree and its branch (non-Tauri, callable from background threads).
 pub fn remove_worktree_sync(repo_path: &str, worktree_path: &str) -> Result<(), String> {
-    let effective_repo = find_repo_root(repo_path)?;
+    remove_worktree_on(None, repo_path, worktree_path)
+}
+
+/// [`remove_worktree_sync`] for a repo on an SSH host when `host` is set.
+pub fn remove_worktree_on(
+    host: Option<&SshHost>,
+    repo_path: &str,
+    worktree_path: &str,
+) -> Result<(), String> {
+    let effective_repo = find_repo_root_on(host, repo_path)?;
 
     // Get the branch name before removing
-    let worktrees = list_worktrees_sync(repo_path)?;
+    let worktrees = list_worktrees_on(host, repo_path)?;
     let branch = worktrees
         .iter()
         .find(|w| w.path == worktree_path)
         .map(|w| w.branch.clone());
 
-    run_git(
+    run_git_on(
+        host,
         &effective_repo,
         &["worktree", "remove", worktree_path, "--force"],
     )?;
@@ -145,7 +166,7 @@ pub fn remove_worktree_sync(repo_path: &str, worktree_path: &str) -> Result<(),
     // Clean up the branch (best-effort — worktree is already removed)
     if let Some(branch_name) = branch {
         if branch_name != "main" && branch_name != "master" {
-            if let Err(e) = run_git(&effective_repo, &["branch", "-D", &branch_name]) {
+            if let Err(e) = run_git_on(host, &effective_repo, &["branch", "-D", &branch_name]) {
                 log::warn!("Failed to delete branch '{branch_name}' after worktree removal: {e}");
             }
         }
@@ -154,37 +175,76 @@ pub fn remove_worktree_sync(repo_path: &str, worktree_path: &str) -> Result<(),
     Ok(())
 }
 
+// Commands taking the session's `group_path` run git on the group's SSH
+// host, if it has one (see `ssh::host_for`).
+
 #[tauri::command]
-pub async fn remove_worktree(repo_path: String, worktree_path: String) -> Result<(), String> {
-    spawn_git(move || remove_worktree_sync(&repo_path, &worktree_path)).await
+pub async fn remove_worktree(
+    orca_db: tauri::State<'_, OrcaDb>,
+    repo_path: String,
+    worktree_path: String,
+    group_path: Option<String>,
+) -> Result<(), String> {
+    let orca_db = orca_db.inner().clone();
+    spawn_git(move || {
+        let host = ssh::host_for(&orca_db, group_path.as_deref())?;
+        remove_worktree_on(host.as_ref(), &repo_path, &worktree_path)
+    })
+    .await
 }
 
 #[tauri::command]
 pub async fn merge_worktree(
+    orca_db: tauri::State<'_, OrcaDb>,
     repo_path: String,
     branch: String,
     main_branch: Option<String>,
+    strategy: Option<MergeStrategy>,
+    commit_message: Option<String>,
+    group_path: Option<String>,
 ) -> Result<(), String> {
+    let orca_db = orca_db.inner().clone();
     spawn_git(move || {
-        let effective_repo = find_repo_root(&repo_path)?;
+        let host = ssh::host_for(&orca_db, group_path.as_deref())?;
+        let host = host.as_ref();
+        let effective_repo = find_repo_root_on(host, &repo_path)?;
         let target = main_branch.unwrap_or_else(|| "main".to_string());
 
         // Find the main worktree path
-        let worktrees = list_worktrees_sync(&repo_path)?;
+        let worktrees = list_worktrees_on(host, &repo_path)?;
         let main_wt = worktrees
             .iter()
             .find(|w| w.branch == target)
             .ok_or(format!("Could not find worktree for branch '{target}'"))?;
 
         // Merge the branch into main from the main worktree
-        run_git(&main_wt.path, &["merge", &branch])?;
+        let result = run_merge(
+            host,
+            &main_wt.path,
+            &branch,
+            strategy.unwrap_or_default(),
+            commit_message.as_deref(),
+        )?;
+        if !result.success {
+            return Err(result.conflict_message.unwrap_or_default());
+        }
 
         // Clean up the branch worktree (best-effort — merge already succeeded)
         if let Some(branch_wt) = worktrees.iter().find(|w| w.branch == branch) {
-            if let Err(e) = run_git(&effective_repo, &["worktree", "remove", &branch_wt.path]) {
+            if let Err(e) = run_git_on(
+                host,
+                &effective_repo,
+                &["worktree", "remove", &branch_wt.path],
+            ) {
                 log::warn!("Failed to remove worktree '{}': {e}", branch_wt.path);
             }
-            if let Err(e) = run_git(&effective_repo, &["branch", "-d", &branch]) {
+            // A squashed branch isn't merged as far as `-d` can tell.
+            let delete = if strategy == Some(MergeStrategy::Squash) {
+                "-D"
+            } else {
+                "-d"
+            };
+            if let Err(e) = run_git_on(host, &effective_repo, &["branch", delete, &branch]) {
                 log::warn!("Failed to delete branch '{branch}': {e}");
             }
         }
@@ -196,28 +256,37 @@ pub async fn merge_worktree(
 
 #[tauri::command]
 pub async fn rebase_worktree(
+    orca_db: tauri::State<'_, OrcaDb>,
     worktree_path: String,
     main_branch: Option<String>,
+    group_path: Option<String>,
 ) -> Result<(), String> {
+    let orca_db = orca_db.inner().clone();
     spawn_git(move || {
+        let host = ssh::host_for(&orca_db, group_path.as_deref())?;
+        let host = host.as_ref();
         let target = main_branch.unwrap_or_else(|| "main".to_string());
 
         // Fetch latest (best-effort — may be offline) then rebase
-        if let Err(e) = run_git(&worktree_path, &["fetch", "origin", &target]) {
+        if let Err(e) = run_git_on(host, &worktree_path, &["fetch", "origin", &target]) {
             log::warn!("Failed to fetch origin/{target}, rebasing against local: {e}");
         }
-        run_git(&worktree_path, &["rebase", &target])?;
+        run_git_on(host, &worktree_path, &["rebase", &target])?;
 
         Ok(())
     })
     .await
 }
 
-fn get_default_branch_inner(repo_path: &str) -> Result<String, String> {
+pub fn get_default_branch_inner(repo_path: &str) -> Result<String, String> {
+    get_default_branch_on(None, repo_path)
+}
+
+/// [`get_default_branch_inner`] for a repo on an SSH host when `host` is set.
+pub fn get_default_branch_on(host: Option<&SshHost>, repo_path: &str) -> Result<String, String> {
+    let git_status = |args: &[&str]| run_cmd_status_on(host, "git", repo_path, args);
     // Try symbolic-ref of origin/HEAD first (expected to fail if origin/HEAD is not set)
-    if let Ok((output, true)) =
-        run_git_status(repo_path, &["symbolic-ref", "refs/remotes/origin/HEAD"])
-    {
+    if let Ok((output, true)) = git_status(&["symbolic-ref", "refs/remotes/origin/HEAD"]) {
         let trimmed = output.trim();
         if let Some(branch) = trimmed.strip_prefix("refs/remotes/origin/") {
             return Ok(branch.to_string());
@@ -225,24 +294,18 @@ fn get_default_branch_inner(repo_path: &str) -> Result<String, String> {
     }
 
     // Fallback: check if local "main" or "master" branches exist
-    if let Ok((_, true)) = run_git_status(repo_path, &["rev-parse", "--verify", "main"]) {
+    if let Ok((_, true)) = git_status(&["rev-parse", "--verify", "main"]) {
         return Ok("main".to_string());
     }
-    if let Ok((_, true)) = run_git_status(repo_path, &["rev-parse", "--verify", "master"]) {
+    if let Ok((_, true)) = git_status(&["rev-parse", "--verify", "master"]) {
         return Ok("master".to_string());
     }
 
     // Fallback: check remote tracking branches (for bare repos before worktree creation)
-    if let Ok((_, true)) = run_git_status(
-        repo_path,
-        &["rev-parse", "--verify", "refs/remotes/origin/main"],
-    ) {
+    if let Ok((_, true)) = git_status(&["rev-parse", "--verify", "refs/remotes/origin/main"]) {
         return Ok("main".to_string());
     }
-    if let Ok((_, true)) = run_git_status(
-        repo_path,
-        &["rev-parse", "--verify", "refs/remotes/origin/master"],
-    ) {
+    if let Ok((_, true)) = git_status(&["rev-parse", "--verify", "refs/remotes/origin/master"]) {
         return Ok("master".to_string());
     }
 
@@ -250,37 +313,60 @@ fn get_default_branch_inner(repo_path: &str) -> Result<String, String> {
 }
 
 #[tauri::command]
-pub async fn get_default_branch(repo_path: String) -> Result<String, String> {
-    spawn_git(move || get_default_branch_inner(&repo_path)).await
+pub async fn get_default_branch(
+    orca_db: tauri::State<'_, OrcaDb>,
+    repo_path: String,
+    group_path: Option<String>,
+) -> Result<String, String> {
+    let orca_db = orca_db.inner().clone();
+    spawn_git(move || {
+        let host = ssh::host_for(&orca_db, group_path.as_deref())?;
+        get_default_branch_on(host.as_ref(), &repo_path)
+    })
+    .await
 }
 
+/// `group_path` locates the worktree on the group's SSH host, if it has one.
 #[tauri::command]
-pub async fn get_branch_diff(worktree_path: String, _branch: String) -> Result<String, String> {
+pub async fn get_branch_diff(
+    orca_db: tauri::State<'_, OrcaDb>,
+    worktree_path: String,
+    _branch: String,
+    group_path: Option<String>,
+) -> Result<String, String> {
+    let orca_db = orca_db.inner().clone();
     spawn_git(move || {
-        let base = get_default_branch_inner(&worktree_path)?;
-        // Show only the changes unique to this branch vs the default branch.
-        //
-        // We use `git diff <default>...HEAD` (three-dot) which diffs from the
-        // merge-base of <default> and HEAD to HEAD. This means:
-        //   - Only this branch's changes are shown, not commits on <default>
-        //   - Even if <default> moves forward (e.g. other branches merged), the
-        //     merge-base stays at the fork point, so the diff stays correct
-        //   - If this branch has been merged into <default>, the diff is empty
-        //     (correct: the changes are already on <default>)
-        //
-        // We use the LOCAL default branch, not origin/<default>. Using origin/
-        // shows too many changes when origin is behind local (common in bare
-        // worktree setups where you merge locally before pushing).
-        //
-        // We use HEAD instead of the branch name because the session's
-        // worktree_branch can be the directory name rather than the actual git
-        // branch name.
-        let range = format!("{base}...HEAD");
-        run_git(&worktree_path, &["diff", &range])
+        let host = ssh::host_for(&orca_db, group_path.as_deref())?;
+        get_branch_diff_on(host.as_ref(), &worktree_path)
     })
     .await
 }
 
+/// The worktree's changes against the local default branch, on an SSH host
+/// when `host` is set.
+pub fn get_branch_diff_on(host: Option<&SshHost>, worktree_path: &str) -> Result<String, String> {
+    let base = get_default_branch_on(host, worktree_path)?;
+    // Show only the changes unique to this branch vs the default branch.
+    //
+    // We use `git diff <default>...HEAD` (three-dot) which diffs from the
+    // merge-base of <default> and HEAD to HEAD. This means:
+    //   - Only this branch's changes are shown, not commits on <default>
+    //   - Even if <default> moves forward (e.g. other branches merged), the
+    //     merge-base stays at the fork point, so the diff stays correct
+    //   - If this branch has been merged into <default>, the diff is empty
+    //     (correct: the changes are already on <default>)
+    //
+    // We use the LOCAL default branch, not origin/<default>. Using origin/
+    // shows too many changes when origin is behind local (common in bare
+    // worktree setups where you merge locally before pushing).
+    //
+    // We use HEAD instead of the branch name because the session's
+    // worktree_branch can be the directory name rather than the actual git
+    // branch name.
+    let range = format!("{base}...HEAD");
+    run_cmd_on(host, "git", worktree_path, &["diff", &range])
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorktreeStatus {
     pub has_dirty_files: bool,
@@ -291,15 +377,20 @@ pub struct WorktreeStatus {
 
 #[tauri::command]
 pub async fn check_worktree_status(
+    orca_db: tauri::State<'_, OrcaDb>,
     repo_path: String,
     worktree_path: String,
     branch: String,
+    group_path: Option<String>,
 ) -> Result<WorktreeStatus, String> {
+    let orca_db = orca_db.inner().clone();
     spawn_git(move || {
+        let host = ssh::host_for(&orca_db, group_path.as_deref())?;
+        let run_git_status = |args: &[&str]| run_git_status_on(host.as_ref(), &worktree_path, args);
         let mut warnings = Vec::new();
 
         // 1. Check for dirty files (uncommitted changes)
-        let (status_output, _) = run_git_status(&worktree_path, &["status", "--porcelain"])?;
+        let (status_output, _) = run_git_status(&["status", "--porcelain"])?;
         let has_dirty_files = !status_output.trim().is_empty();
         if has_dirty_files {
             let file_count = status_output.trim().lines().count();
@@ -310,13 +401,11 @@ pub async fn check_worktree_status(
         }
 
         // 2. Check if branch is merged into default branch (skip for main/master)
-        let default_branch = get_default_branch_inner(&repo_path)?;
+        let default_branch = get_default_branch_on(host.as_ref(), &repo_path)?;
         let has_unmerged_branch =
             if branch != "main" && branch != "master" && branch != default_branch {
-                let (_, is_ancestor) = run_git_status(
-                    &worktree_path,
-                    &["merge-base", "--is-ancestor", &branch, &default_branch],
-                )?;
+                let (_, is_ancestor) =
+                    run_git_status(&["merge-base", "--is-ancestor", &branch, &default_branch])?;
                 if !is_ancestor {
                     warnings.push(format!(
                         "Branch '{branch}' not merged into {default_branch}"
@@ -332,8 +421,7 @@ pub async fn check_worktree_status(
             false
         } else {
             // Try upstream tracking ref first
-            let (log_output, ok) =
-                run_git_status(&worktree_path, &["log", "@{upstream}..HEAD", "--oneline"])?;
+            let (log_output, ok) = run_git_status(&["log", "@{upstream}..HEAD", "--oneline"])?;
             if ok {
                 let unpushed = !log_output.trim().is_empty();
                 if unpushed {
@@ -348,8 +436,7 @@ pub async fn check_worktree_status(
                 // No upstream — try origin/<branch>
                 let remote_ref = format!("origin/{branch}");
                 let range = format!("{remote_ref}..HEAD");
-                let (log_output, ok) =
-                    run_git_status(&worktree_path, &["log", &range, "--oneline"])?;
+                let (log_output, ok) = run_git_status(&["log", &range, "--oneline"])?;
                 if ok {
                     let unpushed = !log_output.trim().is_empty();
                     if unpushed {
@@ -383,72 +470,276 @@ pub struct MergeResult {
     pub success: bool,
     pub main_worktree_path: String,
     pub conflict_message: Option<String>,
+    /// Whether git stopped with conflicts to resolve in `main_worktree_path`;
+    /// false when a failed merge was rolled back.
+    pub conflicted: bool,
+}
+
+/// How the local merge workflow lands a branch on the default branch, from
+/// the group's `merge_workflow` (`pr` goes through GitHub instead). The app
+/// rebases the branch first in every case.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MergeStrategy {
+    /// `git merge --no-ff`: a merge commit, even after the rebase.
+    #[default]
+    Merge,
+    /// `git merge --squash`, committed as one commit.
+    Squash,
+    /// `git merge --ff-only`: the rebased commits, no merge commit.
+    RebaseFf,
+}
+
+impl MergeStrategy {
+    pub fn from_workflow(merge_workflow: &str) -> MergeStrategy {
+        match merge_workflow {
+            "squash" => MergeStrategy::Squash,
+            "rebase-ff" => MergeStrategy::RebaseFf,
+            _ => MergeStrategy::Merge,
+        }
+    }
 }
 
 #[tauri::command]
 pub async fn try_merge_branch(
+    orca_db: tauri::State<'_, OrcaDb>,
     repo_path: String,
     branch: String,
     main_branch: Option<String>,
+    strategy: Option<MergeStrategy>,
+    commit_message: Option<String>,
+    group_path: Option<String>,
 ) -> Result<MergeResult, String> {
+    let orca_db = orca_db.inner().clone();
     spawn_git(move || {
-        let target = main_branch.unwrap_or_else(|| "main".to_string());
+        let host = ssh::host_for(&orca_db, group_path.as_deref())?;
+        try_merge_branch_on(
+            host.as_ref(),
+            &repo_path,
+            &branch,
+            main_branch.as_deref(),
+            strategy.unwrap_or_default(),
+            commit_message.as_deref(),
+        )
+    })
+    .await
+}
 
-        // Find the main worktree path
-        let worktrees = list_worktrees_sync(&repo_path)?;
-        let main_wt = worktrees
-            .iter()
-            .find(|w| w.branch == target)
-            .ok_or(format!("No worktree found for branch '{target}'"))?;
-        let main_path = main_wt.path.clone();
+/// Merge `branch` into the target branch's worktree with `strategy`,
+/// reporting conflicts instead of failing. `commit_message` is the squash
+/// commit's message; without one git's generated message is used. The repo
+/// is on an SSH host when `host` is set.
+pub fn try_merge_branch_on(
+    host: Option<&SshHost>,
+    repo_path: &str,
+    branch: &str,
+    main_branch: Option<&str>,
+    strategy: MergeStrategy,
+    commit_message: Option<&str>,
+) -> Result<MergeResult, String> {
+    let target = main_branch.unwrap_or("main");
 
-        // Check if main worktree has uncommitted changes
-        let (status_output, status_ok) =
-            run_git_status(&main_path, &["status", "--porcelain"])?;
-        if status_ok && !status_output.trim().is_empty() {
+    // Find the main worktree path
+    let worktrees = list_worktrees_on(host, repo_path)?;
+    let main_wt = worktrees
+        .iter()
+        .find(|w| w.branch == target)
+        .ok_or(format!("No worktree found for branch '{target}'"))?;
+    let main_path = main_wt.path.clone();
+
+    // Check if main worktree has uncommitted changes
+    let (status_output, status_ok) =
+        run_git_status_on(host, &main_path, &["status", "--porcelain"])?;
+    if status_ok && !status_output.trim().is_empty() {
+        return Err(format!(
+            "The '{target}' worktree has uncommitted changes. Please commit or stash them before merging."
+        ));
+    }
+
+    // Best-effort pull on main (may be offline or have no remote)
+    if let Err(e) = run_git_on(host, &main_path, &["pull", "--ff-only"]) {
+        log::warn!("Failed to pull --ff-only on '{target}', merging against local: {e}");
+    }
+
+    run_merge(host, &main_path, branch, strategy, commit_message)
+}
+
+/// Run git in `cwd`, here or on an SSH host, returning whether it succeeded
+/// and its stdout and stderr together.
+pub(crate) fn git_combined_output_on(
+    host: Option<&SshHost>,
+    cwd: &str,
+    args: &[&str],
+) -> Result<(bool, String), String> {
+    let output = output_on(host, "git", cwd, args)?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Ok((
+        output.status.success(),
+        format!("{}{}", stdout.trim(), stderr.trim()),
+    ))
+}
+
+/// Merge `branch` into the branch checked out in `main_path`. Conflicts are
+/// left in place for the user to resolve; a failed squash commit is rolled
+/// back. A rebased branch that no longer fast-forwards (the target moved
+/// since the rebase) is an error, not a conflict.
+fn run_merge(
+    host: Option<&SshHost>,
+    main_path: &str,
+    branch: &str,
+    strategy: MergeStrategy,
+    commit_message: Option<&str>,
+) -> Result<MergeResult, String> {
+    let failed = |message: String, conflicted: bool| MergeResult {
+        success: false,
+        main_worktree_path: main_path.to_string(),
+        conflict_message: Some(message),
+        conflicted,
+    };
+    let args = match strategy {
+        // --no-ff always creates a merge commit, even after the rebase
+        MergeStrategy::Merge => vec!["merge", "--no-ff", branch, "--no-edit"],
+        MergeStrategy::Squash => vec!["merge", "--squash", branch],
+        MergeStrategy::RebaseFf => vec!["merge", "--ff-only", branch],
+    };
+    let (success, message) = git_combined_output_on(host, main_path, &args)?;
+    if !success {
+        if strategy == MergeStrategy::RebaseFf {
             return Err(format!(
-                "The '{target}' worktree has uncommitted changes. Please commit or stash them before merging."
+                "'{branch}' is not a fast-forward of the target branch, which moved since the \
+                 rebase; merge again to rebase onto it: {message}"
             ));
         }
+        log::warn!("git merge conflict/failure: {message}");
+        return Ok(failed(message, true));
+    }
+    if strategy == MergeStrategy::Squash {
+        let args = match commit_message.map(str::trim).filter(|m| !m.is_empty()) {
+            Some(message) => vec!["commit", "-m", message],
+            None => vec!["commit", "--no-edit"],
+        };
+        let (success, message) = git_combined_output_on(host, main_path, &args)?;
+        if !success {
+            log::warn!("Failed to commit the squashed changes, resetting: {message}");
+            if let Err(e) = run_git_on(host, main_path, &["reset", "--merge"]) {
+                log::warn!("Failed to reset the squashed changes: {e}");
+            }
+            return Ok(failed(message, false));
+        }
+    }
+    Ok(MergeResult {
+        success: true,
+        main_worktree_path: main_path.to_string(),
+        conflict_message: None,
+        conflicted: false,
+    })
+}
 
-        // Best-effort pull on main (may be offline or have no remote)
-        if let Err(e) = run_git(&main_path, &["pull", "--ff-only"]) {
-            log::warn!("Failed to pull --ff-only on '{target}', merging against local: {e}");
+/// The suggested squash commit message: a subject from the session's summary
+/// or prompt, then the prompt and the branch's commit subjects.
+pub fn squash_message(
+    branch: &str,
+    prompt: Option<&str>,
+    summary: Option<&str>,
+    commits: &[&str],
+) -> String {
+    let prompt = prompt.map(str::trim).filter(|p| !p.is_empty());
+    let subject = summary
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .or_else(|| prompt.and_then(|p| p.lines().map(str::trim).find(|l| !l.is_empty())))
+        .map(|s| {
+            s.chars()
+                .take(72)
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        })
+        .unwrap_or_else(|| format!("Squash merge branch '{branch}'"));
+    let mut message = subject.clone();
+    if let Some(prompt) = prompt.filter(|p| *p != subject) {
+        message.push_str("\n\n");
+        message.push_str(prompt);
+    }
+    if !commits.is_empty() {
+        message.push_str("\n\nCommits:");
+        for commit in commits {
+            message.push_str(&format!("\n- {commit}"));
         }
+    }
+    message
+}
 
-        // Try merge — use --no-ff to always create a merge commit even after rebase
-        log::info!("git merge --no-ff {branch} --no-edit (cwd: {main_path})");
-        let merge_output = new_command("git")
-            .current_dir(&main_path)
-            .args(["merge", "--no-ff", &branch, "--no-edit"])
-            .output()
-            .map_err(|e| format!("Failed to run git merge: {e}"))?;
+/// Subjects of the commits on `branch` that `target` doesn't have, oldest
+/// first, in a repo on an SSH host when `host` is set.
+pub fn branch_commit_subjects_on(
+    host: Option<&SshHost>,
+    repo_path: &str,
+    target: &str,
+    branch: &str,
+) -> Result<Vec<String>, String> {
+    let range = format!("{target}..{branch}");
+    let log = run_git_on(
+        host,
+        repo_path,
+        &["log", "--reverse", "--format=%s", &range],
+    )?;
+    Ok(log
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(ToString::to_string)
+        .collect())
+}
 
-        if merge_output.status.success() {
-            Ok(MergeResult {
-                success: true,
-                main_worktree_path: main_path,
-                conflict_message: None,
-            })
-        } else {
-            let stdout = String::from_utf8_lossy(&merge_output.stdout);
-            let stderr = String::from_utf8_lossy(&merge_output.stderr);
-            let message = format!("{}{}", stdout.trim(), stderr.trim());
-            log::warn!("git merge conflict/failure: {message}");
-            Ok(MergeResult {
-                success: false,
-                main_worktree_path: main_path,
-                conflict_message: Some(message),
-            })
-        }
+/// A squash commit message for `branch` to show, editable, before merging.
+#[tauri::command]
+pub async fn get_squash_message(
+    orca_db: tauri::State<'_, OrcaDb>,
+    repo_path: String,
+    branch: String,
+    main_branch: Option<String>,
+    prompt: Option<String>,
+    summary: Option<String>,
+    group_path: Option<String>,
+) -> Result<String, String> {
+    let orca_db = orca_db.inner().clone();
+    spawn_git(move || {
+        let host = ssh::host_for(&orca_db, group_path.as_deref())?;
+        let target = main_branch.unwrap_or_else(|| "main".to_string());
+        let commits = branch_commit_subjects_on(host.as_ref(), &repo_path, &target, &branch)?;
+        let commits: Vec<&str> = commits.iter().map(String::as_str).collect();
+        Ok(squash_message(
+            &branch,
+            prompt.as_deref(),
+            summary.as_deref(),
+            &commits,
+        ))
     })
     .await
 }
 
 #[tauri::command]
-pub async fn abort_merge(worktree_path: String) -> Result<(), String> {
+pub async fn abort_merge(
+    orca_db: tauri::State<'_, OrcaDb>,
+    worktree_path: String,
+    group_path: Option<String>,
+) -> Result<(), String> {
+    let orca_db = orca_db.inner().clone();
     spawn_git(move || {
-        run_git(&worktree_path, &["merge", "--abort"])?;
+        let host = ssh::host_for(&orca_db, group_path.as_deref())?;
+        let host = host.as_ref();
+        // A conflicted squash leaves no MERGE_HEAD for `merge --abort` to use.
+        let (_, merging) = run_git_status_on(
+            host,
+            &worktree_path,
+            &["rev-parse", "-q", "--verify", "MERGE_HEAD"],
+        )?;
+        if merging {
+            run_git_on(host, &worktree_path, &["merge", "--abort"])?;
+        } else {
+            run_git_on(host, &worktree_path, &["reset", "--merge"])?;
+        }
         Ok(())
     })
     .await
@@ -467,9 +758,20 @@ pub struct RebaseResult {
 }
 
 #[tauri::command]
-pub async fn push_branch(worktree_path: String, branch: String) -> Result<PushResult, String> {
+pub async fn push_branch(
+    orca_db: tauri::State<'_, OrcaDb>,
+    worktree_path: String,
+    branch: String,
+    group_path: Option<String>,
+) -> Result<PushResult, String> {
+    let orca_db = orca_db.inner().clone();
     spawn_git(move || {
-        let (output, success) = run_git_status(&worktree_path, &["push", "-u", "origin", &branch])?;
+        let host = ssh::host_for(&orca_db, group_path.as_deref())?;
+        let (output, success) = run_git_status_on(
+            host.as_ref(),
+            &worktree_path,
+            &["push", "-u", "origin", &branch],
+        )?;
         Ok(PushResult {
             success,
             message: output.trim().to_string(),
@@ -480,11 +782,16 @@ pub async fn push_branch(worktree_path: String, branch: String) -> Result<PushRe
 
 #[tauri::command]
 pub async fn force_push_branch(
+    orca_db: tauri::State<'_, OrcaDb>,
     worktree_path: String,
     branch: String,
+    group_path: Option<String>,
 ) -> Result<PushResult, String> {
+    let orca_db = orca_db.inner().clone();
     spawn_git(move || {
-        let (output, success) = run_git_status(
+        let host = ssh::host_for(&orca_db, group_path.as_deref())?;
+        let (output, success) = run_git_status_on(
+            host.as_ref(),
             &worktree_path,
             &["push", "--force-with-lease", "origin", &branch],
         )?;
@@ -498,45 +805,58 @@ pub async fn force_push_branch(
 
 #[tauri::command]
 pub async fn rebase_branch(
+    orca_db: tauri::State<'_, OrcaDb>,
     worktree_path: String,
     main_branch: String,
     use_remote: Option<bool>,
+    group_path: Option<String>,
 ) -> Result<RebaseResult, String> {
+    let orca_db = orca_db.inner().clone();
     spawn_git(move || {
-        let rebase_target = if use_remote.unwrap_or(false) {
-            // Fetch latest and rebase onto remote (for PR workflow)
-            let _ = run_git_status(&worktree_path, &["fetch", "origin", &main_branch]);
-            format!("origin/{main_branch}")
-        } else {
-            // Rebase onto local branch (for merge workflow)
-            main_branch.clone()
-        };
-        log::info!("git rebase {rebase_target} (cwd: {worktree_path})");
-        let output = new_command("git")
-            .current_dir(&worktree_path)
-            .args(["rebase", &rebase_target])
-            .output()
-            .map_err(|e| format!("Failed to run git rebase: {e}"))?;
-
-        if output.status.success() {
-            Ok(RebaseResult {
-                success: true,
-                conflict_message: None,
-            })
-        } else {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            let message = format!("{}{}", stdout.trim(), stderr.trim());
-            log::warn!("git rebase conflict/failure: {message}");
-            Ok(RebaseResult {
-                success: false,
-                conflict_message: Some(message),
-            })
-        }
+        let host = ssh::host_for(&orca_db, group_path.as_deref())?;
+        rebase_branch_on(
+            host.as_ref(),
+            &worktree_path,
+            &main_branch,
+            use_remote.unwrap_or(false),
+        )
     })
     .await
 }
 
+/// Rebase a worktree (on an SSH host when `host` is set) onto the main
+/// branch, or `origin/<main>` after a fetch, reporting conflicts instead of
+/// failing.
+pub fn rebase_branch_on(
+    host: Option<&SshHost>,
+    worktree_path: &str,
+    main_branch: &str,
+    use_remote: bool,
+) -> Result<RebaseResult, String> {
+    let rebase_target = if use_remote {
+        // Fetch latest and rebase onto remote (for PR workflow)
+        let _ = run_git_status_on(host, worktree_path, &["fetch", "origin", main_branch]);
+        format!("origin/{main_branch}")
+    } else {
+        // Rebase onto local branch (for merge workflow)
+        main_branch.to_string()
+    };
+    let (success, message) =
+        git_combined_output_on(host, worktree_path, &["rebase", &rebase_target])?;
+    if success {
+        Ok(RebaseResult {
+            success: true,
+            conflict_message: None,
+        })
+    } else {
+        log::warn!("git rebase conflict/failure: {message}");
+        Ok(RebaseResult {
+            success: false,
+            conflict_message: Some(message),
+        })
+    }
+}
+
 #[tauri::command]
 pub async fn clone_bare_worktree_repo(
     git_url: String,
@@ -729,9 +1049,15 @@ pub async fn list_components(repo_path: String, depth: u32) -> Result<Vec<String
 }
 
 #[tauri::command]
-pub async fn abort_rebase(worktree_path: String) -> Result<(), String> {
+pub async fn abort_rebase(
+    orca_db: tauri::State<'_, OrcaDb>,
+    worktree_path: String,
+    group_path: Option<String>,
+) -> Result<(), String> {
+    let orca_db = orca_db.inner().clone();
     spawn_git(move || {
-        run_git(&worktree_path, &["rebase", "--abort"])?;
+        let host = ssh::host_for(&orca_db, group_path.as_deref())?;
+        run_git_on(host.as_ref(), &worktree_path, &["rebase", "--abort"])?;
         Ok(())
     })
     .await
@@ -739,14 +1065,19 @@ pub async fn abort_rebase(worktree_path: String) -> Result<(), String> {
 
 #[tauri::command]
 pub async fn update_main_branch(
+    orca_db: tauri::State<'_, OrcaDb>,
     repo_path: String,
     main_branch: String,
+    group_path: Option<String>,
 ) -> Result<PushResult, String> {
+    let orca_db = orca_db.inner().clone();
     spawn_git(move || {
-        let effective_repo = find_repo_root(&repo_path)?;
+        let host = ssh::host_for(&orca_db, group_path.as_deref())?;
+        let host = host.as_ref();
+        let effective_repo = find_repo_root_on(host, &repo_path)?;
 
         // Find the main worktree path
-        let worktrees = list_worktrees_sync(&repo_path)?;
+        let worktrees = list_worktrees_on(host, &repo_path)?;
         let main_wt = worktrees
             .iter()
             .find(|w| w.branch == main_branch)
@@ -754,7 +1085,7 @@ pub async fn update_main_branch(
         let main_path = main_wt.path.clone();
 
         // Check if main worktree has uncommitted changes
-        let (status_output, _) = run_git_status(&main_path, &["status", "--porcelain"])?;
+        let (status_output, _) = run_git_status_on(host, &main_path, &["status", "--porcelain"])?;
         if !status_output.trim().is_empty() {
             return Ok(PushResult {
                 success: false,
@@ -763,9 +1094,9 @@ pub async fn update_main_branch(
         }
 
         // Use the effective repo for fetch
-        let _ = run_git_status(&effective_repo, &["fetch", "origin", &main_branch]);
+        let _ = run_git_status_on(host, &effective_repo, &["fetch", "origin", &main_branch]);
 
-        let (output, success) = run_git_status(&main_path, &["pull", "--ff-only"])?;
+        let (output, success) = run_git_status_on(host, &main_path, &["pull", "--ff-only"])?;
         Ok(PushResult {
             success,
             message: output.trim().to_string(),
@@ -775,11 +1106,19 @@ pub async fn update_main_branch(
 }
 
 fn find_repo_root(path: &str) -> Result<String, String> {
+    find_repo_root_on(None, path)
+}
+
+/// [`find_repo_root`] for a repo on an SSH host when `host` is set, where
+/// `~` is left to the remote shell.
+fn find_repo_root_on(host: Option<&SshHost>, path: &str) -> Result<String, String> {
     // Validate this is a git repository by checking rev-parse succeeds.
     // Returns the expanded path since git commands work from any worktree.
-    let expanded = expand_tilde(path);
-    let cwd = expanded.to_string_lossy().to_string();
-    run_git(&cwd, &["rev-parse", "--git-common-dir"])
+    let cwd = match host {
+        Some(_) => path.to_string(),
+        None => expand_tilde(path).to_string_lossy().to_string(),
+    };
+    run_git_on(host, &cwd, &["rev-parse", "--git-common-dir"])
         .map_err(|_| format!("Not a git repository: {cwd}"))?;
     Ok(cwd)
 }
@@ -1043,4 +1382,137 @@ branch refs/heads/feature-x
         assert_eq!(result[1].path, "/home/user/repo/feature");
         assert_eq!(result[1].branch, "feature-x");
     }
+
+    #[test]
+    fn squash_message_from_summary_and_prompt() {
+        assert_eq!(
+            squash_message(
+                "fix-login",
+                Some("Fix the login redirect\n\nIt loops on Safari."),
+                Some("Login redirect fix"),
+                &["Fix redirect", "Add test"],
+            ),
+            "Login redirect fix\n\nFix the login redirect\n\nIt loops on Safari.\
+             \n\nCommits:\n- Fix redirect\n- Add test"
+        );
+        assert_eq!(
+            squash_message("b", Some("  One line prompt "), None, &[]),
+            "One line prompt"
+        );
+        assert_eq!(
+            squash_message("b", None, Some(" "), &[]),
+            "Squash merge branch 'b'"
+        );
+        let long = "x".repeat(100);
+        assert_eq!(squash_message("b", None, Some(&long), &[]).len(), 72);
+    }
+
+    fn run_test_git(dir: &Path, args: &[&str]) -> String {
+        let output = std::process::Command::new("git")
+            .args(["-c", "user.name=t", "-c", "user.email=t@t"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .expect("git");
+        assert!(output.status.success(), "git {args:?}: {output:?}");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    /// A repo with `main` checked out and `branch` in a worktree next to it,
+    /// with one commit writing `content` to `file`.
+    fn branch_with_commit(repo: &Path, branch: &str, file: &str, content: &str) -> String {
+        let worktree = repo.with_file_name(branch);
+        run_test_git(
+            repo,
+            &["worktree", "add", "-qb", branch, worktree.to_str().unwrap()],
+        );
+        std::fs::write(worktree.join(file), content).unwrap();
+        run_test_git(&worktree, &["add", "."]);
+        run_test_git(&worktree, &["commit", "-qm", &format!("Change {file}")]);
+        worktree.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn merge_strategies() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = tmp.path().join("main");
+        std::fs::create_dir(&repo).unwrap();
+        run_test_git(&repo, &["init", "-q", "-b", "main"]);
+        run_test_git(&repo, &["config", "user.name", "t"]);
+        run_test_git(&repo, &["config", "user.email", "t@t"]);
+        std::fs::write(repo.join("a.txt"), "a\n").unwrap();
+        run_test_git(&repo, &["add", "."]);
+        run_test_git(&repo, &["commit", "-qm", "init"]);
+        let repo_path = repo.to_str().unwrap();
+        let merge = |branch: &str, strategy, message| {
+            try_merge_branch_on(None, repo_path, branch, Some("main"), strategy, message).unwrap()
+        };
+
+        // Squash: one new commit on main with the given message.
+        branch_with_commit(&repo, "squashed", "b.txt", "b\n");
+        let result = merge("squashed", MergeStrategy::Squash, Some("Add b"));
+        assert!(result.success, "{result:?}");
+        assert_eq!(
+            run_test_git(&repo, &["log", "-1", "--format=%p"])
+                .split(' ')
+                .count(),
+            1
+        );
+        assert_eq!(run_test_git(&repo, &["log", "-1", "--format=%s"]), "Add b");
+
+        // Rebase-ff: main moves to the branch's commit.
+        let worktree = branch_with_commit(&repo, "linear", "c.txt", "c\n");
+        let result = merge("linear", MergeStrategy::RebaseFf, None);
+        assert!(result.success, "{result:?}");
+        assert_eq!(
+            run_test_git(&repo, &["rev-parse", "main"]),
+            run_test_git(Path::new(&worktree), &["rev-parse", "HEAD"])
+        );
+
+        // Merge: a merge commit with two parents.
+        branch_with_commit(&repo, "merged", "d.txt", "d\n");
+        assert!(merge("merged", MergeStrategy::Merge, None).success);
+        assert_eq!(
+            run_test_git(&repo, &["log", "-1", "--format=%p"])
+                .split(' ')
+                .count(),
+            2
+        );
+
+        // A failed squash commit is rolled back, leaving main clean.
+        branch_with_commit(&repo, "rejected", "e.txt", "e\n");
+        let hook = repo.join(".git/hooks/pre-commit");
+        std::fs::write(&hook, "#!/bin/sh\necho rejected by hook >&2\nexit 1\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        let result = merge("rejected", MergeStrategy::Squash, Some("Add e"));
+        std::fs::remove_file(&hook).unwrap();
+        assert!(!result.success && !result.conflicted, "{result:?}");
+        assert!(result
+            .conflict_message
+            .unwrap()
+            .contains("rejected by hook"));
+        assert_eq!(run_test_git(&repo, &["status", "--porcelain"]), "");
+
+        // Conflicts are reported, not errors; a non-fast-forward is an error.
+        branch_with_commit(&repo, "conflicting", "a.txt", "theirs\n");
+        std::fs::write(repo.join("a.txt"), "ours\n").unwrap();
+        run_test_git(&repo, &["commit", "-qam", "ours"]);
+        let err = try_merge_branch_on(
+            None,
+            repo_path,
+            "conflicting",
+            Some("main"),
+            MergeStrategy::RebaseFf,
+            None,
+        )
+        .unwrap_err();
+        assert!(err.contains("not a fast-forward"), "{err}");
+        let result = merge("conflicting", MergeStrategy::Squash, Some("x"));
+        assert!(!result.success && result.conflicted);
+        assert!(result.conflict_message.unwrap().contains("CONFLICT"));
+    }
 }
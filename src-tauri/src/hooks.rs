@@ -0,0 +1,591 @@
+//! Claude Code hooks as the authoritative attention signal.
+//!
+//! Orca installs `Notification`, `Stop`, `PreToolUse`, `PostToolUse` and
+//! `UserPromptSubmit` hooks (among others) into a worktree's
+//! `.claude/settings.local.json`, and lists that file in the repo's
+//! `info/exclude` so it doesn't make the worktree dirty. Each hook writes its
+//! JSON payload to a file in Orca's hook events directory, named by a
+//! nanosecond timestamp so events from the same second keep their order, which
+//! the session watcher picks up. [`hook_attention`] turns the latest event for a Claude
+//! session into an attention status, which `claude_logs::compute_attention`
+//! prefers over the transcript and tmux heuristics.
+
+use crate::claude_logs::AttentionStatus;
+use crate::command::{expand_tilde, run_cmd, shell_quote};
+use crate::dates::now_secs;
+use crate::orca_db::{HookSignal, OrcaDb};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::UNIX_EPOCH;
+
+/// Hook events Orca listens to.
+const HOOK_EVENTS: &[&str] = &[
+    "Notification",
+    "Stop",
+    "PreToolUse",
+    "PostToolUse",
+    "UserPromptSubmit",
+    "PreCompact",
+    "SessionStart",
+];
+/// Hook events that take a tool matcher.
+const TOOL_EVENTS: &[&str] = &["PreToolUse", "PostToolUse"];
+/// Claude's local settings file, relative to the worktree.
+const SETTINGS_FILE: &str = ".claude/settings.local.json";
+/// Trailing shell comment that identifies Orca's entries in a settings file.
+const HOOK_MARKER: &str = "# orca-attention-hook";
+/// Signals older than this are ignored in favour of the transcript heuristic,
+/// which also covers sessions whose hooks were removed.
+const SIGNAL_MAX_AGE_SECS: u64 = 3600;
+/// Longest notification message kept as detail.
+const MAX_DETAIL_LEN: usize = 200;
+
+static EVENTS_DIR: OnceLock<PathBuf> = OnceLock::new();
+static SIGNALS: OnceLock<Mutex<HashMap<String, HookSignal>>> = OnceLock::new();
+
+fn signals() -> &'static Mutex<HashMap<String, HookSignal>> {
+    SIGNALS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Directory hook commands write event files to.
+pub fn events_dir() -> Option<&'static Path> {
+    EVENTS_DIR.get().map(PathBuf::as_path)
+}
+
+/// Create the events directory, load persisted signals and pick up events
+/// written while Orca wasn't running. Call once at startup.
+pub fn init(app_data_dir: &Path, orca_db: &OrcaDb) -> Result<(), String> {
+    load(app_data_dir, orca_db)?;
+    ingest_pending(orca_db);
+    Ok(())
+}
+
+/// Create the events directory and load persisted signals, leaving pending
+/// events to the app's watcher. Used by the CLI.
+pub fn load(app_data_dir: &Path, orca_db: &OrcaDb) -> Result<(), String> {
+    let dir = app_data_dir.join("hook-events");
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    let _ = EVENTS_DIR.set(dir);
+
+    let persisted = orca_db.get_hook_signals()?;
+    signals()
+        .lock()
+        .map_err(|e| format!("Lock error: {e}"))?
+        .extend(persisted);
+    Ok(())
+}
+
+/// Shell command that atomically writes the hook's stdin payload into `dir`.
+///
+/// Files are named `<nanoseconds>-<pid>.json`. BSD `date` has no `%N`, so on
+/// macOS the timestamp comes from perl's microsecond clock instead.
+fn hook_command(dir: &Path) -> String {
+    format!(
+        "d={}; t=$(date +%s%N); case $t in *N) t=$(perl -MTime::HiRes=gettimeofday -e 'printf \"%d%06d000\", gettimeofday');; esac; \
+         f=\"$d/$t-$$\"; mkdir -p \"$d\" && cat > \"$f.tmp\" && mv \"$f.tmp\" \"$f.json\" {HOOK_MARKER}",
+        shell_quote(&dir.to_string_lossy())
+    )
+}
+
+fn is_orca_entry(entry: &Value) -> bool {
+    entry["hooks"].as_array().is_some_and(|hooks| {
+        hooks.iter().any(|h| {
+            h["command"]
+                .as_str()
+                .is_some_and(|c| c.ends_with(HOOK_MARKER))
+        })
+    })
+}
+
+/// Remove Orca's entries from a settings object, dropping event lists (and
+/// the `hooks` key) that end up empty. Returns whether anything was removed.
+fn strip_orca_hooks(settings: &mut Value) -> bool {
+    let Some(hooks) = settings.get_mut("hooks").and_then(Value::as_object_mut) else {
+        return false;
+    };
+    let mut removed = false;
+    for entries in hooks.values_mut() {
+        if let Some(list) = entries.as_array_mut() {
+            let before = list.len();
+            list.retain(|entry| !is_orca_entry(entry));
+            removed |= list.len() != before;
+        }
+    }
+    hooks.retain(|_, entries| entries.as_array().map_or(true, |l| !l.is_empty()));
+    if hooks.is_empty() {
+        if let Some(obj) = settings.as_object_mut() {
+            obj.remove("hooks");
+        }
+    }
+    removed
+}
+
+/// Add Orca's hook to every event, replacing any earlier Orca entries and
+/// leaving the user's own hooks alone.
+fn add_orca_hooks(settings: &mut Value, command: &str) -> Result<(), String> {
+    strip_orca_hooks(settings);
+    let obj = settings
+        .as_object_mut()
+        .ok_or("Claude settings file is not a JSON object")?;
+    let hooks = obj
+        .entry("hooks")
+        .or_insert_with(|| json!({}))
+        .as_object_mut()
+        .ok_or("Claude settings \"hooks\" is not a JSON object")?;
+    for event in HOOK_EVENTS {
+        let mut entry = json!({"hooks": [{"type": "command", "command": command}]});
+        if TOOL_EVENTS.contains(event) {
+            entry["matcher"] = json!("*");
+        }
+        let list = hooks.entry(*event).or_insert_with(|| json!([]));
+        list.as_array_mut()
+            .ok_or_else(|| format!("Claude settings hooks.{event} is not a list"))?
+            .push(entry);
+    }
+    Ok(())
+}
+
+fn settings_path(project_path: &str) -> PathBuf {
+    expand_tilde(project_path).join(SETTINGS_FILE)
+}
+
+/// List the settings file in the repo's `info/exclude` (shared by all its
+/// worktrees) so installing hooks doesn't leave an untracked file behind,
+/// which would block merges. Does nothing outside a git repo.
+fn exclude_settings(project_path: &str) -> Result<(), String> {
+    let cwd = expand_tilde(project_path);
+    let Ok(out) = run_cmd(
+        "git",
+        &cwd.to_string_lossy(),
+        &["rev-parse", "--show-prefix", "--git-path", "info/exclude"],
+    ) else {
+        return Ok(());
+    };
+    let mut lines = out.lines();
+    let (Some(prefix), Some(exclude)) = (lines.next(), lines.next()) else {
+        return Ok(());
+    };
+    let pattern = format!("/{prefix}{SETTINGS_FILE}");
+    let path = cwd.join(exclude);
+    let existing = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("Failed to read {}: {e}", path.display())),
+    };
+    if existing.lines().any(|line| line.trim() == pattern) {
+        return Ok(());
+    }
+    let mut text = existing;
+    if !text.is_empty() && !text.ends_with('\n') {
+        text.push('\n');
+    }
+    text.push_str(&pattern);
+    text.push('\n');
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    }
+    std::fs::write(&path, text).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+fn read_settings(path: &Path) -> Result<Value, String> {
+    match std::fs::read_to_string(path) {
+        Ok(text) if text.trim().is_empty() => Ok(json!({})),
+        Ok(text) => serde_json::from_str(&text)
+            .map_err(|e| format!("Failed to parse {}: {e}", path.display())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(json!({})),
+        Err(e) => Err(format!("Failed to read {}: {e}", path.display())),
+    }
+}
+
+fn write_settings(path: &Path, settings: &Value) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    }
+    let text = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    std::fs::write(path, text + "\n")
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+/// Install Orca's hooks into a worktree's local Claude settings.
+pub fn install_hooks(project_path: &str) -> Result<(), String> {
+    let dir = events_dir().ok_or("Hook events directory is not initialised")?;
+    let path = settings_path(project_path);
+    let mut settings = read_settings(&path)?;
+    add_orca_hooks(&mut settings, &hook_command(dir))?;
+    exclude_settings(project_path)?;
+    write_settings(&path, &settings)?;
+    log::info!("Installed attention hooks in {}", path.display());
+    Ok(())
+}
+
+/// Remove Orca's hooks from a worktree, leaving other settings in place.
+pub fn remove_hooks(project_path: &str) -> Result<(), String> {
+    let path = settings_path(project_path);
+    if !path.exists() {
+        return Ok(());
+    }
+    let mut settings = read_settings(&path)?;
+    if strip_orca_hooks(&mut settings) {
+        write_settings(&path, &settings)?;
+        log::info!("Removed attention hooks from {}", path.display());
+    }
+    Ok(())
+}
+
+fn hooks_installed(project_path: &str) -> bool {
+    let Ok(settings) = read_settings(&settings_path(project_path)) else {
+        return false;
+    };
+    HOOK_EVENTS.iter().all(|event| {
+        settings["hooks"][*event]
+            .as_array()
+            .is_some_and(|list| list.iter().any(is_orca_entry))
+    })
+}
+
+/// Parse a hook payload into the Claude session ID and its signal.
+fn parse_event(payload: &Value, received_at: u64) -> Option<(String, HookSignal)> {
+    let session_id = payload["session_id"].as_str()?;
+    let event = payload["hook_event_name"].as_str()?;
+    if !HOOK_EVENTS.contains(&event) {
+        return None;
+    }
+    let detail = match event {
+        "Notification" => payload["message"]
+            .as_str()
+            .map(|m| m.chars().take(MAX_DETAIL_LEN).collect()),
+        "PreToolUse" | "PostToolUse" => payload["tool_name"].as_str().map(str::to_string),
+        "PreCompact" => payload["trigger"].as_str().map(str::to_string),
+        "SessionStart" => payload["source"].as_str().map(str::to_string),
+        _ => None,
+    };
+    Some((
+        session_id.to_string(),
+        HookSignal {
+            event: event.to_string(),
+            detail,
+            received_at,
+        },
+    ))
+}
+
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+/// When an event file was written, in nanoseconds: the timestamp in its name,
+/// or its mtime. Hooks installed by older versions named files in seconds.
+fn event_time_nanos(path: &Path) -> u64 {
+    let stamp = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .and_then(|stem| stem.split('-').next())
+        .and_then(|t| t.parse::<u64>().ok());
+    match stamp {
+        Some(t) if t >= 1_000_000_000_000 => t,
+        Some(secs) => secs.saturating_mul(NANOS_PER_SEC),
+        None => modified_secs(path).saturating_mul(NANOS_PER_SEC),
+    }
+}
+
+fn modified_secs(path: &Path) -> u64 {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or_else(now_secs, |d| d.as_secs())
+}
+
+/// Read and delete pending event files, oldest first, updating the latest
+/// signal per Claude session. Returns the Claude session IDs that changed.
+pub fn ingest_pending(orca_db: &OrcaDb) -> HashSet<String> {
+    let mut changed = HashSet::new();
+    let Some(dir) = events_dir() else {
+        return changed;
+    };
+    let Ok(read_dir) = std::fs::read_dir(dir) else {
+        return changed;
+    };
+    let mut files: Vec<(u64, PathBuf)> = read_dir
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .map(|path| (event_time_nanos(&path), path))
+        .collect();
+    files.sort();
+
+    for (nanos, path) in files {
+        let received_at = nanos / NANOS_PER_SEC;
+        let parsed = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|text| serde_json::from_str::<Value>(&text).ok())
+            .and_then(|payload| parse_event(&payload, received_at));
+        if let Err(e) = std::fs::remove_file(&path) {
+            log::warn!("hooks: failed to remove {}: {e}", path.display());
+        }
+        let Some((session_id, signal)) = parsed else {
+            continue;
+        };
+        if let Err(e) = orca_db.record_hook_signal(&session_id, &signal) {
+            log::warn!("hooks: {e}");
+        }
+        if let Ok(mut map) = signals().lock() {
+            if map
+                .get(&session_id)
+                .map_or(true, |prev| prev.received_at <= received_at)
+            {
+                map.insert(session_id.clone(), signal);
+                changed.insert(session_id);
+            }
+        }
+    }
+    changed
+}
+
+fn signal_attention(signal: &HookSignal, now: u64) -> Option<AttentionStatus> {
+    if now.saturating_sub(signal.received_at) > SIGNAL_MAX_AGE_SECS {
+        return None;
+    }
+    match signal.event.as_str() {
+        // PostToolUse also clears a permission prompt once the tool ran.
+        "PreToolUse" | "PostToolUse" | "UserPromptSubmit" => Some(AttentionStatus::Running),
+        "PreCompact" => Some(AttentionStatus::Compacting),
+        // Notification: a permission prompt or an idle input prompt.
+        "Notification"
+            if signal
+                .detail
+                .as_deref()
+                .is_some_and(|d| d.to_lowercase().contains("permission")) =>
+        {
+            Some(AttentionStatus::AwaitingPermission)
+        }
+        // Stop: Claude finished its turn and is waiting for the user.
+        "Notification" | "Stop" => Some(AttentionStatus::NeedsInput),
+        // SessionStart (startup, resume, after a compaction) says nothing
+        // about what comes next; defer to the transcript.
+        _ => None,
+    }
+}
+
+/// Attention implied by the latest hook event for a Claude session, or None
+/// when there is no recent event.
+pub fn hook_attention(claude_session_id: &str) -> Option<AttentionStatus> {
+    let map = signals().lock().ok()?;
+    signal_attention(map.get(claude_session_id)?, now_secs())
+}
+
+#[tauri::command]
+pub fn install_attention_hooks(project_path: String) -> Result<(), String> {
+    install_hooks(&project_path)
+}
+
+#[tauri::command]
+pub fn remove_attention_hooks(project_path: String) -> Result<(), String> {
+    remove_hooks(&project_path)
+}
+
+#[tauri::command]
+pub fn get_attention_hooks_installed(project_path: String) -> bool {
+    hooks_installed(&project_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signal(event: &str, received_at: u64) -> HookSignal {
+        HookSignal {
+            event: event.into(),
+            detail: None,
+            received_at,
+        }
+    }
+
+    #[test]
+    fn hook_command_quotes_dir() {
+        let cmd = hook_command(Path::new("/data/it's here/hook-events"));
+        assert!(cmd.starts_with("d='/data/it'\\''s here/hook-events';"));
+        assert!(cmd.ends_with(HOOK_MARKER));
+    }
+
+    #[test]
+    fn hook_command_names_files_in_nanoseconds() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("events");
+        let mut child = std::process::Command::new("sh")
+            .args(["-c", &hook_command(&dir)])
+            .stdin(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        {
+            use std::io::Write;
+            let mut stdin = child.stdin.take().unwrap();
+            stdin.write_all(b"{}").unwrap();
+        }
+        assert!(child.wait().unwrap().success());
+        let files: Vec<PathBuf> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(files.len(), 1);
+        assert_eq!(std::fs::read_to_string(&files[0]).unwrap(), "{}");
+        let nanos = event_time_nanos(&files[0]);
+        assert_eq!(nanos / NANOS_PER_SEC, modified_secs(&files[0]));
+        assert_ne!(nanos % NANOS_PER_SEC, 0);
+    }
+
+    #[test]
+    fn event_time_orders_within_a_second() {
+        let early = event_time_nanos(Path::new("/e/1700000000000000500-99.json"));
+        let late = event_time_nanos(Path::new("/e/1700000000000000900-12.json"));
+        assert!(early < late);
+        assert_eq!(early / NANOS_PER_SEC, 1_700_000_000);
+        // Files from hooks installed by older versions are named in seconds
+        assert_eq!(
+            event_time_nanos(Path::new("/e/1700000000-12.json")),
+            1_700_000_000 * NANOS_PER_SEC
+        );
+    }
+
+    #[test]
+    fn exclude_settings_keeps_worktree_clean() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = tmp.path();
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .args(args)
+                .current_dir(repo)
+                .output()
+                .unwrap()
+        };
+        git(&["init", "-q"]);
+        std::fs::create_dir_all(repo.join("app")).unwrap();
+        let project = repo.join("app").to_string_lossy().to_string();
+        write_settings(&settings_path(&project), &json!({})).unwrap();
+
+        exclude_settings(&project).unwrap();
+        // Installing again doesn't duplicate the entry
+        exclude_settings(&project).unwrap();
+        let exclude = std::fs::read_to_string(repo.join(".git/info/exclude")).unwrap();
+        assert_eq!(
+            exclude.matches("/app/.claude/settings.local.json").count(),
+            1
+        );
+        let status = git(&["status", "--porcelain", "--untracked-files=all"]);
+        assert_eq!(String::from_utf8_lossy(&status.stdout), "");
+
+        // Outside a repo there's nothing to exclude
+        let plain = tempfile::tempdir().unwrap();
+        assert!(exclude_settings(&plain.path().to_string_lossy()).is_ok());
+    }
+
+    #[test]
+    fn add_and_strip_preserve_user_hooks() {
+        let mut settings = json!({
+            "permissions": {"allow": ["Bash(ls)"]},
+            "hooks": {"Stop": [{"hooks": [{"type": "command", "command": "say done"}]}]}
+        });
+        let cmd = hook_command(Path::new("/tmp/events"));
+        add_orca_hooks(&mut settings, &cmd).unwrap();
+        // Installing twice doesn't duplicate entries
+        add_orca_hooks(&mut settings, &cmd).unwrap();
+        assert_eq!(settings["hooks"]["Stop"].as_array().unwrap().len(), 2);
+        assert_eq!(settings["hooks"]["PreToolUse"][0]["matcher"], "*");
+        assert_eq!(settings["hooks"]["PostToolUse"][0]["matcher"], "*");
+        for event in HOOK_EVENTS {
+            assert!(settings["hooks"][*event]
+                .as_array()
+                .unwrap()
+                .iter()
+                .any(is_orca_entry));
+        }
+
+        assert!(strip_orca_hooks(&mut settings));
+        assert_eq!(
+            settings,
+            json!({
+                "permissions": {"allow": ["Bash(ls)"]},
+                "hooks": {"Stop": [{"hooks": [{"type": "command", "command": "say done"}]}]}
+            })
+        );
+        assert!(!strip_orca_hooks(&mut settings));
+    }
+
+    #[test]
+    fn strip_drops_empty_hooks_key() {
+        let mut settings = json!({});
+        add_orca_hooks(&mut settings, &hook_command(Path::new("/tmp/events"))).unwrap();
+        assert!(strip_orca_hooks(&mut settings));
+        assert_eq!(settings, json!({}));
+    }
+
+    #[test]
+    fn parse_event_extracts_detail() {
+        let payload = json!({
+            "session_id": "abc",
+            "hook_event_name": "Notification",
+            "message": "Claude needs your permission to use Bash"
+        });
+        let (id, sig) = parse_event(&payload, 10).unwrap();
+        assert_eq!(id, "abc");
+        assert_eq!(sig.event, "Notification");
+        assert_eq!(
+            sig.detail.as_deref(),
+            Some("Claude needs your permission to use Bash")
+        );
+
+        let payload =
+            json!({"session_id": "abc", "hook_event_name": "PreToolUse", "tool_name": "Edit"});
+        assert_eq!(
+            parse_event(&payload, 10).unwrap().1.detail.as_deref(),
+            Some("Edit")
+        );
+
+        let payload = json!({"session_id": "abc", "hook_event_name": "SubagentStop"});
+        assert!(parse_event(&payload, 10).is_none());
+        assert!(parse_event(&json!({"hook_event_name": "Stop"}), 10).is_none());
+    }
+
+    #[test]
+    fn signal_attention_maps_events() {
+        let now = 10_000;
+        assert_eq!(
+            signal_attention(&signal("PreToolUse", now - 5), now),
+            Some(AttentionStatus::Running)
+        );
+        assert_eq!(
+            signal_attention(&signal("PostToolUse", now), now),
+            Some(AttentionStatus::Running)
+        );
+        assert_eq!(
+            signal_attention(&signal("UserPromptSubmit", now), now),
+            Some(AttentionStatus::Running)
+        );
+        assert_eq!(
+            signal_attention(&signal("Notification", now), now),
+            Some(AttentionStatus::NeedsInput)
+        );
+        assert_eq!(
+            signal_attention(&signal("Stop", now), now),
+            Some(AttentionStatus::NeedsInput)
+        );
+        let permission = HookSignal {
+            detail: Some("Claude needs your permission to use Bash".into()),
+            ..signal("Notification", now)
+        };
+        assert_eq!(
+            signal_attention(&permission, now),
+            Some(AttentionStatus::AwaitingPermission)
+        );
+        assert_eq!(
+            signal_attention(&signal("PreCompact", now), now),
+            Some(AttentionStatus::Compacting)
+        );
+        assert_eq!(signal_attention(&signal("SessionStart", now), now), None);
+        // Old signals fall back to the transcript heuristic
+        assert_eq!(signal_attention(&signal("Stop", now - 3601), now), None);
+    }
+}
@@ -0,0 +1,90 @@
+//! One Orca per data directory.
+//!
+//! The desktop app and `orca-daemon` both ingest hook events, track attention
+//! and fire webhooks from the same data directory, so two of them at once
+//! would handle every event twice. Each holds an exclusive lock on
+//! `orca.lock` for as long as it runs, and a second one refuses to start.
+
+use std::fs::{File, OpenOptions};
+use std::io::{Seek, Write};
+use std::path::Path;
+
+pub const LOCK_FILE: &str = "orca.lock";
+
+/// The held lock; released when dropped or when the process exits.
+pub struct InstanceLock {
+    #[cfg(unix)]
+    _file: nix::fcntl::Flock<File>,
+    #[cfg(not(unix))]
+    _file: File,
+}
+
+/// Take the data directory's lock, or say which process holds it.
+pub fn acquire(data_dir: &Path) -> Result<InstanceLock, String> {
+    std::fs::create_dir_all(data_dir)
+        .map_err(|e| format!("Failed to create {}: {e}", data_dir.display()))?;
+    let path = data_dir.join(LOCK_FILE);
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&path)
+        .map_err(|e| format!("Failed to open {}: {e}", path.display()))?;
+    let mut file = lock(file, data_dir)?;
+    // Record the holder for the error a second instance shows
+    file.set_len(0)
+        .and_then(|()| file.rewind())
+        .and_then(|()| write!(file, "{}", std::process::id()))
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+    Ok(InstanceLock { _file: file })
+}
+
+#[cfg(unix)]
+fn lock(file: File, data_dir: &Path) -> Result<nix::fcntl::Flock<File>, String> {
+    use nix::errno::Errno;
+    use nix::fcntl::{Flock, FlockArg};
+    use std::io::Read;
+
+    match Flock::lock(file, FlockArg::LockExclusiveNonblock) {
+        Ok(locked) => Ok(locked),
+        Err((mut file, Errno::EWOULDBLOCK)) => {
+            let mut pid = String::new();
+            let _ = file.read_to_string(&mut pid);
+            let holder = match pid.trim() {
+                "" => "Another Orca process".to_string(),
+                pid => format!("Another Orca process (pid {pid})"),
+            };
+            Err(format!(
+                "{holder} is already running on {}; quit it first (the app and orca-daemon \
+                 can't share a data directory)",
+                data_dir.display()
+            ))
+        }
+        Err((_, errno)) => Err(format!("Failed to lock {LOCK_FILE}: {errno}")),
+    }
+}
+
+/// Without `flock` there's no lock; a second instance isn't detected.
+#[cfg(not(unix))]
+fn lock(file: File, _data_dir: &Path) -> Result<File, String> {
+    Ok(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn second_instance_is_refused_until_the_first_exits() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = acquire(dir.path()).unwrap();
+        let err = acquire(dir.path()).err().unwrap();
+        assert!(
+            err.contains(&format!("pid {}", std::process::id())),
+            "{err}"
+        );
+        drop(first);
+        assert!(acquire(dir.path()).is_ok());
+    }
+}
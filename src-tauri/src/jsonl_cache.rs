@@ -0,0 +1,316 @@
+//! Incremental reader for Claude Code JSONL transcripts.
+//!
+//! Remembers, per file, how far it has read and the [`LogState`] parsed so
+//! far, so repeated summary/attention lookups only parse newly appended
+//! lines. A file that shrinks or whose first bytes change (truncated,
+//! rotated or rewritten) is re-read from scratch. At most [`MAX_ENTRIES`]
+//! files are remembered; the least recently read is dropped first, so
+//! transcripts of deleted sessions don't accumulate.
+
+use crate::claude_logs::{self, LogState};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+/// On first read of a large file, only the tail is parsed (plus the head for
+/// the initial prompt), matching what a full re-parse used to look at.
+const TAIL_BYTES: u64 = 256 * 1024;
+const HEAD_BYTES: u64 = 32 * 1024;
+/// Leading bytes used to detect that a file was replaced.
+const FINGERPRINT_BYTES: u64 = 256;
+/// Files remembered at once.
+const MAX_ENTRIES: usize = 512;
+
+#[derive(Debug, Clone)]
+struct CachedLog {
+    /// Byte offset just past the last complete line parsed.
+    offset: u64,
+    fingerprint: Vec<u8>,
+    state: LogState,
+    last_read: Instant,
+}
+
+type Cache = HashMap<PathBuf, CachedLog>;
+
+fn cache() -> &'static Mutex<Cache> {
+    static CACHE: OnceLock<Mutex<Cache>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Insert `cached`, first dropping the least recently read entry if the
+/// cache is full.
+fn insert(cache: &mut Cache, path: PathBuf, cached: CachedLog) {
+    if cache.len() >= MAX_ENTRIES && !cache.contains_key(&path) {
+        let oldest = cache
+            .iter()
+            .min_by_key(|(_, c)| c.last_read)
+            .map(|(p, _)| p.clone());
+        if let Some(oldest) = oldest {
+            cache.remove(&oldest);
+        }
+    }
+    cache.insert(path, cached);
+}
+
+/// Parsed state for a JSONL file, reading only what was appended since the
+/// last call. Returns an empty state if the file can't be read.
+pub fn read_log_state(path: &Path) -> LogState {
+    // Don't hold the lock during IO; concurrent refreshes of the same file
+    // just do redundant work.
+    let previous = cache()
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .remove(path);
+
+    let Some(cached) = refresh(path, previous) else {
+        return LogState::default();
+    };
+    let state = cached.state.clone();
+    insert(
+        &mut cache()
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner),
+        path.to_path_buf(),
+        cached,
+    );
+    state
+}
+
+fn read_fingerprint(file: &mut File) -> Option<Vec<u8>> {
+    let mut fingerprint = Vec::new();
+    file.seek(SeekFrom::Start(0)).ok()?;
+    file.take(FINGERPRINT_BYTES)
+        .read_to_end(&mut fingerprint)
+        .ok()?;
+    Some(fingerprint)
+}
+
+fn refresh(path: &Path, previous: Option<CachedLog>) -> Option<CachedLog> {
+    let mut file = File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+    let fingerprint = read_fingerprint(&mut file)?;
+
+    let (mut cached, skip_partial) = match previous {
+        // The fingerprint only grows until the file reaches FINGERPRINT_BYTES.
+        Some(prev) if len >= prev.offset && fingerprint.starts_with(&prev.fingerprint) => {
+            (prev, false)
+        }
+        prev => {
+            if prev.is_some() {
+                log::debug!(
+                    "jsonl_cache: {} was truncated or replaced, re-reading",
+                    path.display()
+                );
+            }
+            initial_state(path, len)
+        }
+    };
+
+    if len > cached.offset {
+        cached.offset = read_appended(&mut file, cached.offset, skip_partial, &mut cached.state)?;
+    }
+    cached.fingerprint = fingerprint;
+    cached.last_read = Instant::now();
+    Some(cached)
+}
+
+/// Starting point for a file we haven't read (or must re-read). Returns the
+/// state and whether the start offset may land mid-line.
+fn initial_state(path: &Path, len: u64) -> (CachedLog, bool) {
+    let mut state = LogState::default();
+    let offset = len.saturating_sub(TAIL_BYTES);
+    if offset > 0 {
+        let head = claude_logs::read_head_lines(path, HEAD_BYTES);
+        state.initial_prompt = LogState::from_lines(&head).initial_prompt;
+    }
+    let cached = CachedLog {
+        offset,
+        fingerprint: Vec::new(),
+        state,
+        last_read: Instant::now(),
+    };
+    (cached, offset > 0)
+}
+
+/// Parse complete lines from `offset` onwards, returning the new offset.
+/// A trailing line without a newline is still being written and is left for
+/// the next call.
+fn read_appended(
+    file: &mut File,
+    offset: u64,
+    skip_partial: bool,
+    state: &mut LogState,
+) -> Option<u64> {
+    file.seek(SeekFrom::Start(offset)).ok()?;
+    let mut reader = BufReader::new(file);
+    let mut pos = offset;
+    let mut buf = Vec::new();
+
+    if skip_partial {
+        pos += reader.read_until(b'\n', &mut buf).ok()? as u64;
+    }
+
+    loop {
+        buf.clear();
+        let n = reader.read_until(b'\n', &mut buf).ok()?;
+        if n == 0 || buf.last() != Some(&b'\n') {
+            break;
+        }
+        pos += n as u64;
+        if buf.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+        if let Ok(val) = serde_json::from_slice::<serde_json::Value>(&buf) {
+            state.push_line(&val);
+        }
+    }
+    Some(pos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn user_line(text: &str) -> String {
+        format!(
+            "{}\n",
+            serde_json::json!({
+                "type": "user",
+                "message": {"role": "user", "content": [{"type": "text", "text": text}]}
+            })
+        )
+    }
+
+    fn assistant_line(text: &str) -> String {
+        format!(
+            "{}\n",
+            serde_json::json!({
+                "type": "assistant",
+                "message": {"role": "assistant", "content": [{"type": "text", "text": text}]}
+            })
+        )
+    }
+
+    fn append(path: &Path, data: &str) {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .expect("open failed");
+        file.write_all(data.as_bytes()).expect("write failed");
+    }
+
+    #[test]
+    fn reads_only_appended_lines() {
+        let tmp = tempfile::tempdir().expect("failed to create temp dir");
+        let path = tmp.path().join("s.jsonl");
+        append(&path, &user_line("Fix it"));
+
+        let first = refresh(&path, None).expect("refresh failed");
+        assert_eq!(first.state.initial_prompt, Some("Fix it".into()));
+        assert_eq!(first.state.last_text, None);
+        let offset = first.offset;
+
+        append(&path, &assistant_line("Done"));
+        let second = refresh(&path, Some(first)).expect("refresh failed");
+        assert!(second.offset > offset);
+        assert_eq!(second.state.initial_prompt, Some("Fix it".into()));
+        assert_eq!(second.state.last_text, Some("Done".into()));
+    }
+
+    #[test]
+    fn partial_line_waits_for_newline() {
+        let tmp = tempfile::tempdir().expect("failed to create temp dir");
+        let path = tmp.path().join("s.jsonl");
+        append(&path, &user_line("Go"));
+        let line = assistant_line("Halfway");
+        let (head, tail) = line.split_at(20);
+        append(&path, head);
+
+        let first = refresh(&path, None).expect("refresh failed");
+        assert_eq!(first.state.last_text, None);
+
+        append(&path, tail);
+        let second = refresh(&path, Some(first)).expect("refresh failed");
+        assert_eq!(second.state.last_text, Some("Halfway".into()));
+    }
+
+    #[test]
+    fn truncated_file_is_reread() {
+        let tmp = tempfile::tempdir().expect("failed to create temp dir");
+        let path = tmp.path().join("s.jsonl");
+        append(&path, &user_line("Old prompt"));
+        append(&path, &assistant_line("Old answer"));
+        let first = refresh(&path, None).expect("refresh failed");
+
+        std::fs::write(&path, user_line("New")).expect("write failed");
+        let second = refresh(&path, Some(first)).expect("refresh failed");
+        assert_eq!(second.state.initial_prompt, Some("New".into()));
+        assert_eq!(second.state.last_text, None);
+    }
+
+    #[test]
+    fn replaced_file_same_length_is_reread() {
+        let tmp = tempfile::tempdir().expect("failed to create temp dir");
+        let path = tmp.path().join("s.jsonl");
+        append(&path, &user_line("AAAA"));
+        let first = refresh(&path, None).expect("refresh failed");
+
+        std::fs::write(&path, user_line("BBBB")).expect("write failed");
+        let second = refresh(&path, Some(first)).expect("refresh failed");
+        assert_eq!(second.state.initial_prompt, Some("BBBB".into()));
+    }
+
+    #[test]
+    fn large_file_reads_head_and_tail_only() {
+        let tmp = tempfile::tempdir().expect("failed to create temp dir");
+        let path = tmp.path().join("s.jsonl");
+        append(&path, &user_line("First prompt"));
+        let filler = assistant_line(&"x".repeat(1000));
+        let mut body = String::new();
+        while (body.len() as u64) < TAIL_BYTES * 2 {
+            body.push_str(&filler);
+        }
+        append(&path, &body);
+        append(&path, &assistant_line("Latest"));
+
+        let cached = refresh(&path, None).expect("refresh failed");
+        assert_eq!(cached.state.initial_prompt, Some("First prompt".into()));
+        assert_eq!(cached.state.last_text, Some("Latest".into()));
+        assert_eq!(
+            cached.offset,
+            std::fs::metadata(&path).expect("stat failed").len()
+        );
+    }
+
+    #[test]
+    fn missing_file_is_empty_state() {
+        let state = read_log_state(Path::new("/nonexistent/orca-test.jsonl"));
+        assert_eq!(state.summary, None);
+        assert_eq!(state.last_text, None);
+    }
+
+    #[test]
+    fn full_cache_drops_least_recently_read() {
+        let tmp = tempfile::tempdir().expect("failed to create temp dir");
+        let path = tmp.path().join("s.jsonl");
+        append(&path, &user_line("Go"));
+        let cached = refresh(&path, None).expect("refresh failed");
+
+        let mut cache = Cache::new();
+        for i in 0..MAX_ENTRIES {
+            let mut entry = cached.clone();
+            entry.last_read += std::time::Duration::from_secs(i as u64);
+            insert(&mut cache, PathBuf::from(format!("/p/{i}.jsonl")), entry);
+        }
+        insert(&mut cache, path.clone(), cached);
+        assert_eq!(cache.len(), MAX_ENTRIES);
+        assert!(!cache.contains_key(Path::new("/p/0.jsonl")));
+        assert!(cache.contains_key(Path::new("/p/1.jsonl")));
+        assert!(cache.contains_key(&path));
+    }
+}
@@ -1,37 +1,85 @@
 mod agentdeck;
+mod backend;
+mod budget;
 mod claude_logs;
 mod claude_remote;
+pub mod cli;
 mod command;
+mod conflicts;
+pub mod daemon;
+mod dates;
+mod diff;
+mod export;
 mod git;
 mod github;
+mod hooks;
+mod instance_lock;
+mod jsonl_cache;
+mod local_api;
+mod mcp;
 mod models;
+mod notifications;
+mod opencode_logs;
 mod opencode_remote;
 mod orca_db;
 mod pty;
 mod remote_common;
+mod review;
+mod search;
+mod ssh;
 mod tmux;
+mod tool_profile;
+mod transcript;
+mod usage;
+mod watcher;
+mod webhooks;
 
 use crate::command::new_command;
 use std::io::{BufRead, BufReader};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tauri::menu::{Menu, MenuItem, PredefinedMenuItem, Submenu};
-use tauri::{Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
+use tauri::Manager;
+#[cfg(feature = "desktop")]
+use tauri::{
+    menu::{Menu, MenuItem, PredefinedMenuItem, Submenu},
+    Emitter, WebviewUrl, WebviewWindowBuilder,
+};
+#[cfg(feature = "desktop")]
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
 
 /// Placeholder DSN — replace with your actual Sentry project DSN.
+#[cfg(feature = "desktop")]
 const SENTRY_DSN: &str =
     "https://784e5e4951e78d437d264568ae36dd53@o1366758.ingest.us.sentry.io/4510964361658368";
 
 /// Wrapper so we can store the flag as Tauri managed state.
 pub struct SentryEnabled(Arc<AtomicBool>);
 
+/// The runtime Orca's window runs on. A headless build (without the
+/// `desktop` feature, for `orca-daemon`) has no webview: it never creates an
+/// app handle, and Tauri's mock runtime only stands in for the type.
+#[cfg(feature = "desktop")]
+type Runtime = tauri::Wry;
+#[cfg(not(feature = "desktop"))]
+type Runtime = tauri::test::MockRuntime;
+
+/// The handle background tasks emit frontend events through.
+pub(crate) type AppHandle = tauri::AppHandle<Runtime>;
+
+/// Orca's data directory, as Tauri's `app_data_dir` resolves it, for code
+/// that runs before Tauri's path resolver is available or without Tauri.
+fn data_dir() -> Option<std::path::PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("dk.beaufour.orca"))
+}
+
 /// Read the analytics_enabled flag directly from the Orca SQLite DB
 /// *before* Tauri's path resolver is available.
+#[cfg(feature = "desktop")]
 fn read_analytics_enabled_early() -> bool {
-    let Some(data_dir) = dirs::data_dir() else {
+    let Some(data_dir) = data_dir() else {
         return false;
     };
-    let db_path = data_dir.join("dk.beaufour.orca").join("orca.db");
+    let db_path = data_dir.join("orca.db");
     let Ok(conn) =
         rusqlite::Connection::open_with_flags(&db_path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
     else {
@@ -47,7 +95,7 @@ fn read_analytics_enabled_early() -> bool {
 }
 
 #[tauri::command]
-fn read_app_log(app: tauri::AppHandle, tail_lines: Option<usize>) -> Result<String, String> {
+fn read_app_log(app: AppHandle, tail_lines: Option<usize>) -> Result<String, String> {
     let log_dir = app
         .path()
         .app_log_dir()
@@ -138,6 +186,7 @@ fn get_resolved_credentials(
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
+#[cfg(feature = "desktop")]
 pub fn run() {
     command::init_path();
 
@@ -177,6 +226,7 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_process::init())
+        .plugin(tauri_plugin_notification::init())
         .manage(SentryEnabled(sentry_flag))
         .manage(pty::PtyManager::default())
         .manage(remote_common::SseHandles::default())
@@ -193,6 +243,27 @@ pub fn run() {
                     .build(),
             )?;
 
+            let data_dir = app
+                .path()
+                .app_data_dir()
+                .map_err(|e| format!("Failed to get app data dir: {e}"))?;
+            // Refuse to run next to orca-daemon (or a second app) on the same
+            // data dir: both would ingest hook events and fire webhooks.
+            let lock = match instance_lock::acquire(&data_dir) {
+                Ok(lock) => lock,
+                Err(e) => {
+                    log::error!("{e}");
+                    let handle = handle.clone();
+                    app.dialog()
+                        .message(e)
+                        .title("Orca is already running")
+                        .kind(MessageDialogKind::Error)
+                        .show(move |_| handle.exit(1));
+                    return Ok(());
+                }
+            };
+            app.manage(lock);
+
             // Native app menu
             let about_item =
                 MenuItem::with_id(handle, "about_orca", "About Orca", true, None::<&str>)?;
@@ -271,16 +342,31 @@ pub fn run() {
                 })
                 .build()?;
 
-            let data_dir = app
-                .path()
-                .app_data_dir()
-                .map_err(|e| format!("Failed to get app data dir: {e}"))?;
             let orca_db = orca_db::OrcaDb::init(&data_dir)
                 .map_err(|e| format!("Failed to init Orca DB: {e}"))?;
+            if let Err(e) = tool_profile::reload(&orca_db) {
+                log::warn!("Failed to load tool profiles: {e}");
+            }
+            if let Err(e) = hooks::init(&data_dir, &orca_db) {
+                log::warn!("Claude Code hook events unavailable: {e}");
+            }
+            if let Err(e) = ssh::init(&data_dir) {
+                log::warn!("{e}");
+            }
+            watcher::start(Some(app.handle().clone()), orca_db.clone());
+            budget::start(Some(app.handle().clone()), orca_db.clone());
+            if let Err(e) = local_api::restart(&orca_db, &data_dir) {
+                log::warn!("Automation API unavailable: {e}");
+            }
             app.manage(orca_db);
 
             Ok(())
         })
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::Focused(focused) = event {
+                notifications::set_window_focused(window.app_handle(), *focused);
+            }
+        })
         .on_menu_event(|app, event| {
             if event.id() == "about_orca" {
                 let _ = app.emit("show-about", ());
@@ -310,7 +396,48 @@ pub fn run() {
             agentdeck::store_session_pr_info,
             agentdeck::get_dismissed_ids,
             agentdeck::set_dismissed,
+            backend::backend_list_sessions,
+            backend::backend_create_session,
+            backend::backend_delete_session,
+            backend::backend_send_message,
+            backend::backend_subscribe_events,
+            backend::backend_get_summary,
+            backend::backend_get_attention,
             claude_logs::get_session_summary,
+            claude_logs::get_attention_thresholds,
+            claude_logs::set_attention_thresholds,
+            claude_logs::get_session_file_timeline,
+            opencode_logs::get_opencode_session_summary,
+            tool_profile::get_tool_profiles,
+            tool_profile::set_tool_profile,
+            tool_profile::delete_tool_profile,
+            hooks::install_attention_hooks,
+            hooks::remove_attention_hooks,
+            hooks::get_attention_hooks_installed,
+            notifications::get_notification_settings,
+            notifications::set_notification_settings,
+            notifications::get_group_notifications_muted,
+            notifications::set_group_notifications_muted,
+            webhooks::get_webhooks,
+            webhooks::save_webhook,
+            webhooks::delete_webhook,
+            webhooks::get_webhook_deliveries,
+            webhooks::test_webhook,
+            local_api::get_api_settings,
+            local_api::set_api_settings,
+            local_api::get_api_token,
+            local_api::regenerate_api_token,
+            local_api::get_api_address,
+            transcript::get_session_transcript,
+            export::export_session_transcript,
+            search::search_sessions,
+            usage::get_usage_report,
+            usage::get_model_prices,
+            usage::set_model_price,
+            usage::delete_model_price,
+            budget::get_group_budget,
+            budget::set_group_budget,
+            budget::get_budget_status,
             git::get_default_branch,
             git::list_worktrees,
             git::add_worktree,
@@ -318,15 +445,27 @@ pub fn run() {
             git::merge_worktree,
             git::rebase_worktree,
             git::get_branch_diff,
+            diff::get_branch_diff_v2,
+            diff::get_branch_diff_file,
+            review::add_review_comment,
+            review::list_review_comments,
+            review::update_review_comment,
+            review::delete_review_comment,
+            review::send_review_comments,
             git::check_worktree_status,
             git::try_merge_branch,
             git::abort_merge,
+            git::get_squash_message,
             git::clone_bare_worktree_repo,
             git::init_bare_repo,
             git::push_branch,
             git::force_push_branch,
             git::rebase_branch,
             git::abort_rebase,
+            conflicts::get_conflicts,
+            conflicts::resolve_conflict,
+            conflicts::continue_rebase,
+            conflicts::continue_merge,
             git::update_main_branch,
             git::list_components,
             tmux::list_tmux_sessions,
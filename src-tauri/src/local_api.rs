@@ -0,0 +1,913 @@
+//! Local automation API.
+//!
+//! An opt-in HTTP/1.1 server for scripts and editor plugins, listening on
+//! 127.0.0.1 or a Unix socket (see [`ApiSettings`]). Every request needs the
+//! API token as `Authorization: Bearer <token>`. The endpoints mirror the
+//! Tauri commands and answer with JSON (errors as `{"error": "..."}`).
+//! Session endpoints go through each group's backend (see
+//! [`crate::backend`]), like the MCP tools:
+//!
+//! - `GET /v1/groups`
+//! - `GET /v1/sessions[?group=<path>]`, `GET /v1/sessions/<id>`
+//! - `POST /v1/sessions` — create a session (started by default)
+//! - `DELETE /v1/sessions/<id>`
+//! - `POST /v1/sessions/<id>/prompt` — send `text` to the session as a prompt
+//! - `GET /v1/attention`, `GET /v1/attention/sessions`
+//! - `GET /v1/events` — Server-Sent Events for the watcher's
+//!   `session-updated` and `attention-changed` events and the budget
+//!   monitor's `budget-warning` and `budget-exhausted`
+//! - `POST /mcp` — the MCP server (see [`crate::mcp`])
+//!
+//! While the server runs, its address and the token are written to
+//! `api.json` (mode 0600) in Orca's data directory so local scripts can find
+//! them.
+
+use crate::agentdeck;
+use crate::backend::{self, backend_for_group, CreateSessionParams};
+use crate::mcp;
+use crate::models::ApiSettings;
+use crate::orca_db::OrcaDb;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{Manager, State};
+
+const SOCKET_NAME: &str = "orca.sock";
+pub(crate) const INFO_FILE: &str = "api.json";
+const MAX_HEADER_BYTES: usize = 64 * 1024;
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+const IO_TIMEOUT: Duration = Duration::from_secs(30);
+/// SSE comment interval, so idle streams notice closed connections.
+const KEEPALIVE: Duration = Duration::from_secs(15);
+
+static SERVER: Mutex<Option<Server>> = Mutex::new(None);
+static SUBSCRIBERS: Mutex<Vec<Sender<String>>> = Mutex::new(Vec::new());
+
+trait Stream: Read + Write + Send {}
+impl<T: Read + Write + Send> Stream for T {}
+
+enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
+impl Listener {
+    fn accept(&self) -> std::io::Result<Box<dyn Stream>> {
+        match self {
+            Listener::Tcp(listener) => {
+                let (stream, _) = listener.accept()?;
+                stream.set_read_timeout(Some(IO_TIMEOUT))?;
+                stream.set_write_timeout(Some(IO_TIMEOUT))?;
+                Ok(Box::new(stream))
+            }
+            #[cfg(unix)]
+            Listener::Unix(listener) => {
+                let (stream, _) = listener.accept()?;
+                stream.set_read_timeout(Some(IO_TIMEOUT))?;
+                stream.set_write_timeout(Some(IO_TIMEOUT))?;
+                Ok(Box::new(stream))
+            }
+        }
+    }
+}
+
+/// Where a running server listens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Endpoint {
+    Tcp(SocketAddr),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl std::fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Endpoint::Tcp(addr) => write!(f, "http://{addr}"),
+            #[cfg(unix)]
+            Endpoint::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+impl Endpoint {
+    /// Connect to ourselves so a blocked `accept` returns.
+    fn wake(&self) {
+        match self {
+            Endpoint::Tcp(addr) => {
+                let _ = TcpStream::connect_timeout(addr, Duration::from_secs(1));
+            }
+            #[cfg(unix)]
+            Endpoint::Unix(path) => {
+                let _ = UnixStream::connect(path);
+            }
+        }
+    }
+}
+
+/// A running API server. Dropping it does not stop it; call [`Server::stop`].
+pub struct Server {
+    endpoint: Endpoint,
+    stop: Arc<AtomicBool>,
+    info_path: Option<PathBuf>,
+}
+
+impl Server {
+    /// Bind and serve on a background thread.
+    pub fn start(
+        settings: &ApiSettings,
+        data_dir: &Path,
+        orca_db: OrcaDb,
+        token: String,
+    ) -> Result<Server, String> {
+        let (listener, endpoint) = bind(settings, data_dir)?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_flag = stop.clone();
+        let token: Arc<str> = token.into();
+        std::thread::spawn(move || serve(&listener, &stop_flag, &orca_db, &token));
+        log::info!("Automation API listening on {endpoint}");
+        Ok(Server {
+            endpoint,
+            stop,
+            info_path: None,
+        })
+    }
+
+    pub fn endpoint(&self) -> &Endpoint {
+        &self.endpoint
+    }
+
+    pub fn stop(self) {
+        self.stop.store(true, Ordering::Relaxed);
+        self.endpoint.wake();
+        #[cfg(unix)]
+        if let Endpoint::Unix(path) = &self.endpoint {
+            let _ = std::fs::remove_file(path);
+        }
+        if let Some(path) = &self.info_path {
+            let _ = std::fs::remove_file(path);
+        }
+        log::info!("Automation API on {} stopped", self.endpoint);
+    }
+}
+
+fn bind(settings: &ApiSettings, data_dir: &Path) -> Result<(Listener, Endpoint), String> {
+    match settings.transport.as_str() {
+        "tcp" => {
+            let listener = TcpListener::bind(("127.0.0.1", settings.port))
+                .map_err(|e| format!("Failed to listen on 127.0.0.1:{}: {e}", settings.port))?;
+            let addr = listener
+                .local_addr()
+                .map_err(|e| format!("Failed to get API address: {e}"))?;
+            Ok((Listener::Tcp(listener), Endpoint::Tcp(addr)))
+        }
+        #[cfg(unix)]
+        "unix" => {
+            use std::os::unix::fs::PermissionsExt;
+
+            let path = settings
+                .socket_path
+                .as_ref()
+                .map_or_else(|| data_dir.join(SOCKET_NAME), PathBuf::from);
+            if path.exists() {
+                if UnixStream::connect(&path).is_ok() {
+                    return Err(format!("{} is already in use", path.display()));
+                }
+                // Left behind by an Orca that didn't shut down cleanly.
+                let _ = std::fs::remove_file(&path);
+            }
+            let listener = UnixListener::bind(&path)
+                .map_err(|e| format!("Failed to listen on {}: {e}", path.display()))?;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))
+                .map_err(|e| format!("Failed to restrict {}: {e}", path.display()))?;
+            Ok((Listener::Unix(listener), Endpoint::Unix(path)))
+        }
+        other => Err(format!(
+            "Unknown API transport: '{other}' (expected 'tcp' or 'unix')"
+        )),
+    }
+}
+
+fn serve(listener: &Listener, stop: &AtomicBool, orca_db: &OrcaDb, token: &Arc<str>) {
+    loop {
+        let stream = listener.accept();
+        if stop.load(Ordering::Relaxed) {
+            break;
+        }
+        match stream {
+            Ok(stream) => {
+                let orca_db = orca_db.clone();
+                let token = token.clone();
+                std::thread::spawn(move || handle_connection(stream, &orca_db, &token));
+            }
+            Err(e) => {
+                log::warn!("Automation API: accept failed: {e}");
+                std::thread::sleep(Duration::from_millis(100));
+            }
+        }
+    }
+}
+
+// ── Requests and responses ───────────────────────────────────────────
+
+#[derive(Debug, Default)]
+struct Request {
+    method: String,
+    /// Percent-decoded path segments, e.g. `["v1", "sessions", "<id>"]`.
+    segments: Vec<String>,
+    query: Vec<(String, String)>,
+    /// Header names are lowercased.
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn query(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn json<T: serde::de::DeserializeOwned>(&self) -> Result<T, ApiError> {
+        serde_json::from_slice(&self.body)
+            .map_err(|e| ApiError::new(400, format!("Invalid request body: {e}")))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Response {
+    status: u16,
+    body: String,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct ApiError {
+    status: u16,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+/// Errors from the shared command code are server errors, except lookups.
+impl From<String> for ApiError {
+    fn from(message: String) -> Self {
+        let status = if message.contains("not found") {
+            404
+        } else {
+            500
+        };
+        Self { status, message }
+    }
+}
+
+fn json_response<T: Serialize>(status: u16, value: &T) -> Result<Response, ApiError> {
+    let body = serde_json::to_string(value).map_err(|e| e.to_string())?;
+    Ok(Response { status, body })
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        409 => "Conflict",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        _ => "Internal Server Error",
+    }
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .filter(|hex| bytes[i] == b'%' && hex.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        if let Some(byte) = escaped {
+            out.push(byte);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Query strings also encode spaces as `+`.
+fn query_decode(text: &str) -> String {
+    percent_decode(&text.replace('+', " "))
+}
+
+fn read_request(stream: &mut dyn Stream) -> Result<Request, ApiError> {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader
+        .read_line(&mut request_line)
+        .map_err(|e| ApiError::new(400, format!("Failed to read request: {e}")))?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(ApiError::new(400, "Malformed request line"));
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let mut request = Request {
+        method: method.to_string(),
+        segments: path
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .map(percent_decode)
+            .collect(),
+        query: query
+            .split('&')
+            .filter(|p| !p.is_empty())
+            .map(|pair| {
+                let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+                (query_decode(name), query_decode(value))
+            })
+            .collect(),
+        ..Request::default()
+    };
+
+    let mut header_bytes = request_line.len();
+    loop {
+        let mut line = String::new();
+        let read = reader
+            .read_line(&mut line)
+            .map_err(|e| ApiError::new(400, format!("Failed to read headers: {e}")))?;
+        header_bytes += read;
+        if header_bytes > MAX_HEADER_BYTES {
+            return Err(ApiError::new(413, "Headers too large"));
+        }
+        if read == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            request
+                .headers
+                .push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+
+    let length: usize = request
+        .header("content-length")
+        .map_or(Ok(0), str::parse)
+        .map_err(|_| ApiError::new(400, "Invalid Content-Length"))?;
+    if length > MAX_BODY_BYTES {
+        return Err(ApiError::new(413, "Request body too large"));
+    }
+    request.body = vec![0; length];
+    reader
+        .read_exact(&mut request.body)
+        .map_err(|e| ApiError::new(400, format!("Failed to read request body: {e}")))?;
+    Ok(request)
+}
+
+fn write_response(stream: &mut dyn Stream, response: &Response) -> std::io::Result<()> {
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n",
+        response.status,
+        reason(response.status),
+        response.body.len()
+    );
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(response.body.as_bytes())?;
+    stream.flush()
+}
+
+/// Compare without bailing out at the first differing byte.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn authorized(request: &Request, token: &str) -> bool {
+    let bearer = request
+        .header("authorization")
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim);
+    // EventSource can't send headers, so the event stream also takes ?token=
+    let presented = bearer.or_else(|| {
+        if request.segments == ["v1", "events"] {
+            request.query("token")
+        } else {
+            None
+        }
+    });
+    presented.is_some_and(|p| constant_time_eq(p.as_bytes(), token.as_bytes()))
+}
+
+fn handle_connection(mut stream: Box<dyn Stream>, orca_db: &OrcaDb, token: &str) {
+    let result = read_request(stream.as_mut()).and_then(|request| {
+        if !authorized(&request, token) {
+            return Err(ApiError::new(401, "Missing or invalid API token"));
+        }
+        Ok(request)
+    });
+    let request = match result {
+        Ok(request) => request,
+        Err(e) => {
+            let _ = write_response(stream.as_mut(), &error_response(&e));
+            return;
+        }
+    };
+    log::debug!(
+        "Automation API: {} /{}",
+        request.method,
+        request.segments.join("/")
+    );
+    if request.method == "GET" && request.segments == ["v1", "events"] {
+        stream_events(stream);
+        return;
+    }
+    let response = handle(orca_db, &request).unwrap_or_else(|e| {
+        log::warn!("Automation API: {} {}", e.status, e.message);
+        error_response(&e)
+    });
+    let _ = write_response(stream.as_mut(), &response);
+}
+
+fn error_response(error: &ApiError) -> Response {
+    Response {
+        status: error.status,
+        body: json!({ "error": error.message }).to_string(),
+    }
+}
+
+// ── Endpoints ────────────────────────────────────────────────────────
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize)]
+struct CreateSessionRequest {
+    group: String,
+    /// Defaults to the worktree branch.
+    title: Option<String>,
+    /// Defaults to the group's default path.
+    project_path: Option<String>,
+    tool: Option<String>,
+    worktree_branch: Option<String>,
+    #[serde(default)]
+    new_branch: bool,
+    #[serde(default = "default_true")]
+    start: bool,
+    prompt: Option<String>,
+    components: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SendPromptRequest {
+    text: String,
+}
+
+fn handle(orca_db: &OrcaDb, request: &Request) -> Result<Response, ApiError> {
+    let segments: Vec<&str> = request.segments.iter().map(String::as_str).collect();
+    match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["v1", "groups"]) => json_response(200, &agentdeck::get_groups_impl(orca_db)?),
+        ("GET", ["v1", "sessions"]) => json_response(
+            200,
+            &backend::list_sessions_blocking(orca_db, request.query("group"))?,
+        ),
+        ("POST", ["v1", "sessions"]) => create_session(orca_db, request.json()?),
+        ("GET", ["v1", "sessions", id]) => {
+            json_response(200, &backend::find_session_blocking(orca_db, id)?.0)
+        }
+        ("DELETE", ["v1", "sessions", id]) => {
+            let (session, backend) = backend::find_session_blocking(orca_db, id)?;
+            backend::block_on(backend.delete_session(&session.id))?;
+            json_response(200, &json!({ "removed": id }))
+        }
+        ("POST", ["v1", "sessions", id, "prompt"]) => send_prompt(orca_db, id, &request.json()?),
+        ("GET", ["v1", "attention"]) => {
+            json_response(200, &agentdeck::get_attention_counts_impl(orca_db)?)
+        }
+        ("GET", ["v1", "attention", "sessions"]) => {
+            json_response(200, &agentdeck::get_attention_sessions_impl(orca_db)?)
+        }
+        ("POST", ["mcp"]) => Ok(mcp_message(orca_db, &request.body)),
+        (_, ["mcp"]) => Err(ApiError::new(
+            405,
+            "The MCP endpoint only takes POSTed JSON-RPC messages",
+        )),
+        (method, _) => Err(ApiError::new(
+            404,
+            format!("No endpoint for {method} /{}", segments.join("/")),
+        )),
+    }
+}
+
+fn create_session(orca_db: &OrcaDb, body: CreateSessionRequest) -> Result<Response, ApiError> {
+    let title = body
+        .title
+        .clone()
+        .or_else(|| body.worktree_branch.clone())
+        .filter(|t| !t.trim().is_empty())
+        .ok_or_else(|| ApiError::new(400, "title or worktree_branch is required"))?;
+    let (group_path, project_path) = match body.project_path {
+        Some(path) => (body.group, path),
+        None => {
+            let group = agentdeck::find_group(orca_db, &body.group)?;
+            if group.default_path.is_empty() {
+                return Err(ApiError::new(
+                    400,
+                    "The group has no default path; pass project_path",
+                ));
+            }
+            (group.path, group.default_path)
+        }
+    };
+    let backend = backend_for_group(orca_db, &group_path)?;
+    let session_id = backend::block_on(backend.create_session(CreateSessionParams {
+        group_path,
+        project_path,
+        title,
+        tool: body.tool,
+        worktree_branch: body.worktree_branch,
+        new_branch: body.new_branch,
+        start: Some(body.start),
+        prompt: body.prompt,
+        components: body.components,
+    }))?;
+    let session = backend::block_on(backend.list_sessions())?
+        .into_iter()
+        .find(|s| s.id == session_id)
+        .ok_or_else(|| format!("Created session {session_id}, but it isn't listed"))?;
+    json_response(201, &session)
+}
+
+fn send_prompt(
+    orca_db: &OrcaDb,
+    session_id: &str,
+    body: &SendPromptRequest,
+) -> Result<Response, ApiError> {
+    if body.text.trim().is_empty() {
+        return Err(ApiError::new(400, "text must not be empty"));
+    }
+    let (session, backend) = backend::find_session_blocking(orca_db, session_id)?;
+    // Only a local session's tmux pane can be checked here; other backends
+    // report their own errors.
+    if orca_db.get_group_backend(&session.group_path)? == "local" && session.tmux_session.is_empty()
+    {
+        return Err(ApiError::new(
+            409,
+            format!("Session {session_id} has no tmux session; start it first"),
+        ));
+    }
+    backend::block_on(backend.send_message(&session.id, &body.text))?;
+    json_response(200, &json!({ "sent": session_id }))
+}
+
+/// MCP's streamable HTTP transport without server-initiated streams:
+/// one JSON response per request, 202 for notifications.
+fn mcp_message(orca_db: &OrcaDb, body: &[u8]) -> Response {
+    match mcp::handle_json(orca_db, &String::from_utf8_lossy(body)) {
+        Some(body) => Response { status: 200, body },
+        None => Response {
+            status: 202,
+            body: String::new(),
+        },
+    }
+}
+
+// ── Events ───────────────────────────────────────────────────────────
+
+fn sse_message(event: &str, data: &str) -> String {
+    format!("event: {event}\ndata: {data}\n\n")
+}
+
+/// Send an event to `/v1/events` subscribers. Does nothing when nobody listens.
+pub fn publish<T: Serialize>(event: &str, payload: &T) {
+    let Ok(mut subscribers) = SUBSCRIBERS.lock() else {
+        return;
+    };
+    if subscribers.is_empty() {
+        return;
+    }
+    let Ok(data) = serde_json::to_string(payload) else {
+        return;
+    };
+    let message = sse_message(event, &data);
+    subscribers.retain(|tx| tx.send(message.clone()).is_ok());
+}
+
+fn stream_events(mut stream: Box<dyn Stream>) {
+    let (tx, rx) = mpsc::channel();
+    if let Ok(mut subscribers) = SUBSCRIBERS.lock() {
+        subscribers.push(tx);
+    }
+    let head = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\n\
+                Connection: close\r\n\r\n: connected\n\n";
+    if stream.write_all(head.as_bytes()).is_err() {
+        return;
+    }
+    loop {
+        let chunk = match rx.recv_timeout(KEEPALIVE) {
+            Ok(message) => message,
+            Err(RecvTimeoutError::Timeout) => ": keepalive\n\n".to_string(),
+            Err(RecvTimeoutError::Disconnected) => return,
+        };
+        if stream
+            .write_all(chunk.as_bytes())
+            .and_then(|()| stream.flush())
+            .is_err()
+        {
+            return;
+        }
+    }
+}
+
+// ── Lifecycle ────────────────────────────────────────────────────────
+
+fn generate_token() -> Result<String, String> {
+    let mut bytes = [0u8; 32];
+    std::fs::File::open("/dev/urandom")
+        .and_then(|mut f| f.read_exact(&mut bytes))
+        .map_err(|e| format!("Failed to generate API token: {e}"))?;
+    Ok(bytes.iter().map(|b| format!("{b:02x}")).collect())
+}
+
+/// The API token, created on first use.
+fn ensure_token(orca_db: &OrcaDb) -> Result<String, String> {
+    if let Some(token) = orca_db.get_api_token()? {
+        return Ok(token);
+    }
+    let token = generate_token()?;
+    orca_db.set_api_token(&token)?;
+    Ok(token)
+}
+
+fn write_info(path: &Path, endpoint: &Endpoint, token: &str) -> Result<(), String> {
+    let info = json!({
+        "url": endpoint.to_string(),
+        "token": token,
+        "pid": std::process::id(),
+    });
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options
+        .open(path)
+        .and_then(|mut f| f.write_all(info.to_string().as_bytes()))
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+/// Stop the running server, if any.
+pub fn stop() {
+    let server = SERVER.lock().ok().and_then(|mut s| s.take());
+    if let Some(server) = server {
+        server.stop();
+    }
+    // Open event streams end when their channel closes.
+    if let Ok(mut subscribers) = SUBSCRIBERS.lock() {
+        subscribers.clear();
+    }
+}
+
+/// (Re)start the server from the stored settings; stops it when disabled.
+/// Called at startup and whenever the settings or token change.
+pub fn restart(orca_db: &OrcaDb, data_dir: &Path) -> Result<(), String> {
+    stop();
+    let settings = orca_db.get_api_settings()?;
+    if !settings.enabled {
+        return Ok(());
+    }
+    start(&settings, orca_db, data_dir).map(|_| ())
+}
+
+/// Start the server with the given settings, whether or not they're enabled,
+/// and write `api.json`. Returns the endpoint it listens on.
+pub fn start(settings: &ApiSettings, orca_db: &OrcaDb, data_dir: &Path) -> Result<String, String> {
+    validate(settings)?;
+    let token = ensure_token(orca_db)?;
+    let mut server = Server::start(settings, data_dir, orca_db.clone(), token.clone())?;
+    let endpoint = server.endpoint().to_string();
+    let info_path = data_dir.join(INFO_FILE);
+    match write_info(&info_path, server.endpoint(), &token) {
+        Ok(()) => server.info_path = Some(info_path),
+        Err(e) => log::warn!("{e}"),
+    }
+    *SERVER.lock().map_err(|e| format!("Lock error: {e}"))? = Some(server);
+    Ok(endpoint)
+}
+
+fn app_data_dir(app: &crate::AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {e}"))
+}
+
+fn validate(settings: &ApiSettings) -> Result<(), String> {
+    match settings.transport.as_str() {
+        "tcp" if settings.port == 0 => Err("Port must be between 1 and 65535".to_string()),
+        "tcp" => Ok(()),
+        "unix" if cfg!(unix) => Ok(()),
+        other => Err(format!("Unsupported API transport: '{other}'")),
+    }
+}
+
+#[tauri::command]
+pub fn get_api_settings(orca_db: State<'_, OrcaDb>) -> Result<ApiSettings, String> {
+    orca_db.get_api_settings()
+}
+
+/// Save the settings and start, restart or stop the server to match.
+#[tauri::command]
+pub fn set_api_settings(
+    app: crate::AppHandle,
+    orca_db: State<'_, OrcaDb>,
+    settings: ApiSettings,
+) -> Result<(), String> {
+    validate(&settings)?;
+    orca_db.set_api_settings(&settings)?;
+    restart(&orca_db, &app_data_dir(&app)?)
+}
+
+#[tauri::command]
+pub fn get_api_token(orca_db: State<'_, OrcaDb>) -> Result<String, String> {
+    ensure_token(&orca_db)
+}
+
+/// Replace the token, invalidating the old one.
+#[tauri::command]
+pub fn regenerate_api_token(
+    app: crate::AppHandle,
+    orca_db: State<'_, OrcaDb>,
+) -> Result<String, String> {
+    let token = generate_token()?;
+    orca_db.set_api_token(&token)?;
+    restart(&orca_db, &app_data_dir(&app)?)?;
+    Ok(token)
+}
+
+/// Where the server listens, or None when it isn't running.
+#[tauri::command]
+pub fn get_api_address() -> Option<String> {
+    SERVER
+        .lock()
+        .ok()?
+        .as_ref()
+        .map(|s| s.endpoint().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(raw: &str) -> Request {
+        let mut stream = std::io::Cursor::new(raw.as_bytes().to_vec());
+        read_request(&mut stream).unwrap()
+    }
+
+    /// Send a raw request and return the status code and body.
+    fn call(stream: &mut impl Stream, raw: &str) -> (u16, String) {
+        stream.write_all(raw.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let status = response[9..12].parse().unwrap();
+        let body = response
+            .split_once("\r\n\r\n")
+            .map(|(_, b)| b.to_string())
+            .unwrap_or_default();
+        (status, body)
+    }
+
+    #[test]
+    fn parses_requests() {
+        let request = parse(
+            "POST /v1/sessions/a%2Fb/prompt?group=%2Fsrc%2Forca&x HTTP/1.1\r\n\
+             Authorization: Bearer t0k\r\nContent-Length: 13\r\n\r\n{\"text\":\"hi\"}",
+        );
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.segments, ["v1", "sessions", "a/b", "prompt"]);
+        assert_eq!(request.query("group"), Some("/src/orca"));
+        assert_eq!(request.query("x"), Some(""));
+        assert_eq!(request.header("authorization"), Some("Bearer t0k"));
+        let body: SendPromptRequest = request.json().unwrap();
+        assert_eq!(body.text, "hi");
+
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("a+b%zz%4"), "a+b%zz%4");
+        assert_eq!(query_decode("a+b%2B"), "a b+");
+    }
+
+    #[test]
+    fn requires_the_token() {
+        let with_header = parse("GET /v1/groups HTTP/1.1\r\nAuthorization: Bearer secret\r\n\r\n");
+        assert!(authorized(&with_header, "secret"));
+        assert!(!authorized(&with_header, "secret2"));
+        assert!(!authorized(
+            &parse("GET /v1/groups HTTP/1.1\r\n\r\n"),
+            "secret"
+        ));
+        assert!(!authorized(
+            &parse("GET /v1/groups?token=secret HTTP/1.1\r\n\r\n"),
+            "secret"
+        ));
+        assert!(authorized(
+            &parse("GET /v1/events?token=secret HTTP/1.1\r\n\r\n"),
+            "secret"
+        ));
+    }
+
+    #[test]
+    fn publish_reaches_live_subscribers_only() {
+        let (tx, rx) = mpsc::channel();
+        let (closed, _) = mpsc::channel();
+        SUBSCRIBERS.lock().unwrap().extend([tx, closed]);
+        publish("attention-changed", &json!([{ "session_id": "s1" }]));
+        assert_eq!(
+            rx.recv().unwrap(),
+            "event: attention-changed\ndata: [{\"session_id\":\"s1\"}]\n\n"
+        );
+        assert_eq!(SUBSCRIBERS.lock().unwrap().len(), 1);
+        SUBSCRIBERS.lock().unwrap().clear();
+    }
+
+    #[test]
+    fn serves_over_tcp_and_unix_sockets() {
+        let tmp = tempfile::tempdir().unwrap();
+        let orca_db = OrcaDb::init(tmp.path()).unwrap();
+        let tcp = ApiSettings {
+            enabled: true,
+            port: 0,
+            ..ApiSettings::default()
+        };
+        let server = Server::start(&tcp, tmp.path(), orca_db.clone(), "secret".into()).unwrap();
+        let Endpoint::Tcp(addr) = server.endpoint().clone() else {
+            panic!("expected a TCP endpoint");
+        };
+        assert!(addr.ip().is_loopback());
+
+        let (status, body) = call(
+            &mut TcpStream::connect(addr).unwrap(),
+            "GET /v1/groups HTTP/1.1\r\n\r\n",
+        );
+        assert_eq!(status, 401);
+        assert!(body.contains("invalid API token"));
+
+        let (status, _) = call(
+            &mut TcpStream::connect(addr).unwrap(),
+            "GET /v1/nope HTTP/1.1\r\nAuthorization: Bearer secret\r\n\r\n",
+        );
+        assert_eq!(status, 404);
+
+        let (status, body) = call(
+            &mut TcpStream::connect(addr).unwrap(),
+            "POST /v1/sessions HTTP/1.1\r\nAuthorization: Bearer secret\r\n\
+             Content-Length: 2\r\n\r\n{}",
+        );
+        assert_eq!(status, 400);
+        assert!(body.contains("missing field `group`"));
+        server.stop();
+
+        #[cfg(unix)]
+        {
+            let socket = tmp.path().join("api.sock");
+            let unix = ApiSettings {
+                enabled: true,
+                transport: "unix".into(),
+                socket_path: Some(socket.to_string_lossy().into_owned()),
+                ..ApiSettings::default()
+            };
+            let server = Server::start(&unix, tmp.path(), orca_db, "secret".into()).unwrap();
+            let (status, _) = call(
+                &mut UnixStream::connect(&socket).unwrap(),
+                "DELETE /v1/groups HTTP/1.1\r\nAuthorization: Bearer secret\r\n\r\n",
+            );
+            assert_eq!(status, 404);
+            server.stop();
+            assert!(!socket.exists());
+        }
+    }
+}
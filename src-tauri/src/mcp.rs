@@ -0,0 +1,490 @@
+//! MCP (Model Context Protocol) server exposing Orca to agents.
+//!
+//! An orchestrating agent can list sessions, spawn worktree sessions with a
+//! prompt, send follow-ups, read summaries and diffs, and see which sessions
+//! need attention. Two transports share [`handle_json`]:
+//!
+//! - stdio: `orca-cli mcp`, one JSON-RPC message per line
+//!   (`claude mcp add orca -- orca-cli mcp`)
+//! - HTTP: `POST /mcp` on the local automation API (see [`crate::local_api`]),
+//!   with the same bearer token
+//!
+//! Tool failures are reported as tool results with `isError`, so the agent
+//! sees the message; protocol errors are JSON-RPC errors.
+//!
+//! Like the Tauri commands, the session tools go through the group's
+//! [`backend_for_group`], so they work for SSH and remote groups too, and
+//! diffs of an SSH group's worktrees are taken on its host.
+
+use crate::agentdeck;
+use crate::backend::{
+    backend_for_group, block_on, find_session_blocking, list_sessions_blocking, CreateSessionParams,
+};
+use crate::claude_logs;
+use crate::git;
+use crate::models::Session;
+use crate::orca_db::OrcaDb;
+use crate::ssh;
+use crate::tmux;
+use crate::tool_profile;
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+
+/// Newest first; an unknown requested version gets the newest.
+const PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+/// Longest diff returned in one tool result.
+const MAX_DIFF_CHARS: usize = 100_000;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+fn tools() -> Value {
+    let session_id = json!({
+        "type": "string",
+        "description": "Session ID from list_sessions or spawn_session",
+    });
+    json!([
+        {
+            "name": "list_sessions",
+            "description": "List Orca's agent sessions with their status, branch and worktree.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "group": { "type": "string", "description": "Only this group (path or name)" },
+                },
+            },
+        },
+        {
+            "name": "spawn_session",
+            "description": "Start a new agent session, in a new git worktree when a branch is \
+                            given, and send it a prompt once the agent is ready.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "group": { "type": "string", "description": "Group path or name" },
+                    "prompt": { "type": "string", "description": "The task for the new session" },
+                    "branch": { "type": "string", "description": "New branch (and worktree) to work on" },
+                    "title": { "type": "string", "description": "Defaults to the branch" },
+                    "tool": { "type": "string", "description": "Agent CLI, default claude" },
+                    "project_path": { "type": "string", "description": "Defaults to the group's path" },
+                },
+                "required": ["group", "prompt"],
+            },
+        },
+        {
+            "name": "send_to_session",
+            "description": "Send a message to a session's agent, as if typed at its prompt.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "session_id": session_id,
+                    "text": { "type": "string" },
+                },
+                "required": ["session_id", "text"],
+            },
+        },
+        {
+            "name": "get_session_summary",
+            "description": "A session's attention status (running, needs_input, idle, ...), \
+                            summary, initial prompt, last tool and last assistant message.",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "session_id": session_id },
+                "required": ["session_id"],
+            },
+        },
+        {
+            "name": "get_branch_diff",
+            "description": "The changes on a worktree session's branch against the default branch.",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "session_id": session_id },
+                "required": ["session_id"],
+            },
+        },
+        {
+            "name": "get_attention",
+            "description": "Sessions waiting on a person (permission prompts, questions, \
+                            errors, rate limits), most urgent first.",
+            "inputSchema": { "type": "object", "properties": {} },
+        },
+    ])
+}
+
+fn response(id: &Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+fn error(id: &Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+fn tool_result(result: Result<String, String>) -> Value {
+    let (text, is_error) = match result {
+        Ok(text) => (text, false),
+        Err(message) => (message, true),
+    };
+    json!({ "content": [{ "type": "text", "text": text }], "isError": is_error })
+}
+
+fn required<'a>(args: &'a Value, name: &str) -> Result<&'a str, String> {
+    args[name]
+        .as_str()
+        .filter(|s| !s.trim().is_empty())
+        .ok_or_else(|| format!("Missing argument: {name}"))
+}
+
+fn optional(args: &Value, name: &str) -> Option<String> {
+    args[name]
+        .as_str()
+        .filter(|s| !s.trim().is_empty())
+        .map(str::to_string)
+}
+
+fn pretty(value: &Value) -> Result<String, String> {
+    serde_json::to_string_pretty(value).map_err(|e| e.to_string())
+}
+
+/// The fields an agent needs, without Orca's bookkeeping.
+fn session_json(session: &Session) -> Value {
+    json!({
+        "id": session.id,
+        "title": session.title,
+        "group": session.group_path,
+        "status": session.status,
+        "tool": session.tool,
+        "branch": session.worktree_branch,
+        "worktree_path": session.worktree_path,
+        "prompt": session.prompt,
+        "pr_url": session.pr_url,
+    })
+}
+
+fn list_sessions(orca_db: &OrcaDb, args: &Value) -> Result<String, String> {
+    let group = match optional(args, "group") {
+        Some(group) => Some(agentdeck::find_group(orca_db, &group)?.path),
+        None => None,
+    };
+    let sessions = list_sessions_blocking(orca_db, group.as_deref())?;
+    pretty(&sessions.iter().map(session_json).collect())
+}
+
+fn spawn_session(orca_db: &OrcaDb, args: &Value) -> Result<String, String> {
+    let group = agentdeck::find_group(orca_db, required(args, "group")?)?;
+    let prompt = required(args, "prompt")?.to_string();
+    let branch = optional(args, "branch");
+    let project_path = match optional(args, "project_path") {
+        Some(path) => path,
+        None if group.default_path.is_empty() => {
+            return Err(format!(
+                "Group '{}' has no default path; pass project_path",
+                group.name
+            ))
+        }
+        None => group.default_path,
+    };
+    let title = optional(args, "title")
+        .or_else(|| branch.clone())
+        .unwrap_or_else(|| "agent task".to_string());
+    let backend = backend_for_group(orca_db, &group.path)?;
+    let session_id = block_on(backend.create_session(CreateSessionParams {
+        group_path: group.path,
+        project_path,
+        title,
+        tool: optional(args, "tool"),
+        new_branch: branch.is_some(),
+        worktree_branch: branch,
+        start: Some(true),
+        prompt: Some(prompt),
+        components: None,
+    }))?;
+    let session = block_on(backend.list_sessions())?
+        .into_iter()
+        .find(|s| s.id == session_id)
+        .ok_or_else(|| format!("Created session {session_id}, but it isn't listed"))?;
+    pretty(&session_json(&session))
+}
+
+fn send_to_session(orca_db: &OrcaDb, args: &Value) -> Result<String, String> {
+    let (session, backend) = find_session_blocking(orca_db, required(args, "session_id")?)?;
+    let text = required(args, "text")?;
+    // Checks on the local tmux pane; other backends report their own errors.
+    if orca_db.get_group_backend(&session.group_path)? == "local" {
+        if session.tmux_session.is_empty() {
+            return Err(format!(
+                "Session '{}' is not running; it has no tmux session",
+                session.title
+            ));
+        }
+        // Typed text would answer the permission prompt instead of reaching the agent.
+        let profile = tool_profile::profile_for(&session.tool);
+        if tmux::pane_attention(&session.tmux_session, &profile)
+            == Some(claude_logs::AttentionStatus::AwaitingPermission)
+        {
+            return Err(format!(
+                "Session '{}' is waiting for a permission prompt; a person has to answer it",
+                session.title
+            ));
+        }
+    }
+    block_on(backend.send_message(&session.id, text))?;
+    Ok(format!("Sent to '{}'", session.title))
+}
+
+fn get_session_summary(orca_db: &OrcaDb, args: &Value) -> Result<String, String> {
+    let (session, backend) = find_session_blocking(orca_db, required(args, "session_id")?)?;
+    let summary = block_on(backend.get_summary(&session.id))?;
+    let mut value = serde_json::to_value(&summary).map_err(|e| e.to_string())?;
+    value["session"] = session_json(&session);
+    pretty(&value)
+}
+
+fn get_branch_diff(orca_db: &OrcaDb, args: &Value) -> Result<String, String> {
+    let (session, _) = find_session_blocking(orca_db, required(args, "session_id")?)?;
+    if session.worktree_path.is_empty() {
+        return Err(format!("Session '{}' is not in a worktree", session.title));
+    }
+    let host = ssh::host_for(orca_db, Some(&session.group_path))?;
+    let diff = git::get_branch_diff_on(host.as_ref(), &session.worktree_path)?;
+    if diff.is_empty() {
+        return Ok("No changes against the default branch".to_string());
+    }
+    let total = diff.chars().count();
+    if total <= MAX_DIFF_CHARS {
+        return Ok(diff);
+    }
+    let mut truncated: String = diff.chars().take(MAX_DIFF_CHARS).collect();
+    truncated.push_str(&format!(
+        "\n\n[diff truncated: showing {MAX_DIFF_CHARS} of {total} characters]"
+    ));
+    Ok(truncated)
+}
+
+fn get_attention(orca_db: &OrcaDb) -> Result<String, String> {
+    let sessions = agentdeck::get_attention_sessions_with_status(orca_db)?;
+    let entries: Vec<Value> = sessions
+        .iter()
+        .map(|(attention, session)| {
+            let mut entry = session_json(session);
+            entry["attention"] = json!(attention);
+            entry
+        })
+        .collect();
+    pretty(&Value::Array(entries))
+}
+
+fn call_tool(orca_db: &OrcaDb, id: &Value, params: &Value) -> Value {
+    let args = params
+        .get("arguments")
+        .cloned()
+        .unwrap_or_else(|| json!({}));
+    let name = params["name"].as_str().unwrap_or_default();
+    let result = match name {
+        "list_sessions" => list_sessions(orca_db, &args),
+        "spawn_session" => spawn_session(orca_db, &args),
+        "send_to_session" => send_to_session(orca_db, &args),
+        "get_session_summary" => get_session_summary(orca_db, &args),
+        "get_branch_diff" => get_branch_diff(orca_db, &args),
+        "get_attention" => get_attention(orca_db),
+        _ => return error(id, INVALID_PARAMS, &format!("Unknown tool: {name}")),
+    };
+    if let Err(e) = &result {
+        log::warn!("MCP tool {name} failed: {e}");
+    }
+    response(id, tool_result(result))
+}
+
+/// Handle one JSON-RPC message, returning the response (None for notifications).
+fn handle_message(orca_db: &OrcaDb, message: &Value) -> Option<Value> {
+    let Some(method) = message["method"].as_str() else {
+        // A response to a server request; Orca never sends any.
+        return message
+            .get("id")
+            .is_none()
+            .then(|| error(&Value::Null, INVALID_REQUEST, "Missing method"));
+    };
+    let id = message.get("id")?;
+    let params = &message["params"];
+    log::debug!("MCP request: {method}");
+    Some(match method {
+        "initialize" => {
+            let requested = params["protocolVersion"].as_str().unwrap_or_default();
+            let version = PROTOCOL_VERSIONS
+                .iter()
+                .find(|v| **v == requested)
+                .unwrap_or(&PROTOCOL_VERSIONS[0]);
+            response(
+                id,
+                json!({
+                    "protocolVersion": version,
+                    "capabilities": { "tools": {} },
+                    "serverInfo": { "name": "orca", "version": env!("CARGO_PKG_VERSION") },
+                    "instructions": "Orca runs coding agents in parallel git worktrees. Use \
+                                     spawn_session to delegate a subtask, then poll \
+                                     get_attention or get_session_summary, read results with \
+                                     get_branch_diff and follow up with send_to_session.",
+                }),
+            )
+        }
+        "ping" => response(id, json!({})),
+        "tools/list" => response(id, json!({ "tools": tools() })),
+        "tools/call" => call_tool(orca_db, id, params),
+        _ => error(id, METHOD_NOT_FOUND, &format!("Method not found: {method}")),
+    })
+}
+
+/// Handle a JSON-RPC message or batch, returning the serialized response, if any.
+pub fn handle_json(orca_db: &OrcaDb, text: &str) -> Option<String> {
+    let message: Value = match serde_json::from_str(text) {
+        Ok(message) => message,
+        Err(e) => {
+            let reply = error(&Value::Null, PARSE_ERROR, &format!("Parse error: {e}"));
+            return Some(reply.to_string());
+        }
+    };
+    let reply = match &message {
+        Value::Array(batch) => {
+            let replies: Vec<Value> = batch
+                .iter()
+                .filter_map(|m| handle_message(orca_db, m))
+                .collect();
+            (!replies.is_empty()).then_some(Value::Array(replies))
+        }
+        _ => handle_message(orca_db, &message),
+    };
+    reply.map(|r| r.to_string())
+}
+
+/// Serve MCP over stdin/stdout until stdin closes.
+pub fn serve_stdio(orca_db: &OrcaDb) -> Result<(), String> {
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout();
+    for line in stdin.lock().lines() {
+        let line = line.map_err(|e| format!("Failed to read stdin: {e}"))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(reply) = handle_json(orca_db, &line) {
+            writeln!(stdout, "{reply}")
+                .and_then(|()| stdout.flush())
+                .map_err(|e| format!("Failed to write stdout: {e}"))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(orca_db: &OrcaDb, request: Value) -> Value {
+        let reply = handle_json(orca_db, &request.to_string()).expect("no reply");
+        serde_json::from_str(&reply).unwrap()
+    }
+
+    fn setup() -> (OrcaDb, tempfile::TempDir) {
+        let tmp = tempfile::tempdir().unwrap();
+        (OrcaDb::init(tmp.path()).unwrap(), tmp)
+    }
+
+    #[test]
+    fn initialize_negotiates_the_protocol_version() {
+        let (db, _tmp) = setup();
+        let reply = call(
+            &db,
+            json!({"jsonrpc": "2.0", "id": 1, "method": "initialize",
+                   "params": {"protocolVersion": "2025-03-26", "capabilities": {}}}),
+        );
+        assert_eq!(reply["id"], 1);
+        assert_eq!(reply["result"]["protocolVersion"], "2025-03-26");
+        assert_eq!(reply["result"]["serverInfo"]["name"], "orca");
+
+        let reply = call(
+            &db,
+            json!({"jsonrpc": "2.0", "id": 2, "method": "initialize",
+                   "params": {"protocolVersion": "1999-01-01"}}),
+        );
+        assert_eq!(reply["result"]["protocolVersion"], PROTOCOL_VERSIONS[0]);
+    }
+
+    #[test]
+    fn lists_tools_and_ignores_notifications() {
+        let (db, _tmp) = setup();
+        let reply = call(
+            &db,
+            json!({"jsonrpc": "2.0", "id": "a", "method": "tools/list"}),
+        );
+        let names: Vec<&str> = reply["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap())
+            .collect();
+        assert!(names.contains(&"spawn_session"));
+        assert!(names.contains(&"get_branch_diff"));
+
+        let notification = json!({"jsonrpc": "2.0", "method": "notifications/initialized"});
+        assert_eq!(handle_json(&db, &notification.to_string()), None);
+    }
+
+    #[test]
+    fn reports_protocol_and_tool_errors() {
+        let (db, _tmp) = setup();
+        let parse = call_raw(&db, "{not json");
+        assert_eq!(parse["error"]["code"], PARSE_ERROR);
+
+        let unknown = call(
+            &db,
+            json!({"jsonrpc": "2.0", "id": 1, "method": "resources/list"}),
+        );
+        assert_eq!(unknown["error"]["code"], METHOD_NOT_FOUND);
+
+        let bad_tool = call(
+            &db,
+            json!({"jsonrpc": "2.0", "id": 2, "method": "tools/call",
+                   "params": {"name": "rm_rf", "arguments": {}}}),
+        );
+        assert_eq!(bad_tool["error"]["code"], INVALID_PARAMS);
+
+        let missing = call(
+            &db,
+            json!({"jsonrpc": "2.0", "id": 3, "method": "tools/call",
+                   "params": {"name": "send_to_session", "arguments": {"text": "hi"}}}),
+        );
+        assert_eq!(missing["result"]["isError"], true);
+        assert_eq!(
+            missing["result"]["content"][0]["text"],
+            "Missing argument: session_id"
+        );
+
+        let unknown_session = call(
+            &db,
+            json!({"jsonrpc": "2.0", "id": 4, "method": "tools/call",
+                   "params": {"name": "get_session_summary",
+                              "arguments": {"session_id": "no-such-session"}}}),
+        );
+        assert_eq!(unknown_session["result"]["isError"], true);
+        assert_eq!(
+            unknown_session["result"]["content"][0]["text"],
+            "Session not found: no-such-session"
+        );
+    }
+
+    #[test]
+    fn answers_batches() {
+        let (db, _tmp) = setup();
+        let batch = json!([
+            {"jsonrpc": "2.0", "id": 1, "method": "ping"},
+            {"jsonrpc": "2.0", "method": "notifications/initialized"},
+        ]);
+        let reply = call(&db, batch);
+        assert_eq!(reply.as_array().unwrap().len(), 1);
+        assert_eq!(reply[0]["id"], 1);
+    }
+
+    fn call_raw(orca_db: &OrcaDb, text: &str) -> Value {
+        serde_json::from_str(&handle_json(orca_db, text).unwrap()).unwrap()
+    }
+}
@@ -44,7 +44,7 @@ pub struct Group {
     pub server_url: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Session {
     pub id: String,
     pub title: String,
@@ -58,15 +58,247 @@ pub struct Session {
     pub worktree_path: String,
     pub worktree_repo: String,
     pub worktree_branch: String,
+    /// agent-deck tool name, e.g. "claude", "codex" or "shell".
+    #[serde(default)]
+    pub tool: String,
     pub claude_session_id: Option<String>,
+    /// OpenCode's session ID, when agent-deck recorded one.
+    #[serde(default)]
+    pub opencode_session_id: Option<String>,
     pub prompt: Option<String>,
     pub pr_url: Option<String>,
     pub pr_number: Option<u64>,
     pub pr_state: Option<String>,
 }
 
+impl Session {
+    /// The agent CLI's own session ID: OpenCode's for OpenCode sessions,
+    /// Claude's otherwise.
+    pub fn tool_session_id(&self) -> Option<&str> {
+        if self.tool == "opencode" {
+            self.opencode_session_id.as_deref()
+        } else {
+            self.claude_session_id.as_deref()
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VersionCheck {
     pub supported: String,
     pub installed: String,
 }
+
+/// Token counts from Claude's `message.usage`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenCounts {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+}
+
+impl TokenCounts {
+    pub fn add(&mut self, other: &TokenCounts) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_creation_tokens += other.cache_creation_tokens;
+        self.cache_read_tokens += other.cache_read_tokens;
+    }
+
+    pub fn total(&self) -> u64 {
+        self.input_tokens + self.output_tokens + self.cache_creation_tokens + self.cache_read_tokens
+    }
+}
+
+/// Price per million tokens for models whose name contains `model`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    pub model: String,
+    pub input_per_mtok: f64,
+    pub output_per_mtok: f64,
+    pub cache_write_per_mtok: f64,
+    pub cache_read_per_mtok: f64,
+}
+
+/// Daily spend/runtime limits for a group, stored in `group_settings`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupBudget {
+    pub max_tokens: Option<u64>,
+    pub max_usd: Option<f64>,
+    pub max_runtime_mins: Option<u64>,
+    /// Emit a warning once usage reaches this percentage of a limit.
+    pub warn_percent: u32,
+    /// What to do when a limit is reached: "warn", "interrupt" or "stop".
+    pub action: String,
+}
+
+impl GroupBudget {
+    pub fn has_limits(&self) -> bool {
+        self.max_tokens.is_some() || self.max_usd.is_some() || self.max_runtime_mins.is_some()
+    }
+}
+
+/// A user-configured webhook, stored in `webhooks`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Webhook {
+    /// None for a webhook that hasn't been saved yet.
+    #[serde(default)]
+    pub id: Option<i64>,
+    pub name: String,
+    pub url: String,
+    /// Event names it receives, e.g. `session.needs_input`.
+    pub events: Vec<String>,
+    /// Request body with `{{field}}` placeholders; None posts the event as JSON.
+    #[serde(default)]
+    pub body_template: Option<String>,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// One delivery of an event to a webhook, kept in `webhook_deliveries`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub webhook_id: i64,
+    pub event: String,
+    pub delivered: bool,
+    pub attempts: u32,
+    pub status_code: Option<u16>,
+    pub error: Option<String>,
+    pub created_at: u64,
+}
+
+/// A review comment on a line range of a session's diff, kept in
+/// `review_comments` until it is sent to the agent.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReviewComment {
+    pub id: i64,
+    pub session_id: String,
+    pub branch: String,
+    pub file_path: String,
+    /// `new` for lines of the changed file, `old` for removed lines.
+    pub side: String,
+    /// The blob the line numbers refer to, if known.
+    pub blob_sha: Option<String>,
+    pub start_line: u32,
+    pub end_line: u32,
+    /// The commented diff lines, with their `+`/`-`/space prefixes.
+    pub snippet: String,
+    pub body: String,
+    /// `pending` or `sent`.
+    pub status: String,
+    pub created_at: u64,
+    pub sent_at: Option<u64>,
+    /// The commented lines could not be found in the file any more.
+    pub outdated: bool,
+}
+
+/// Desktop notification preferences, stored in Orca's metadata table.
+/// Groups can also be muted individually (`group_settings`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotificationSettings {
+    pub enabled: bool,
+    pub sound: bool,
+    /// Local start of quiet hours as "HH:MM"; quiet hours may wrap midnight.
+    pub quiet_start: Option<String>,
+    /// Local end of quiet hours as "HH:MM".
+    pub quiet_end: Option<String>,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            sound: true,
+            quiet_start: None,
+            quiet_end: None,
+        }
+    }
+}
+
+/// Local automation API settings, stored in Orca's metadata table.
+/// The API is off until the user enables it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiSettings {
+    pub enabled: bool,
+    /// "tcp" (127.0.0.1 only) or "unix".
+    pub transport: String,
+    /// Port for the "tcp" transport.
+    pub port: u16,
+    /// Socket path for the "unix" transport; defaults to `orca.sock` in
+    /// Orca's data directory.
+    pub socket_path: Option<String>,
+}
+
+impl Default for ApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            transport: "tcp".to_string(),
+            port: 7419,
+            socket_path: None,
+        }
+    }
+}
+
+/// Per-group attention timing, stored in `group_settings`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttentionThresholds {
+    /// A session with no transcript activity for this long is stale.
+    pub stale_after_secs: u64,
+    /// A session agent-deck reports as running, with no transcript activity
+    /// for this long, is shown as idle (agent-deck's status can lag).
+    /// None disables this.
+    pub idle_after_secs: Option<u64>,
+}
+
+impl Default for AttentionThresholds {
+    fn default() -> Self {
+        Self {
+            stale_after_secs: 3600,
+            idle_after_secs: None,
+        }
+    }
+}
+
+/// How Orca recognises an agent CLI in a tmux pane.
+///
+/// Patterns are plain text matched against the captured pane. A pattern
+/// starting with `$` is a prompt marker instead: it matches when the last
+/// non-empty line is the marker or ends with a space and the marker.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolProfile {
+    /// agent-deck tool name (`agent-deck add -c <name>`).
+    pub name: String,
+    /// Executable checked by `check_prerequisites`.
+    pub binary: String,
+    #[serde(default)]
+    pub version_args: Vec<String>,
+    #[serde(default)]
+    pub required: bool,
+    /// The TUI has started and accepts a prompt. Empty means "use
+    /// `waiting_patterns`".
+    #[serde(default)]
+    pub ready_patterns: Vec<String>,
+    /// The tool is idle at its input prompt.
+    #[serde(default)]
+    pub waiting_patterns: Vec<String>,
+    /// The tool is asking the user to approve an action.
+    #[serde(default)]
+    pub permission_patterns: Vec<String>,
+    /// The tool is compacting its context.
+    #[serde(default)]
+    pub compacting_patterns: Vec<String>,
+    /// Where the tool keeps transcripts: `~/`-relative, absolute, or
+    /// relative to the session's project.
+    #[serde(default)]
+    pub transcript_location: Option<String>,
+    /// Shipped with Orca (possibly overridden by the user).
+    #[serde(default)]
+    pub builtin: bool,
+}
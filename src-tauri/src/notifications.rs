@@ -0,0 +1,328 @@
+//! Desktop notifications for attention transitions.
+//!
+//! The session watcher passes each batch of attention changes to
+//! [`notify_changes`]. A change into a "Needs Action" state (permission
+//! prompt, input, error, rate limit) shows an OS notification with the
+//! session title and its last assistant text, unless:
+//!
+//! - notifications are disabled, or it is quiet hours (local time)
+//! - the session's group is muted
+//! - the Orca window is focused, so the badges are already on screen
+//! - the session was notified about the same state within [`DEDUP_SECS`]
+//!   (a hook event and the transcript write can report one state twice)
+//!
+//! Desktop notifications don't report clicks, but clicking one activates
+//! Orca. When the window gains focus within [`CLICK_WINDOW`] of a
+//! notification, Orca emits `focus-session` with that session so the
+//! frontend can open it. The first focus after a notification consumes it,
+//! so only a quick switch back navigates; later focuses leave the view alone.
+
+use crate::claude_logs::{self, AttentionStatus};
+use crate::dates::now_secs;
+use crate::models::{NotificationSettings, Session};
+use crate::orca_db::OrcaDb;
+use crate::watcher::AttentionChange;
+use chrono::Timelike;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{Emitter, State};
+#[cfg(feature = "desktop")]
+use tauri_plugin_notification::NotificationExt;
+
+/// Repeat notifications for the same session and state are dropped for this long.
+const DEDUP_SECS: u64 = 120;
+/// Focusing the window this soon after a notification counts as clicking it.
+/// Kept short so switching back to Orca on your own doesn't change the view.
+const CLICK_WINDOW: Duration = Duration::from_secs(5);
+const MAX_BODY_CHARS: usize = 200;
+
+static WINDOW_FOCUSED: AtomicBool = AtomicBool::new(true);
+static LAST_SHOWN: Mutex<Option<(FocusTarget, Instant)>> = Mutex::new(None);
+
+/// Payload of the `focus-session` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct FocusTarget {
+    pub session_id: String,
+    pub group_path: String,
+}
+
+/// Notification headline for states that need the user, None for the rest.
+fn headline(attention: &AttentionStatus) -> Option<&'static str> {
+    match attention {
+        AttentionStatus::AwaitingPermission => Some("Needs permission"),
+        AttentionStatus::NeedsInput => Some("Needs input"),
+        AttentionStatus::Error => Some("Error"),
+        AttentionStatus::RateLimited => Some("Rate limited"),
+        _ => None,
+    }
+}
+
+/// Minutes since midnight for "HH:MM".
+fn parse_hhmm(text: &str) -> Option<u32> {
+    let (hours, minutes) = text.trim().split_once(':')?;
+    let hours: u32 = hours.parse().ok()?;
+    let minutes: u32 = minutes.parse().ok()?;
+    (hours < 24 && minutes < 60).then_some(hours * 60 + minutes)
+}
+
+fn in_quiet_hours(settings: &NotificationSettings, minutes: u32) -> bool {
+    let start = settings.quiet_start.as_deref().and_then(parse_hhmm);
+    let end = settings.quiet_end.as_deref().and_then(parse_hhmm);
+    let (Some(start), Some(end)) = (start, end) else {
+        return false;
+    };
+    if start <= end {
+        start <= minutes && minutes < end
+    } else {
+        // Wraps midnight, e.g. 22:00–07:00
+        minutes >= start || minutes < end
+    }
+}
+
+/// Local wall-clock minutes since midnight.
+fn local_minutes() -> u32 {
+    let now = chrono::Local::now();
+    now.hour() * 60 + now.minute()
+}
+
+fn attention_key(attention: &AttentionStatus) -> String {
+    serde_json::to_value(attention)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn is_duplicate(last: Option<&(String, u64)>, key: &str, now: u64) -> bool {
+    last.is_some_and(|(attention, sent_at)| {
+        attention == key && now.saturating_sub(*sent_at) < DEDUP_SECS
+    })
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut out: String = text.chars().take(max_chars).collect();
+    out.push('…');
+    out
+}
+
+#[cfg(feature = "desktop")]
+fn show(
+    app: &crate::AppHandle,
+    settings: &NotificationSettings,
+    title: &str,
+    body: &str,
+) -> Result<(), String> {
+    let mut builder = app.notification().builder().title(title).body(body);
+    if settings.sound {
+        builder = builder.sound("default");
+    }
+    builder
+        .show()
+        .map_err(|e| format!("Failed to show notification: {e}"))
+}
+
+/// A headless build has no notification plugin, and never an app to notify
+/// through.
+#[cfg(not(feature = "desktop"))]
+fn show(
+    _app: &crate::AppHandle,
+    _settings: &NotificationSettings,
+    _title: &str,
+    _body: &str,
+) -> Result<(), String> {
+    Err("Desktop notifications need the desktop feature".to_string())
+}
+
+/// Show notifications for attention changes from the session watcher.
+pub fn notify_changes(
+    app: &crate::AppHandle,
+    orca_db: &OrcaDb,
+    changes: &[AttentionChange],
+    sessions: &HashMap<String, Session>,
+) {
+    if WINDOW_FOCUSED.load(Ordering::Relaxed) {
+        return;
+    }
+    let actionable: Vec<(&AttentionChange, &str)> = changes
+        .iter()
+        .filter_map(|c| headline(&c.attention).map(|h| (c, h)))
+        .collect();
+    if actionable.is_empty() {
+        return;
+    }
+    let settings = match orca_db.get_notification_settings() {
+        Ok(settings) => settings,
+        Err(e) => {
+            log::warn!("Notifications: {e}");
+            return;
+        }
+    };
+    if !settings.enabled || in_quiet_hours(&settings, local_minutes()) {
+        return;
+    }
+
+    for (change, headline) in actionable {
+        let Some(session) = sessions.get(&change.session_id) else {
+            continue;
+        };
+        if orca_db
+            .get_group_notifications_muted(&change.group_path)
+            .unwrap_or(false)
+        {
+            continue;
+        }
+        let key = attention_key(&change.attention);
+        let now = now_secs();
+        let last = orca_db
+            .get_last_notification(&change.session_id)
+            .unwrap_or_default();
+        if is_duplicate(last.as_ref(), &key, now) {
+            continue;
+        }
+
+        let title = format!("{headline}: {}", session.title);
+        let body = claude_logs::session_last_text(session).map_or_else(
+            || session.group_path.clone(),
+            |t| truncate(&t, MAX_BODY_CHARS),
+        );
+        if let Err(e) = show(app, &settings, &title, &body) {
+            log::warn!("{e}");
+            continue;
+        }
+        log::info!("Notified {key} for session {}", change.session_id);
+        if let Err(e) = orca_db.record_notification(&change.session_id, &key, now) {
+            log::warn!("{e}");
+        }
+        if let Ok(mut last_shown) = LAST_SHOWN.lock() {
+            *last_shown = Some((
+                FocusTarget {
+                    session_id: change.session_id.clone(),
+                    group_path: change.group_path.clone(),
+                },
+                Instant::now(),
+            ));
+        }
+    }
+}
+
+/// Track the main window's focus. Gaining focus right after a notification
+/// (as clicking it does) emits `focus-session` for that notification; any
+/// focus clears the pending notification, in or out of the window.
+pub fn set_window_focused(app: &crate::AppHandle, focused: bool) {
+    WINDOW_FOCUSED.store(focused, Ordering::Relaxed);
+    if !focused {
+        return;
+    }
+    let target = LAST_SHOWN
+        .lock()
+        .ok()
+        .and_then(|mut last| last.take())
+        .filter(|(_, shown)| shown.elapsed() < CLICK_WINDOW);
+    if let Some((target, _)) = target {
+        let _ = app.emit("focus-session", &target);
+    }
+}
+
+#[tauri::command]
+pub fn get_notification_settings(
+    orca_db: State<'_, OrcaDb>,
+) -> Result<NotificationSettings, String> {
+    orca_db.get_notification_settings()
+}
+
+#[tauri::command]
+pub fn set_notification_settings(
+    orca_db: State<'_, OrcaDb>,
+    settings: NotificationSettings,
+) -> Result<(), String> {
+    for time in [&settings.quiet_start, &settings.quiet_end]
+        .into_iter()
+        .flatten()
+    {
+        if parse_hhmm(time).is_none() {
+            return Err(format!(
+                "Invalid quiet hours time: '{time}' (expected HH:MM)"
+            ));
+        }
+    }
+    if settings.quiet_start.is_some() != settings.quiet_end.is_some() {
+        return Err("Quiet hours need both a start and an end".to_string());
+    }
+    orca_db.set_notification_settings(&settings)
+}
+
+#[tauri::command]
+pub fn get_group_notifications_muted(
+    orca_db: State<'_, OrcaDb>,
+    group_path: String,
+) -> Result<bool, String> {
+    orca_db.get_group_notifications_muted(&group_path)
+}
+
+#[tauri::command]
+pub fn set_group_notifications_muted(
+    orca_db: State<'_, OrcaDb>,
+    group_path: String,
+    muted: bool,
+) -> Result<(), String> {
+    orca_db.set_group_notifications_muted(&group_path, muted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quiet(start: &str, end: &str) -> NotificationSettings {
+        NotificationSettings {
+            quiet_start: Some(start.into()),
+            quiet_end: Some(end.into()),
+            ..NotificationSettings::default()
+        }
+    }
+
+    #[test]
+    fn quiet_hours_can_wrap_midnight() {
+        let night = quiet("22:00", "07:30");
+        assert!(in_quiet_hours(&night, 23 * 60));
+        assert!(in_quiet_hours(&night, 60));
+        assert!(!in_quiet_hours(&night, 7 * 60 + 30));
+        assert!(!in_quiet_hours(&night, 12 * 60));
+
+        let lunch = quiet("12:00", "13:00");
+        assert!(in_quiet_hours(&lunch, 12 * 60 + 15));
+        assert!(!in_quiet_hours(&lunch, 13 * 60));
+
+        assert!(!in_quiet_hours(&NotificationSettings::default(), 0));
+        assert_eq!(parse_hhmm("24:00"), None);
+        assert_eq!(parse_hhmm("09:05\n"), Some(545));
+    }
+
+    #[test]
+    fn duplicates_only_within_window_and_same_state() {
+        let last = ("needs_input".to_string(), 1_000);
+        assert!(is_duplicate(Some(&last), "needs_input", 1_060));
+        assert!(!is_duplicate(
+            Some(&last),
+            "needs_input",
+            1_000 + DEDUP_SECS
+        ));
+        assert!(!is_duplicate(Some(&last), "awaiting_permission", 1_060));
+        assert!(!is_duplicate(None, "needs_input", 1_060));
+    }
+
+    #[test]
+    fn only_needs_action_states_notify() {
+        assert_eq!(
+            attention_key(&AttentionStatus::AwaitingPermission),
+            "awaiting_permission"
+        );
+        assert!(headline(&AttentionStatus::RateLimited).is_some());
+        assert!(headline(&AttentionStatus::Running).is_none());
+        assert!(headline(&AttentionStatus::Compacting).is_none());
+    }
+}
@@ -0,0 +1,548 @@
+//! Read OpenCode's on-disk session storage for local (agent-deck) sessions.
+//!
+//! OpenCode keeps one JSON file per session, message and message part under
+//! `$XDG_DATA_HOME/opencode/storage` (default `~/.local/share`):
+//!
+//! - `session/<project-id>/<session-id>.json` — title, directory, timestamps
+//! - `message/<session-id>/<message-id>.json` — role, timestamps, error
+//! - `part/<message-id>/<part-id>.json` — text, tool calls and their state
+//!
+//! IDs sort chronologically. This module turns that into the same
+//! [`SessionSummary`] the Claude JSONL reader produces.
+//!
+//! OpenCode rewrites a message or part file whole rather than appending to
+//! it, so like `jsonl_cache` each file's parse is remembered, keyed by path
+//! and checked against its mtime and length: repeated summaries only read
+//! the files that are new or changed since the last call.
+
+use crate::claude_logs::{AttentionStatus, SessionSummary};
+use crate::command::expand_tilde;
+use crate::dates::now_secs;
+use crate::models::AttentionThresholds;
+use crate::orca_db::OrcaDb;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+use tauri::State;
+
+/// Messages walked back from the end when looking for the last tool/text.
+const MAX_MESSAGES_SCANNED: usize = 20;
+
+pub fn opencode_storage_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("XDG_DATA_HOME").filter(|d| !d.is_empty()) {
+        return Some(PathBuf::from(dir).join("opencode/storage"));
+    }
+    Some(dirs::home_dir()?.join(".local/share/opencode/storage"))
+}
+
+/// A storage file's parsed JSON (None if it didn't parse), valid while the
+/// file's mtime and length are unchanged.
+struct CachedJson {
+    modified: Option<SystemTime>,
+    len: u64,
+    value: Option<Value>,
+}
+
+fn cache() -> &'static Mutex<HashMap<PathBuf, CachedJson>> {
+    static CACHE: OnceLock<Mutex<HashMap<PathBuf, CachedJson>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// A storage file's JSON, parsed again only when the file has changed.
+fn read_json(path: &Path) -> Option<Value> {
+    let lock = || {
+        cache()
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    };
+    let Ok(metadata) = std::fs::metadata(path) else {
+        lock().remove(path);
+        return None;
+    };
+    let (modified, len) = (metadata.modified().ok(), metadata.len());
+    if let Some(cached) = lock().get(path) {
+        if cached.modified == modified && cached.len == len {
+            return cached.value.clone();
+        }
+    }
+    // Don't hold the lock during IO; concurrent reads of the same file just
+    // do redundant work.
+    let value = std::fs::read_to_string(path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok());
+    lock().insert(
+        path.to_path_buf(),
+        CachedJson {
+            modified,
+            len,
+            value: value.clone(),
+        },
+    );
+    value
+}
+
+/// JSON files in a directory, sorted by name (and so by creation order).
+fn sorted_json_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    files.sort();
+    files
+}
+
+fn updated_ms(session: &Value) -> u64 {
+    session["time"]["updated"]
+        .as_u64()
+        .or_else(|| session["time"]["created"].as_u64())
+        .unwrap_or(0)
+}
+
+/// Find a session's metadata: by ID when agent-deck recorded one, otherwise
+/// the most recently updated session started in `project_path`.
+pub fn find_session(storage: &Path, project_path: &str, session_id: Option<&str>) -> Option<Value> {
+    let Ok(projects) = std::fs::read_dir(storage.join("session")) else {
+        return None;
+    };
+    let project_dirs: Vec<PathBuf> = projects.filter_map(Result::ok).map(|e| e.path()).collect();
+
+    if let Some(id) = session_id.filter(|id| !id.is_empty()) {
+        return project_dirs
+            .iter()
+            .find_map(|dir| read_json(&dir.join(format!("{id}.json"))));
+    }
+
+    let expanded = expand_tilde(project_path);
+    let directory = expanded.to_string_lossy();
+    let directory = directory.trim_end_matches('/');
+    project_dirs
+        .iter()
+        .flat_map(|dir| sorted_json_files(dir))
+        .filter_map(|path| read_json(&path))
+        .filter(|s| s["directory"].as_str().map(|d| d.trim_end_matches('/')) == Some(directory))
+        // Subagent sessions share the directory; only top-level ones count.
+        .filter(|s| s["parentID"].is_null())
+        .max_by_key(updated_ms)
+}
+
+fn text_of(parts: &[Value]) -> Option<String> {
+    parts.iter().find_map(|part| {
+        if part["type"] != "text" || part["synthetic"] == true {
+            return None;
+        }
+        let trimmed = part["text"].as_str()?.trim();
+        if trimmed.is_empty() {
+            None
+        } else {
+            Some(trimmed.chars().take(200).collect())
+        }
+    })
+}
+
+fn tool_running(part: &Value) -> bool {
+    part["type"] == "tool"
+        && matches!(
+            part["state"]["status"].as_str(),
+            Some("pending" | "running")
+        )
+}
+
+/// What the session's stored messages say, before agent-deck's status and
+/// tmux are taken into account.
+#[derive(Debug, Default, PartialEq)]
+pub struct OpenCodeState {
+    pub title: Option<String>,
+    pub initial_prompt: Option<String>,
+    pub last_tool: Option<String>,
+    pub last_text: Option<String>,
+    pub has_assistant: bool,
+    /// The last assistant message hasn't finished, or one of its tools is
+    /// still pending or running.
+    pub in_progress: bool,
+    /// The last assistant message ended in an error other than an abort.
+    pub errored: bool,
+    /// That error was a provider rate or usage limit.
+    pub rate_limited: bool,
+    /// Unix millis of the latest activity.
+    pub last_activity_ms: u64,
+}
+
+/// Messages and parts of a session, oldest first, as read from storage.
+type Conversation = Vec<(Value, Vec<Value>)>;
+
+fn read_conversation(storage: &Path, session_id: &str) -> Conversation {
+    let messages = sorted_json_files(&storage.join("message").join(session_id));
+    let tail_start = messages.len().saturating_sub(MAX_MESSAGES_SCANNED);
+    let mut seen_user = false;
+    messages
+        .iter()
+        .enumerate()
+        .filter_map(|(i, path)| {
+            let message = read_json(path)?;
+            // Parts are only needed for the first user message (the initial
+            // prompt) and the last few messages.
+            let first_user = !seen_user && message["role"] == "user";
+            seen_user |= first_user;
+            let parts = match message["id"].as_str() {
+                Some(id) if first_user || i >= tail_start => {
+                    sorted_json_files(&storage.join("part").join(id))
+                        .iter()
+                        .filter_map(|p| read_json(p))
+                        .collect()
+                }
+                _ => Vec::new(),
+            };
+            Some((message, parts))
+        })
+        .collect()
+}
+
+impl OpenCodeState {
+    pub fn from_conversation(session: &Value, conversation: &Conversation) -> Self {
+        let mut state = Self {
+            title: session["title"]
+                .as_str()
+                .filter(|t| !t.starts_with("New session") && !t.starts_with("Child session"))
+                .map(String::from),
+            last_activity_ms: updated_ms(session),
+            ..Self::default()
+        };
+
+        state.initial_prompt = conversation
+            .iter()
+            .find(|(m, _)| m["role"] == "user")
+            .and_then(|(_, parts)| text_of(parts));
+        state.has_assistant = conversation.iter().any(|(m, _)| m["role"] == "assistant");
+
+        for (message, parts) in conversation.iter().rev().take(MAX_MESSAGES_SCANNED) {
+            if message["role"] != "assistant" {
+                continue;
+            }
+            if state.last_text.is_none() {
+                state.last_text = parts
+                    .iter()
+                    .rev()
+                    .find_map(|p| text_of(std::slice::from_ref(p)));
+            }
+            if state.last_tool.is_none() {
+                state.last_tool = parts
+                    .iter()
+                    .rev()
+                    .find(|p| p["type"] == "tool")
+                    .and_then(|p| p["tool"].as_str())
+                    .map(String::from);
+            }
+            if state.last_text.is_some() && state.last_tool.is_some() {
+                break;
+            }
+        }
+
+        if let Some((message, parts)) = conversation.last() {
+            let time = &message["time"];
+            state.last_activity_ms = state
+                .last_activity_ms
+                .max(time["created"].as_u64().unwrap_or(0))
+                .max(time["completed"].as_u64().unwrap_or(0));
+            if message["role"] == "assistant" {
+                let error = &message["error"];
+                state.errored = !error.is_null() && error["name"] != "MessageAbortedError";
+                state.rate_limited = state.errored && is_rate_limit(error);
+                state.in_progress = error.is_null()
+                    && (time["completed"].is_null() || parts.iter().any(tool_running));
+            }
+        }
+        state
+    }
+
+    /// Derive attention the way `LogState::attention` does for Claude:
+    /// agent-deck's status first, refined by what the messages show.
+    pub fn attention(
+        &self,
+        agentdeck_status: &str,
+        thresholds: &AttentionThresholds,
+    ) -> AttentionStatus {
+        if agentdeck_status == "waiting" {
+            return if self.has_assistant {
+                AttentionStatus::NeedsInput
+            } else {
+                AttentionStatus::Idle
+            };
+        }
+        if self.last_activity_ms == 0 {
+            return match agentdeck_status {
+                "running" => AttentionStatus::Running,
+                "error" => AttentionStatus::Error,
+                "idle" => AttentionStatus::Idle,
+                _ => AttentionStatus::Unknown,
+            };
+        }
+        if self.rate_limited {
+            return AttentionStatus::RateLimited;
+        }
+        if agentdeck_status == "error" || self.errored {
+            return AttentionStatus::Error;
+        }
+        if self.in_progress {
+            return AttentionStatus::Running;
+        }
+        let age = now_secs().saturating_sub(self.last_activity_ms / 1000);
+        if age > thresholds.stale_after_secs {
+            return AttentionStatus::Stale;
+        }
+        if agentdeck_status == "running"
+            && thresholds.idle_after_secs.is_some_and(|idle| age > idle)
+        {
+            return AttentionStatus::Idle;
+        }
+        match agentdeck_status {
+            "running" => AttentionStatus::Running,
+            _ => AttentionStatus::Idle,
+        }
+    }
+}
+
+/// OpenCode reports provider failures as `APIError` with the HTTP status.
+fn is_rate_limit(error: &Value) -> bool {
+    if error["data"]["statusCode"] == 429 {
+        return true;
+    }
+    error["data"]["message"]
+        .as_str()
+        .is_some_and(|m| m.to_lowercase().contains("rate limit"))
+}
+
+/// Read a local OpenCode session's state, or None when it isn't on disk.
+pub fn read_state(project_path: &str, session_id: Option<&str>) -> Option<OpenCodeState> {
+    let storage = opencode_storage_dir()?;
+    let session = find_session(&storage, project_path, session_id)?;
+    let id = session["id"].as_str()?;
+    let conversation = read_conversation(&storage, id);
+    Some(OpenCodeState::from_conversation(&session, &conversation))
+}
+
+/// Summary for an agent-deck OpenCode session, matching Claude's cards.
+pub fn session_summary(
+    project_path: &str,
+    session_id: Option<&str>,
+    agentdeck_status: &str,
+    tmux_session: Option<&str>,
+    thresholds: &AttentionThresholds,
+) -> SessionSummary {
+    let state = read_state(project_path, session_id).unwrap_or_default();
+    SessionSummary {
+        attention: crate::claude_logs::compute_attention(
+            project_path,
+            session_id,
+            agentdeck_status,
+            tmux_session,
+            "opencode",
+            thresholds,
+        ),
+        summary: state.title,
+        initial_prompt: state.initial_prompt,
+        last_tool: state.last_tool,
+        last_text: state.last_text,
+        subagents: Vec::new(),
+    }
+}
+
+#[tauri::command]
+pub fn get_opencode_session_summary(
+    orca_db: State<'_, OrcaDb>,
+    project_path: String,
+    opencode_session_id: Option<String>,
+    agentdeck_status: String,
+    tmux_session: Option<String>,
+    group_path: Option<String>,
+) -> SessionSummary {
+    session_summary(
+        &project_path,
+        opencode_session_id.as_deref(),
+        &agentdeck_status,
+        tmux_session.as_deref(),
+        &orca_db.attention_thresholds_for(group_path.as_deref()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn defaults() -> AttentionThresholds {
+        AttentionThresholds::default()
+    }
+
+    fn write(path: &Path, value: &Value) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, value.to_string()).unwrap();
+    }
+
+    fn now_ms() -> u64 {
+        now_secs() * 1000
+    }
+
+    /// A storage dir with one session: a user prompt and an assistant reply
+    /// that ran a tool and answered.
+    fn fixture(completed: bool) -> tempfile::TempDir {
+        let tmp = tempfile::tempdir().unwrap();
+        let s = tmp.path();
+        let t = now_ms();
+        write(
+            &s.join("session/proj1/ses_1.json"),
+            &json!({"id": "ses_1", "directory": "/src/app", "title": "Fix login flow",
+                    "time": {"created": t - 5000, "updated": t - 1000}}),
+        );
+        write(
+            &s.join("session/proj1/ses_0.json"),
+            &json!({"id": "ses_0", "directory": "/src/app", "title": "New session - 2025",
+                    "time": {"created": t - 90_000, "updated": t - 80_000}}),
+        );
+        write(
+            &s.join("message/ses_1/msg_1.json"),
+            &json!({"id": "msg_1", "role": "user", "time": {"created": t - 5000}}),
+        );
+        write(
+            &s.join("part/msg_1/prt_1.json"),
+            &json!({"type": "text", "text": "  Fix the login redirect  "}),
+        );
+        let mut time = json!({"created": t - 4000});
+        if completed {
+            time["completed"] = json!(t - 1000);
+        }
+        write(
+            &s.join("message/ses_1/msg_2.json"),
+            &json!({"id": "msg_2", "role": "assistant", "time": time}),
+        );
+        let status = if completed { "completed" } else { "running" };
+        write(
+            &s.join("part/msg_2/prt_1.json"),
+            &json!({"type": "step-start"}),
+        );
+        write(
+            &s.join("part/msg_2/prt_2.json"),
+            &json!({"type": "text", "text": "Looking at the router."}),
+        );
+        write(
+            &s.join("part/msg_2/prt_3.json"),
+            &json!({"type": "tool", "tool": "edit", "state": {"status": status}}),
+        );
+        tmp
+    }
+
+    fn state_of(storage: &Path, session_id: Option<&str>) -> OpenCodeState {
+        let session = find_session(storage, "/src/app/", session_id).expect("no session");
+        let conversation = read_conversation(storage, session["id"].as_str().unwrap());
+        OpenCodeState::from_conversation(&session, &conversation)
+    }
+
+    #[test]
+    fn rereads_changed_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("message/ses_1/msg_1.json");
+        write(&path, &json!({"id": "msg_1", "role": "assistant"}));
+        assert_eq!(read_json(&path).unwrap()["role"], "assistant");
+        assert_eq!(read_json(&path).unwrap()["id"], "msg_1");
+
+        write(
+            &path,
+            &json!({"id": "msg_1", "role": "assistant", "time": {"completed": 1}}),
+        );
+        assert_eq!(read_json(&path).unwrap()["time"]["completed"], 1);
+
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read_json(&path), None);
+        assert!(!cache().lock().unwrap().contains_key(&path));
+    }
+
+    #[test]
+    fn finds_latest_session_for_directory() {
+        let tmp = fixture(true);
+        let session = find_session(tmp.path(), "/src/app", None).unwrap();
+        assert_eq!(session["id"], "ses_1");
+        let session = find_session(tmp.path(), "/src/app", Some("ses_0")).unwrap();
+        assert_eq!(session["id"], "ses_0");
+        assert!(find_session(tmp.path(), "/src/other", None).is_none());
+    }
+
+    #[test]
+    fn summary_fields_match_claude_cards() {
+        let tmp = fixture(true);
+        let state = state_of(tmp.path(), None);
+        assert_eq!(state.title.as_deref(), Some("Fix login flow"));
+        assert_eq!(
+            state.initial_prompt.as_deref(),
+            Some("Fix the login redirect")
+        );
+        assert_eq!(state.last_tool.as_deref(), Some("edit"));
+        assert_eq!(state.last_text.as_deref(), Some("Looking at the router."));
+        assert!(!state.in_progress);
+        assert_eq!(state.attention("idle", &defaults()), AttentionStatus::Idle);
+        assert_eq!(
+            state.attention("waiting", &defaults()),
+            AttentionStatus::NeedsInput
+        );
+
+        // Default titles aren't summaries
+        assert_eq!(state_of(tmp.path(), Some("ses_0")).title, None);
+    }
+
+    #[test]
+    fn running_tool_is_in_progress() {
+        let tmp = fixture(false);
+        let state = state_of(tmp.path(), None);
+        assert!(state.in_progress);
+        assert_eq!(
+            state.attention("idle", &defaults()),
+            AttentionStatus::Running
+        );
+        assert_eq!(
+            state.attention("error", &defaults()),
+            AttentionStatus::Error
+        );
+    }
+
+    #[test]
+    fn errors_and_staleness() {
+        let tmp = fixture(true);
+        let mut state = state_of(tmp.path(), None);
+        state.errored = true;
+        assert_eq!(state.attention("idle", &defaults()), AttentionStatus::Error);
+
+        state.rate_limited = true;
+        assert_eq!(
+            state.attention("idle", &defaults()),
+            AttentionStatus::RateLimited
+        );
+
+        let old = OpenCodeState {
+            last_activity_ms: (now_secs() - 600) * 1000,
+            has_assistant: true,
+            ..OpenCodeState::default()
+        };
+        assert_eq!(
+            old.attention("running", &defaults()),
+            AttentionStatus::Running
+        );
+        let tight = AttentionThresholds {
+            stale_after_secs: 900,
+            idle_after_secs: Some(300),
+        };
+        assert_eq!(old.attention("running", &tight), AttentionStatus::Idle);
+        let tighter = AttentionThresholds {
+            stale_after_secs: 300,
+            idle_after_secs: None,
+        };
+        assert_eq!(old.attention("running", &tighter), AttentionStatus::Stale);
+        assert_eq!(
+            OpenCodeState::default().attention("running", &defaults()),
+            AttentionStatus::Running
+        );
+    }
+}
@@ -266,12 +266,22 @@ pub async fn oc_respond_to_permission(
 
 #[tauri::command]
 pub async fn oc_subscribe_events(
-    app: tauri::AppHandle,
+    app: crate::AppHandle,
     handles: tauri::State<'_, remote_common::SseHandles>,
     server_url: String,
     password: String,
 ) -> Result<(), String> {
-    let client = build_client(&password)?;
-    let url = format!("{}/event", remote_common::normalize_url(&server_url));
-    remote_common::subscribe_sse(&app, &handles, &client, &url, "oc-event").await
+    subscribe_events(&app, &handles, &server_url, &password).await
+}
+
+/// Start streaming the server's global SSE feed as "oc-event" Tauri events.
+pub async fn subscribe_events(
+    app: &crate::AppHandle,
+    handles: &remote_common::SseHandles,
+    server_url: &str,
+    password: &str,
+) -> Result<(), String> {
+    let client = build_client(password)?;
+    let url = format!("{}/event", remote_common::normalize_url(server_url));
+    remote_common::subscribe_sse(app, handles, &client, &url, "oc-event").await
 }
@@ -1,8 +1,43 @@
+use crate::models::{
+    ApiSettings, AttentionThresholds, GroupBudget, ModelPrice, NotificationSettings, ReviewComment,
+    TokenCounts, ToolProfile, Webhook, WebhookDelivery,
+};
 use rusqlite::Connection;
 use std::collections::HashMap;
 use std::path::Path;
 use std::sync::{Arc, Mutex};
 
+/// A searchable piece of a transcript: a prompt, assistant text or tool call.
+#[derive(Debug, Clone)]
+pub struct SearchDoc {
+    pub session_id: String,
+    pub group_path: String,
+    pub timestamp: Option<String>,
+    pub kind: String,
+    pub content: String,
+}
+
+/// A full-text search match, with matched terms in `snippet` wrapped in « ».
+#[derive(Debug, Clone)]
+pub struct SearchRow {
+    pub session_id: String,
+    pub group_path: String,
+    pub timestamp: Option<String>,
+    pub kind: String,
+    pub snippet: String,
+}
+
+/// The latest Claude Code hook event received for a Claude session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookSignal {
+    /// Hook event name, e.g. "Notification", "Stop" or "PreToolUse".
+    pub event: String,
+    /// Notification message or tool name, when the event carries one.
+    pub detail: Option<String>,
+    /// Unix seconds when the event was written.
+    pub received_at: u64,
+}
+
 /// Settings for a group, stored in Orca's own DB.
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
@@ -16,6 +51,16 @@ pub struct GroupSettings {
     pub server_password: Option<String>,
 }
 
+/// One row of aggregated token usage (per session, day and model).
+#[derive(Debug, Clone)]
+pub struct UsageRow {
+    pub session_id: String,
+    pub group_path: String,
+    pub day: String,
+    pub model: String,
+    pub tokens: TokenCounts,
+}
+
 /// Orca's own SQLite database for data that shouldn't be stored in agent-deck's DB.
 ///
 /// Uses a persistent connection behind an `Arc<Mutex>` so all Tauri command
@@ -60,6 +105,96 @@ impl OrcaDb {
                 created_at      INTEGER,
                 last_accessed   INTEGER,
                 sort_order      INTEGER DEFAULT 0
+            );
+            CREATE TABLE IF NOT EXISTS usage_daily (
+                session_id            TEXT NOT NULL,
+                group_path            TEXT NOT NULL,
+                day                   TEXT NOT NULL,
+                model                 TEXT NOT NULL,
+                input_tokens          INTEGER NOT NULL DEFAULT 0,
+                output_tokens         INTEGER NOT NULL DEFAULT 0,
+                cache_creation_tokens INTEGER NOT NULL DEFAULT 0,
+                cache_read_tokens     INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (session_id, day, model)
+            );
+            CREATE TABLE IF NOT EXISTS usage_scan (
+                jsonl_path      TEXT PRIMARY KEY,
+                offset          INTEGER NOT NULL,
+                last_message_id TEXT
+            );
+            CREATE TABLE IF NOT EXISTS webhooks (
+                id            INTEGER PRIMARY KEY AUTOINCREMENT,
+                name          TEXT NOT NULL,
+                url           TEXT NOT NULL,
+                events        TEXT NOT NULL,
+                body_template TEXT,
+                enabled       INTEGER NOT NULL DEFAULT 1
+            );
+            CREATE TABLE IF NOT EXISTS webhook_deliveries (
+                id          INTEGER PRIMARY KEY AUTOINCREMENT,
+                webhook_id  INTEGER NOT NULL,
+                event       TEXT NOT NULL,
+                delivered   INTEGER NOT NULL,
+                attempts    INTEGER NOT NULL,
+                status_code INTEGER,
+                error       TEXT,
+                created_at  INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS review_comments (
+                id         INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id TEXT NOT NULL,
+                branch     TEXT NOT NULL,
+                file_path  TEXT NOT NULL,
+                side       TEXT NOT NULL DEFAULT 'new',
+                blob_sha   TEXT,
+                start_line INTEGER NOT NULL,
+                end_line   INTEGER NOT NULL,
+                snippet    TEXT NOT NULL,
+                body       TEXT NOT NULL,
+                status     TEXT NOT NULL DEFAULT 'pending',
+                created_at INTEGER NOT NULL,
+                sent_at    INTEGER
+            );
+            CREATE TABLE IF NOT EXISTS notification_log (
+                session_id TEXT PRIMARY KEY,
+                attention  TEXT NOT NULL,
+                sent_at    INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS group_runtime (
+                group_path TEXT NOT NULL,
+                day        TEXT NOT NULL,
+                seconds    INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (group_path, day)
+            );
+            CREATE VIRTUAL TABLE IF NOT EXISTS search_index USING fts5(
+                content,
+                kind UNINDEXED,
+                session_id UNINDEXED,
+                group_path UNINDEXED,
+                timestamp UNINDEXED,
+                source UNINDEXED
+            );
+            CREATE TABLE IF NOT EXISTS search_scan (
+                source        TEXT PRIMARY KEY,
+                position      INTEGER NOT NULL,
+                last_accessed INTEGER
+            );
+            CREATE TABLE IF NOT EXISTS hook_signals (
+                claude_session_id TEXT PRIMARY KEY,
+                event             TEXT NOT NULL,
+                detail            TEXT,
+                received_at       INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS tool_profiles (
+                name    TEXT PRIMARY KEY,
+                profile TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS model_prices (
+                model                TEXT PRIMARY KEY,
+                input_per_mtok       REAL NOT NULL,
+                output_per_mtok      REAL NOT NULL,
+                cache_write_per_mtok REAL NOT NULL,
+                cache_read_per_mtok  REAL NOT NULL
             );",
         )
         .map_err(|e| format!("Failed to create Orca DB tables: {e}"))?;
@@ -69,6 +204,9 @@ impl OrcaDb {
         Self::ensure_worktree_columns(&conn)?;
         Self::ensure_backend_columns(&conn)?;
         Self::ensure_dismissed_column(&conn)?;
+        Self::ensure_budget_columns(&conn)?;
+        Self::ensure_attention_columns(&conn)?;
+        Self::ensure_notifications_muted_column(&conn)?;
 
         let orca_db = Self {
             conn: Arc::new(Mutex::new(conn)),
@@ -189,6 +327,21 @@ impl OrcaDb {
         }
     }
 
+    /// Get the backend name configured for a group (defaults to "local").
+    pub fn get_group_backend(&self, group_path: &str) -> Result<String, String> {
+        let conn = self.lock()?;
+        let result = conn.query_row(
+            "SELECT backend FROM group_settings WHERE group_path = ?1",
+            [group_path],
+            |row| row.get::<_, String>(0),
+        );
+        match result {
+            Ok(backend) => Ok(backend),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok("local".to_string()),
+            Err(e) => Err(format!("Failed to get group backend: {e}")),
+        }
+    }
+
     /// Ensure merge_workflow column exists (for DBs created before it was added).
     fn ensure_merge_workflow_column(conn: &Connection) -> Result<(), String> {
         let has_column: bool = conn
@@ -235,6 +388,75 @@ impl OrcaDb {
         Ok(())
     }
 
+    /// Ensure the budget_* columns exist on group_settings.
+    fn ensure_budget_columns(conn: &Connection) -> Result<(), String> {
+        let columns: Vec<String> = conn
+            .prepare("PRAGMA table_info(group_settings)")
+            .map_err(|e| e.to_string())?
+            .query_map([], |row| row.get::<_, String>(1))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<_, _>>()
+            .map_err(|e| e.to_string())?;
+
+        for (name, definition) in [
+            ("budget_tokens", "INTEGER"),
+            ("budget_usd", "REAL"),
+            ("budget_runtime_mins", "INTEGER"),
+            ("budget_warn_percent", "INTEGER NOT NULL DEFAULT 80"),
+            ("budget_action", "TEXT NOT NULL DEFAULT 'warn'"),
+        ] {
+            if !columns.iter().any(|c| c == name) {
+                conn.execute(
+                    &format!("ALTER TABLE group_settings ADD COLUMN {name} {definition}"),
+                    [],
+                )
+                .map_err(|e| format!("Failed to add {name} column: {e}"))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Ensure the attention threshold columns exist on group_settings.
+    fn ensure_attention_columns(conn: &Connection) -> Result<(), String> {
+        let columns: Vec<String> = conn
+            .prepare("PRAGMA table_info(group_settings)")
+            .map_err(|e| e.to_string())?
+            .query_map([], |row| row.get::<_, String>(1))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<_, _>>()
+            .map_err(|e| e.to_string())?;
+
+        for name in ["stale_after_secs", "idle_after_secs"] {
+            if !columns.iter().any(|c| c == name) {
+                conn.execute(
+                    &format!("ALTER TABLE group_settings ADD COLUMN {name} INTEGER"),
+                    [],
+                )
+                .map_err(|e| format!("Failed to add {name} column: {e}"))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Ensure the notifications_muted column exists on group_settings.
+    fn ensure_notifications_muted_column(conn: &Connection) -> Result<(), String> {
+        let has_column: bool = conn
+            .prepare("PRAGMA table_info(group_settings)")
+            .map_err(|e| e.to_string())?
+            .query_map([], |row| row.get::<_, String>(1))
+            .map_err(|e| e.to_string())?
+            .any(|name| name.as_deref() == Ok("notifications_muted"));
+
+        if !has_column {
+            conn.execute(
+                "ALTER TABLE group_settings ADD COLUMN notifications_muted INTEGER NOT NULL DEFAULT 0",
+                [],
+            )
+            .map_err(|e| format!("Failed to add notifications_muted column: {e}"))?;
+        }
+        Ok(())
+    }
+
     /// Get the server password for a group (kept separate from Group struct for security).
     pub fn get_server_password(&self, group_path: &str) -> Result<Option<String>, String> {
         let conn = self.lock()?;
@@ -341,6 +563,16 @@ impl OrcaDb {
             [session_id],
         )
         .map_err(|e| format!("Failed to delete session data: {e}"))?;
+        conn.execute(
+            "DELETE FROM notification_log WHERE session_id = ?1",
+            [session_id],
+        )
+        .map_err(|e| format!("Failed to delete notification log: {e}"))?;
+        conn.execute(
+            "DELETE FROM review_comments WHERE session_id = ?1",
+            [session_id],
+        )
+        .map_err(|e| format!("Failed to delete review comments: {e}"))?;
         Ok(())
     }
 
@@ -391,113 +623,1044 @@ impl OrcaDb {
         Ok(())
     }
 
-    // ── Private metadata helpers ───────────────────────────────────
-
-    fn get_metadata_inner(conn: &Connection, key: &str) -> Result<Option<String>, String> {
-        let result = conn.query_row("SELECT value FROM metadata WHERE key = ?1", [key], |row| {
-            row.get::<_, String>(0)
-        });
-        match result {
-            Ok(val) => Ok(Some(val)),
-            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(format!("Failed to get metadata '{key}': {e}")),
-        }
-    }
+    // ── Private metadata helpers ───────────────────────────────────
+
+    fn get_metadata_inner(conn: &Connection, key: &str) -> Result<Option<String>, String> {
+        let result = conn.query_row("SELECT value FROM metadata WHERE key = ?1", [key], |row| {
+            row.get::<_, String>(0)
+        });
+        match result {
+            Ok(val) => Ok(Some(val)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(format!("Failed to get metadata '{key}': {e}")),
+        }
+    }
+
+    fn set_metadata_inner(conn: &Connection, key: &str, value: &str) -> Result<(), String> {
+        conn.execute(
+            "INSERT INTO metadata (key, value) VALUES (?1, ?2) \
+             ON CONFLICT(key) DO UPDATE SET value = ?2",
+            rusqlite::params![key, value],
+        )
+        .map_err(|e| format!("Failed to set metadata '{key}': {e}"))?;
+        Ok(())
+    }
+
+    fn delete_metadata_inner(conn: &Connection, key: &str) -> Result<(), String> {
+        conn.execute("DELETE FROM metadata WHERE key = ?1", [key])
+            .map_err(|e| format!("Failed to delete metadata '{key}': {e}"))?;
+        Ok(())
+    }
+
+    // ── Analytics ────────────────────────────────────────────────────
+
+    /// Check if analytics is enabled (defaults to false).
+    pub fn get_analytics_enabled(&self) -> Result<bool, String> {
+        let conn = self.lock()?;
+        Ok(Self::get_metadata_inner(&conn, "analytics_enabled")?.as_deref() == Some("true"))
+    }
+
+    /// Set whether analytics is enabled.
+    pub fn set_analytics_enabled(&self, enabled: bool) -> Result<(), String> {
+        let conn = self.lock()?;
+        Self::set_metadata_inner(
+            &conn,
+            "analytics_enabled",
+            if enabled { "true" } else { "false" },
+        )
+    }
+
+    // ── Global remote server settings ────────────────────────────────
+
+    pub fn get_remote_server_url(&self) -> Result<Option<String>, String> {
+        let conn = self.lock()?;
+        Self::get_metadata_inner(&conn, "remote_server_url")
+    }
+
+    pub fn set_remote_server_url(&self, url: Option<&str>) -> Result<(), String> {
+        let conn = self.lock()?;
+        match url.filter(|u| !u.is_empty()) {
+            Some(u) => Self::set_metadata_inner(&conn, "remote_server_url", u),
+            None => Self::delete_metadata_inner(&conn, "remote_server_url"),
+        }
+    }
+
+    pub fn get_remote_auth_token(&self) -> Result<Option<String>, String> {
+        let conn = self.lock()?;
+        Self::get_metadata_inner(&conn, "remote_auth_token")
+    }
+
+    pub fn set_remote_auth_token(&self, token: Option<&str>) -> Result<(), String> {
+        let conn = self.lock()?;
+        match token.filter(|t| !t.is_empty()) {
+            Some(t) => Self::set_metadata_inner(&conn, "remote_auth_token", t),
+            None => Self::delete_metadata_inner(&conn, "remote_auth_token"),
+        }
+    }
+
+    /// Resolve server credentials for a group: per-group values win if non-empty,
+    /// otherwise fall back to global defaults.
+    /// Returns `(Option<url>, Option<token>)`.
+    pub fn resolve_server_credentials(
+        &self,
+        group_path: &str,
+    ) -> Result<(Option<String>, Option<String>), String> {
+        let conn = self.lock()?;
+
+        // Per-group values
+        let (group_url, group_token) = match conn.query_row(
+            "SELECT server_url, server_password FROM group_settings WHERE group_path = ?1",
+            [group_path],
+            |row| {
+                Ok((
+                    row.get::<_, Option<String>>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                ))
+            },
+        ) {
+            Ok(pair) => pair,
+            Err(rusqlite::Error::QueryReturnedNoRows) => (None, None),
+            Err(e) => return Err(format!("Failed to get group credentials: {e}")),
+        };
+
+        let global_url = Self::get_metadata_inner(&conn, "remote_server_url")?;
+        let global_token = Self::get_metadata_inner(&conn, "remote_auth_token")?;
+
+        let url = group_url.filter(|u| !u.is_empty()).or(global_url);
+        let token = group_token.filter(|t| !t.is_empty()).or(global_token);
+
+        Ok((url, token))
+    }
+
+    // ── Token usage ──────────────────────────────────────────────────
+
+    /// Scan position for a JSONL file: `(byte offset, last assistant message ID)`.
+    pub fn get_usage_scan(
+        &self,
+        jsonl_path: &str,
+    ) -> Result<Option<(u64, Option<String>)>, String> {
+        let conn = self.lock()?;
+        let result = conn.query_row(
+            "SELECT offset, last_message_id FROM usage_scan WHERE jsonl_path = ?1",
+            [jsonl_path],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)? as u64,
+                    row.get::<_, Option<String>>(1)?,
+                ))
+            },
+        );
+        match result {
+            Ok(scan) => Ok(Some(scan)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(format!("Failed to get usage scan state: {e}")),
+        }
+    }
+
+    /// Add usage deltas for a session and advance the file's scan position,
+    /// atomically so a crash can't double count.
+    pub fn record_usage(
+        &self,
+        jsonl_path: &str,
+        session_id: &str,
+        group_path: &str,
+        deltas: &[(String, String, TokenCounts)],
+        offset: u64,
+        last_message_id: Option<&str>,
+    ) -> Result<(), String> {
+        let mut conn = self.lock()?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start transaction: {e}"))?;
+        for (day, model, tokens) in deltas {
+            tx.execute(
+                "INSERT INTO usage_daily (session_id, group_path, day, model, input_tokens, \
+                 output_tokens, cache_creation_tokens, cache_read_tokens) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8) \
+                 ON CONFLICT(session_id, day, model) DO UPDATE SET group_path = ?2, \
+                 input_tokens = input_tokens + ?5, output_tokens = output_tokens + ?6, \
+                 cache_creation_tokens = cache_creation_tokens + ?7, \
+                 cache_read_tokens = cache_read_tokens + ?8",
+                rusqlite::params![
+                    session_id,
+                    group_path,
+                    day,
+                    model,
+                    tokens.input_tokens as i64,
+                    tokens.output_tokens as i64,
+                    tokens.cache_creation_tokens as i64,
+                    tokens.cache_read_tokens as i64,
+                ],
+            )
+            .map_err(|e| format!("Failed to record usage: {e}"))?;
+        }
+        tx.execute(
+            "INSERT INTO usage_scan (jsonl_path, offset, last_message_id) VALUES (?1, ?2, ?3) \
+             ON CONFLICT(jsonl_path) DO UPDATE SET offset = ?2, last_message_id = ?3",
+            rusqlite::params![jsonl_path, offset as i64, last_message_id],
+        )
+        .map_err(|e| format!("Failed to update usage scan state: {e}"))?;
+        tx.commit()
+            .map_err(|e| format!("Failed to commit usage: {e}"))
+    }
+
+    /// All usage rows, optionally only from `since` (YYYY-MM-DD) onwards.
+    pub fn get_usage_rows(&self, since: Option<&str>) -> Result<Vec<UsageRow>, String> {
+        let conn = self.lock()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT session_id, group_path, day, model, input_tokens, output_tokens, \
+                 cache_creation_tokens, cache_read_tokens FROM usage_daily \
+                 WHERE ?1 IS NULL OR day >= ?1 ORDER BY day",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([since], |row| {
+                Ok(UsageRow {
+                    session_id: row.get(0)?,
+                    group_path: row.get(1)?,
+                    day: row.get(2)?,
+                    model: row.get(3)?,
+                    tokens: TokenCounts {
+                        input_tokens: row.get::<_, i64>(4)? as u64,
+                        output_tokens: row.get::<_, i64>(5)? as u64,
+                        cache_creation_tokens: row.get::<_, i64>(6)? as u64,
+                        cache_read_tokens: row.get::<_, i64>(7)? as u64,
+                    },
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())
+    }
+
+    /// User-configured model prices (overrides for the built-in table).
+    pub fn get_model_prices(&self) -> Result<Vec<ModelPrice>, String> {
+        let conn = self.lock()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT model, input_per_mtok, output_per_mtok, cache_write_per_mtok, \
+                 cache_read_per_mtok FROM model_prices ORDER BY model",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(ModelPrice {
+                    model: row.get(0)?,
+                    input_per_mtok: row.get(1)?,
+                    output_per_mtok: row.get(2)?,
+                    cache_write_per_mtok: row.get(3)?,
+                    cache_read_per_mtok: row.get(4)?,
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn set_model_price(&self, price: &ModelPrice) -> Result<(), String> {
+        let conn = self.lock()?;
+        conn.execute(
+            "INSERT INTO model_prices (model, input_per_mtok, output_per_mtok, \
+             cache_write_per_mtok, cache_read_per_mtok) VALUES (?1, ?2, ?3, ?4, ?5) \
+             ON CONFLICT(model) DO UPDATE SET input_per_mtok = ?2, output_per_mtok = ?3, \
+             cache_write_per_mtok = ?4, cache_read_per_mtok = ?5",
+            rusqlite::params![
+                price.model,
+                price.input_per_mtok,
+                price.output_per_mtok,
+                price.cache_write_per_mtok,
+                price.cache_read_per_mtok,
+            ],
+        )
+        .map_err(|e| format!("Failed to set model price: {e}"))?;
+        Ok(())
+    }
+
+    pub fn delete_model_price(&self, model: &str) -> Result<(), String> {
+        let conn = self.lock()?;
+        conn.execute("DELETE FROM model_prices WHERE model = ?1", [model])
+            .map_err(|e| format!("Failed to delete model price: {e}"))?;
+        Ok(())
+    }
+
+    // ── Search index ─────────────────────────────────────────────────
+
+    /// How far a search source has been indexed: a byte offset for JSONL
+    /// files, a message count for remote sessions.
+    pub fn get_search_position(&self, source: &str) -> Result<Option<u64>, String> {
+        let conn = self.lock()?;
+        let result = conn.query_row(
+            "SELECT position FROM search_scan WHERE source = ?1",
+            [source],
+            |row| row.get::<_, i64>(0),
+        );
+        match result {
+            Ok(position) => Ok(Some(position as u64)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(format!("Failed to get search position: {e}")),
+        }
+    }
+
+    /// The session `last_accessed` a remote source was last indexed at.
+    pub fn get_search_accessed(&self, source: &str) -> Result<Option<i64>, String> {
+        let conn = self.lock()?;
+        let result = conn.query_row(
+            "SELECT last_accessed FROM search_scan WHERE source = ?1",
+            [source],
+            |row| row.get::<_, Option<i64>>(0),
+        );
+        match result {
+            Ok(accessed) => Ok(accessed),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(format!("Failed to get search access time: {e}")),
+        }
+    }
+
+    /// Record the session `last_accessed` a remote source is indexed up to.
+    pub fn set_search_accessed(&self, source: &str, last_accessed: i64) -> Result<(), String> {
+        let conn = self.lock()?;
+        conn.execute(
+            "INSERT INTO search_scan (source, position, last_accessed) VALUES (?1, 0, ?2) \
+             ON CONFLICT(source) DO UPDATE SET last_accessed = ?2",
+            rusqlite::params![source, last_accessed],
+        )
+        .map_err(|e| format!("Failed to update search access time: {e}"))?;
+        Ok(())
+    }
+
+    /// Add documents from a source and advance its position atomically.
+    /// With `reset`, the source's existing documents are dropped first
+    /// (the transcript was rewritten).
+    pub fn index_search_docs(
+        &self,
+        source: &str,
+        docs: &[SearchDoc],
+        position: u64,
+        reset: bool,
+    ) -> Result<(), String> {
+        let mut conn = self.lock()?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start transaction: {e}"))?;
+        if reset {
+            tx.execute("DELETE FROM search_index WHERE source = ?1", [source])
+                .map_err(|e| format!("Failed to reset search source: {e}"))?;
+        }
+        for doc in docs {
+            tx.execute(
+                "INSERT INTO search_index (content, kind, session_id, group_path, timestamp, \
+                 source) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                rusqlite::params![
+                    doc.content,
+                    doc.kind,
+                    doc.session_id,
+                    doc.group_path,
+                    doc.timestamp,
+                    source
+                ],
+            )
+            .map_err(|e| format!("Failed to index document: {e}"))?;
+        }
+        tx.execute(
+            "INSERT INTO search_scan (source, position) VALUES (?1, ?2) \
+             ON CONFLICT(source) DO UPDATE SET position = ?2",
+            rusqlite::params![source, position as i64],
+        )
+        .map_err(|e| format!("Failed to update search position: {e}"))?;
+        tx.commit()
+            .map_err(|e| format!("Failed to commit search index: {e}"))
+    }
+
+    /// Run an FTS5 MATCH query, best matches first. `since` compares against
+    /// the RFC 3339 timestamp, so a bare `YYYY-MM-DD` works.
+    pub fn search_docs(
+        &self,
+        match_query: &str,
+        group_path: Option<&str>,
+        since: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<SearchRow>, String> {
+        let conn = self.lock()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT session_id, group_path, timestamp, kind, \
+                 snippet(search_index, 0, '«', '»', '…', 16) \
+                 FROM search_index WHERE search_index MATCH ?1 \
+                 AND (?2 IS NULL OR group_path = ?2) \
+                 AND (?3 IS NULL OR timestamp >= ?3) \
+                 ORDER BY rank LIMIT ?4",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(
+                rusqlite::params![match_query, group_path, since, limit as i64],
+                |row| {
+                    Ok(SearchRow {
+                        session_id: row.get(0)?,
+                        group_path: row.get(1)?,
+                        timestamp: row.get(2)?,
+                        kind: row.get(3)?,
+                        snippet: row.get(4)?,
+                    })
+                },
+            )
+            .map_err(|e| format!("Search failed: {e}"))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Search failed: {e}"))
+    }
+
+    // ── Tool profiles ────────────────────────────────────────────────
+
+    /// User-defined tool profiles, stored as JSON so new fields don't need
+    /// migrations. Rows that no longer parse are skipped with a warning.
+    pub fn get_tool_profiles(&self) -> Result<Vec<ToolProfile>, String> {
+        let conn = self.lock()?;
+        let mut stmt = conn
+            .prepare("SELECT name, profile FROM tool_profiles ORDER BY name")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })
+            .map_err(|e| e.to_string())?;
+        let mut profiles = Vec::new();
+        for row in rows {
+            let (name, json) = row.map_err(|e| e.to_string())?;
+            match serde_json::from_str::<ToolProfile>(&json) {
+                Ok(profile) => profiles.push(profile),
+                Err(e) => log::warn!("Skipping invalid tool profile '{name}': {e}"),
+            }
+        }
+        Ok(profiles)
+    }
+
+    pub fn set_tool_profile(&self, profile: &ToolProfile) -> Result<(), String> {
+        let json = serde_json::to_string(profile).map_err(|e| e.to_string())?;
+        let conn = self.lock()?;
+        conn.execute(
+            "INSERT INTO tool_profiles (name, profile) VALUES (?1, ?2) \
+             ON CONFLICT(name) DO UPDATE SET profile = ?2",
+            rusqlite::params![profile.name, json],
+        )
+        .map_err(|e| format!("Failed to save tool profile: {e}"))?;
+        Ok(())
+    }
+
+    pub fn delete_tool_profile(&self, name: &str) -> Result<(), String> {
+        let conn = self.lock()?;
+        conn.execute("DELETE FROM tool_profiles WHERE name = ?1", [name])
+            .map_err(|e| format!("Failed to delete tool profile: {e}"))?;
+        Ok(())
+    }
+
+    // ── Hook signals ─────────────────────────────────────────────────
+
+    /// Store a hook event unless a newer one is already recorded for the session.
+    pub fn record_hook_signal(
+        &self,
+        claude_session_id: &str,
+        signal: &HookSignal,
+    ) -> Result<(), String> {
+        let conn = self.lock()?;
+        conn.execute(
+            "INSERT INTO hook_signals (claude_session_id, event, detail, received_at) \
+             VALUES (?1, ?2, ?3, ?4) \
+             ON CONFLICT(claude_session_id) DO UPDATE SET event = ?2, detail = ?3, \
+             received_at = ?4 WHERE received_at <= ?4",
+            rusqlite::params![
+                claude_session_id,
+                signal.event,
+                signal.detail,
+                signal.received_at as i64
+            ],
+        )
+        .map_err(|e| format!("Failed to record hook signal: {e}"))?;
+        Ok(())
+    }
+
+    pub fn get_hook_signals(&self) -> Result<HashMap<String, HookSignal>, String> {
+        let conn = self.lock()?;
+        let mut stmt = conn
+            .prepare("SELECT claude_session_id, event, detail, received_at FROM hook_signals")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    HookSignal {
+                        event: row.get(1)?,
+                        detail: row.get(2)?,
+                        received_at: row.get::<_, i64>(3)? as u64,
+                    },
+                ))
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<HashMap<_, _>, _>>()
+            .map_err(|e| e.to_string())
+    }
+
+    // ── Budgets ──────────────────────────────────────────────────────
+
+    fn map_budget_row(row: &rusqlite::Row, offset: usize) -> rusqlite::Result<GroupBudget> {
+        Ok(GroupBudget {
+            max_tokens: row.get::<_, Option<i64>>(offset)?.map(|v| v as u64),
+            max_usd: row.get(offset + 1)?,
+            max_runtime_mins: row.get::<_, Option<i64>>(offset + 2)?.map(|v| v as u64),
+            warn_percent: row.get(offset + 3)?,
+            action: row.get(offset + 4)?,
+        })
+    }
+
+    /// Get a group's budget, or None if it has no limits set.
+    pub fn get_group_budget(&self, group_path: &str) -> Result<Option<GroupBudget>, String> {
+        let conn = self.lock()?;
+        let result = conn.query_row(
+            "SELECT budget_tokens, budget_usd, budget_runtime_mins, budget_warn_percent, \
+             budget_action FROM group_settings WHERE group_path = ?1",
+            [group_path],
+            |row| Self::map_budget_row(row, 0),
+        );
+        match result {
+            Ok(budget) => Ok(Some(budget).filter(GroupBudget::has_limits)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(format!("Failed to get group budget: {e}")),
+        }
+    }
+
+    /// All groups that have at least one budget limit set.
+    pub fn get_all_group_budgets(&self) -> Result<HashMap<String, GroupBudget>, String> {
+        let conn = self.lock()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT group_path, budget_tokens, budget_usd, budget_runtime_mins, \
+                 budget_warn_percent, budget_action FROM group_settings \
+                 WHERE budget_tokens IS NOT NULL OR budget_usd IS NOT NULL \
+                 OR budget_runtime_mins IS NOT NULL",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, Self::map_budget_row(row, 1)?))
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<HashMap<_, _>, _>>()
+            .map_err(|e| e.to_string())
+    }
+
+    /// Set (upsert) or clear a group's budget.
+    pub fn set_group_budget(
+        &self,
+        group_path: &str,
+        budget: Option<&GroupBudget>,
+    ) -> Result<(), String> {
+        let conn = self.lock()?;
+        let (tokens, usd, runtime, warn_percent, action) = match budget {
+            Some(b) => (
+                b.max_tokens.map(|v| v as i64),
+                b.max_usd,
+                b.max_runtime_mins.map(|v| v as i64),
+                b.warn_percent,
+                b.action.as_str(),
+            ),
+            None => (None, None, None, 80, "warn"),
+        };
+        conn.execute(
+            "INSERT INTO group_settings (group_path, budget_tokens, budget_usd, \
+             budget_runtime_mins, budget_warn_percent, budget_action) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6) \
+             ON CONFLICT(group_path) DO UPDATE SET budget_tokens = ?2, budget_usd = ?3, \
+             budget_runtime_mins = ?4, budget_warn_percent = ?5, budget_action = ?6",
+            rusqlite::params![group_path, tokens, usd, runtime, warn_percent, action],
+        )
+        .map_err(|e| format!("Failed to set group budget: {e}"))?;
+        Ok(())
+    }
+
+    // ── Notifications ────────────────────────────────────────────────
+
+    pub fn get_notification_settings(&self) -> Result<NotificationSettings, String> {
+        let conn = self.lock()?;
+        match Self::get_metadata_inner(&conn, "notification_settings")? {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| format!("Invalid notification settings: {e}")),
+            None => Ok(NotificationSettings::default()),
+        }
+    }
+
+    pub fn set_notification_settings(&self, settings: &NotificationSettings) -> Result<(), String> {
+        let json = serde_json::to_string(settings).map_err(|e| e.to_string())?;
+        let conn = self.lock()?;
+        Self::set_metadata_inner(&conn, "notification_settings", &json)
+    }
+
+    pub fn get_group_notifications_muted(&self, group_path: &str) -> Result<bool, String> {
+        let conn = self.lock()?;
+        let result = conn.query_row(
+            "SELECT notifications_muted FROM group_settings WHERE group_path = ?1",
+            [group_path],
+            |row| row.get::<_, bool>(0),
+        );
+        match result {
+            Ok(muted) => Ok(muted),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(false),
+            Err(e) => Err(format!("Failed to get notification mute: {e}")),
+        }
+    }
+
+    pub fn set_group_notifications_muted(
+        &self,
+        group_path: &str,
+        muted: bool,
+    ) -> Result<(), String> {
+        let conn = self.lock()?;
+        conn.execute(
+            "INSERT INTO group_settings (group_path, notifications_muted) VALUES (?1, ?2) \
+             ON CONFLICT(group_path) DO UPDATE SET notifications_muted = ?2",
+            rusqlite::params![group_path, muted],
+        )
+        .map_err(|e| format!("Failed to set notification mute: {e}"))?;
+        Ok(())
+    }
+
+    /// The attention status a session was last notified about, and when.
+    pub fn get_last_notification(&self, session_id: &str) -> Result<Option<(String, u64)>, String> {
+        let conn = self.lock()?;
+        let result = conn.query_row(
+            "SELECT attention, sent_at FROM notification_log WHERE session_id = ?1",
+            [session_id],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as u64)),
+        );
+        match result {
+            Ok(entry) => Ok(Some(entry)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(format!("Failed to get last notification: {e}")),
+        }
+    }
+
+    pub fn record_notification(
+        &self,
+        session_id: &str,
+        attention: &str,
+        sent_at: u64,
+    ) -> Result<(), String> {
+        let conn = self.lock()?;
+        conn.execute(
+            "INSERT INTO notification_log (session_id, attention, sent_at) VALUES (?1, ?2, ?3) \
+             ON CONFLICT(session_id) DO UPDATE SET attention = ?2, sent_at = ?3",
+            rusqlite::params![session_id, attention, sent_at as i64],
+        )
+        .map_err(|e| format!("Failed to record notification: {e}"))?;
+        Ok(())
+    }
+
+    // ── Automation API ───────────────────────────────────────────────
+
+    pub fn get_api_settings(&self) -> Result<ApiSettings, String> {
+        let conn = self.lock()?;
+        match Self::get_metadata_inner(&conn, "api_settings")? {
+            Some(json) => {
+                serde_json::from_str(&json).map_err(|e| format!("Invalid API settings: {e}"))
+            }
+            None => Ok(ApiSettings::default()),
+        }
+    }
+
+    pub fn set_api_settings(&self, settings: &ApiSettings) -> Result<(), String> {
+        let json = serde_json::to_string(settings).map_err(|e| e.to_string())?;
+        let conn = self.lock()?;
+        Self::set_metadata_inner(&conn, "api_settings", &json)
+    }
+
+    pub fn get_api_token(&self) -> Result<Option<String>, String> {
+        let conn = self.lock()?;
+        Self::get_metadata_inner(&conn, "api_token")
+    }
+
+    pub fn set_api_token(&self, token: &str) -> Result<(), String> {
+        let conn = self.lock()?;
+        Self::set_metadata_inner(&conn, "api_token", token)
+    }
+
+    // ── Webhooks ─────────────────────────────────────────────────────
+
+    pub fn get_webhooks(&self) -> Result<Vec<Webhook>, String> {
+        let conn = self.lock()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, name, url, events, body_template, enabled FROM webhooks ORDER BY id",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                let events: String = row.get(3)?;
+                Ok(Webhook {
+                    id: Some(row.get(0)?),
+                    name: row.get(1)?,
+                    url: row.get(2)?,
+                    events: serde_json::from_str(&events).unwrap_or_default(),
+                    body_template: row.get(4)?,
+                    enabled: row.get(5)?,
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())
+    }
+
+    /// Insert a webhook (no id) or update an existing one, returning its id.
+    pub fn save_webhook(&self, webhook: &Webhook) -> Result<i64, String> {
+        let events = serde_json::to_string(&webhook.events).map_err(|e| e.to_string())?;
+        let conn = self.lock()?;
+        match webhook.id {
+            Some(id) => {
+                let updated = conn
+                    .execute(
+                        "UPDATE webhooks SET name = ?2, url = ?3, events = ?4, body_template = ?5, \
+                         enabled = ?6 WHERE id = ?1",
+                        rusqlite::params![
+                            id,
+                            webhook.name,
+                            webhook.url,
+                            events,
+                            webhook.body_template,
+                            webhook.enabled
+                        ],
+                    )
+                    .map_err(|e| format!("Failed to save webhook: {e}"))?;
+                if updated == 0 {
+                    return Err(format!("Webhook {id} not found"));
+                }
+                Ok(id)
+            }
+            None => {
+                conn.execute(
+                    "INSERT INTO webhooks (name, url, events, body_template, enabled) \
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    rusqlite::params![
+                        webhook.name,
+                        webhook.url,
+                        events,
+                        webhook.body_template,
+                        webhook.enabled
+                    ],
+                )
+                .map_err(|e| format!("Failed to save webhook: {e}"))?;
+                Ok(conn.last_insert_rowid())
+            }
+        }
+    }
+
+    /// Delete a webhook and its delivery log.
+    pub fn delete_webhook(&self, id: i64) -> Result<(), String> {
+        let conn = self.lock()?;
+        conn.execute("DELETE FROM webhook_deliveries WHERE webhook_id = ?1", [id])
+            .map_err(|e| format!("Failed to delete webhook deliveries: {e}"))?;
+        conn.execute("DELETE FROM webhooks WHERE id = ?1", [id])
+            .map_err(|e| format!("Failed to delete webhook: {e}"))?;
+        Ok(())
+    }
+
+    /// Log a delivery, keeping only the newest `keep` entries overall.
+    pub fn record_webhook_delivery(
+        &self,
+        delivery: &WebhookDelivery,
+        keep: usize,
+    ) -> Result<i64, String> {
+        let conn = self.lock()?;
+        conn.execute(
+            "INSERT INTO webhook_deliveries \
+             (webhook_id, event, delivered, attempts, status_code, error, created_at) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            rusqlite::params![
+                delivery.webhook_id,
+                delivery.event,
+                delivery.delivered,
+                delivery.attempts,
+                delivery.status_code,
+                delivery.error,
+                delivery.created_at as i64
+            ],
+        )
+        .map_err(|e| format!("Failed to record webhook delivery: {e}"))?;
+        let id = conn.last_insert_rowid();
+        conn.execute(
+            "DELETE FROM webhook_deliveries WHERE id NOT IN \
+             (SELECT id FROM webhook_deliveries ORDER BY id DESC LIMIT ?1)",
+            [keep as i64],
+        )
+        .map_err(|e| format!("Failed to prune webhook deliveries: {e}"))?;
+        Ok(id)
+    }
+
+    /// Newest deliveries first, optionally for one webhook.
+    pub fn get_webhook_deliveries(
+        &self,
+        webhook_id: Option<i64>,
+        limit: usize,
+    ) -> Result<Vec<WebhookDelivery>, String> {
+        let conn = self.lock()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, webhook_id, event, delivered, attempts, status_code, error, created_at \
+                 FROM webhook_deliveries WHERE ?1 IS NULL OR webhook_id = ?1 \
+                 ORDER BY id DESC LIMIT ?2",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(rusqlite::params![webhook_id, limit as i64], |row| {
+                Ok(WebhookDelivery {
+                    id: row.get(0)?,
+                    webhook_id: row.get(1)?,
+                    event: row.get(2)?,
+                    delivered: row.get(3)?,
+                    attempts: row.get(4)?,
+                    status_code: row.get(5)?,
+                    error: row.get(6)?,
+                    created_at: row.get::<_, i64>(7)? as u64,
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())
+    }
+
+    // ── Review comments ──────────────────────────────────────────────
 
-    fn set_metadata_inner(conn: &Connection, key: &str, value: &str) -> Result<(), String> {
+    /// Insert a pending review comment, returning its id.
+    pub fn add_review_comment(&self, comment: &ReviewComment) -> Result<i64, String> {
+        let conn = self.lock()?;
         conn.execute(
-            "INSERT INTO metadata (key, value) VALUES (?1, ?2) \
-             ON CONFLICT(key) DO UPDATE SET value = ?2",
-            rusqlite::params![key, value],
+            "INSERT INTO review_comments \
+             (session_id, branch, file_path, side, blob_sha, start_line, end_line, snippet, body, \
+              status, created_at) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, 'pending', ?10)",
+            rusqlite::params![
+                comment.session_id,
+                comment.branch,
+                comment.file_path,
+                comment.side,
+                comment.blob_sha,
+                comment.start_line,
+                comment.end_line,
+                comment.snippet,
+                comment.body,
+                comment.created_at as i64
+            ],
         )
-        .map_err(|e| format!("Failed to set metadata '{key}': {e}"))?;
-        Ok(())
+        .map_err(|e| format!("Failed to add review comment: {e}"))?;
+        Ok(conn.last_insert_rowid())
     }
 
-    fn delete_metadata_inner(conn: &Connection, key: &str) -> Result<(), String> {
-        conn.execute("DELETE FROM metadata WHERE key = ?1", [key])
-            .map_err(|e| format!("Failed to delete metadata '{key}': {e}"))?;
-        Ok(())
+    /// A session's comments on a branch, oldest first; sent ones only if asked.
+    pub fn get_review_comments(
+        &self,
+        session_id: &str,
+        branch: &str,
+        include_sent: bool,
+    ) -> Result<Vec<ReviewComment>, String> {
+        let conn = self.lock()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, session_id, branch, file_path, side, blob_sha, start_line, end_line, \
+                 snippet, body, status, created_at, sent_at FROM review_comments \
+                 WHERE session_id = ?1 AND branch = ?2 AND (?3 OR status = 'pending') \
+                 ORDER BY id",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(rusqlite::params![session_id, branch, include_sent], |row| {
+                Ok(ReviewComment {
+                    id: row.get(0)?,
+                    session_id: row.get(1)?,
+                    branch: row.get(2)?,
+                    file_path: row.get(3)?,
+                    side: row.get(4)?,
+                    blob_sha: row.get(5)?,
+                    start_line: row.get(6)?,
+                    end_line: row.get(7)?,
+                    snippet: row.get(8)?,
+                    body: row.get(9)?,
+                    status: row.get(10)?,
+                    created_at: row.get::<_, i64>(11)? as u64,
+                    sent_at: row.get::<_, Option<i64>>(12)?.map(|t| t as u64),
+                    outdated: false,
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())
     }
 
-    // ── Analytics ────────────────────────────────────────────────────
-
-    /// Check if analytics is enabled (defaults to false).
-    pub fn get_analytics_enabled(&self) -> Result<bool, String> {
+    pub fn update_review_comment_body(&self, id: i64, body: &str) -> Result<(), String> {
         let conn = self.lock()?;
-        Ok(Self::get_metadata_inner(&conn, "analytics_enabled")?.as_deref() == Some("true"))
+        let updated = conn
+            .execute(
+                "UPDATE review_comments SET body = ?2 WHERE id = ?1",
+                rusqlite::params![id, body],
+            )
+            .map_err(|e| format!("Failed to update review comment: {e}"))?;
+        if updated == 0 {
+            return Err(format!("Review comment {id} not found"));
+        }
+        Ok(())
     }
 
-    /// Set whether analytics is enabled.
-    pub fn set_analytics_enabled(&self, enabled: bool) -> Result<(), String> {
+    /// Move a comment to where its lines are now, in the given blob.
+    pub fn reanchor_review_comment(
+        &self,
+        id: i64,
+        blob_sha: &str,
+        start_line: u32,
+        end_line: u32,
+    ) -> Result<(), String> {
         let conn = self.lock()?;
-        Self::set_metadata_inner(
-            &conn,
-            "analytics_enabled",
-            if enabled { "true" } else { "false" },
+        conn.execute(
+            "UPDATE review_comments SET blob_sha = ?2, start_line = ?3, end_line = ?4 \
+             WHERE id = ?1",
+            rusqlite::params![id, blob_sha, start_line, end_line],
         )
+        .map_err(|e| format!("Failed to re-anchor review comment: {e}"))?;
+        Ok(())
     }
 
-    // ── Global remote server settings ────────────────────────────────
-
-    pub fn get_remote_server_url(&self) -> Result<Option<String>, String> {
+    pub fn delete_review_comment(&self, id: i64) -> Result<(), String> {
         let conn = self.lock()?;
-        Self::get_metadata_inner(&conn, "remote_server_url")
+        conn.execute("DELETE FROM review_comments WHERE id = ?1", [id])
+            .map_err(|e| format!("Failed to delete review comment: {e}"))?;
+        Ok(())
     }
 
-    pub fn set_remote_server_url(&self, url: Option<&str>) -> Result<(), String> {
+    pub fn mark_review_comments_sent(&self, ids: &[i64], sent_at: u64) -> Result<(), String> {
         let conn = self.lock()?;
-        match url.filter(|u| !u.is_empty()) {
-            Some(u) => Self::set_metadata_inner(&conn, "remote_server_url", u),
-            None => Self::delete_metadata_inner(&conn, "remote_server_url"),
+        for id in ids {
+            conn.execute(
+                "UPDATE review_comments SET status = 'sent', sent_at = ?2 WHERE id = ?1",
+                rusqlite::params![id, sent_at as i64],
+            )
+            .map_err(|e| format!("Failed to mark review comment sent: {e}"))?;
         }
+        Ok(())
     }
 
-    pub fn get_remote_auth_token(&self) -> Result<Option<String>, String> {
+    // ── Attention thresholds ─────────────────────────────────────────
+
+    /// Get a group's attention thresholds, or None if it uses the defaults.
+    pub fn get_attention_thresholds(
+        &self,
+        group_path: &str,
+    ) -> Result<Option<AttentionThresholds>, String> {
         let conn = self.lock()?;
-        Self::get_metadata_inner(&conn, "remote_auth_token")
+        let result = conn.query_row(
+            "SELECT stale_after_secs, idle_after_secs FROM group_settings \
+             WHERE group_path = ?1 AND stale_after_secs IS NOT NULL",
+            [group_path],
+            |row| Self::map_thresholds_row(row, 0),
+        );
+        match result {
+            Ok(thresholds) => Ok(Some(thresholds)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(format!("Failed to get attention thresholds: {e}")),
+        }
     }
 
-    pub fn set_remote_auth_token(&self, token: Option<&str>) -> Result<(), String> {
+    /// All groups with their own attention thresholds.
+    pub fn get_all_attention_thresholds(
+        &self,
+    ) -> Result<HashMap<String, AttentionThresholds>, String> {
         let conn = self.lock()?;
-        match token.filter(|t| !t.is_empty()) {
-            Some(t) => Self::set_metadata_inner(&conn, "remote_auth_token", t),
-            None => Self::delete_metadata_inner(&conn, "remote_auth_token"),
-        }
+        let mut stmt = conn
+            .prepare(
+                "SELECT group_path, stale_after_secs, idle_after_secs FROM group_settings \
+                 WHERE stale_after_secs IS NOT NULL",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, Self::map_thresholds_row(row, 1)?))
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<HashMap<_, _>, _>>()
+            .map_err(|e| e.to_string())
     }
 
-    /// Resolve server credentials for a group: per-group values win if non-empty,
-    /// otherwise fall back to global defaults.
-    /// Returns `(Option<url>, Option<token>)`.
-    pub fn resolve_server_credentials(
+    /// Thresholds for a group, falling back to the defaults when the group
+    /// has none or the lookup fails.
+    pub fn attention_thresholds_for(&self, group_path: Option<&str>) -> AttentionThresholds {
+        group_path
+            .and_then(|g| {
+                self.get_attention_thresholds(g)
+                    .map_err(|e| log::warn!("{e}"))
+                    .ok()
+                    .flatten()
+            })
+            .unwrap_or_default()
+    }
+
+    fn map_thresholds_row(
+        row: &rusqlite::Row,
+        offset: usize,
+    ) -> rusqlite::Result<AttentionThresholds> {
+        Ok(AttentionThresholds {
+            stale_after_secs: row.get::<_, i64>(offset)? as u64,
+            idle_after_secs: row.get::<_, Option<i64>>(offset + 1)?.map(|v| v as u64),
+        })
+    }
+
+    /// Set (upsert) or clear a group's attention thresholds.
+    pub fn set_attention_thresholds(
         &self,
         group_path: &str,
-    ) -> Result<(Option<String>, Option<String>), String> {
+        thresholds: Option<&AttentionThresholds>,
+    ) -> Result<(), String> {
         let conn = self.lock()?;
-
-        // Per-group values
-        let (group_url, group_token) = match conn.query_row(
-            "SELECT server_url, server_password FROM group_settings WHERE group_path = ?1",
-            [group_path],
-            |row| {
-                Ok((
-                    row.get::<_, Option<String>>(0)?,
-                    row.get::<_, Option<String>>(1)?,
-                ))
-            },
-        ) {
-            Ok(pair) => pair,
-            Err(rusqlite::Error::QueryReturnedNoRows) => (None, None),
-            Err(e) => return Err(format!("Failed to get group credentials: {e}")),
+        let (stale, idle) = match thresholds {
+            Some(t) => (
+                Some(t.stale_after_secs as i64),
+                t.idle_after_secs.map(|v| v as i64),
+            ),
+            None => (None, None),
         };
+        conn.execute(
+            "INSERT INTO group_settings (group_path, stale_after_secs, idle_after_secs) \
+             VALUES (?1, ?2, ?3) \
+             ON CONFLICT(group_path) DO UPDATE SET stale_after_secs = ?2, idle_after_secs = ?3",
+            rusqlite::params![group_path, stale, idle],
+        )
+        .map_err(|e| format!("Failed to set attention thresholds: {e}"))?;
+        Ok(())
+    }
 
-        let global_url = Self::get_metadata_inner(&conn, "remote_server_url")?;
-        let global_token = Self::get_metadata_inner(&conn, "remote_auth_token")?;
-
-        let url = group_url.filter(|u| !u.is_empty()).or(global_url);
-        let token = group_token.filter(|t| !t.is_empty()).or(global_token);
+    /// Add agent running time (seconds) to a group's total for a day.
+    pub fn add_group_runtime(
+        &self,
+        group_path: &str,
+        day: &str,
+        seconds: u64,
+    ) -> Result<(), String> {
+        let conn = self.lock()?;
+        conn.execute(
+            "INSERT INTO group_runtime (group_path, day, seconds) VALUES (?1, ?2, ?3) \
+             ON CONFLICT(group_path, day) DO UPDATE SET seconds = seconds + ?3",
+            rusqlite::params![group_path, day, seconds as i64],
+        )
+        .map_err(|e| format!("Failed to record group runtime: {e}"))?;
+        Ok(())
+    }
 
-        Ok((url, token))
+    /// Running seconds per group for a day.
+    pub fn get_group_runtime(&self, day: &str) -> Result<HashMap<String, u64>, String> {
+        let conn = self.lock()?;
+        let mut stmt = conn
+            .prepare("SELECT group_path, seconds FROM group_runtime WHERE day = ?1")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([day], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as u64))
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<HashMap<_, _>, _>>()
+            .map_err(|e| e.to_string())
     }
 
     /// One-time migration: copy github_issues_enabled and prompt data from
@@ -833,7 +1996,7 @@ mod tests {
 
         let prompts = db.get_all_prompts().expect("get failed");
         assert_eq!(prompts.len(), baseline + 1);
-        assert!(prompts.get("sess-1").is_none());
+        assert!(!prompts.contains_key("sess-1"));
         assert_eq!(prompts.get("sess-2").unwrap(), "prompt 2");
     }
 
@@ -1002,4 +2165,421 @@ mod tests {
         assert_eq!(url.as_deref(), Some("https://global.example.com"));
         assert_eq!(token.as_deref(), Some("global-token"));
     }
+
+    // ── 19. get_group_backend defaults to local ─────────────────────
+
+    #[test]
+    fn test_group_backend_default_and_configured() {
+        let (db, _tmp) = setup();
+
+        assert_eq!(db.get_group_backend("/nonexistent").unwrap(), "local");
+
+        db.update_group_settings(
+            "/repo",
+            true,
+            "merge",
+            None,
+            2,
+            "opencode-remote",
+            Some("http://localhost:4096"),
+            None,
+        )
+        .expect("update failed");
+        assert_eq!(db.get_group_backend("/repo").unwrap(), "opencode-remote");
+    }
+
+    // ── 20. record_usage accumulates and tracks scan position ────────
+
+    #[test]
+    fn test_record_usage_accumulates() {
+        let (db, _tmp) = setup();
+        let tokens = TokenCounts {
+            input_tokens: 10,
+            output_tokens: 20,
+            cache_creation_tokens: 30,
+            cache_read_tokens: 40,
+        };
+        let deltas = vec![(
+            "2025-01-01".to_string(),
+            "claude-sonnet-4".to_string(),
+            tokens,
+        )];
+
+        assert_eq!(db.get_usage_scan("/a.jsonl").unwrap(), None);
+        db.record_usage("/a.jsonl", "s1", "/repo", &deltas, 100, Some("msg_1"))
+            .expect("record failed");
+        db.record_usage("/a.jsonl", "s1", "/repo", &deltas, 200, Some("msg_2"))
+            .expect("record failed");
+
+        assert_eq!(
+            db.get_usage_scan("/a.jsonl").unwrap(),
+            Some((200, Some("msg_2".to_string())))
+        );
+        let rows = db.get_usage_rows(None).expect("rows failed");
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].tokens.input_tokens, 20);
+        assert_eq!(rows[0].tokens.cache_read_tokens, 80);
+
+        assert!(db.get_usage_rows(Some("2025-01-02")).unwrap().is_empty());
+    }
+
+    // ── 21. model price overrides round-trip ─────────────────────────
+
+    #[test]
+    fn test_model_prices_round_trip() {
+        let (db, _tmp) = setup();
+        let price = ModelPrice {
+            model: "opus".to_string(),
+            input_per_mtok: 1.0,
+            output_per_mtok: 2.0,
+            cache_write_per_mtok: 3.0,
+            cache_read_per_mtok: 4.0,
+        };
+        db.set_model_price(&price).expect("set failed");
+        assert_eq!(db.get_model_prices().unwrap(), vec![price.clone()]);
+
+        let updated = ModelPrice {
+            input_per_mtok: 5.0,
+            ..price
+        };
+        db.set_model_price(&updated).expect("set failed");
+        assert_eq!(db.get_model_prices().unwrap(), vec![updated]);
+
+        db.delete_model_price("opus").expect("delete failed");
+        assert!(db.get_model_prices().unwrap().is_empty());
+    }
+
+    // ── 22. group budget set, read and clear ─────────────────────────
+
+    #[test]
+    fn test_group_budget_round_trip() {
+        let (db, _tmp) = setup();
+        assert_eq!(db.get_group_budget("/repo").unwrap(), None);
+
+        let budget = GroupBudget {
+            max_tokens: None,
+            max_usd: Some(25.0),
+            max_runtime_mins: Some(120),
+            warn_percent: 75,
+            action: "interrupt".to_string(),
+        };
+        db.set_group_budget("/repo", Some(&budget))
+            .expect("set failed");
+        assert_eq!(db.get_group_budget("/repo").unwrap(), Some(budget.clone()));
+        assert_eq!(db.get_all_group_budgets().unwrap().len(), 1);
+
+        // Other settings are untouched by budget updates
+        assert_eq!(db.get_group_backend("/repo").unwrap(), "local");
+
+        db.set_group_budget("/repo", None).expect("clear failed");
+        assert_eq!(db.get_group_budget("/repo").unwrap(), None);
+        assert!(db.get_all_group_budgets().unwrap().is_empty());
+    }
+
+    // ── 23. group runtime accumulates per day ────────────────────────
+
+    #[test]
+    fn test_group_runtime_accumulates() {
+        let (db, _tmp) = setup();
+        db.add_group_runtime("/repo", "2025-01-01", 60).unwrap();
+        db.add_group_runtime("/repo", "2025-01-01", 30).unwrap();
+        db.add_group_runtime("/repo", "2025-01-02", 5).unwrap();
+        assert_eq!(db.get_group_runtime("2025-01-01").unwrap()["/repo"], 90);
+        assert_eq!(db.get_group_runtime("2025-01-02").unwrap()["/repo"], 5);
+    }
+
+    // ── 24. search index: incremental add, reset and filters ────────
+
+    #[test]
+    fn test_search_index() {
+        let (db, _tmp) = setup();
+        let doc = |session: &str, ts: &str, content: &str| SearchDoc {
+            session_id: session.into(),
+            group_path: "/repo".into(),
+            timestamp: Some(ts.into()),
+            kind: "tool".into(),
+            content: content.into(),
+        };
+
+        assert_eq!(db.get_search_position("/a.jsonl").unwrap(), None);
+        db.index_search_docs(
+            "/a.jsonl",
+            &[doc("s1", "2025-01-01T10:00:00Z", "Edit billing/invoice.rs")],
+            100,
+            false,
+        )
+        .expect("index failed");
+        db.index_search_docs(
+            "/a.jsonl",
+            &[doc("s1", "2025-01-05T10:00:00Z", "Read src/main.rs")],
+            200,
+            false,
+        )
+        .expect("index failed");
+        assert_eq!(db.get_search_position("/a.jsonl").unwrap(), Some(200));
+
+        assert_eq!(db.get_search_accessed("/a.jsonl").unwrap(), None);
+        db.set_search_accessed("/a.jsonl", 1_700_000_000).unwrap();
+        assert_eq!(
+            db.get_search_accessed("/a.jsonl").unwrap(),
+            Some(1_700_000_000)
+        );
+        assert_eq!(db.get_search_position("/a.jsonl").unwrap(), Some(200));
+
+        let hits = db
+            .search_docs("\"billing/invoice.rs\"", None, None, 10)
+            .expect("search failed");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_id, "s1");
+        assert!(hits[0].snippet.contains("«billing"));
+
+        assert!(db
+            .search_docs("invoice", None, Some("2025-01-02"), 10)
+            .unwrap()
+            .is_empty());
+        assert!(db
+            .search_docs("invoice", Some("/other"), None, 10)
+            .unwrap()
+            .is_empty());
+
+        // Reset drops earlier documents from the source
+        db.index_search_docs("/a.jsonl", &[], 0, true).unwrap();
+        assert!(db
+            .search_docs("invoice", None, None, 10)
+            .unwrap()
+            .is_empty());
+    }
+
+    // ── 25. hook signals keep the newest event per session ──────────
+    #[test]
+    fn hook_signals_keep_newest() {
+        let (db, _tmp) = setup();
+        let signal = |event: &str, received_at| HookSignal {
+            event: event.into(),
+            detail: None,
+            received_at,
+        };
+        db.record_hook_signal("c1", &signal("PreToolUse", 100))
+            .unwrap();
+        db.record_hook_signal("c1", &signal("Stop", 200)).unwrap();
+        // An older event arriving late doesn't overwrite the newer one
+        db.record_hook_signal("c1", &signal("PreToolUse", 150))
+            .unwrap();
+        db.record_hook_signal("c2", &signal("Notification", 50))
+            .unwrap();
+
+        let signals = db.get_hook_signals().unwrap();
+        assert_eq!(signals.len(), 2);
+        assert_eq!(signals["c1"], signal("Stop", 200));
+        assert_eq!(signals["c2"].event, "Notification");
+    }
+
+    // ── 26. tool profiles round-trip ─────────────────────────────────
+    #[test]
+    fn tool_profiles_round_trip() {
+        let (db, _tmp) = setup();
+        assert!(db.get_tool_profiles().unwrap().is_empty());
+
+        let mut profile = ToolProfile {
+            name: "codex".into(),
+            binary: "codex".into(),
+            version_args: vec!["--version".into()],
+            required: false,
+            ready_patterns: Vec::new(),
+            waiting_patterns: vec!["$›".into()],
+            permission_patterns: Vec::new(),
+            compacting_patterns: Vec::new(),
+            transcript_location: None,
+            builtin: false,
+        };
+        db.set_tool_profile(&profile).unwrap();
+        profile.binary = "/opt/bin/codex".into();
+        db.set_tool_profile(&profile).unwrap();
+        assert_eq!(db.get_tool_profiles().unwrap(), vec![profile]);
+
+        db.delete_tool_profile("codex").unwrap();
+        assert!(db.get_tool_profiles().unwrap().is_empty());
+    }
+
+    // ── 27. attention thresholds set, read and clear ─────────────────
+    #[test]
+    fn attention_thresholds_round_trip() {
+        let (db, _tmp) = setup();
+        assert_eq!(db.get_attention_thresholds("/repo").unwrap(), None);
+        assert_eq!(
+            db.attention_thresholds_for(Some("/repo")),
+            AttentionThresholds::default()
+        );
+
+        let thresholds = AttentionThresholds {
+            stale_after_secs: 7200,
+            idle_after_secs: Some(300),
+        };
+        db.set_attention_thresholds("/repo", Some(&thresholds))
+            .unwrap();
+        assert_eq!(
+            db.get_attention_thresholds("/repo").unwrap(),
+            Some(thresholds.clone())
+        );
+        assert_eq!(db.attention_thresholds_for(Some("/repo")), thresholds);
+        assert_eq!(db.get_all_attention_thresholds().unwrap().len(), 1);
+
+        db.set_attention_thresholds("/repo", None).unwrap();
+        assert_eq!(db.get_attention_thresholds("/repo").unwrap(), None);
+        assert!(db.get_all_attention_thresholds().unwrap().is_empty());
+    }
+
+    // ── 28. notification settings, mutes and de-dup log ──────────────
+    #[test]
+    fn notification_settings_round_trip() {
+        let (db, _tmp) = setup();
+        assert_eq!(
+            db.get_notification_settings().unwrap(),
+            NotificationSettings::default()
+        );
+        let settings = NotificationSettings {
+            enabled: true,
+            sound: false,
+            quiet_start: Some("22:00".into()),
+            quiet_end: Some("07:30".into()),
+        };
+        db.set_notification_settings(&settings).unwrap();
+        assert_eq!(db.get_notification_settings().unwrap(), settings);
+
+        assert!(!db.get_group_notifications_muted("/repo").unwrap());
+        db.set_group_notifications_muted("/repo", true).unwrap();
+        assert!(db.get_group_notifications_muted("/repo").unwrap());
+
+        assert_eq!(db.get_last_notification("s1").unwrap(), None);
+        db.record_notification("s1", "needs_input", 100).unwrap();
+        db.record_notification("s1", "awaiting_permission", 200)
+            .unwrap();
+        assert_eq!(
+            db.get_last_notification("s1").unwrap(),
+            Some(("awaiting_permission".to_string(), 200))
+        );
+    }
+
+    // ── 29. webhooks and their delivery log ──────────────────────────
+    #[test]
+    fn webhooks_round_trip() {
+        let (db, _tmp) = setup();
+        let mut hook = Webhook {
+            id: None,
+            name: "chat".into(),
+            url: "http://127.0.0.1:9/hook".into(),
+            events: vec!["session.needs_input".into()],
+            body_template: Some(r#"{"text": "{{session_title}}"}"#.into()),
+            enabled: true,
+        };
+        let id = db.save_webhook(&hook).unwrap();
+        hook.id = Some(id);
+        hook.enabled = false;
+        assert_eq!(db.save_webhook(&hook).unwrap(), id);
+        assert_eq!(db.get_webhooks().unwrap(), vec![hook]);
+
+        for attempts in 1..=3 {
+            let delivery = WebhookDelivery {
+                id: 0,
+                webhook_id: id,
+                event: "session.needs_input".into(),
+                delivered: attempts == 3,
+                attempts,
+                status_code: Some(if attempts == 3 { 200 } else { 500 }),
+                error: None,
+                created_at: 100,
+            };
+            db.record_webhook_delivery(&delivery, 2).unwrap();
+        }
+        let log = db.get_webhook_deliveries(Some(id), 10).unwrap();
+        assert_eq!(log.len(), 2);
+        assert!(log[0].delivered);
+        assert_eq!(log[0].attempts, 3);
+        assert!(db
+            .get_webhook_deliveries(Some(id + 1), 10)
+            .unwrap()
+            .is_empty());
+
+        db.delete_webhook(id).unwrap();
+        assert!(db.get_webhooks().unwrap().is_empty());
+        assert!(db.get_webhook_deliveries(None, 10).unwrap().is_empty());
+    }
+
+    // ── 30. automation API settings and token ────────────────────────
+    #[test]
+    fn api_settings_round_trip() {
+        let (db, _tmp) = setup();
+        let defaults = db.get_api_settings().unwrap();
+        assert!(!defaults.enabled);
+        assert_eq!(defaults.transport, "tcp");
+
+        let settings = ApiSettings {
+            enabled: true,
+            transport: "unix".into(),
+            socket_path: Some("/tmp/orca.sock".into()),
+            ..defaults
+        };
+        db.set_api_settings(&settings).unwrap();
+        assert_eq!(db.get_api_settings().unwrap(), settings);
+
+        assert_eq!(db.get_api_token().unwrap(), None);
+        db.set_api_token("abc").unwrap();
+        db.set_api_token("def").unwrap();
+        assert_eq!(db.get_api_token().unwrap().as_deref(), Some("def"));
+    }
+
+    // ── 31. review comments: edit, re-anchor, send and clean up ──────
+    #[test]
+    fn review_comments_round_trip() {
+        let (db, _tmp) = setup();
+        let comment = ReviewComment {
+            id: 0,
+            session_id: "s1".into(),
+            branch: "feature".into(),
+            file_path: "src/main.rs".into(),
+            side: "new".into(),
+            blob_sha: Some("abc".into()),
+            start_line: 3,
+            end_line: 4,
+            snippet: "+let a = 1;\n+let b = 2;".into(),
+            body: "Use a constant".into(),
+            status: "pending".into(),
+            created_at: 100,
+            sent_at: None,
+            outdated: false,
+        };
+        let first = db.add_review_comment(&comment).unwrap();
+        let second = db.add_review_comment(&comment).unwrap();
+        assert!(db
+            .get_review_comments("s1", "main", true)
+            .unwrap()
+            .is_empty());
+
+        db.update_review_comment_body(first, "Use a const").unwrap();
+        db.reanchor_review_comment(first, "def", 7, 8).unwrap();
+        assert!(db.update_review_comment_body(second + 1, "x").is_err());
+        let comments = db.get_review_comments("s1", "feature", false).unwrap();
+        assert_eq!(comments.len(), 2);
+        assert_eq!(comments[0].body, "Use a const");
+        assert_eq!(comments[0].blob_sha.as_deref(), Some("def"));
+        assert_eq!((comments[0].start_line, comments[0].end_line), (7, 8));
+
+        db.mark_review_comments_sent(&[first], 200).unwrap();
+        let pending = db.get_review_comments("s1", "feature", false).unwrap();
+        assert_eq!(pending.iter().map(|c| c.id).collect::<Vec<_>>(), [second]);
+        let all = db.get_review_comments("s1", "feature", true).unwrap();
+        assert_eq!(all[0].status, "sent");
+        assert_eq!(all[0].sent_at, Some(200));
+
+        db.delete_review_comment(second).unwrap();
+        assert_eq!(
+            db.get_review_comments("s1", "feature", true).unwrap().len(),
+            1
+        );
+        db.delete_session_data("s1").unwrap();
+        assert!(db
+            .get_review_comments("s1", "feature", true)
+            .unwrap()
+            .is_empty());
+    }
 }
@@ -1,4 +1,6 @@
 use crate::command::new_command;
+use crate::orca_db::OrcaDb;
+use crate::ssh;
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use portable_pty::{native_pty_system, CommandBuilder, PtySize};
 use std::collections::HashMap;
@@ -45,13 +47,16 @@ pub struct PtyManager {
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub fn attach_pty(
     state: State<'_, PtyManager>,
+    orca_db: State<'_, OrcaDb>,
     session_id: String,
     tmux_session: String,
     cols: u16,
     rows: u16,
     on_output: Channel<String>,
+    group_path: Option<String>,
 ) -> Result<(), String> {
     log::info!("attach_pty: session_id={session_id}, tmux_session={tmux_session}, cols={cols}, rows={rows}");
     let pty_system = native_pty_system();
@@ -70,16 +75,32 @@ pub fn attach_pty(
     // Disable mouse mode so xterm.js handles text selection natively
     // (with mouse on, tmux intercepts selections into its copy buffer
     // instead of the system clipboard, and shows a yellow copy-mode indicator).
-    let _ = new_command("tmux")
-        .args(["set-option", "-t", &tmux_session, "window-size", "latest"])
-        .output();
-    let _ = new_command("tmux")
-        .args(["set-option", "-t", &tmux_session, "mouse", "off"])
-        .output();
-
-    let mut cmd = CommandBuilder::new("tmux");
+    let set_options = [
+        ["set-option", "-t", &tmux_session, "window-size", "latest"],
+        ["set-option", "-t", &tmux_session, "mouse", "off"],
+    ];
+    let attach = ["attach-session", "-t", &tmux_session];
+
+    // Sessions of SSH groups are attached through `ssh -t host tmux attach`.
+    let mut cmd = match ssh::host_for(&orca_db, group_path.as_deref())? {
+        Some(host) => {
+            for args in set_options {
+                let _ = host.run(&[&["tmux"], &args[..]].concat());
+            }
+            let mut cmd = CommandBuilder::new("ssh");
+            cmd.args(host.interactive_args(&[&["tmux"], &attach[..]].concat()));
+            cmd
+        }
+        None => {
+            for args in set_options {
+                let _ = new_command("tmux").args(args).output();
+            }
+            let mut cmd = CommandBuilder::new("tmux");
+            cmd.args(attach);
+            cmd
+        }
+    };
     cmd.env("TERM", "xterm-256color");
-    cmd.args(["attach-session", "-t", &tmux_session]);
 
     let child = pair
         .slave
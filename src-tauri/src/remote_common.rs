@@ -76,7 +76,7 @@ pub fn parse_sse_event(text: &str) -> Option<SseEvent> {
 /// Any previous SSE stream for the same `event_name` is aborted before
 /// starting the new one.
 pub async fn subscribe_sse(
-    app: &tauri::AppHandle,
+    app: &crate::AppHandle,
     handles: &SseHandles,
     client: &reqwest::Client,
     url: &str,
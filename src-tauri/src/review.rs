@@ -0,0 +1,418 @@
+//! Review comments on a session's diff, sent back to its agent.
+//!
+//! The diff viewer stores comments on line ranges in `OrcaDb`, per session
+//! and branch, so they outlive the viewer. A comment on the changed side of a
+//! file records the blob its line numbers refer to. When the file has changed
+//! since (the agent kept working, or the branch was rebased), the commented
+//! lines are looked up in the current file and the comment moves with them;
+//! if they are gone it is kept but marked outdated. Comments on removed lines
+//! keep their line numbers.
+//!
+//! `send_review_comments` compiles the pending comments into one prompt and
+//! sends it through the group's backend: pasted into tmux for local and SSH
+//! sessions, as a message for remote ones.
+
+use crate::backend::backend_for_group;
+use crate::command::run_cmd_on;
+use crate::dates::now_secs;
+use crate::git::spawn_git;
+use crate::models::ReviewComment;
+use crate::orca_db::OrcaDb;
+use crate::ssh::{self, SshHost};
+use serde::Deserialize;
+use tauri::State;
+
+/// A comment as the diff viewer creates it.
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewReviewComment {
+    pub file_path: String,
+    #[serde(default = "default_side")]
+    pub side: String,
+    /// The blob the lines were seen in; None for the working tree.
+    #[serde(default)]
+    pub blob_sha: Option<String>,
+    pub start_line: u32,
+    pub end_line: u32,
+    pub snippet: String,
+    pub body: String,
+}
+
+fn default_side() -> String {
+    "new".to_string()
+}
+
+/// The branch checked out in a worktree; empty without one (remote sessions).
+fn current_branch(host: Option<&SshHost>, worktree_path: Option<&str>) -> Result<String, String> {
+    match worktree_path {
+        Some(path) => Ok(
+            run_cmd_on(host, "git", path, &["rev-parse", "--abbrev-ref", "HEAD"])?
+                .trim()
+                .to_string(),
+        ),
+        None => Ok(String::new()),
+    }
+}
+
+/// The blob SHA of a file as it is in the working tree; None if it's gone.
+fn working_blob(host: Option<&SshHost>, worktree_path: &str, file_path: &str) -> Option<String> {
+    run_cmd_on(
+        host,
+        "git",
+        worktree_path,
+        &["hash-object", "--", file_path],
+    )
+    .ok()
+    .map(|sha| sha.trim().to_string())
+    .filter(|sha| !sha.is_empty())
+}
+
+/// The snippet's lines on the comment's side, without their diff prefixes.
+fn side_lines<'a>(snippet: &'a str, side: &str) -> Vec<&'a str> {
+    let removed = side == "old";
+    snippet
+        .lines()
+        .filter_map(|line| {
+            let mut chars = line.chars();
+            match chars.next() {
+                Some(' ') => Some(chars.as_str()),
+                Some('-') if removed => Some(chars.as_str()),
+                Some('+') if !removed => Some(chars.as_str()),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// The 1-based line where `needle` occurs in `content`, choosing the
+/// occurrence closest to `near` when there are several.
+fn find_lines(content: &str, needle: &[&str], near: u32) -> Option<u32> {
+    if needle.is_empty() {
+        return None;
+    }
+    let lines: Vec<&str> = content.lines().collect();
+    (0..lines.len().saturating_sub(needle.len() - 1))
+        .filter(|&start| {
+            needle
+                .iter()
+                .zip(&lines[start..])
+                .all(|(want, have)| want.trim_end() == have.trim_end())
+        })
+        .map(|start| start as u32 + 1)
+        .min_by_key(|start| start.abs_diff(near))
+}
+
+/// Move comments on changed files to where their lines are now, storing the
+/// new position, or mark them outdated.
+fn reanchor(
+    host: Option<&SshHost>,
+    orca_db: &OrcaDb,
+    worktree_path: &str,
+    comments: &mut [ReviewComment],
+) -> Result<(), String> {
+    for comment in comments.iter_mut().filter(|c| c.side == "new") {
+        let Some(blob) = working_blob(host, worktree_path, &comment.file_path) else {
+            comment.outdated = true;
+            continue;
+        };
+        if comment.blob_sha.as_deref() == Some(blob.as_str()) {
+            continue;
+        }
+        let content = run_cmd_on(host, "cat", worktree_path, &["--", &comment.file_path])?;
+        let needle = side_lines(&comment.snippet, &comment.side);
+        match find_lines(&content, &needle, comment.start_line) {
+            Some(start) => {
+                let end = start + needle.len() as u32 - 1;
+                orca_db.reanchor_review_comment(comment.id, &blob, start, end)?;
+                comment.blob_sha = Some(blob);
+                comment.start_line = start;
+                comment.end_line = end;
+            }
+            None => comment.outdated = true,
+        }
+    }
+    Ok(())
+}
+
+/// A session's pending comments on its current branch, re-anchored.
+fn pending_comments(
+    host: Option<&SshHost>,
+    orca_db: &OrcaDb,
+    session_id: &str,
+    worktree_path: Option<&str>,
+) -> Result<(String, Vec<ReviewComment>), String> {
+    let branch = current_branch(host, worktree_path)?;
+    let mut comments = orca_db.get_review_comments(session_id, &branch, false)?;
+    if let Some(worktree_path) = worktree_path {
+        reanchor(host, orca_db, worktree_path, &mut comments)?;
+    }
+    Ok((branch, comments))
+}
+
+/// The prompt that asks the agent to address `comments`.
+pub fn compile_prompt(branch: &str, comments: &[ReviewComment]) -> String {
+    let mut prompt = if branch.is_empty() {
+        "I have review comments on your changes. Please address each one:".to_string()
+    } else {
+        format!(
+            "I have review comments on the changes in branch `{branch}`. Please address each one:"
+        )
+    };
+    for (i, comment) in comments.iter().enumerate() {
+        let lines = if comment.start_line == comment.end_line {
+            format!("line {}", comment.start_line)
+        } else {
+            format!("lines {}-{}", comment.start_line, comment.end_line)
+        };
+        let lines = if comment.side == "old" {
+            format!("removed {lines}")
+        } else {
+            lines
+        };
+        let note = if comment.outdated {
+            " (the code has changed since this comment)"
+        } else {
+            ""
+        };
+        prompt.push_str(&format!(
+            "\n\n## Comment {}: {}, {lines}{note}\n```diff\n{}\n```\n{}",
+            i + 1,
+            comment.file_path,
+            comment.snippet.trim_end_matches('\n'),
+            comment.body
+        ));
+    }
+    prompt
+}
+
+// --- Tauri Commands ---
+
+/// Store a comment on the session's current branch. `group_path` locates the
+/// worktree on the group's SSH host, if it has one.
+#[tauri::command]
+pub async fn add_review_comment(
+    orca_db: State<'_, OrcaDb>,
+    session_id: String,
+    worktree_path: Option<String>,
+    comment: NewReviewComment,
+    group_path: Option<String>,
+) -> Result<ReviewComment, String> {
+    let body = comment.body.trim().to_string();
+    if body.is_empty() {
+        return Err("Review comment is empty".to_string());
+    }
+    if comment.side != "new" && comment.side != "old" {
+        return Err(format!("Invalid review comment side: {}", comment.side));
+    }
+    if comment.start_line == 0 || comment.end_line < comment.start_line {
+        return Err(format!(
+            "Invalid line range {}-{}",
+            comment.start_line, comment.end_line
+        ));
+    }
+    let orca_db = orca_db.inner().clone();
+    spawn_git(move || {
+        let host = ssh::host_for(&orca_db, group_path.as_deref())?;
+        let worktree_path = worktree_path.as_deref();
+        let blob_sha = match (comment.blob_sha, worktree_path) {
+            (Some(sha), _) => Some(sha),
+            (None, Some(path)) if comment.side == "new" => {
+                working_blob(host.as_ref(), path, &comment.file_path)
+            }
+            (None, _) => None,
+        };
+        let mut stored = ReviewComment {
+            id: 0,
+            session_id,
+            branch: current_branch(host.as_ref(), worktree_path)?,
+            file_path: comment.file_path,
+            side: comment.side,
+            blob_sha,
+            start_line: comment.start_line,
+            end_line: comment.end_line,
+            snippet: comment.snippet,
+            body,
+            status: "pending".to_string(),
+            created_at: now_secs(),
+            sent_at: None,
+            outdated: false,
+        };
+        stored.id = orca_db.add_review_comment(&stored)?;
+        Ok(stored)
+    })
+    .await
+}
+
+/// The session's pending comments, moved to where their lines are now.
+#[tauri::command]
+pub async fn list_review_comments(
+    orca_db: State<'_, OrcaDb>,
+    session_id: String,
+    worktree_path: Option<String>,
+    group_path: Option<String>,
+) -> Result<Vec<ReviewComment>, String> {
+    let orca_db = orca_db.inner().clone();
+    spawn_git(move || {
+        let host = ssh::host_for(&orca_db, group_path.as_deref())?;
+        pending_comments(
+            host.as_ref(),
+            &orca_db,
+            &session_id,
+            worktree_path.as_deref(),
+        )
+        .map(|(_, comments)| comments)
+    })
+    .await
+}
+
+#[tauri::command]
+pub fn update_review_comment(
+    orca_db: State<'_, OrcaDb>,
+    id: i64,
+    body: String,
+) -> Result<(), String> {
+    let body = body.trim();
+    if body.is_empty() {
+        return orca_db.delete_review_comment(id);
+    }
+    orca_db.update_review_comment_body(id, body)
+}
+
+#[tauri::command]
+pub fn delete_review_comment(orca_db: State<'_, OrcaDb>, id: i64) -> Result<(), String> {
+    orca_db.delete_review_comment(id)
+}
+
+/// Send the pending comments to the session as one prompt and mark them
+/// sent, returning how many were sent.
+#[tauri::command]
+pub async fn send_review_comments(
+    orca_db: State<'_, OrcaDb>,
+    session_id: String,
+    worktree_path: Option<String>,
+    group_path: String,
+) -> Result<usize, String> {
+    let db = orca_db.inner().clone();
+    let (id, group) = (session_id.clone(), group_path.clone());
+    let (branch, comments) = spawn_git(move || {
+        let host = ssh::host_for(&db, Some(&group))?;
+        pending_comments(host.as_ref(), &db, &id, worktree_path.as_deref())
+    })
+    .await?;
+    if comments.is_empty() {
+        return Err("No pending review comments".to_string());
+    }
+    let prompt = compile_prompt(&branch, &comments);
+    backend_for_group(&orca_db, &group_path)?
+        .send_message(&session_id, &prompt)
+        .await?;
+    let ids: Vec<i64> = comments.iter().map(|c| c.id).collect();
+    orca_db.mark_review_comments_sent(&ids, now_secs())?;
+    Ok(ids.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comment(start_line: u32, end_line: u32, snippet: &str, body: &str) -> ReviewComment {
+        ReviewComment {
+            id: 1,
+            session_id: "s1".into(),
+            branch: "feature".into(),
+            file_path: "src/main.rs".into(),
+            side: "new".into(),
+            blob_sha: None,
+            start_line,
+            end_line,
+            snippet: snippet.into(),
+            body: body.into(),
+            status: "pending".into(),
+            created_at: 0,
+            sent_at: None,
+            outdated: false,
+        }
+    }
+
+    #[test]
+    fn finds_moved_lines_nearest_the_old_position() {
+        let snippet = " fn a() {\n-    old();\n+    new();\n }";
+        assert_eq!(side_lines(snippet, "new"), ["fn a() {", "    new();", "}"]);
+        assert_eq!(side_lines(snippet, "old"), ["fn a() {", "    old();", "}"]);
+
+        let content = "x\nfn a() {\n    new();\n}\ny\nz\nfn a() {\n    new();\n}\n";
+        let needle = side_lines(snippet, "new");
+        assert_eq!(find_lines(content, &needle, 1), Some(2));
+        assert_eq!(find_lines(content, &needle, 9), Some(7));
+        assert_eq!(find_lines(content, &["gone"], 1), None);
+        assert_eq!(find_lines(content, &[], 1), None);
+        assert_eq!(find_lines("a", &["a", "b"], 1), None);
+    }
+
+    #[test]
+    fn compiles_comments_into_one_prompt() {
+        let mut removed = comment(3, 3, "-let b = 2;", "Why remove this?");
+        removed.side = "old".into();
+        let mut outdated = comment(10, 12, "+a\n+b\n+c\n", "Split this up");
+        outdated.outdated = true;
+        let prompt = compile_prompt(
+            "feature",
+            &[
+                comment(5, 5, "+let a = 1;", "Use a constant"),
+                removed,
+                outdated,
+            ],
+        );
+        assert_eq!(
+            prompt,
+            "I have review comments on the changes in branch `feature`. Please address each one:\
+             \n\n## Comment 1: src/main.rs, line 5\n```diff\n+let a = 1;\n```\nUse a constant\
+             \n\n## Comment 2: src/main.rs, removed line 3\n```diff\n-let b = 2;\n```\nWhy remove this?\
+             \n\n## Comment 3: src/main.rs, lines 10-12 (the code has changed since this comment)\
+             \n```diff\n+a\n+b\n+c\n```\nSplit this up"
+        );
+    }
+
+    #[test]
+    fn reanchors_comments_after_the_file_changes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("repo");
+        std::fs::create_dir(&dir).unwrap();
+        let git = |args: &[&str]| {
+            let output = std::process::Command::new("git")
+                .args(["-c", "user.name=t", "-c", "user.email=t@t"])
+                .args(args)
+                .current_dir(&dir)
+                .output()
+                .expect("git");
+            assert!(output.status.success(), "git {args:?}: {output:?}");
+        };
+        git(&["init", "-q", "-b", "feature"]);
+        std::fs::write(dir.join("a.txt"), "one\ntwo\nthree\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-qm", "init"]);
+        let path = dir.to_str().unwrap();
+        let orca_db = OrcaDb::init(&tmp.path().join("data")).unwrap();
+
+        let mut moved = comment(2, 3, "+two\n+three", "Moves");
+        moved.file_path = "a.txt".into();
+        moved.blob_sha = working_blob(None, path, "a.txt");
+        moved.id = orca_db.add_review_comment(&moved).unwrap();
+        let mut gone = comment(1, 1, "+one", "Goes");
+        gone.file_path = moved.file_path.clone();
+        gone.blob_sha = moved.blob_sha.clone();
+        orca_db.add_review_comment(&gone).unwrap();
+
+        std::fs::write(dir.join("a.txt"), "zero\nhalf\ntwo\nthree\n").unwrap();
+        let (branch, comments) = pending_comments(None, &orca_db, "s1", Some(path)).unwrap();
+        assert_eq!(branch, "feature");
+        assert_eq!((comments[0].start_line, comments[0].end_line), (3, 4));
+        assert!(!comments[0].outdated);
+        assert!(comments[1].outdated);
+
+        // The new position is stored, and the outdated comment keeps its lines.
+        let stored = orca_db.get_review_comments("s1", "feature", false).unwrap();
+        assert_eq!(stored[0].start_line, 3);
+        assert_eq!(stored[0].blob_sha, working_blob(None, path, "a.txt"));
+        assert_eq!(stored[1].start_line, 1);
+    }
+}
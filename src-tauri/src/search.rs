@@ -0,0 +1,338 @@
+//! Full-text search across session transcripts.
+//!
+//! Prompts, assistant text and tool calls (tool name plus file paths,
+//! commands and patterns from the input) are indexed into an FTS5 table in
+//! `OrcaDb`. Local JSONL transcripts are indexed by byte offset and remote
+//! sessions by message count, so each search only indexes what was added
+//! since the last one. Remote sessions that haven't been accessed since are
+//! not fetched at all.
+
+use crate::agentdeck;
+use crate::backend::backend_for_group;
+use crate::claude_logs::find_jsonl_path;
+use crate::models::Session;
+use crate::orca_db::{OrcaDb, SearchDoc};
+use crate::transcript::{self, TranscriptBlock, TranscriptEntry};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use tauri::State;
+
+const DEFAULT_LIMIT: usize = 50;
+const MAX_LIMIT: usize = 500;
+/// Tool input fields holding file contents or patches rather than names.
+const SKIPPED_INPUT_KEYS: &[&str] = &["content", "old_string", "new_string", "edits", "todos"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub session_id: String,
+    pub group_path: String,
+    /// None once the session has been removed, or for remote sessions.
+    pub title: Option<String>,
+    pub timestamp: Option<String>,
+    /// "prompt", "assistant" or "tool".
+    pub kind: String,
+    /// Matching excerpt with matched terms wrapped in « ».
+    pub snippet: String,
+}
+
+/// Searchable text from a tool input: every short string field, skipping
+/// file contents and patches.
+fn tool_input_terms(input: &serde_json::Value) -> Vec<&str> {
+    let Some(fields) = input.as_object() else {
+        return Vec::new();
+    };
+    fields
+        .iter()
+        .filter(|(key, _)| !SKIPPED_INPUT_KEYS.contains(&key.as_str()))
+        .filter_map(|(_, value)| value.as_str())
+        .collect()
+}
+
+fn documents(entry: &TranscriptEntry, session_id: &str, group_path: &str) -> Vec<SearchDoc> {
+    entry
+        .blocks
+        .iter()
+        .filter_map(|block| {
+            let (kind, content) = match (entry.role.as_str(), block) {
+                ("user", TranscriptBlock::Text { text }) => ("prompt", text.clone()),
+                ("assistant", TranscriptBlock::Text { text }) => ("assistant", text.clone()),
+                (_, TranscriptBlock::ToolUse { name, input, .. }) => {
+                    let mut terms = vec![name.as_str()];
+                    terms.extend(tool_input_terms(input));
+                    ("tool", terms.join(" "))
+                }
+                _ => return None,
+            };
+            if content.trim().is_empty() {
+                return None;
+            }
+            Some(SearchDoc {
+                session_id: session_id.to_string(),
+                group_path: group_path.to_string(),
+                timestamp: entry.timestamp.clone(),
+                kind: kind.to_string(),
+                content,
+            })
+        })
+        .collect()
+}
+
+/// Index complete lines appended to a JSONL transcript since the last call.
+fn index_file(
+    orca_db: &OrcaDb,
+    path: &Path,
+    session_id: &str,
+    group_path: &str,
+) -> Result<(), String> {
+    let source = path.to_string_lossy().to_string();
+    let len = std::fs::metadata(path)
+        .map_err(|e| format!("Failed to stat {source}: {e}"))?
+        .len();
+    let position = orca_db.get_search_position(&source)?.unwrap_or(0);
+    // A transcript that shrank was rewritten; index it again from the start.
+    let reset = len < position;
+    let position = if reset { 0 } else { position };
+    if len == position && !reset {
+        return Ok(());
+    }
+
+    let page = transcript::read_page(path, position, usize::MAX)?;
+    let docs: Vec<SearchDoc> = page
+        .entries
+        .iter()
+        .flat_map(|entry| documents(entry, session_id, group_path))
+        .collect();
+    orca_db.index_search_docs(&source, &docs, page.next_cursor, reset)
+}
+
+fn index_local_session(orca_db: &OrcaDb, session: &Session) -> Result<(), String> {
+    let Some(csid) = session.claude_session_id.as_deref() else {
+        return Ok(());
+    };
+    let Some(path) = find_jsonl_path(&session.project_path, csid) else {
+        return Ok(());
+    };
+    index_file(orca_db, &path, &session.id, &session.group_path)
+}
+
+/// Bring the index up to date for every agent-deck session.
+fn refresh_local_index(orca_db: &OrcaDb) -> Result<Vec<Session>, String> {
+    let sessions = agentdeck::list_sessions_with_prompts(orca_db, None)?;
+    for session in &sessions {
+        if let Err(e) = index_local_session(orca_db, session) {
+            log::warn!("search: failed to index session {}: {e}", session.id);
+        }
+    }
+    Ok(sessions)
+}
+
+/// Bring the index up to date for sessions in remote-backend groups. An
+/// unreachable server is logged and skipped so local search still works.
+/// Sessions whose `last_accessed` hasn't moved since they were last indexed
+/// aren't fetched again; 0 means the backend doesn't report it.
+async fn refresh_remote_index(orca_db: &OrcaDb) -> Result<(), String> {
+    for (group_path, settings) in orca_db.get_all_group_settings()? {
+        if settings.backend == "local" {
+            continue;
+        }
+        let result: Result<(), String> = async {
+            let backend = backend_for_group(orca_db, &group_path)?;
+            for session in backend.list_sessions().await? {
+                let source = format!("{}:{group_path}:{}", settings.backend, session.id);
+                let accessed = session.last_accessed;
+                if accessed != 0 && orca_db.get_search_accessed(&source)? == Some(accessed) {
+                    continue;
+                }
+                let position = orca_db.get_search_position(&source)?.unwrap_or(0) as usize;
+                let entries = backend.get_transcript(&session.id).await?;
+                let reset = entries.len() < position;
+                let start = if reset { 0 } else { position };
+                if start < entries.len() || reset {
+                    let docs: Vec<SearchDoc> = entries[start..]
+                        .iter()
+                        .flat_map(|entry| documents(entry, &session.id, &group_path))
+                        .collect();
+                    orca_db.index_search_docs(&source, &docs, entries.len() as u64, reset)?;
+                }
+                orca_db.set_search_accessed(&source, accessed)?;
+            }
+            Ok(())
+        }
+        .await;
+        if let Err(e) = result {
+            log::warn!("search: failed to index remote group {group_path}: {e}");
+        }
+    }
+    Ok(())
+}
+
+/// Turn user input into an FTS5 query: each whitespace-separated term is
+/// quoted (so paths like `billing/invoice.rs` match as a phrase) and a
+/// trailing `*` keeps prefix matching.
+fn fts_query(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .filter_map(|term| {
+            let (term, prefix) = match term.strip_suffix('*') {
+                Some(stem) => (stem, "*"),
+                None => (term, ""),
+            };
+            if term.is_empty() {
+                return None;
+            }
+            Some(format!("\"{}\"{prefix}", term.replace('"', "\"\"")))
+        })
+        .collect();
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+/// Search transcripts for sessions matching all terms in `query`, optionally
+/// limited to a group and to entries on or after `since` (YYYY-MM-DD).
+#[tauri::command]
+pub async fn search_sessions(
+    orca_db: State<'_, OrcaDb>,
+    query: String,
+    group_path: Option<String>,
+    since: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<SearchHit>, String> {
+    let match_query = fts_query(&query).ok_or("Search query is empty")?;
+    let orca_db = orca_db.inner().clone();
+    refresh_remote_index(&orca_db).await?;
+
+    tokio::task::spawn_blocking(move || {
+        let titles: HashMap<String, String> = refresh_local_index(&orca_db)?
+            .into_iter()
+            .map(|s| (s.id, s.title))
+            .collect();
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+        let rows =
+            orca_db.search_docs(&match_query, group_path.as_deref(), since.as_deref(), limit)?;
+        Ok(rows
+            .into_iter()
+            .map(|row| SearchHit {
+                title: titles.get(&row.session_id).cloned(),
+                session_id: row.session_id,
+                group_path: row.group_path,
+                timestamp: row.timestamp,
+                kind: row.kind,
+                snippet: row.snippet,
+            })
+            .collect())
+    })
+    .await
+    .map_err(|e| format!("Task failed: {e}"))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::Write;
+
+    fn append(path: &Path, lines: &[serde_json::Value]) {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .expect("open failed");
+        for line in lines {
+            writeln!(file, "{line}").expect("write failed");
+        }
+    }
+
+    fn tool_line(name: &str, input: serde_json::Value, ts: &str) -> serde_json::Value {
+        json!({
+            "type": "assistant",
+            "timestamp": ts,
+            "message": {"role": "assistant", "content": [
+                {"type": "tool_use", "id": "t1", "name": name, "input": input}
+            ]}
+        })
+    }
+
+    #[test]
+    fn fts_query_quotes_terms() {
+        assert_eq!(
+            fts_query("billing/invoice.rs  fix"),
+            Some("\"billing/invoice.rs\" \"fix\"".into())
+        );
+        assert_eq!(fts_query("inv*"), Some("\"inv\"*".into()));
+        assert_eq!(
+            fts_query("say \"hi\""),
+            Some("\"say\" \"\"\"hi\"\"\"".into())
+        );
+        assert_eq!(fts_query("  * "), None);
+    }
+
+    #[test]
+    fn tool_documents_skip_file_contents() {
+        let entry = transcript::remote_entry(
+            "m1",
+            "assistant",
+            "tool",
+            &json!({"file_path": "/src/a.rs", "old_string": "secret", "new_string": "x"}),
+            Some("Edit"),
+            None,
+            None,
+        );
+        let docs = documents(&entry, "s1", "/repo");
+        assert_eq!(docs.len(), 1);
+        assert_eq!(docs[0].kind, "tool");
+        assert_eq!(docs[0].content, "Edit /src/a.rs");
+    }
+
+    #[test]
+    fn indexes_incrementally() {
+        let tmp = tempfile::tempdir().expect("failed to create temp dir");
+        let db = OrcaDb::init(tmp.path()).expect("init failed");
+        let path = tmp.path().join("s.jsonl");
+        append(
+            &path,
+            &[
+                json!({"type": "user", "timestamp": "2025-01-01T09:00:00Z",
+                       "message": {"role": "user", "content": "Fix the invoice totals"}}),
+                tool_line(
+                    "Edit",
+                    json!({"file_path": "billing/invoice.rs"}),
+                    "2025-01-01T09:01:00Z",
+                ),
+            ],
+        );
+        index_file(&db, &path, "s1", "/repo").expect("index failed");
+
+        let hits = db
+            .search_docs(&fts_query("billing/invoice.rs").unwrap(), None, None, 10)
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].kind, "tool");
+        assert_eq!(hits[0].timestamp.as_deref(), Some("2025-01-01T09:01:00Z"));
+
+        // Re-indexing an unchanged file adds nothing
+        index_file(&db, &path, "s1", "/repo").expect("index failed");
+        let query = fts_query("invoice").unwrap();
+        assert_eq!(db.search_docs(&query, None, None, 10).unwrap().len(), 2);
+
+        append(
+            &path,
+            &[tool_line(
+                "Bash",
+                json!({"command": "cargo test invoice"}),
+                "2025-01-02T09:00:00Z",
+            )],
+        );
+        index_file(&db, &path, "s1", "/repo").expect("index failed");
+        assert_eq!(db.search_docs(&query, None, None, 10).unwrap().len(), 3);
+        assert_eq!(
+            db.search_docs(&query, None, Some("2025-01-02"), 10)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,426 @@
+//! Running commands on another machine over SSH.
+//!
+//! Groups with the `ssh` backend (see `backend::ssh`) keep their agent-deck
+//! sessions, tmux server, worktrees and Claude logs on a remote host. The
+//! group's server URL names it, as `[user@]host` (a `~/.ssh/config` alias
+//! works) or `ssh://[user@]host[:port]`, optionally followed by
+//! `?profile=<name>` to use one of the host's agent-deck profiles.
+//!
+//! Everything goes through the system `ssh`, so keys, agents and config
+//! apply as in a terminal. Connections are multiplexed (`ControlMaster`), so
+//! the many short commands Orca runs reuse one login; the control sockets
+//! live in a private directory in Orca's data directory. Non-interactive
+//! commands use `BatchMode`: a host that needs a password fails instead of
+//! hanging.
+
+use crate::command::{new_command, shell_quote};
+use crate::orca_db::OrcaDb;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+/// How long idle multiplexed connections stay open.
+const CONTROL_PERSIST: &str = "ControlPersist=120";
+const CONNECT_TIMEOUT: &str = "ConnectTimeout=10";
+/// Longest control socket directory: a socket path can't exceed 104 bytes
+/// (macOS), and `%C` expands to 40 characters after the separator.
+const MAX_CONTROL_DIR_LEN: usize = 104 - 1 - 40 - 1;
+
+static CONTROL_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Create the private directory for multiplexed connections' control
+/// sockets. Call once at startup; until then connections aren't shared.
+pub fn init(app_data_dir: &Path) -> Result<(), String> {
+    let dir = app_data_dir.join("ssh");
+    let path = dir.to_string_lossy();
+    if path.len() > MAX_CONTROL_DIR_LEN || path.contains('"') {
+        return Err(format!(
+            "{path} can't hold SSH control sockets; SSH connections won't be shared"
+        ));
+    }
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {path}: {e}"))?;
+    // Anyone who can reach a control socket can run commands on the host.
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o700))
+            .map_err(|e| format!("Failed to restrict {path}: {e}"))?;
+    }
+    let _ = CONTROL_DIR.set(dir);
+    Ok(())
+}
+
+/// The `ControlPath` option for sockets in `dir`. `%` starts an ssh token
+/// and the value may contain spaces, so it is escaped and quoted.
+fn control_path_option(dir: &Path) -> String {
+    let dir = dir.to_string_lossy().replace('%', "%%");
+    format!("ControlPath=\"{dir}/%C\"")
+}
+
+/// A profile name, as agent-deck keeps it in `~/.agent-deck/profiles/`.
+fn is_profile_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SshHost {
+    destination: String,
+    port: Option<u16>,
+    /// The host's agent-deck profile; None for its default one.
+    profile: Option<String>,
+}
+
+impl SshHost {
+    pub fn parse(url: &str) -> Result<SshHost, String> {
+        let url = url.trim();
+        let (address, profile) = match url.split_once('?') {
+            Some((address, query)) => {
+                let profile = query
+                    .strip_prefix("profile=")
+                    .filter(|name| is_profile_name(name))
+                    .ok_or_else(|| {
+                        format!("Invalid SSH host options in '{url}' (expected ?profile=<name>)")
+                    })?;
+                (address, Some(profile.to_string()))
+            }
+            None => (url, None),
+        };
+        let (destination, port) = match address.strip_prefix("ssh://") {
+            Some(rest) => {
+                let rest = rest.trim_end_matches('/');
+                match rest.rsplit_once(':') {
+                    Some((destination, port)) => {
+                        let port = port
+                            .parse::<u16>()
+                            .ok()
+                            .filter(|p| *p != 0)
+                            .ok_or_else(|| format!("Invalid SSH port in '{url}'"))?;
+                        (destination, Some(port))
+                    }
+                    None => (rest, None),
+                }
+            }
+            None if address.contains("://") => {
+                return Err(format!(
+                    "'{url}' is not an SSH host (expected user@host or ssh://user@host:port)"
+                ))
+            }
+            None => (address, None),
+        };
+        // A leading '-' would be read as an ssh option.
+        if destination.is_empty()
+            || destination.starts_with('-')
+            || destination.contains(|c: char| c.is_whitespace() || c == '/')
+        {
+            return Err(format!("Invalid SSH host: '{url}'"));
+        }
+        Ok(SshHost {
+            destination: destination.to_string(),
+            port,
+            profile,
+        })
+    }
+
+    /// `[user@]host`, for messages and cache keys.
+    pub fn destination(&self) -> &str {
+        &self.destination
+    }
+
+    /// agent-deck's DB on the host, relative to the remote home directory.
+    pub fn agent_deck_db(&self) -> String {
+        let profile = self.profile.as_deref().unwrap_or("default");
+        format!(".agent-deck/profiles/{profile}/state.db")
+    }
+
+    /// Run the host's `agent-deck` with `args`, in the host's profile.
+    pub fn agent_deck(&self, args: &[&str]) -> Result<String, String> {
+        let mut argv = vec!["agent-deck"];
+        if let Some(profile) = &self.profile {
+            argv.extend(["-p", profile]);
+        }
+        argv.extend_from_slice(args);
+        self.run(&argv)
+    }
+
+    fn options(&self) -> Vec<String> {
+        let mut options = vec!["-o".to_string(), CONNECT_TIMEOUT.to_string()];
+        if let Some(dir) = CONTROL_DIR.get() {
+            options.extend([
+                "-o".to_string(),
+                "ControlMaster=auto".to_string(),
+                "-o".to_string(),
+                control_path_option(dir),
+                "-o".to_string(),
+                CONTROL_PERSIST.to_string(),
+            ]);
+        }
+        if let Some(port) = self.port {
+            options.push("-p".to_string());
+            options.push(port.to_string());
+        }
+        options
+    }
+
+    /// An `ssh` command running `argv` on the host, in `cwd` when given.
+    pub fn command(&self, cwd: Option<&str>, argv: &[&str]) -> Command {
+        let mut command = new_command("ssh");
+        command
+            .args(["-o", "BatchMode=yes"])
+            .args(self.options())
+            .arg("--")
+            .arg(&self.destination)
+            .arg(remote_command_line(cwd, argv));
+        command
+    }
+
+    /// Run `argv` on the host, returning stdout, or stderr as the error.
+    pub fn run(&self, argv: &[&str]) -> Result<String, String> {
+        self.run_bytes(argv)
+            .map(|stdout| String::from_utf8_lossy(&stdout).to_string())
+    }
+
+    /// Run `argv` in `cwd` on the host with `input` on its stdin.
+    pub fn run_with_input(&self, cwd: &str, argv: &[&str], input: &[u8]) -> Result<String, String> {
+        log::info!("ssh {}: {} (cwd: {cwd})", self.destination, argv.join(" "));
+        let mut child = self
+            .command(Some(cwd), argv)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to run ssh: {e}"))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(input)
+                .map_err(|e| format!("Failed to write to ssh: {e}"))?;
+        }
+        let output = child
+            .wait_with_output()
+            .map_err(|e| format!("Failed to run ssh: {e}"))?;
+        if !output.status.success() {
+            return Err(self.failure(argv, &output.stderr));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    /// [`SshHost::run`], with stdout as it came.
+    pub fn run_bytes(&self, argv: &[&str]) -> Result<Vec<u8>, String> {
+        log::info!("ssh {}: {}", self.destination, argv.join(" "));
+        let output = self
+            .command(None, argv)
+            .output()
+            .map_err(|e| format!("Failed to run ssh: {e}"))?;
+        if !output.status.success() {
+            return Err(self.failure(argv, &output.stderr));
+        }
+        Ok(output.stdout)
+    }
+
+    fn failure(&self, argv: &[&str], stderr: &[u8]) -> String {
+        let stderr = String::from_utf8_lossy(stderr);
+        log::error!(
+            "ssh {}: {} failed: {}",
+            self.destination,
+            argv.join(" "),
+            stderr.trim()
+        );
+        format!(
+            "{} on {} failed: {}",
+            argv.first().unwrap_or(&"command"),
+            self.destination,
+            stderr.trim()
+        )
+    }
+
+    /// Arguments for an interactive `ssh -t` running `argv`, for a terminal.
+    pub fn interactive_args(&self, argv: &[&str]) -> Vec<String> {
+        let mut args = vec!["-t".to_string()];
+        args.extend(self.options());
+        args.push("--".to_string());
+        args.push(self.destination.clone());
+        args.push(remote_command_line(None, argv));
+        args
+    }
+}
+
+/// The shell command line the remote side runs. `~/` in `cwd` is left for
+/// the remote shell to expand.
+fn remote_command_line(cwd: Option<&str>, argv: &[&str]) -> String {
+    let command = argv
+        .iter()
+        .map(|arg| shell_quote(arg))
+        .collect::<Vec<_>>()
+        .join(" ");
+    match cwd {
+        Some(cwd) => {
+            let dir = match cwd.strip_prefix("~/") {
+                Some(rest) => format!("~/{}", shell_quote(rest)),
+                None => shell_quote(cwd),
+            };
+            format!("cd {dir} && {command}")
+        }
+        None => command,
+    }
+}
+
+/// The SSH host of a group using the `ssh` backend; None for other backends.
+pub fn host_for_group(orca_db: &OrcaDb, group_path: &str) -> Result<Option<SshHost>, String> {
+    if orca_db.get_group_backend(group_path)? != "ssh" {
+        return Ok(None);
+    }
+    let (server_url, _) = orca_db.resolve_server_credentials(group_path)?;
+    let url = server_url
+        .filter(|u| !u.is_empty())
+        .ok_or_else(|| format!("No SSH host configured for group {group_path}"))?;
+    SshHost::parse(&url).map(Some)
+}
+
+/// [`host_for_group`] for commands whose group is optional (None: local).
+pub fn host_for(orca_db: &OrcaDb, group_path: Option<&str>) -> Result<Option<SshHost>, String> {
+    match group_path {
+        Some(group_path) => host_for_group(orca_db, group_path),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hosts() {
+        let host = SshHost::parse("dev@devbox").unwrap();
+        assert_eq!(host.destination(), "dev@devbox");
+        assert_eq!(host.port, None);
+
+        let host = SshHost::parse("ssh://dev@devbox:2222/").unwrap();
+        assert_eq!(host.destination(), "dev@devbox");
+        assert_eq!(host.port, Some(2222));
+        assert_eq!(
+            host.agent_deck_db(),
+            ".agent-deck/profiles/default/state.db"
+        );
+
+        let host = SshHost::parse("ssh://devbox:2222?profile=work").unwrap();
+        assert_eq!((host.destination(), host.port), ("devbox", Some(2222)));
+        assert_eq!(host.agent_deck_db(), ".agent-deck/profiles/work/state.db");
+        let host = SshHost::parse("dev@devbox?profile=team-a").unwrap();
+        assert_eq!(host.profile.as_deref(), Some("team-a"));
+
+        for bad in [
+            "",
+            "-oProxyCommand=evil",
+            "ssh://-x",
+            "http://devbox:4096",
+            "ssh://devbox:ssh",
+            "dev box",
+            "devbox?profile=",
+            "devbox?profile=../x",
+            "devbox?user=me",
+        ] {
+            assert!(SshHost::parse(bad).is_err(), "{bad} should be rejected");
+        }
+    }
+
+    #[test]
+    fn quotes_remote_commands() {
+        assert_eq!(
+            remote_command_line(None, &["tmux", "has-session", "-t", "it's"]),
+            "'tmux' 'has-session' '-t' 'it'\\''s'"
+        );
+        assert_eq!(
+            remote_command_line(Some("~/src/my repo"), &["git", "status"]),
+            "cd ~/'src/my repo' && 'git' 'status'"
+        );
+    }
+
+    #[test]
+    fn builds_ssh_arguments() {
+        let host = SshHost::parse("ssh://devbox:2222").unwrap();
+        let command = host.command(None, &["true"]);
+        let args: Vec<String> = command
+            .get_args()
+            .map(|a| a.to_string_lossy().to_string())
+            .collect();
+        assert_eq!(&args[..2], ["-o", "BatchMode=yes"]);
+        assert!(args.windows(2).any(|w| w == ["-p", "2222"]));
+        assert_eq!(&args[args.len() - 3..], ["--", "devbox", "'true'"]);
+
+        let interactive = host.interactive_args(&["tmux", "attach-session", "-t", "s"]);
+        assert_eq!(interactive[0], "-t");
+        assert!(!interactive.iter().any(|a| a == "BatchMode=yes"));
+    }
+
+    #[test]
+    fn quotes_control_paths() {
+        assert_eq!(
+            control_path_option(Path::new("/Users/me/Library/Application Support/orca/ssh")),
+            "ControlPath=\"/Users/me/Library/Application Support/orca/ssh/%C\""
+        );
+        assert_eq!(
+            control_path_option(Path::new("/home/100%/ssh")),
+            "ControlPath=\"/home/100%%/ssh/%C\""
+        );
+    }
+
+    /// Needs an sshd on this machine that takes the user's key without a
+    /// prompt. `ORCA_TEST_SSH_HOST` overrides the host (default `localhost`);
+    /// it must share this machine's filesystem.
+    #[test]
+    #[ignore = "needs a local sshd: cargo test -- --ignored over_localhost_ssh"]
+    fn runs_git_over_localhost_ssh() {
+        use crate::command::run_cmd_on;
+
+        let data = tempfile::tempdir().unwrap();
+        init(data.path()).unwrap();
+        let url = std::env::var("ORCA_TEST_SSH_HOST").unwrap_or_else(|_| "localhost".into());
+        let host = SshHost::parse(&url).unwrap();
+
+        assert_eq!(
+            host.run(&["printf", "%s", "it's $HOME"]).unwrap(),
+            "it's $HOME"
+        );
+        let echoed = host
+            .run_with_input("/", &["sh", "-c", "cat"], b"from stdin")
+            .unwrap();
+        assert_eq!(echoed, "from stdin");
+
+        let repo = tempfile::tempdir().unwrap();
+        let path = repo.path().join("my repo");
+        std::fs::create_dir(&path).unwrap();
+        let path = path.to_string_lossy().to_string();
+        let git = |args: &[&str]| run_cmd_on(Some(&host), "git", &path, args).unwrap();
+        git(&["init", "-q", "-b", "main"]);
+        git(&[
+            "-c",
+            "user.name=t",
+            "-c",
+            "user.email=t@t",
+            "commit",
+            "-q",
+            "--allow-empty",
+            "-m",
+            "one",
+        ]);
+        assert_eq!(
+            crate::git::get_default_branch_on(Some(&host), &path).unwrap(),
+            "main"
+        );
+        assert_eq!(git(&["status", "--porcelain"]), "");
+
+        // The connection is shared through a socket only this user can reach
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let dir = data.path().join("ssh");
+            let mode = std::fs::metadata(&dir).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o700);
+            assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        }
+    }
+}
@@ -1,5 +1,22 @@
+use crate::claude_logs::AttentionStatus;
 use crate::command::new_command;
-use tauri::command;
+use crate::models::ToolProfile;
+use crate::orca_db::OrcaDb;
+use crate::ssh::{self, SshHost};
+use std::process::Output;
+use tauri::{command, State};
+
+/// Run tmux here, or on an SSH host when `host` is set.
+fn tmux_output(host: Option<&SshHost>, args: &[&str]) -> std::io::Result<Output> {
+    match host {
+        Some(host) => {
+            let mut argv = vec!["tmux"];
+            argv.extend_from_slice(args);
+            host.command(None, &argv).output()
+        }
+        None => new_command("tmux").args(args).output(),
+    }
+}
 
 /// Paste text into a tmux pane using bracketed paste mode.
 ///
@@ -11,9 +28,11 @@ use tauri::command;
 /// the pasted text (e.g. to a Claude Code prompt).
 #[command]
 pub fn paste_to_tmux_pane(
+    orca_db: State<'_, OrcaDb>,
     tmux_session: String,
     text: String,
     submit: Option<bool>,
+    group_path: Option<String>,
 ) -> Result<(), String> {
     log::info!(
         "paste_to_tmux_pane: tmux_session={tmux_session}, text_len={}, submit={:?}",
@@ -21,10 +40,11 @@ pub fn paste_to_tmux_pane(
         submit
     );
 
+    let host = ssh::host_for(&orca_db, group_path.as_deref())?;
     if submit.unwrap_or(false) {
-        paste_and_submit(&tmux_session, &text)
+        paste_and_submit_on(host.as_ref(), &tmux_session, &text)
     } else {
-        bracketed_paste(&tmux_session, &text)
+        bracketed_paste(host.as_ref(), &tmux_session, &text)
     }
 }
 
@@ -32,7 +52,13 @@ pub fn paste_to_tmux_pane(
 ///
 /// Uses `-e` flag so copy mode auto-exits when scrolled back to the bottom.
 #[command]
-pub fn scroll_tmux_pane(tmux_session: String, direction: String, lines: u32) -> Result<(), String> {
+pub fn scroll_tmux_pane(
+    orca_db: State<'_, OrcaDb>,
+    tmux_session: String,
+    direction: String,
+    lines: u32,
+    group_path: Option<String>,
+) -> Result<(), String> {
     log::debug!(
         "scroll_tmux_pane: tmux_session={tmux_session}, direction={direction}, lines={lines}"
     );
@@ -41,12 +67,10 @@ pub fn scroll_tmux_pane(tmux_session: String, direction: String, lines: u32) ->
             "Invalid scroll direction: '{direction}' (expected 'up' or 'down')"
         ));
     }
+    let host = ssh::host_for(&orca_db, group_path.as_deref())?;
     if direction == "up" {
         // Enter copy mode with auto-exit at bottom (-e)
-        if let Err(e) = new_command("tmux")
-            .args(["copy-mode", "-t", &tmux_session, "-e"])
-            .output()
-        {
+        if let Err(e) = tmux_output(host.as_ref(), &["copy-mode", "-t", &tmux_session, "-e"]) {
             log::warn!("tmux copy-mode failed for '{tmux_session}': {e}");
         }
     }
@@ -56,8 +80,9 @@ pub fn scroll_tmux_pane(tmux_session: String, direction: String, lines: u32) ->
     } else {
         "scroll-down"
     };
-    if let Err(e) = new_command("tmux")
-        .args([
+    if let Err(e) = tmux_output(
+        host.as_ref(),
+        &[
             "send-keys",
             "-t",
             &tmux_session,
@@ -65,61 +90,78 @@ pub fn scroll_tmux_pane(tmux_session: String, direction: String, lines: u32) ->
             "-N",
             &lines.to_string(),
             scroll_cmd,
-        ])
-        .output()
-    {
+        ],
+    ) {
         log::warn!("tmux scroll ({scroll_cmd}) failed for '{tmux_session}': {e}");
     }
 
     Ok(())
 }
 
-/// Check if a tmux session exists (is alive).
-pub fn is_tmux_session_alive(tmux_session: &str) -> bool {
-    let ok = new_command("tmux")
-        .args(["has-session", "-t", tmux_session])
-        .output()
+/// Check if a tmux session exists (is alive), here or on `host`.
+pub fn is_tmux_session_alive(host: Option<&SshHost>, tmux_session: &str) -> bool {
+    let ok = tmux_output(host, &["has-session", "-t", tmux_session])
         .map(|o| o.status.success())
         .unwrap_or(false);
     log::debug!("is_tmux_session_alive: tmux_session={tmux_session}, result={ok}");
     ok
 }
 
-/// Check if a tmux session is showing a Claude Code input prompt.
-/// Captures the last 20 lines of the pane and looks for permission prompts
-/// ("Do you want to proceed?") or Claude Code's general input prompt (❯ / >).
-pub fn is_waiting_for_input(tmux_session: &str) -> bool {
-    let output = match new_command("tmux")
-        .args(["capture-pane", "-t", tmux_session, "-p", "-l", "20"])
-        .output()
-    {
-        Ok(o) if o.status.success() => o,
-        _ => return false,
-    };
+/// The visible text of a tmux pane, or its last `lines` lines. None if the
+/// session doesn't exist (yet).
+pub(crate) fn capture_pane(
+    host: Option<&SshHost>,
+    tmux_session: &str,
+    lines: Option<u32>,
+) -> Option<String> {
+    let lines = lines.map(|n| n.to_string());
+    let mut args = vec!["capture-pane", "-t", tmux_session, "-p"];
+    if let Some(lines) = &lines {
+        args.extend(["-l", lines]);
+    }
+    match tmux_output(host, &args) {
+        Ok(o) if o.status.success() => Some(String::from_utf8_lossy(&o.stdout).to_string()),
+        _ => None,
+    }
+}
 
-    let text = String::from_utf8_lossy(&output.stdout);
-    let waiting = pane_shows_input_prompt(&text);
+/// Check what a tmux session's pane shows: a permission prompt, compaction
+/// or the tool's input prompt. Captures the last 20 lines of the pane and
+/// matches them against the tool's profile.
+pub fn pane_attention(tmux_session: &str, profile: &ToolProfile) -> Option<AttentionStatus> {
+    pane_attention_on(None, tmux_session, profile)
+}
 
-    log::debug!("is_waiting_for_input: tmux_session={tmux_session}, result={waiting}");
-    waiting
+pub(crate) fn pane_attention_on(
+    host: Option<&SshHost>,
+    tmux_session: &str,
+    profile: &ToolProfile,
+) -> Option<AttentionStatus> {
+    let text = capture_pane(host, tmux_session, Some(20))?;
+    let attention = profile.pane_attention(&text);
+
+    log::debug!(
+        "pane_attention: tmux_session={tmux_session}, tool={}, result={attention:?}",
+        profile.name
+    );
+    attention
 }
 
 /// Paste text into a tmux pane using bracketed paste mode.
 ///
 /// Uses `tmux set-buffer` + `paste-buffer -p` so the text arrives as a
 /// single bracketed paste event rather than individual keystrokes.
-fn bracketed_paste(tmux_session: &str, text: &str) -> Result<(), String> {
-    let status = new_command("tmux")
-        .args(["set-buffer", "-b", "_orca", "--", text])
-        .status()
+fn bracketed_paste(host: Option<&SshHost>, tmux_session: &str, text: &str) -> Result<(), String> {
+    let output = tmux_output(host, &["set-buffer", "-b", "_orca", "--", text])
         .map_err(|e| format!("Failed to set tmux buffer: {e}"))?;
 
-    if !status.success() {
+    if !output.status.success() {
         return Err("tmux set-buffer failed".to_string());
     }
 
-    let output = new_command("tmux")
-        .args([
+    let output = tmux_output(
+        host,
+        &[
             "paste-buffer",
             "-t",
             tmux_session,
@@ -127,9 +169,9 @@ fn bracketed_paste(tmux_session: &str, text: &str) -> Result<(), String> {
             "_orca",
             "-p",
             "-d",
-        ])
-        .output()
-        .map_err(|e| format!("Failed to paste tmux buffer: {e}"))?;
+        ],
+    )
+    .map_err(|e| format!("Failed to paste tmux buffer: {e}"))?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -139,25 +181,47 @@ fn bracketed_paste(tmux_session: &str, text: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Send Escape to a tmux pane, which interrupts the current Claude Code turn.
+pub(crate) fn send_escape(tmux_session: &str) -> Result<(), String> {
+    log::info!("send_escape: tmux_session={tmux_session}");
+    let output = new_command("tmux")
+        .args(["send-keys", "-t", tmux_session, "Escape"])
+        .output()
+        .map_err(|e| format!("Failed to send Escape via tmux: {e}"))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("tmux send-keys (Escape) failed: {}", stderr.trim()));
+    }
+    Ok(())
+}
+
 /// Paste text into a tmux pane using bracketed paste, then send Enter to submit.
 ///
 /// Uses `tmux set-buffer` + `paste-buffer -p` for reliable delivery, then
 /// sends a literal CR to submit. This is the canonical way to send a prompt
 /// to a Claude Code (or similar) session.
 pub(crate) fn paste_and_submit(tmux_session: &str, text: &str) -> Result<(), String> {
+    paste_and_submit_on(None, tmux_session, text)
+}
+
+/// [`paste_and_submit`] on an SSH host when `host` is set.
+pub(crate) fn paste_and_submit_on(
+    host: Option<&SshHost>,
+    tmux_session: &str,
+    text: &str,
+) -> Result<(), String> {
     log::info!(
         "paste_and_submit: tmux_session={tmux_session}, text_len={}",
         text.len(),
     );
 
-    bracketed_paste(tmux_session, text)?;
+    bracketed_paste(host, tmux_session, text)?;
 
     // Brief pause so the TUI processes the pasted text before submitting
     std::thread::sleep(std::time::Duration::from_millis(200));
 
-    let enter_output = new_command("tmux")
-        .args(["send-keys", "-l", "-t", tmux_session, "\r"])
-        .output()
+    let enter_output = tmux_output(host, &["send-keys", "-l", "-t", tmux_session, "\r"])
         .map_err(|e| format!("Failed to send Enter via tmux: {e}"))?;
 
     if !enter_output.status.success() {
@@ -168,32 +232,15 @@ pub(crate) fn paste_and_submit(tmux_session: &str, text: &str) -> Result<(), Str
     Ok(())
 }
 
-/// Check if pane text contains a Claude Code input prompt.
-///
-/// Detects two patterns:
-/// 1. Permission prompt: text contains "Do you want to proceed?"
-/// 2. General input prompt: last non-empty line is or ends with `❯` or `>`
-pub(crate) fn pane_shows_input_prompt(text: &str) -> bool {
-    if text.contains("Do you want to proceed?") {
-        return true;
-    }
-
-    text.lines()
-        .rev()
-        .find(|line| !line.trim().is_empty())
-        .map(|line| {
-            let trimmed = line.trim();
-            trimmed == "❯" || trimmed == ">" || trimmed.ends_with(" ❯") || trimmed.ends_with(" >")
-        })
-        .unwrap_or(false)
-}
-
+/// The live tmux sessions, on the group's SSH host if it has one.
 #[tauri::command]
-pub fn list_tmux_sessions() -> Result<Vec<String>, String> {
+pub fn list_tmux_sessions(
+    orca_db: State<'_, OrcaDb>,
+    group_path: Option<String>,
+) -> Result<Vec<String>, String> {
     log::debug!("tmux list-sessions -F #{{session_name}}");
-    let output = new_command("tmux")
-        .args(["list-sessions", "-F", "#{session_name}"])
-        .output()
+    let host = ssh::host_for(&orca_db, group_path.as_deref())?;
+    let output = tmux_output(host.as_ref(), &["list-sessions", "-F", "#{session_name}"])
         .map_err(|e| format!("Failed to run tmux: {e}"))?;
 
     if !output.status.success() {
@@ -222,7 +269,12 @@ pub fn list_tmux_sessions() -> Result<Vec<String>, String> {
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    /// Claude Code's prompt detection, as used for attention refinement.
+    fn pane_shows_input_prompt(text: &str) -> bool {
+        crate::tool_profile::profile_for("claude")
+            .pane_attention(text)
+            .is_some()
+    }
 
     #[test]
     fn prompt_permission_prompt() {
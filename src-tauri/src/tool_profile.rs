@@ -0,0 +1,352 @@
+//! Registry of agent CLI profiles.
+//!
+//! A [`ToolProfile`] tells Orca how to recognise a tool in its tmux pane:
+//! when it has started, when it is waiting at its prompt and when it is
+//! asking for permission. Built-in profiles cover Claude Code, OpenCode,
+//! Codex, Aider and Gemini CLI; profiles stored in `OrcaDb` override them by
+//! name or add new tools. The merged registry is cached in memory so the
+//! attention code can use it without a DB handle.
+
+use crate::claude_logs::AttentionStatus;
+use crate::models::ToolProfile;
+use crate::orca_db::OrcaDb;
+use std::sync::{OnceLock, RwLock};
+use tauri::State;
+
+static REGISTRY: OnceLock<RwLock<Vec<ToolProfile>>> = OnceLock::new();
+
+fn strings(items: &[&str]) -> Vec<String> {
+    items.iter().map(|s| (*s).to_string()).collect()
+}
+
+fn builtin(
+    name: &str,
+    required: bool,
+    ready: &[&str],
+    waiting: &[&str],
+    permission: &[&str],
+    transcripts: &str,
+) -> ToolProfile {
+    ToolProfile {
+        name: name.to_string(),
+        binary: name.to_string(),
+        version_args: strings(&["--version"]),
+        required,
+        ready_patterns: strings(ready),
+        waiting_patterns: strings(waiting),
+        permission_patterns: strings(permission),
+        compacting_patterns: Vec::new(),
+        transcript_location: Some(transcripts.to_string()),
+        builtin: true,
+    }
+}
+
+/// Profiles shipped with Orca.
+pub fn builtin_profiles() -> Vec<ToolProfile> {
+    vec![
+        ToolProfile {
+            compacting_patterns: strings(&["Compacting conversation"]),
+            ..builtin(
+                "claude",
+                true,
+                &[],
+                &["$❯", "$>"],
+                &["Do you want to proceed?"],
+                "~/.claude/projects",
+            )
+        },
+        // OpenCode's TUI shows "tab agents" once it is fully loaded.
+        builtin(
+            "opencode",
+            false,
+            &["tab agents"],
+            &[],
+            &["Permission required"],
+            "~/.local/share/opencode/storage",
+        ),
+        builtin(
+            "codex",
+            false,
+            &[],
+            &["$›", "$▌"],
+            &[
+                "Would you like to run the following command?",
+                "Would you like to make the following edits?",
+            ],
+            "~/.codex/sessions",
+        ),
+        builtin(
+            "aider",
+            false,
+            &[],
+            &["$>", "$ask>", "$code>", "$architect>"],
+            &["(Y)es/(N)o"],
+            ".aider.chat.history.md",
+        ),
+        builtin(
+            "gemini",
+            false,
+            &[],
+            &["Type your message"],
+            &["Allow execution", "Apply this change?"],
+            "~/.gemini/tmp",
+        ),
+    ]
+}
+
+/// Built-in profiles with the user's profiles applied on top.
+fn merge(user: Vec<ToolProfile>) -> Vec<ToolProfile> {
+    let mut profiles = builtin_profiles();
+    for mut profile in user {
+        match profiles.iter_mut().find(|p| p.name == profile.name) {
+            Some(existing) => {
+                profile.builtin = true;
+                *existing = profile;
+            }
+            None => {
+                profile.builtin = false;
+                profiles.push(profile);
+            }
+        }
+    }
+    profiles
+}
+
+fn registry() -> &'static RwLock<Vec<ToolProfile>> {
+    REGISTRY.get_or_init(|| RwLock::new(builtin_profiles()))
+}
+
+/// Reload the registry from `OrcaDb`. Called at startup and after edits.
+pub fn reload(orca_db: &OrcaDb) -> Result<(), String> {
+    let profiles = merge(orca_db.get_tool_profiles()?);
+    *registry().write().map_err(|e| format!("Lock error: {e}"))? = profiles;
+    Ok(())
+}
+
+/// All known profiles, built-in ones first.
+pub fn profiles() -> Vec<ToolProfile> {
+    registry()
+        .read()
+        .map(|p| p.clone())
+        .unwrap_or_else(|_| builtin_profiles())
+}
+
+/// The profile for an agent-deck tool name. Unknown tools (and sessions
+/// without a tool) get Claude's prompt detection, as before profiles existed.
+pub fn profile_for(tool: &str) -> ToolProfile {
+    let tool = if tool.is_empty() { "claude" } else { tool };
+    let profiles = profiles();
+    if let Some(profile) = profiles.iter().find(|p| p.name == tool) {
+        return profile.clone();
+    }
+    let claude = profiles
+        .into_iter()
+        .find(|p| p.name == "claude")
+        .unwrap_or_else(|| builtin_profiles().remove(0));
+    ToolProfile {
+        name: tool.to_string(),
+        binary: tool.to_string(),
+        required: false,
+        transcript_location: None,
+        builtin: false,
+        ..claude
+    }
+}
+
+fn pattern_matches(text: &str, pattern: &str) -> bool {
+    let Some(marker) = pattern.strip_prefix('$') else {
+        return !pattern.is_empty() && text.contains(pattern);
+    };
+    text.lines()
+        .rev()
+        .find(|line| !line.trim().is_empty())
+        .is_some_and(|line| {
+            let trimmed = line.trim();
+            trimmed == marker || trimmed.ends_with(&format!(" {marker}"))
+        })
+}
+
+fn any_matches(text: &str, patterns: &[String]) -> bool {
+    patterns.iter().any(|p| pattern_matches(text, p))
+}
+
+impl ToolProfile {
+    /// The pane shows a permission prompt.
+    pub fn shows_permission_prompt(&self, pane: &str) -> bool {
+        any_matches(pane, &self.permission_patterns)
+    }
+
+    /// What the pane says about attention, most urgent first: a permission
+    /// prompt, compaction in progress, or the input prompt.
+    pub fn pane_attention(&self, pane: &str) -> Option<AttentionStatus> {
+        if self.shows_permission_prompt(pane) {
+            Some(AttentionStatus::AwaitingPermission)
+        } else if any_matches(pane, &self.compacting_patterns) {
+            Some(AttentionStatus::Compacting)
+        } else if any_matches(pane, &self.waiting_patterns) {
+            Some(AttentionStatus::NeedsInput)
+        } else {
+            None
+        }
+    }
+
+    /// The tool has started and can take a prompt.
+    pub fn is_ready(&self, pane: &str) -> bool {
+        if self.ready_patterns.is_empty() {
+            any_matches(pane, &self.waiting_patterns)
+        } else {
+            any_matches(pane, &self.ready_patterns)
+        }
+    }
+}
+
+fn validate(profile: &ToolProfile) -> Result<(), String> {
+    if profile.name.trim().is_empty() {
+        return Err("Tool name is required".to_string());
+    }
+    if profile.binary.trim().is_empty() {
+        return Err("Tool binary is required".to_string());
+    }
+    let patterns = profile
+        .ready_patterns
+        .iter()
+        .chain(&profile.waiting_patterns)
+        .chain(&profile.permission_patterns)
+        .chain(&profile.compacting_patterns);
+    for pattern in patterns {
+        if pattern.is_empty() || pattern == "$" {
+            return Err("Patterns must not be empty".to_string());
+        }
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_tool_profiles() -> Vec<ToolProfile> {
+    profiles()
+}
+
+/// Add a tool profile, or override a built-in one with the same name.
+#[tauri::command]
+pub fn set_tool_profile(orca_db: State<'_, OrcaDb>, profile: ToolProfile) -> Result<(), String> {
+    validate(&profile)?;
+    orca_db.set_tool_profile(&profile)?;
+    reload(&orca_db)
+}
+
+/// Delete a user profile. Deleting an override restores the built-in profile.
+#[tauri::command]
+pub fn delete_tool_profile(orca_db: State<'_, OrcaDb>, name: String) -> Result<(), String> {
+    orca_db.delete_tool_profile(&name)?;
+    reload(&orca_db)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(profiles: &[ToolProfile], name: &str) -> ToolProfile {
+        profiles
+            .iter()
+            .find(|p| p.name == name)
+            .cloned()
+            .unwrap_or_else(|| panic!("no profile {name}"))
+    }
+
+    #[test]
+    fn prompt_marker_only_matches_last_line() {
+        assert!(pattern_matches("output\n  ❯  \n\n", "$❯"));
+        assert!(pattern_matches("project >", "$>"));
+        assert!(!pattern_matches("foo ❯ bar", "$❯"));
+        assert!(!pattern_matches("❯\nstill working", "$❯"));
+        assert!(pattern_matches("a\n(Y)es/(N)o [Yes]:\n", "(Y)es/(N)o"));
+    }
+
+    #[test]
+    fn builtin_profiles_detect_their_prompts() {
+        let profiles = builtin_profiles();
+        let codex = find(&profiles, "codex");
+        assert!(codex.pane_attention("• Updated src/lib.rs\n\n›").is_some());
+        assert!(codex
+            .shows_permission_prompt("Would you like to run the following command?\n$ cargo test"));
+        assert_eq!(
+            codex.pane_attention("Working (12s • esc to interrupt)"),
+            None
+        );
+
+        let aider = find(&profiles, "aider");
+        assert!(aider
+            .pane_attention("Tokens: 2.1k sent\narchitect>")
+            .is_some());
+        assert!(aider.shows_permission_prompt("Add src/main.rs to the chat? (Y)es/(N)o [Yes]:"));
+
+        let gemini = find(&profiles, "gemini");
+        assert!(gemini.is_ready(">   Type your message or @path/to/file"));
+
+        let opencode = find(&profiles, "opencode");
+        assert!(!opencode.is_ready("❯"));
+        assert!(opencode.is_ready("build  tab agents"));
+    }
+
+    #[test]
+    fn pane_attention_prefers_permission_prompts() {
+        let claude = find(&builtin_profiles(), "claude");
+        assert_eq!(
+            claude.pane_attention("Bash(rm -rf target)\nDo you want to proceed?\n❯ 1. Yes"),
+            Some(AttentionStatus::AwaitingPermission)
+        );
+        assert_eq!(
+            claude.pane_attention("✻ Compacting conversation… (esc to interrupt)"),
+            Some(AttentionStatus::Compacting)
+        );
+        assert_eq!(
+            claude.pane_attention("Done.\n❯"),
+            Some(AttentionStatus::NeedsInput)
+        );
+        assert_eq!(claude.pane_attention("✻ Thinking…"), None);
+    }
+
+    #[test]
+    fn user_profiles_override_and_extend_builtins() {
+        let mut claude = find(&builtin_profiles(), "claude");
+        claude.binary = "/opt/claude".into();
+        claude.builtin = false;
+        let custom = ToolProfile {
+            name: "goose".into(),
+            binary: "goose".into(),
+            version_args: Vec::new(),
+            required: false,
+            ready_patterns: Vec::new(),
+            waiting_patterns: vec!["$( O)>".into()],
+            permission_patterns: Vec::new(),
+            compacting_patterns: Vec::new(),
+            transcript_location: None,
+            builtin: true,
+        };
+        let merged = merge(vec![claude, custom]);
+        assert_eq!(merged.len(), builtin_profiles().len() + 1);
+        let claude = find(&merged, "claude");
+        assert_eq!(claude.binary, "/opt/claude");
+        assert!(claude.builtin);
+        assert!(!find(&merged, "goose").builtin);
+    }
+
+    #[test]
+    fn unknown_tools_fall_back_to_claude_detection() {
+        let profile = profile_for("shell");
+        assert_eq!(profile.name, "shell");
+        assert!(profile.pane_attention("$ ls\n>").is_some());
+        assert_eq!(profile_for("").name, "claude");
+    }
+
+    #[test]
+    fn validate_rejects_empty_patterns() {
+        let mut profile = find(&builtin_profiles(), "codex");
+        assert!(validate(&profile).is_ok());
+        profile.waiting_patterns.push("$".into());
+        assert!(validate(&profile).is_err());
+        profile.waiting_patterns.pop();
+        profile.binary = " ".into();
+        assert!(validate(&profile).is_err());
+    }
+}
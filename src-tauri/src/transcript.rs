@@ -0,0 +1,450 @@
+//! Full, typed view of a Claude Code JSONL transcript, read a page at a time.
+//!
+//! Pages are addressed by byte offset into the JSONL file, so reading the next
+//! page never re-parses earlier lines and a live session can be followed by
+//! polling with the last `next_cursor`.
+
+use crate::claude_logs::find_jsonl_path;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::Path;
+
+const DEFAULT_LIMIT: usize = 100;
+const MAX_LIMIT: usize = 500;
+/// Tool outputs can be megabytes (file dumps, test logs); cap what we ship to the UI.
+const MAX_TOOL_OUTPUT_CHARS: usize = 20_000;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TranscriptBlock {
+    Text {
+        text: String,
+    },
+    Thinking {
+        text: String,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        input: serde_json::Value,
+    },
+    ToolResult {
+        tool_use_id: String,
+        output: String,
+        is_error: bool,
+        truncated: bool,
+    },
+    Image,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptEntry {
+    pub uuid: Option<String>,
+    pub parent_uuid: Option<String>,
+    /// "user", "assistant" or "system".
+    pub role: String,
+    pub timestamp: Option<String>,
+    pub model: Option<String>,
+    /// Part of a subagent (Task tool) conversation rather than the main thread.
+    pub is_sidechain: bool,
+    pub agent_id: Option<String>,
+    pub blocks: Vec<TranscriptBlock>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptPage {
+    pub entries: Vec<TranscriptEntry>,
+    /// Byte offset to pass as `cursor` for the next page.
+    pub next_cursor: u64,
+    /// False once the page reached the end of the file (new lines may still
+    /// be appended later by a running session).
+    pub has_more: bool,
+}
+
+fn str_field(value: &serde_json::Value, key: &str) -> Option<String> {
+    value.get(key).and_then(|v| v.as_str()).map(String::from)
+}
+
+fn truncate_output(text: String) -> (String, bool) {
+    if text.chars().count() <= MAX_TOOL_OUTPUT_CHARS {
+        (text, false)
+    } else {
+        (text.chars().take(MAX_TOOL_OUTPUT_CHARS).collect(), true)
+    }
+}
+
+/// Flatten tool_result content, which is either a string or a list of
+/// text/image parts.
+fn tool_result_text(content: Option<&serde_json::Value>) -> String {
+    match content {
+        Some(serde_json::Value::String(s)) => s.clone(),
+        Some(serde_json::Value::Array(parts)) => parts
+            .iter()
+            .map(|part| match part.get("type").and_then(|v| v.as_str()) {
+                Some("text") => part
+                    .get("text")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .to_string(),
+                Some("image") => "[image]".to_string(),
+                _ => String::new(),
+            })
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+fn parse_block(item: &serde_json::Value) -> Option<TranscriptBlock> {
+    match item.get("type").and_then(|v| v.as_str())? {
+        "text" => Some(TranscriptBlock::Text {
+            text: str_field(item, "text")?,
+        }),
+        "thinking" => Some(TranscriptBlock::Thinking {
+            text: str_field(item, "thinking")?,
+        }),
+        "tool_use" => Some(TranscriptBlock::ToolUse {
+            id: str_field(item, "id").unwrap_or_default(),
+            name: str_field(item, "name").unwrap_or_default(),
+            input: item
+                .get("input")
+                .cloned()
+                .unwrap_or(serde_json::Value::Null),
+        }),
+        "tool_result" => {
+            let (output, truncated) = truncate_output(tool_result_text(item.get("content")));
+            Some(TranscriptBlock::ToolResult {
+                tool_use_id: str_field(item, "tool_use_id").unwrap_or_default(),
+                output,
+                is_error: item
+                    .get("is_error")
+                    .and_then(serde_json::Value::as_bool)
+                    .unwrap_or(false),
+                truncated,
+            })
+        }
+        "image" => Some(TranscriptBlock::Image),
+        _ => None,
+    }
+}
+
+/// Convert one JSONL line into a transcript entry. Bookkeeping lines
+/// (summaries, file snapshots, queue operations) return `None`.
+fn parse_entry(line: &serde_json::Value) -> Option<TranscriptEntry> {
+    let line_type = line.get("type").and_then(|v| v.as_str())?;
+    if !matches!(line_type, "user" | "assistant" | "system") {
+        return None;
+    }
+
+    let msg = line.get("message").unwrap_or(line);
+    let blocks = match msg.get("content") {
+        Some(serde_json::Value::String(text)) => vec![TranscriptBlock::Text { text: text.clone() }],
+        Some(serde_json::Value::Array(items)) => items.iter().filter_map(parse_block).collect(),
+        _ => Vec::new(),
+    };
+    if blocks.is_empty() {
+        return None;
+    }
+
+    Some(TranscriptEntry {
+        uuid: str_field(line, "uuid"),
+        parent_uuid: str_field(line, "parentUuid"),
+        role: str_field(msg, "role").unwrap_or_else(|| line_type.to_string()),
+        timestamp: str_field(line, "timestamp"),
+        model: str_field(msg, "model"),
+        is_sidechain: line
+            .get("isSidechain")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false),
+        agent_id: str_field(line, "agentId"),
+        blocks,
+    })
+}
+
+/// Read up to `limit` entries starting at byte offset `cursor`.
+pub fn read_page(path: &Path, cursor: u64, limit: usize) -> Result<TranscriptPage, String> {
+    let mut file = File::open(path)
+        .map_err(|e| format!("Failed to open transcript {}: {e}", path.display()))?;
+    let len = file
+        .metadata()
+        .map_err(|e| format!("Failed to stat transcript: {e}"))?
+        .len();
+    let cursor = cursor.min(len);
+    file.seek(SeekFrom::Start(cursor))
+        .map_err(|e| format!("Failed to seek transcript: {e}"))?;
+
+    let mut reader = BufReader::new(file);
+    let mut entries = Vec::new();
+    let mut pos = cursor;
+    let mut buf = Vec::new();
+
+    while entries.len() < limit {
+        buf.clear();
+        let n = reader
+            .read_until(b'\n', &mut buf)
+            .map_err(|e| format!("Failed to read transcript: {e}"))?;
+        // Stop before a trailing line that is still being written.
+        if n == 0 || buf.last() != Some(&b'\n') {
+            break;
+        }
+        pos += n as u64;
+        let Ok(value) = serde_json::from_slice::<serde_json::Value>(&buf) else {
+            continue;
+        };
+        if let Some(entry) = parse_entry(&value) {
+            entries.push(entry);
+        }
+    }
+
+    Ok(TranscriptPage {
+        entries,
+        next_cursor: pos,
+        has_more: pos < len,
+    })
+}
+
+/// Read every complete entry in a transcript.
+pub fn read_all(path: &Path) -> Result<Vec<TranscriptEntry>, String> {
+    Ok(read_page(path, 0, usize::MAX)?.entries)
+}
+
+/// Build an entry from a remote backend message (OpenCode or AgentAPI).
+/// These are flatter than Claude's JSONL: one block per message.
+pub fn remote_entry(
+    id: &str,
+    role: &str,
+    msg_type: &str,
+    content: &serde_json::Value,
+    tool_name: Option<&str>,
+    tool_id: Option<&str>,
+    timestamp: Option<String>,
+) -> TranscriptEntry {
+    let text = match content {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Null => String::new(),
+        other => serde_json::to_string_pretty(other).unwrap_or_default(),
+    };
+    let block = match (msg_type, tool_name) {
+        ("tool_result" | "tool-result", _) => {
+            let (output, truncated) = truncate_output(text);
+            TranscriptBlock::ToolResult {
+                tool_use_id: tool_id.unwrap_or_default().to_string(),
+                output,
+                is_error: false,
+                truncated,
+            }
+        }
+        (_, Some(name)) => TranscriptBlock::ToolUse {
+            id: tool_id.unwrap_or_default().to_string(),
+            name: name.to_string(),
+            input: content.clone(),
+        },
+        ("thinking" | "reasoning", None) => TranscriptBlock::Thinking { text },
+        _ => TranscriptBlock::Text { text },
+    };
+    let role = match role {
+        // AgentAPI calls the assistant "agent"
+        "agent" => "assistant",
+        "" => "system",
+        other => other,
+    };
+    TranscriptEntry {
+        uuid: Some(id.to_string()).filter(|id| !id.is_empty()),
+        parent_uuid: None,
+        role: role.to_string(),
+        timestamp,
+        model: None,
+        is_sidechain: false,
+        agent_id: None,
+        blocks: vec![block],
+    }
+}
+
+#[tauri::command]
+pub async fn get_session_transcript(
+    project_path: String,
+    claude_session_id: String,
+    cursor: Option<u64>,
+    limit: Option<usize>,
+) -> Result<TranscriptPage, String> {
+    tokio::task::spawn_blocking(move || {
+        let path = find_jsonl_path(&project_path, &claude_session_id)
+            .ok_or_else(|| format!("No transcript found for session {claude_session_id}"))?;
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+        read_page(&path, cursor.unwrap_or(0), limit)
+    })
+    .await
+    .map_err(|e| format!("Task failed: {e}"))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::Write;
+
+    fn write_lines(path: &Path, lines: &[serde_json::Value]) {
+        let mut file = File::create(path).expect("create failed");
+        for line in lines {
+            writeln!(file, "{line}").expect("write failed");
+        }
+    }
+
+    #[test]
+    fn parses_user_string_content() {
+        let entry = parse_entry(&json!({
+            "type": "user",
+            "uuid": "u1",
+            "timestamp": "2025-01-01T00:00:00Z",
+            "message": {"role": "user", "content": "Fix the bug"}
+        }))
+        .expect("entry");
+        assert_eq!(entry.role, "user");
+        assert_eq!(entry.uuid.as_deref(), Some("u1"));
+        assert_eq!(
+            entry.blocks,
+            vec![TranscriptBlock::Text {
+                text: "Fix the bug".into()
+            }]
+        );
+    }
+
+    #[test]
+    fn parses_assistant_blocks() {
+        let entry = parse_entry(&json!({
+            "type": "assistant",
+            "message": {
+                "role": "assistant",
+                "model": "claude-sonnet-4",
+                "content": [
+                    {"type": "thinking", "thinking": "Let me look"},
+                    {"type": "text", "text": "Reading the file"},
+                    {"type": "tool_use", "id": "t1", "name": "Read", "input": {"file_path": "/a.rs"}}
+                ]
+            }
+        }))
+        .expect("entry");
+        assert_eq!(entry.model.as_deref(), Some("claude-sonnet-4"));
+        assert_eq!(entry.blocks.len(), 3);
+        assert_eq!(
+            entry.blocks[2],
+            TranscriptBlock::ToolUse {
+                id: "t1".into(),
+                name: "Read".into(),
+                input: json!({"file_path": "/a.rs"}),
+            }
+        );
+    }
+
+    #[test]
+    fn parses_tool_result_array_and_error() {
+        let entry = parse_entry(&json!({
+            "type": "user",
+            "isSidechain": true,
+            "agentId": "a1",
+            "message": {"role": "user", "content": [{
+                "type": "tool_result",
+                "tool_use_id": "t1",
+                "is_error": true,
+                "content": [{"type": "text", "text": "boom"}, {"type": "image"}]
+            }]}
+        }))
+        .expect("entry");
+        assert!(entry.is_sidechain);
+        assert_eq!(entry.agent_id.as_deref(), Some("a1"));
+        assert_eq!(
+            entry.blocks[0],
+            TranscriptBlock::ToolResult {
+                tool_use_id: "t1".into(),
+                output: "boom\n[image]".into(),
+                is_error: true,
+                truncated: false,
+            }
+        );
+    }
+
+    #[test]
+    fn tool_output_is_truncated() {
+        let (text, truncated) = truncate_output("x".repeat(MAX_TOOL_OUTPUT_CHARS + 10));
+        assert!(truncated);
+        assert_eq!(text.len(), MAX_TOOL_OUTPUT_CHARS);
+    }
+
+    #[test]
+    fn skips_bookkeeping_lines() {
+        assert!(parse_entry(&json!({"type": "summary", "summary": "x"})).is_none());
+        assert!(parse_entry(&json!({"type": "file-history-snapshot"})).is_none());
+    }
+
+    #[test]
+    fn remote_entry_maps_types() {
+        let text = remote_entry("m1", "agent", "text", &json!("Hello"), None, None, None);
+        assert_eq!(text.role, "assistant");
+        assert_eq!(
+            text.blocks,
+            vec![TranscriptBlock::Text {
+                text: "Hello".into()
+            }]
+        );
+
+        let input = json!({"command": "ls"});
+        let tool = remote_entry(
+            "m2",
+            "assistant",
+            "tool",
+            &input,
+            Some("bash"),
+            Some("t1"),
+            Some("2025-01-01T00:00:00Z".into()),
+        );
+        assert_eq!(
+            tool.blocks,
+            vec![TranscriptBlock::ToolUse {
+                id: "t1".into(),
+                name: "bash".into(),
+                input,
+            }]
+        );
+        assert_eq!(tool.timestamp.as_deref(), Some("2025-01-01T00:00:00Z"));
+
+        let result = remote_entry("", "", "tool_result", &json!("ok"), None, Some("t1"), None);
+        assert_eq!(result.role, "system");
+        assert_eq!(result.uuid, None);
+        assert!(matches!(
+            &result.blocks[0],
+            TranscriptBlock::ToolResult { output, .. } if output == "ok"
+        ));
+    }
+
+    #[test]
+    fn pages_by_byte_cursor() {
+        let tmp = tempfile::tempdir().expect("failed to create temp dir");
+        let path = tmp.path().join("s.jsonl");
+        let lines: Vec<_> = (0..5)
+            .map(|i| json!({"type": "user", "message": {"role": "user", "content": format!("msg {i}")}}))
+            .chain([json!({"type": "summary", "summary": "x"})])
+            .collect();
+        write_lines(&path, &lines);
+
+        let first = read_page(&path, 0, 2).expect("page");
+        assert_eq!(first.entries.len(), 2);
+        assert!(first.has_more);
+
+        let second = read_page(&path, first.next_cursor, 10).expect("page");
+        assert_eq!(second.entries.len(), 3);
+        assert!(!second.has_more);
+        assert_eq!(
+            second.entries[0].blocks,
+            vec![TranscriptBlock::Text {
+                text: "msg 2".into()
+            }]
+        );
+
+        // Cursor at end returns nothing and stays put
+        let end = read_page(&path, second.next_cursor, 10).expect("page");
+        assert!(end.entries.is_empty());
+        assert_eq!(end.next_cursor, second.next_cursor);
+    }
+}
@@ -0,0 +1,604 @@
+//! Token usage and cost accounting from Claude Code JSONL transcripts.
+//!
+//! Assistant entries carry `message.usage` and `message.model`. Usage is
+//! ingested incrementally (per-file byte offset stored in `OrcaDb`) into
+//! per-session/day/model rows; cost is computed at report time so price
+//! changes apply retroactively.
+
+use crate::agentdeck;
+use crate::claude_logs::find_jsonl_path;
+use crate::models::{ModelPrice, Session, TokenCounts};
+use crate::orca_db::{OrcaDb, UsageRow};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::Path;
+use tauri::State;
+
+/// Built-in prices (USD per million tokens), most specific pattern first.
+/// Override or extend with `set_model_price`.
+const DEFAULT_PRICES: &[(&str, f64, f64, f64, f64)] = &[
+    ("opus-4-5", 5.0, 25.0, 6.25, 0.5),
+    ("opus", 15.0, 75.0, 18.75, 1.5),
+    ("sonnet", 3.0, 15.0, 3.75, 0.3),
+    ("haiku-4-5", 1.0, 5.0, 1.25, 0.1),
+    ("haiku-3-5", 0.8, 4.0, 1.0, 0.08),
+    ("haiku", 0.25, 1.25, 0.3, 0.03),
+];
+
+fn default_prices() -> Vec<ModelPrice> {
+    DEFAULT_PRICES
+        .iter()
+        .map(
+            |&(model, input, output, cache_write, cache_read)| ModelPrice {
+                model: model.to_string(),
+                input_per_mtok: input,
+                output_per_mtok: output,
+                cache_write_per_mtok: cache_write,
+                cache_read_per_mtok: cache_read,
+            },
+        )
+        .collect()
+}
+
+/// Find the price for a model: the longest matching user override, then the
+/// first matching built-in pattern.
+fn price_for<'a>(
+    model: &str,
+    overrides: &'a [ModelPrice],
+    defaults: &'a [ModelPrice],
+) -> Option<&'a ModelPrice> {
+    overrides
+        .iter()
+        .filter(|p| model.contains(p.model.as_str()))
+        .max_by_key(|p| p.model.len())
+        .or_else(|| defaults.iter().find(|p| model.contains(p.model.as_str())))
+}
+
+fn cost_usd(tokens: &TokenCounts, price: &ModelPrice) -> f64 {
+    (tokens.input_tokens as f64 * price.input_per_mtok
+        + tokens.output_tokens as f64 * price.output_per_mtok
+        + tokens.cache_creation_tokens as f64 * price.cache_write_per_mtok
+        + tokens.cache_read_tokens as f64 * price.cache_read_per_mtok)
+        / 1_000_000.0
+}
+
+// ── Parsing ──────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq)]
+struct UsageEntry {
+    message_id: Option<String>,
+    model: String,
+    day: String,
+    tokens: TokenCounts,
+}
+
+fn parse_usage(line: &serde_json::Value) -> Option<UsageEntry> {
+    if line.get("type").and_then(|v| v.as_str()) != Some("assistant") {
+        return None;
+    }
+    let msg = line.get("message")?;
+    let usage = msg.get("usage")?;
+    let count = |key: &str| {
+        usage
+            .get(key)
+            .and_then(serde_json::Value::as_u64)
+            .unwrap_or(0)
+    };
+    let tokens = TokenCounts {
+        input_tokens: count("input_tokens"),
+        output_tokens: count("output_tokens"),
+        cache_creation_tokens: count("cache_creation_input_tokens"),
+        cache_read_tokens: count("cache_read_input_tokens"),
+    };
+    // "<synthetic>" entries (e.g. API error placeholders) report zero usage.
+    if tokens.total() == 0 {
+        return None;
+    }
+
+    let day = line
+        .get("timestamp")
+        .and_then(|v| v.as_str())
+        .and_then(|ts| ts.get(..10))
+        .unwrap_or("unknown")
+        .to_string();
+
+    Some(UsageEntry {
+        message_id: msg.get("id").and_then(|v| v.as_str()).map(String::from),
+        model: msg
+            .get("model")
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown")
+            .to_string(),
+        day,
+        tokens,
+    })
+}
+
+struct ScanResult {
+    /// Usage per (day, model) found in the new lines.
+    deltas: HashMap<(String, String), TokenCounts>,
+    offset: u64,
+    last_message_id: Option<String>,
+}
+
+/// Sum usage from complete lines after `offset`.
+///
+/// Claude Code writes one line per content block of a response, each
+/// repeating the same `message.id` with the usage so far, and lines of
+/// parallel responses can interleave. So only the last usage per message ID
+/// is counted. `last_message_id` is the last ID seen by the previous scan,
+/// whose usage was already recorded.
+fn scan_file(
+    path: &Path,
+    offset: u64,
+    last_message_id: Option<String>,
+) -> Result<ScanResult, String> {
+    let mut file =
+        File::open(path).map_err(|e| format!("Failed to open {}: {e}", path.display()))?;
+    file.seek(SeekFrom::Start(offset))
+        .map_err(|e| format!("Failed to seek {}: {e}", path.display()))?;
+
+    let mut reader = BufReader::new(file);
+    let mut result = ScanResult {
+        deltas: HashMap::new(),
+        offset,
+        last_message_id: last_message_id.clone(),
+    };
+    let mut without_id = Vec::new();
+    let mut by_message_id: HashMap<String, UsageEntry> = HashMap::new();
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        let n = reader
+            .read_until(b'\n', &mut buf)
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        if n == 0 || buf.last() != Some(&b'\n') {
+            break;
+        }
+        result.offset += n as u64;
+        let Ok(line) = serde_json::from_slice::<serde_json::Value>(&buf) else {
+            continue;
+        };
+        let Some(entry) = parse_usage(&line) else {
+            continue;
+        };
+        result.last_message_id.clone_from(&entry.message_id);
+        let Some(id) = entry.message_id.clone() else {
+            without_id.push(entry);
+            continue;
+        };
+        // The previous scan's last message was already counted.
+        if Some(&id) != last_message_id.as_ref() {
+            by_message_id.insert(id, entry);
+        }
+    }
+    for entry in without_id.into_iter().chain(by_message_id.into_values()) {
+        result
+            .deltas
+            .entry((entry.day, entry.model))
+            .or_default()
+            .add(&entry.tokens);
+    }
+    Ok(result)
+}
+
+/// Ingest any new usage from a session's transcript into `OrcaDb`.
+fn ingest_session(orca_db: &OrcaDb, session: &Session) -> Result<(), String> {
+    let Some(csid) = session.claude_session_id.as_deref() else {
+        return Ok(());
+    };
+    let Some(path) = find_jsonl_path(&session.project_path, csid) else {
+        return Ok(());
+    };
+    let path_str = path.to_string_lossy().to_string();
+    let len = std::fs::metadata(&path)
+        .map_err(|e| format!("Failed to stat {path_str}: {e}"))?
+        .len();
+
+    let (offset, last_message_id) = orca_db.get_usage_scan(&path_str)?.unwrap_or((0, None));
+    if len < offset {
+        // Transcripts are append-only; if one shrank, skip to the end rather
+        // than re-counting what we already recorded.
+        log::warn!("usage: {path_str} shrank from {offset} to {len} bytes, skipping to end");
+        return orca_db.record_usage(&path_str, &session.id, &session.group_path, &[], len, None);
+    }
+    if len == offset {
+        return Ok(());
+    }
+
+    let scan = scan_file(&path, offset, last_message_id)?;
+    let deltas: Vec<(String, String, TokenCounts)> = scan
+        .deltas
+        .into_iter()
+        .map(|((day, model), tokens)| (day, model, tokens))
+        .collect();
+    orca_db.record_usage(
+        &path_str,
+        &session.id,
+        &session.group_path,
+        &deltas,
+        scan.offset,
+        scan.last_message_id.as_deref(),
+    )
+}
+
+/// Bring stored usage up to date for every agent-deck session.
+pub fn refresh_usage(orca_db: &OrcaDb) -> Result<(), String> {
+    let sessions = agentdeck::list_sessions_with_prompts(orca_db, None)?;
+    for session in &sessions {
+        if let Err(e) = ingest_session(orca_db, session) {
+            log::warn!("usage: failed to ingest session {}: {e}", session.id);
+        }
+    }
+    Ok(())
+}
+
+// ── Reporting ────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UsageTotals {
+    #[serde(flatten)]
+    pub tokens: TokenCounts,
+    pub cost_usd: f64,
+}
+
+impl UsageTotals {
+    fn add(&mut self, tokens: &TokenCounts, cost: f64) {
+        self.tokens.add(tokens);
+        self.cost_usd += cost;
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionUsage {
+    pub session_id: String,
+    pub group_path: String,
+    /// None once the session has been removed from agent-deck.
+    pub title: Option<String>,
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupUsage {
+    pub group_path: String,
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DailyUsage {
+    pub day: String,
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UsageReport {
+    pub total: UsageTotals,
+    /// Sorted by cost, most expensive first.
+    pub sessions: Vec<SessionUsage>,
+    /// Sorted by cost, most expensive first.
+    pub groups: Vec<GroupUsage>,
+    /// Oldest first.
+    pub days: Vec<DailyUsage>,
+    /// Models with no matching price (counted as $0).
+    pub unpriced_models: Vec<String>,
+}
+
+fn sort_by_cost_desc<T>(items: &mut [T], cost: impl Fn(&T) -> f64) {
+    items.sort_by(|a, b| cost(b).total_cmp(&cost(a)));
+}
+
+fn build_report(
+    rows: &[UsageRow],
+    overrides: &[ModelPrice],
+    titles: &HashMap<String, String>,
+) -> UsageReport {
+    let defaults = default_prices();
+    let mut report = UsageReport::default();
+    let mut sessions: HashMap<&str, SessionUsage> = HashMap::new();
+    let mut groups: HashMap<&str, UsageTotals> = HashMap::new();
+    let mut days: HashMap<&str, UsageTotals> = HashMap::new();
+
+    for row in rows {
+        let cost = match price_for(&row.model, overrides, &defaults) {
+            Some(price) => cost_usd(&row.tokens, price),
+            None => {
+                if !report.unpriced_models.contains(&row.model) {
+                    report.unpriced_models.push(row.model.clone());
+                }
+                0.0
+            }
+        };
+        report.total.add(&row.tokens, cost);
+        sessions
+            .entry(&row.session_id)
+            .or_insert_with(|| SessionUsage {
+                session_id: row.session_id.clone(),
+                group_path: row.group_path.clone(),
+                title: titles.get(&row.session_id).cloned(),
+                totals: UsageTotals::default(),
+            })
+            .totals
+            .add(&row.tokens, cost);
+        groups
+            .entry(&row.group_path)
+            .or_default()
+            .add(&row.tokens, cost);
+        days.entry(&row.day).or_default().add(&row.tokens, cost);
+    }
+
+    report.sessions = sessions.into_values().collect();
+    sort_by_cost_desc(&mut report.sessions, |s| s.totals.cost_usd);
+    report.groups = groups
+        .into_iter()
+        .map(|(group_path, totals)| GroupUsage {
+            group_path: group_path.to_string(),
+            totals,
+        })
+        .collect();
+    sort_by_cost_desc(&mut report.groups, |g| g.totals.cost_usd);
+    report.days = days
+        .into_iter()
+        .map(|(day, totals)| DailyUsage {
+            day: day.to_string(),
+            totals,
+        })
+        .collect();
+    report.days.sort_by(|a, b| a.day.cmp(&b.day));
+    report.unpriced_models.sort();
+    report
+}
+
+/// Refresh usage from transcripts and build a report, optionally limited to
+/// one group and/or days on or after `since` (YYYY-MM-DD).
+pub fn usage_report(
+    orca_db: &OrcaDb,
+    group_path: Option<&str>,
+    since: Option<&str>,
+) -> Result<UsageReport, String> {
+    refresh_usage(orca_db)?;
+    let rows: Vec<UsageRow> = orca_db
+        .get_usage_rows(since)?
+        .into_iter()
+        .filter(|r| group_path.map_or(true, |g| r.group_path == g))
+        .collect();
+    let titles = agentdeck::list_sessions_with_prompts(orca_db, group_path)
+        .map(|sessions| sessions.into_iter().map(|s| (s.id, s.title)).collect())
+        .unwrap_or_default();
+    Ok(build_report(&rows, &orca_db.get_model_prices()?, &titles))
+}
+
+/// Per-group totals for days on or after `since`, without refreshing from
+/// transcripts first.
+pub fn group_totals(orca_db: &OrcaDb, since: &str) -> Result<HashMap<String, UsageTotals>, String> {
+    let rows = orca_db.get_usage_rows(Some(since))?;
+    let report = build_report(&rows, &orca_db.get_model_prices()?, &HashMap::new());
+    Ok(report
+        .groups
+        .into_iter()
+        .map(|g| (g.group_path, g.totals))
+        .collect())
+}
+
+#[tauri::command]
+pub async fn get_usage_report(
+    orca_db: State<'_, OrcaDb>,
+    group_path: Option<String>,
+    since: Option<String>,
+) -> Result<UsageReport, String> {
+    let orca_db = orca_db.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        usage_report(&orca_db, group_path.as_deref(), since.as_deref())
+    })
+    .await
+    .map_err(|e| format!("Task failed: {e}"))?
+}
+
+/// The effective price table: user overrides first, then built-in defaults
+/// that aren't overridden.
+#[tauri::command]
+pub fn get_model_prices(orca_db: State<'_, OrcaDb>) -> Result<Vec<ModelPrice>, String> {
+    let mut prices = orca_db.get_model_prices()?;
+    for default in default_prices() {
+        if !prices.iter().any(|p| p.model == default.model) {
+            prices.push(default);
+        }
+    }
+    Ok(prices)
+}
+
+#[tauri::command]
+pub fn set_model_price(orca_db: State<'_, OrcaDb>, price: ModelPrice) -> Result<(), String> {
+    if price.model.trim().is_empty() {
+        return Err("Model pattern cannot be empty".to_string());
+    }
+    orca_db.set_model_price(&price)
+}
+
+#[tauri::command]
+pub fn delete_model_price(orca_db: State<'_, OrcaDb>, model: String) -> Result<(), String> {
+    orca_db.delete_model_price(&model)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::Write;
+
+    fn assistant(id: &str, model: &str, input: u64, output: u64) -> serde_json::Value {
+        json!({
+            "type": "assistant",
+            "timestamp": "2025-03-04T10:00:00.000Z",
+            "message": {
+                "id": id,
+                "model": model,
+                "role": "assistant",
+                "usage": {
+                    "input_tokens": input,
+                    "output_tokens": output,
+                    "cache_creation_input_tokens": 100,
+                    "cache_read_input_tokens": 1000
+                }
+            }
+        })
+    }
+
+    fn row(session: &str, group: &str, day: &str, model: &str, input: u64) -> UsageRow {
+        UsageRow {
+            session_id: session.into(),
+            group_path: group.into(),
+            day: day.into(),
+            model: model.into(),
+            tokens: TokenCounts {
+                input_tokens: input,
+                ..TokenCounts::default()
+            },
+        }
+    }
+
+    #[test]
+    fn parse_usage_reads_tokens_and_day() {
+        let entry = parse_usage(&assistant("msg_1", "claude-sonnet-4-5", 10, 20)).expect("entry");
+        assert_eq!(entry.day, "2025-03-04");
+        assert_eq!(entry.model, "claude-sonnet-4-5");
+        assert_eq!(entry.tokens.output_tokens, 20);
+        assert_eq!(entry.tokens.cache_creation_tokens, 100);
+        assert_eq!(entry.tokens.cache_read_tokens, 1000);
+    }
+
+    #[test]
+    fn parse_usage_skips_users_and_zero_usage() {
+        assert!(parse_usage(&json!({"type": "user", "message": {"role": "user"}})).is_none());
+        let synthetic = json!({
+            "type": "assistant",
+            "message": {"model": "<synthetic>", "usage": {"input_tokens": 0, "output_tokens": 0}}
+        });
+        assert!(parse_usage(&synthetic).is_none());
+    }
+
+    #[test]
+    fn scan_dedupes_repeated_message_ids() {
+        let tmp = tempfile::tempdir().expect("failed to create temp dir");
+        let path = tmp.path().join("s.jsonl");
+        let mut file = File::create(&path).expect("create failed");
+        for line in [
+            assistant("msg_1", "claude-sonnet-4", 10, 5),
+            assistant("msg_1", "claude-sonnet-4", 10, 5),
+            assistant("msg_2", "claude-sonnet-4", 1, 1),
+        ] {
+            writeln!(file, "{line}").expect("write failed");
+        }
+
+        let scan = scan_file(&path, 0, None).expect("scan failed");
+        let tokens = scan.deltas[&("2025-03-04".to_string(), "claude-sonnet-4".to_string())];
+        assert_eq!(tokens.input_tokens, 11);
+        assert_eq!(scan.last_message_id.as_deref(), Some("msg_2"));
+
+        // Resuming with the stored last ID skips a repeat across scans
+        writeln!(file, "{}", assistant("msg_2", "claude-sonnet-4", 1, 1)).expect("write failed");
+        let resumed = scan_file(&path, scan.offset, scan.last_message_id).expect("scan failed");
+        assert!(resumed.deltas.is_empty());
+    }
+
+    #[test]
+    fn scan_counts_last_usage_of_interleaved_message_ids() {
+        let tmp = tempfile::tempdir().expect("failed to create temp dir");
+        let path = tmp.path().join("s.jsonl");
+        let mut file = File::create(&path).expect("create failed");
+        for line in [
+            assistant("msg_1", "claude-sonnet-4", 10, 5),
+            assistant("msg_2", "claude-sonnet-4", 1, 1),
+            assistant("msg_1", "claude-sonnet-4", 10, 50),
+        ] {
+            writeln!(file, "{line}").expect("write failed");
+        }
+
+        let scan = scan_file(&path, 0, None).expect("scan failed");
+        let tokens = scan.deltas[&("2025-03-04".to_string(), "claude-sonnet-4".to_string())];
+        assert_eq!(tokens.input_tokens, 11);
+        assert_eq!(tokens.output_tokens, 51);
+        assert_eq!(scan.last_message_id.as_deref(), Some("msg_1"));
+    }
+
+    #[test]
+    fn price_lookup_prefers_longest_override_then_defaults() {
+        let defaults = default_prices();
+        let overrides = vec![
+            ModelPrice {
+                model: "claude".into(),
+                input_per_mtok: 1.0,
+                output_per_mtok: 1.0,
+                cache_write_per_mtok: 1.0,
+                cache_read_per_mtok: 1.0,
+            },
+            ModelPrice {
+                model: "claude-opus".into(),
+                input_per_mtok: 2.0,
+                output_per_mtok: 2.0,
+                cache_write_per_mtok: 2.0,
+                cache_read_per_mtok: 2.0,
+            },
+        ];
+        assert_eq!(
+            price_for("claude-opus-4-1", &overrides, &defaults)
+                .unwrap()
+                .model,
+            "claude-opus"
+        );
+        assert_eq!(
+            price_for("claude-opus-4-5-20251101", &[], &defaults)
+                .unwrap()
+                .model,
+            "opus-4-5"
+        );
+        assert_eq!(
+            price_for("claude-opus-4-1", &[], &defaults).unwrap().model,
+            "opus"
+        );
+        assert!(price_for("gpt-5", &[], &defaults).is_none());
+    }
+
+    #[test]
+    fn cost_uses_all_token_kinds() {
+        let price = ModelPrice {
+            model: "sonnet".into(),
+            input_per_mtok: 3.0,
+            output_per_mtok: 15.0,
+            cache_write_per_mtok: 3.75,
+            cache_read_per_mtok: 0.3,
+        };
+        let tokens = TokenCounts {
+            input_tokens: 1_000_000,
+            output_tokens: 1_000_000,
+            cache_creation_tokens: 1_000_000,
+            cache_read_tokens: 1_000_000,
+        };
+        assert!((cost_usd(&tokens, &price) - 22.05).abs() < 1e-9);
+    }
+
+    #[test]
+    fn report_aggregates_by_session_group_and_day() {
+        let rows = vec![
+            row("s1", "/a", "2025-01-01", "claude-sonnet-4", 1_000_000),
+            row("s1", "/a", "2025-01-02", "claude-sonnet-4", 1_000_000),
+            row("s2", "/b", "2025-01-02", "claude-opus-4-1", 1_000_000),
+            row("s3", "/b", "2025-01-02", "mystery-model", 5),
+        ];
+        let titles = HashMap::from([("s1".to_string(), "Fix bug".to_string())]);
+        let report = build_report(&rows, &[], &titles);
+
+        assert!((report.total.cost_usd - 21.0).abs() < 1e-9);
+        assert_eq!(report.sessions[0].session_id, "s2");
+        assert_eq!(report.sessions[1].title.as_deref(), Some("Fix bug"));
+        assert_eq!(report.groups[0].group_path, "/b");
+        assert_eq!(report.days.len(), 2);
+        assert_eq!(report.days[0].day, "2025-01-01");
+        assert_eq!(report.days[1].totals.tokens.input_tokens, 2_000_005);
+        assert_eq!(report.unpriced_models, vec!["mystery-model".to_string()]);
+    }
+}
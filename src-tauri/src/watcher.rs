@@ -0,0 +1,509 @@
+//! Filesystem watcher that pushes session changes to the frontend.
+//!
+//! Watches agent-deck's `state.db` (plus its WAL/journal files), the Claude
+//! JSONL transcripts under `~/.claude/projects`, OpenCode's session storage
+//! and Orca's hook events directory (see [`crate::hooks`]). Changes are debounced, diffed
+//! against the last snapshot, and emitted as:
+//!
+//! - `session-updated` — a [`SessionDiff`] of added/updated/removed sessions
+//! - `attention-changed` — a list of [`AttentionChange`]s
+//! - `session-activity` — IDs of the sessions that were re-analysed, so the
+//!   frontend can refresh their summaries without polling
+//!
+//! The first two are also published to the automation API's event stream
+//! (see [`crate::local_api`]). Without an app (the headless daemon, see
+//! [`crate::daemon`]) that stream and webhooks are the only consumers.
+//!
+//! Only sessions touched by a change are re-analysed, so idle sessions cost
+//! nothing between events.
+
+use crate::agentdeck;
+use crate::claude_logs::{self, AttentionStatus};
+use crate::hooks;
+use crate::local_api;
+use crate::models::{AttentionThresholds, Session};
+use crate::notifications;
+use crate::opencode_logs;
+use crate::orca_db::OrcaDb;
+use crate::webhooks;
+use notify_debouncer_mini::notify::{RecursiveMode, Watcher};
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+use tauri::Emitter;
+
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SessionDiff {
+    pub added: Vec<Session>,
+    pub updated: Vec<Session>,
+    pub removed: Vec<String>,
+}
+
+impl SessionDiff {
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.updated.is_empty() && self.removed.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AttentionChange {
+    pub session_id: String,
+    pub group_path: String,
+    pub attention: AttentionStatus,
+    /// The status before this change, if the session had been analysed.
+    pub previous: Option<AttentionStatus>,
+}
+
+/// What a batch of filesystem events means for Orca.
+#[derive(Debug, Default)]
+struct ChangeSet {
+    /// agent-deck's DB (or its WAL) was written.
+    db_changed: bool,
+    /// Claude session IDs whose JSONL transcript changed.
+    claude_session_ids: HashSet<String>,
+    /// OpenCode's storage changed. Its files don't name the agent-deck
+    /// session cheaply, so every OpenCode session is re-checked.
+    opencode_changed: bool,
+}
+
+fn classify_paths<'a>(paths: impl IntoIterator<Item = &'a Path>, db_path: &Path) -> ChangeSet {
+    let db_name = db_path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    let mut changes = ChangeSet::default();
+    for path in paths {
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        if path.extension().is_some_and(|ext| ext == "jsonl") {
+            if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                changes.claude_session_ids.insert(stem.to_string());
+            }
+        } else if !db_name.is_empty() && name.starts_with(db_name) {
+            // state.db, state.db-wal, state.db-journal
+            changes.db_changed = true;
+        }
+    }
+    changes
+}
+
+fn diff_sessions(old: &HashMap<String, Session>, new: &[Session]) -> SessionDiff {
+    let mut diff = SessionDiff::default();
+    for session in new {
+        match old.get(&session.id) {
+            None => diff.added.push(session.clone()),
+            Some(prev) if prev != session => diff.updated.push(session.clone()),
+            Some(_) => {}
+        }
+    }
+    let new_ids: HashSet<&str> = new.iter().map(|s| s.id.as_str()).collect();
+    diff.removed = old
+        .keys()
+        .filter(|id| !new_ids.contains(id.as_str()))
+        .cloned()
+        .collect();
+    diff.removed.sort();
+    diff
+}
+
+fn attention_for(session: &Session, thresholds: &AttentionThresholds) -> AttentionStatus {
+    let tmux = if session.tmux_session.is_empty() {
+        None
+    } else {
+        Some(session.tmux_session.as_str())
+    };
+    claude_logs::compute_attention(
+        &session.project_path,
+        session.tool_session_id(),
+        &session.status,
+        tmux,
+        &session.tool,
+        thresholds,
+    )
+}
+
+/// Last known state, used to turn filesystem events into diffs.
+#[derive(Default)]
+struct Snapshot {
+    sessions: HashMap<String, Session>,
+    attention: HashMap<String, AttentionStatus>,
+}
+
+impl Snapshot {
+    /// Apply a change set, returning the diffs to emit and the IDs of the
+    /// sessions that were re-analysed.
+    fn apply(
+        &mut self,
+        changes: &ChangeSet,
+        orca_db: &OrcaDb,
+    ) -> Result<(SessionDiff, Vec<AttentionChange>, Vec<String>), String> {
+        let mut diff = SessionDiff::default();
+        let mut to_check: HashSet<String> = HashSet::new();
+
+        if changes.db_changed {
+            let sessions = agentdeck::list_sessions_with_prompts(orca_db, None)?;
+            diff = diff_sessions(&self.sessions, &sessions);
+            for id in &diff.removed {
+                self.sessions.remove(id);
+                self.attention.remove(id);
+            }
+            for session in diff.added.iter().chain(&diff.updated) {
+                to_check.insert(session.id.clone());
+                self.sessions.insert(session.id.clone(), session.clone());
+            }
+        }
+
+        for session in self.sessions.values() {
+            if changes.opencode_changed && session.tool == "opencode" {
+                to_check.insert(session.id.clone());
+            }
+            if session
+                .claude_session_id
+                .as_ref()
+                .is_some_and(|csid| changes.claude_session_ids.contains(csid))
+            {
+                to_check.insert(session.id.clone());
+            }
+        }
+
+        let thresholds = orca_db.get_all_attention_thresholds().unwrap_or_default();
+        let mut attention_changes = Vec::new();
+        let mut checked = Vec::new();
+        for id in to_check {
+            let Some(session) = self.sessions.get(&id) else {
+                continue;
+            };
+            checked.push(id.clone());
+            let attention = attention_for(
+                session,
+                &thresholds
+                    .get(&session.group_path)
+                    .cloned()
+                    .unwrap_or_default(),
+            );
+            if self.attention.get(&id) != Some(&attention) {
+                let previous = self.attention.insert(id.clone(), attention.clone());
+                attention_changes.push(AttentionChange {
+                    session_id: id,
+                    group_path: session.group_path.clone(),
+                    attention,
+                    previous,
+                });
+            }
+        }
+
+        checked.sort();
+        Ok((diff, attention_changes, checked))
+    }
+}
+
+/// Directories to watch, with how deep to watch them.
+type WatchList = Vec<(PathBuf, RecursiveMode)>;
+
+/// Watch every directory in `dirs` that exists and return the ones that
+/// don't yet. For those the parent is watched instead, so creating them
+/// (e.g. `~/.claude/projects` on Claude's first run) wakes the event loop,
+/// which calls this again with the returned list.
+fn add_watches(watcher: &mut dyn Watcher, dirs: WatchList) -> WatchList {
+    let mut missing = Vec::new();
+    for (dir, mode) in dirs {
+        if !dir.is_dir() {
+            if let Some(parent) = dir.parent().filter(|p| p.is_dir()) {
+                let _ = watcher.watch(parent, RecursiveMode::NonRecursive);
+            }
+            missing.push((dir, mode));
+            continue;
+        }
+        match watcher.watch(&dir, mode) {
+            Ok(()) => log::info!("Watching {} for session changes", dir.display()),
+            Err(e) => log::warn!("Cannot watch {}: {e}", dir.display()),
+        }
+    }
+    missing
+}
+
+/// Start watching in a background thread. Directories that don't exist yet
+/// are picked up once they're created.
+pub fn start(app: Option<crate::AppHandle>, orca_db: OrcaDb) {
+    std::thread::spawn(move || {
+        if let Err(e) = run(app.as_ref(), &orca_db) {
+            log::error!("Session watcher stopped: {e}");
+        }
+    });
+}
+
+fn run(app: Option<&crate::AppHandle>, orca_db: &OrcaDb) -> Result<(), String> {
+    let db_path = agentdeck::db_path()?;
+    let (tx, rx) = mpsc::channel::<DebounceEventResult>();
+    let mut debouncer =
+        new_debouncer(DEBOUNCE, tx).map_err(|e| format!("Failed to create watcher: {e}"))?;
+
+    let mut watched: WatchList = Vec::new();
+    if let Some(dir) = db_path.parent() {
+        watched.push((dir.to_path_buf(), RecursiveMode::NonRecursive));
+    }
+    if let Some(dir) = claude_logs::claude_projects_dir() {
+        watched.push((dir, RecursiveMode::Recursive));
+    }
+    // Messages and parts only; session metadata changes come with them.
+    let opencode_dir = opencode_logs::opencode_storage_dir();
+    if let Some(dir) = &opencode_dir {
+        for sub in ["message", "part"] {
+            watched.push((dir.join(sub), RecursiveMode::Recursive));
+        }
+    }
+    let hooks_dir = hooks::events_dir();
+    if let Some(dir) = hooks_dir {
+        watched.push((dir.to_path_buf(), RecursiveMode::NonRecursive));
+    }
+    let mut missing = add_watches(debouncer.watcher(), watched);
+    for (dir, _) in &missing {
+        log::info!("{} does not exist yet; waiting for it", dir.display());
+    }
+
+    // Seed the snapshot so the first real change produces a minimal diff.
+    let mut snapshot = Snapshot::default();
+    let seed = ChangeSet {
+        db_changed: true,
+        ..ChangeSet::default()
+    };
+    if let Err(e) = snapshot.apply(&seed, orca_db) {
+        log::warn!("Session watcher: initial snapshot failed: {e}");
+    }
+
+    for result in rx {
+        let events = match result {
+            Ok(events) => events,
+            Err(e) => {
+                log::warn!("Session watcher error: {e}");
+                continue;
+            }
+        };
+        if !missing.is_empty() {
+            missing = add_watches(debouncer.watcher(), missing);
+        }
+        let mut changes = classify_paths(events.iter().map(|e| e.path.as_path()), &db_path);
+        if events
+            .iter()
+            .any(|e| hooks_dir.is_some_and(|dir| e.path.parent() == Some(dir)))
+        {
+            // Hook signals are keyed by Claude session ID, like transcripts.
+            changes
+                .claude_session_ids
+                .extend(hooks::ingest_pending(orca_db));
+        }
+        changes.opencode_changed = events.iter().any(|e| {
+            opencode_dir
+                .as_deref()
+                .is_some_and(|dir| e.path.starts_with(dir))
+        });
+        if !changes.db_changed && changes.claude_session_ids.is_empty() && !changes.opencode_changed
+        {
+            continue;
+        }
+
+        let (diff, attention, checked) = match snapshot.apply(&changes, orca_db) {
+            Ok(result) => result,
+            Err(e) => {
+                log::warn!("Session watcher: failed to refresh sessions: {e}");
+                continue;
+            }
+        };
+        if !diff.is_empty() {
+            log::debug!(
+                "session-updated: {} added, {} updated, {} removed",
+                diff.added.len(),
+                diff.updated.len(),
+                diff.removed.len()
+            );
+            if let Some(app) = app {
+                let _ = app.emit("session-updated", &diff);
+            }
+            local_api::publish("session-updated", &diff);
+        }
+        if !attention.is_empty() {
+            log::debug!("attention-changed: {} sessions", attention.len());
+            local_api::publish("attention-changed", &attention);
+            if let Some(app) = app {
+                let _ = app.emit("attention-changed", &attention);
+                notifications::notify_changes(app, orca_db, &attention, &snapshot.sessions);
+            }
+            webhooks::dispatch_attention_changes(orca_db, &attention, &snapshot.sessions);
+        }
+        if !checked.is_empty() {
+            if let Some(app) = app {
+                let _ = app.emit("session-activity", &checked);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(id: &str, status: &str) -> Session {
+        Session {
+            id: id.into(),
+            title: format!("Session {id}"),
+            project_path: "/tmp/project".into(),
+            group_path: "group".into(),
+            sort_order: 0,
+            status: status.into(),
+            tmux_session: String::new(),
+            created_at: 0,
+            last_accessed: 0,
+            worktree_path: String::new(),
+            worktree_repo: String::new(),
+            worktree_branch: String::new(),
+            tool: "claude".into(),
+            claude_session_id: None,
+            opencode_session_id: None,
+            prompt: None,
+            pr_url: None,
+            pr_number: None,
+            pr_state: None,
+        }
+    }
+
+    #[test]
+    fn classify_db_and_wal() {
+        let db = Path::new("/home/u/.agent-deck/profiles/default/state.db");
+        let changes = classify_paths(
+            [Path::new(
+                "/home/u/.agent-deck/profiles/default/state.db-wal",
+            )],
+            db,
+        );
+        assert!(changes.db_changed);
+        assert!(changes.claude_session_ids.is_empty());
+    }
+
+    #[test]
+    fn classify_jsonl_by_stem() {
+        let db = Path::new("/home/u/.agent-deck/profiles/default/state.db");
+        let changes = classify_paths(
+            [
+                Path::new("/home/u/.claude/projects/-src-app/abc-123.jsonl"),
+                Path::new("/home/u/.claude/projects/-src-app/notes.txt"),
+            ],
+            db,
+        );
+        assert!(!changes.db_changed);
+        assert_eq!(
+            changes.claude_session_ids,
+            HashSet::from(["abc-123".to_string()])
+        );
+    }
+
+    #[test]
+    fn classify_ignores_unrelated_files() {
+        let db = Path::new("/home/u/.agent-deck/profiles/default/state.db");
+        let changes = classify_paths(
+            [Path::new(
+                "/home/u/.agent-deck/profiles/default/config.toml",
+            )],
+            db,
+        );
+        assert!(!changes.db_changed);
+    }
+
+    #[test]
+    fn diff_detects_added_updated_removed() {
+        let old: HashMap<String, Session> = [session("a", "idle"), session("b", "idle")]
+            .into_iter()
+            .map(|s| (s.id.clone(), s))
+            .collect();
+        let new = vec![
+            session("a", "idle"),
+            session("b", "running"),
+            session("c", "idle"),
+        ];
+        let diff = diff_sessions(&old, &new);
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].id, "c");
+        assert_eq!(diff.updated.len(), 1);
+        assert_eq!(diff.updated[0].id, "b");
+        assert!(diff.removed.is_empty());
+
+        let diff = diff_sessions(&old, &[session("a", "idle")]);
+        assert_eq!(diff.removed, vec!["b".to_string()]);
+        assert!(diff.added.is_empty() && diff.updated.is_empty());
+    }
+
+    #[test]
+    fn diff_unchanged_is_empty() {
+        let old: HashMap<String, Session> = [("a".to_string(), session("a", "idle"))].into();
+        assert!(diff_sessions(&old, &[session("a", "idle")]).is_empty());
+    }
+
+    #[test]
+    fn jsonl_change_rechecks_only_matching_session() {
+        let tmp = tempfile::tempdir().expect("failed to create temp dir");
+        let db = OrcaDb::init(tmp.path()).expect("init failed");
+
+        let mut a = session("a", "running");
+        a.claude_session_id = Some("csid-a".into());
+        let b = session("b", "running");
+        let mut snapshot = Snapshot::default();
+        snapshot.sessions.insert(a.id.clone(), a);
+        snapshot.sessions.insert(b.id.clone(), b);
+
+        let changes = ChangeSet {
+            db_changed: false,
+            claude_session_ids: HashSet::from(["csid-a".to_string()]),
+            opencode_changed: false,
+        };
+        let (diff, attention, checked) = snapshot.apply(&changes, &db).expect("apply failed");
+        assert!(diff.is_empty());
+        assert_eq!(attention.len(), 1);
+        assert_eq!(attention[0].session_id, "a");
+        assert_eq!(checked, vec!["a".to_string()]);
+
+        // Same state again produces no change, but is still activity
+        let (_, attention, checked) = snapshot.apply(&changes, &db).expect("apply failed");
+        assert!(attention.is_empty());
+        assert_eq!(checked, vec!["a".to_string()]);
+    }
+
+    #[test]
+    fn opencode_change_rechecks_opencode_sessions() {
+        let tmp = tempfile::tempdir().expect("failed to create temp dir");
+        let db = OrcaDb::init(tmp.path()).expect("init failed");
+
+        let mut a = session("a", "running");
+        a.tool = "opencode".into();
+        let b = session("b", "running");
+        let mut snapshot = Snapshot::default();
+        snapshot.sessions.insert(a.id.clone(), a);
+        snapshot.sessions.insert(b.id.clone(), b);
+
+        let changes = ChangeSet {
+            opencode_changed: true,
+            ..ChangeSet::default()
+        };
+        let (_, attention, _) = snapshot.apply(&changes, &db).expect("apply failed");
+        assert_eq!(attention.len(), 1);
+        assert_eq!(attention[0].session_id, "a");
+    }
+
+    #[test]
+    fn missing_dir_is_watched_once_created() {
+        let tmp = tempfile::tempdir().unwrap();
+        let projects = tmp.path().join("projects");
+        let mut watcher = notify_debouncer_mini::notify::recommended_watcher(|_| {}).unwrap();
+
+        let missing = add_watches(
+            &mut watcher,
+            vec![(projects.clone(), RecursiveMode::Recursive)],
+        );
+        assert_eq!(missing.len(), 1);
+
+        std::fs::create_dir(&projects).unwrap();
+        assert!(add_watches(&mut watcher, missing).is_empty());
+    }
+}
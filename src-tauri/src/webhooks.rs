@@ -0,0 +1,503 @@
+//! Outbound webhooks for attention and lifecycle events.
+//!
+//! Webhooks are stored in `OrcaDb` with the events they subscribe to:
+//!
+//! - `session.needs_input` — a session started waiting for input or permission
+//! - `session.error` — a session errored or hit a rate limit
+//! - `session.finished` — a running session finished its turn
+//! - `pr.created` / `pr.merged` — from the PR info Orca stores per session
+//!
+//! Each delivery POSTs the event as JSON, or the webhook's body template
+//! with `{{field}}` placeholders filled in. Network errors, 429s and 5xx
+//! responses are retried with exponential backoff; every delivery ends up in
+//! the `webhook_deliveries` log.
+
+use crate::claude_logs::{self, AttentionStatus};
+use crate::dates::{now_secs, utc_timestamp};
+use crate::models::{Session, Webhook, WebhookDelivery};
+use crate::orca_db::OrcaDb;
+use crate::remote_common::build_client;
+use crate::watcher::AttentionChange;
+use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+use tauri::State;
+
+pub const EVENTS: &[&str] = &[
+    "session.needs_input",
+    "session.error",
+    "session.finished",
+    "pr.created",
+    "pr.merged",
+];
+const MAX_ATTEMPTS: u32 = 4;
+const INITIAL_BACKOFF: Duration = Duration::from_secs(2);
+/// Deliveries kept in the log, across all webhooks.
+const DELIVERY_LOG_LIMIT: usize = 500;
+
+/// What a webhook receives. Templates can use any of these fields.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WebhookEvent {
+    pub event: String,
+    pub session_id: String,
+    pub session_title: String,
+    pub group_path: String,
+    pub attention: Option<AttentionStatus>,
+    pub last_text: Option<String>,
+    pub pr_url: Option<String>,
+    pub pr_number: Option<u64>,
+    /// UTC, RFC 3339.
+    pub timestamp: String,
+}
+
+impl WebhookEvent {
+    pub fn new(event: &str, session_id: &str, session_title: &str, group_path: &str) -> Self {
+        Self {
+            event: event.to_string(),
+            session_id: session_id.to_string(),
+            session_title: session_title.to_string(),
+            group_path: group_path.to_string(),
+            timestamp: utc_timestamp(now_secs()),
+            ..Self::default()
+        }
+    }
+}
+
+/// Webhook events implied by an attention change.
+fn events_for_change(change: &AttentionChange) -> Vec<&'static str> {
+    let mut events = Vec::new();
+    match change.attention {
+        AttentionStatus::NeedsInput | AttentionStatus::AwaitingPermission => {
+            events.push("session.needs_input");
+        }
+        AttentionStatus::Error | AttentionStatus::RateLimited => events.push("session.error"),
+        _ => {}
+    }
+    let was_running = matches!(
+        change.previous,
+        Some(AttentionStatus::Running | AttentionStatus::Compacting)
+    );
+    if was_running
+        && matches!(
+            change.attention,
+            AttentionStatus::Idle | AttentionStatus::NeedsInput
+        )
+    {
+        events.push("session.finished");
+    }
+    events
+}
+
+/// Fill `{{field}}` placeholders from the event. Values are JSON-escaped
+/// (without quotes) so templates like `{"text": "{{session_title}}"}` stay
+/// valid JSON; missing fields become empty.
+fn render_template(template: &str, event: &WebhookEvent) -> String {
+    let fields = serde_json::to_value(event).unwrap_or_default();
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let value = match &fields[rest[start + 2..start + 2 + len].trim()] {
+            serde_json::Value::Null => String::new(),
+            serde_json::Value::String(s) => {
+                let quoted = serde_json::Value::String(s.clone()).to_string();
+                quoted[1..quoted.len() - 1].to_string()
+            }
+            other => other.to_string(),
+        };
+        out.push_str(&value);
+        rest = &rest[start + 2 + len + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn render_body(webhook: &Webhook, event: &WebhookEvent) -> Result<String, String> {
+    match &webhook.body_template {
+        Some(template) => Ok(render_template(template, event)),
+        None => serde_json::to_string(event).map_err(|e| e.to_string()),
+    }
+}
+
+fn client() -> Result<reqwest::Client, String> {
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    build_client(headers)
+}
+
+struct Outcome {
+    delivered: bool,
+    attempts: u32,
+    status_code: Option<u16>,
+    error: Option<String>,
+}
+
+/// POST a body, retrying transient failures with exponential backoff.
+async fn deliver(client: &reqwest::Client, url: &str, body: String, backoff: Duration) -> Outcome {
+    let mut outcome = Outcome {
+        delivered: false,
+        attempts: 0,
+        status_code: None,
+        error: None,
+    };
+    let mut delay = backoff;
+    while outcome.attempts < MAX_ATTEMPTS {
+        outcome.attempts += 1;
+        let retryable = match client.post(url).body(body.clone()).send().await {
+            Ok(resp) => {
+                let status = resp.status();
+                outcome.status_code = Some(status.as_u16());
+                if status.is_success() {
+                    outcome.delivered = true;
+                    outcome.error = None;
+                    return outcome;
+                }
+                outcome.error = Some(format!("HTTP {status}"));
+                status.is_server_error() || status.as_u16() == 429
+            }
+            Err(e) => {
+                outcome.status_code = None;
+                outcome.error = Some(format!("Request failed: {e}"));
+                true
+            }
+        };
+        if !retryable || outcome.attempts == MAX_ATTEMPTS {
+            break;
+        }
+        tokio::time::sleep(delay).await;
+        delay *= 2;
+    }
+    outcome
+}
+
+/// Deliver an event to one webhook and log the result.
+async fn deliver_and_log(
+    orca_db: &OrcaDb,
+    webhook: &Webhook,
+    event: &WebhookEvent,
+    backoff: Duration,
+) -> Result<WebhookDelivery, String> {
+    let webhook_id = webhook
+        .id
+        .ok_or_else(|| "Webhook has not been saved".to_string())?;
+    let outcome = deliver(
+        &client()?,
+        &webhook.url,
+        render_body(webhook, event)?,
+        backoff,
+    )
+    .await;
+    let mut delivery = WebhookDelivery {
+        id: 0,
+        webhook_id,
+        event: event.event.clone(),
+        delivered: outcome.delivered,
+        attempts: outcome.attempts,
+        status_code: outcome.status_code,
+        error: outcome.error,
+        created_at: now_secs(),
+    };
+    if !delivery.delivered {
+        log::warn!(
+            "Webhook '{}' failed for {}: {:?}",
+            webhook.name,
+            event.event,
+            delivery.error
+        );
+    }
+    delivery.id = orca_db.record_webhook_delivery(&delivery, DELIVERY_LOG_LIMIT)?;
+    Ok(delivery)
+}
+
+/// Send an event to every enabled webhook subscribed to it, in the background.
+pub fn dispatch(orca_db: &OrcaDb, event: WebhookEvent) {
+    let webhooks = match orca_db.get_webhooks() {
+        Ok(webhooks) => webhooks,
+        Err(e) => {
+            log::warn!("Webhooks: {e}");
+            return;
+        }
+    };
+    for webhook in webhooks
+        .into_iter()
+        .filter(|w| w.enabled && w.events.contains(&event.event))
+    {
+        let orca_db = orca_db.clone();
+        let event = event.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = deliver_and_log(&orca_db, &webhook, &event, INITIAL_BACKOFF).await {
+                log::warn!("Webhook '{}': {e}", webhook.name);
+            }
+        });
+    }
+}
+
+/// Dispatch webhook events for attention changes from the session watcher.
+pub fn dispatch_attention_changes(
+    orca_db: &OrcaDb,
+    changes: &[AttentionChange],
+    sessions: &HashMap<String, Session>,
+) {
+    for change in changes {
+        let events = events_for_change(change);
+        if events.is_empty() {
+            continue;
+        }
+        let Some(session) = sessions.get(&change.session_id) else {
+            continue;
+        };
+        let last_text = claude_logs::session_last_text(session);
+        for name in events {
+            dispatch(
+                orca_db,
+                WebhookEvent {
+                    attention: Some(change.attention.clone()),
+                    last_text: last_text.clone(),
+                    ..WebhookEvent::new(name, &session.id, &session.title, &session.group_path)
+                },
+            );
+        }
+    }
+}
+
+fn validate(webhook: &Webhook) -> Result<(), String> {
+    if webhook.name.trim().is_empty() {
+        return Err("Webhook name is required".to_string());
+    }
+    if !webhook.url.starts_with("http://") && !webhook.url.starts_with("https://") {
+        return Err(format!("Invalid webhook URL: '{}'", webhook.url));
+    }
+    if let Some(event) = webhook
+        .events
+        .iter()
+        .find(|e| !EVENTS.contains(&e.as_str()))
+    {
+        return Err(format!(
+            "Unknown webhook event: '{event}' (expected one of {})",
+            EVENTS.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_webhooks(orca_db: State<'_, OrcaDb>) -> Result<Vec<Webhook>, String> {
+    orca_db.get_webhooks()
+}
+
+/// Add a webhook, or update one that has an id. Returns its id.
+#[tauri::command]
+pub fn save_webhook(orca_db: State<'_, OrcaDb>, webhook: Webhook) -> Result<i64, String> {
+    validate(&webhook)?;
+    orca_db.save_webhook(&webhook)
+}
+
+#[tauri::command]
+pub fn delete_webhook(orca_db: State<'_, OrcaDb>, id: i64) -> Result<(), String> {
+    orca_db.delete_webhook(id)
+}
+
+#[tauri::command]
+pub fn get_webhook_deliveries(
+    orca_db: State<'_, OrcaDb>,
+    webhook_id: Option<i64>,
+    limit: Option<usize>,
+) -> Result<Vec<WebhookDelivery>, String> {
+    orca_db.get_webhook_deliveries(webhook_id, limit.unwrap_or(100))
+}
+
+/// Send a sample `session.needs_input` event to a webhook and wait for the result.
+#[tauri::command]
+pub async fn test_webhook(orca_db: State<'_, OrcaDb>, id: i64) -> Result<WebhookDelivery, String> {
+    let orca_db = orca_db.inner().clone();
+    let webhook = orca_db
+        .get_webhooks()?
+        .into_iter()
+        .find(|w| w.id == Some(id))
+        .ok_or_else(|| format!("Webhook {id} not found"))?;
+    let event = WebhookEvent {
+        attention: Some(AttentionStatus::NeedsInput),
+        last_text: Some("This is a test event from Orca.".to_string()),
+        ..WebhookEvent::new("session.needs_input", "test", "Test session", "/test")
+    };
+    deliver_and_log(&orca_db, &webhook, &event, INITIAL_BACKOFF).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+
+    /// A local HTTP stand-in answering with `statuses` in order and sending
+    /// each request body back over the channel.
+    fn serve(statuses: Vec<u16>) -> (String, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            for status in statuses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                tx.send(String::from_utf8(body).unwrap()).unwrap();
+                let response = format!(
+                    "HTTP/1.1 {status} X\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                );
+                reader.get_mut().write_all(response.as_bytes()).unwrap();
+            }
+        });
+        (url, rx)
+    }
+
+    fn event() -> WebhookEvent {
+        WebhookEvent {
+            attention: Some(AttentionStatus::AwaitingPermission),
+            last_text: Some("Run \"cargo test\"?".into()),
+            ..WebhookEvent::new("session.needs_input", "s1", "Fix login", "/repo")
+        }
+    }
+
+    #[test]
+    fn template_escapes_values_as_json() {
+        let body = render_template(
+            r#"{"text": "{{session_title}}: {{last_text}} ({{attention}}, {{pr_number}}{{nope}})"}"#,
+            &event(),
+        );
+        let parsed: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(
+            parsed["text"],
+            r#"Fix login: Run "cargo test"? (awaiting_permission, )"#
+        );
+        assert_eq!(render_template("no {{ end", &event()), "no {{ end");
+    }
+
+    #[test]
+    fn attention_changes_map_to_events() {
+        let change = |previous, attention| AttentionChange {
+            session_id: "s1".into(),
+            group_path: "/repo".into(),
+            attention,
+            previous,
+        };
+        assert_eq!(
+            events_for_change(&change(
+                Some(AttentionStatus::Running),
+                AttentionStatus::NeedsInput
+            )),
+            vec!["session.needs_input", "session.finished"]
+        );
+        assert_eq!(
+            events_for_change(&change(None, AttentionStatus::RateLimited)),
+            vec!["session.error"]
+        );
+        assert_eq!(
+            events_for_change(&change(
+                Some(AttentionStatus::Compacting),
+                AttentionStatus::Idle
+            )),
+            vec!["session.finished"]
+        );
+        assert!(events_for_change(&change(
+            Some(AttentionStatus::Idle),
+            AttentionStatus::Running
+        ))
+        .is_empty());
+    }
+
+    #[test]
+    fn deliver_retries_server_errors() {
+        let (url, bodies) = serve(vec![503, 500, 200]);
+        let outcome = tauri::async_runtime::block_on(deliver(
+            &client().unwrap(),
+            &url,
+            "{}".into(),
+            Duration::from_millis(1),
+        ));
+        assert!(outcome.delivered);
+        assert_eq!(outcome.attempts, 3);
+        assert_eq!(outcome.status_code, Some(200));
+        assert_eq!(bodies.iter().take(3).count(), 3);
+    }
+
+    #[test]
+    fn deliver_gives_up_on_client_errors() {
+        let (url, _bodies) = serve(vec![404]);
+        let outcome = tauri::async_runtime::block_on(deliver(
+            &client().unwrap(),
+            &url,
+            "{}".into(),
+            Duration::from_millis(1),
+        ));
+        assert!(!outcome.delivered);
+        assert_eq!(outcome.attempts, 1);
+        assert_eq!(outcome.error.as_deref(), Some("HTTP 404 Not Found"));
+    }
+
+    #[test]
+    fn deliveries_are_logged() {
+        let tmp = tempfile::tempdir().unwrap();
+        let db = OrcaDb::init(tmp.path()).unwrap();
+        let (url, bodies) = serve(vec![200]);
+        let mut webhook = Webhook {
+            id: None,
+            name: "chat".into(),
+            url,
+            events: vec!["session.needs_input".into()],
+            body_template: Some(r#"{"text": "{{session_title}} needs you"}"#.into()),
+            enabled: true,
+        };
+        webhook.id = Some(db.save_webhook(&webhook).unwrap());
+
+        let delivery = tauri::async_runtime::block_on(deliver_and_log(
+            &db,
+            &webhook,
+            &event(),
+            Duration::from_millis(1),
+        ))
+        .unwrap();
+        assert!(delivery.delivered);
+        assert_eq!(bodies.recv().unwrap(), r#"{"text": "Fix login needs you"}"#);
+        assert_eq!(
+            db.get_webhook_deliveries(webhook.id, 10).unwrap(),
+            vec![delivery]
+        );
+    }
+
+    #[test]
+    fn validate_rejects_unknown_events() {
+        let mut webhook = Webhook {
+            id: None,
+            name: "chat".into(),
+            url: "https://example.com/hook".into(),
+            events: vec!["pr.merged".into()],
+            body_template: None,
+            enabled: true,
+        };
+        assert!(validate(&webhook).is_ok());
+        webhook.events.push("session.exploded".into());
+        assert!(validate(&webhook).is_err());
+        webhook.events.pop();
+        webhook.url = "ftp://example.com".into();
+        assert!(validate(&webhook).is_err());
+    }
+}
@@ -1,5 +1,5 @@
 import { useState, useCallback, useRef, useEffect, useMemo } from "react";
-import { useQuery } from "@tanstack/react-query";
+import { useQuery, useQueryClient } from "@tanstack/react-query";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import { Sidebar } from "./components/Sidebar";
@@ -29,8 +29,8 @@ import {
 } from "./components/PrerequisiteCheck";
 import { UpdateNotification } from "./components/UpdateNotification";
 import { useSessionCreation } from "./hooks/useSessionCreation";
-import type { Group, Session, RemoteSession } from "./types";
-import { isMainSession, storageGet, storageSet } from "./utils";
+import type { FocusSessionEvent, Group, Session, RemoteSession } from "./types";
+import { isMainSession, isRemoteSession, storageGet, storageSet } from "./utils";
 import { queryKeys } from "./queryKeys";
 import { useSidebarResize } from "./hooks/useSidebarResize";
 import { useKeyboardShortcuts } from "./hooks/useKeyboardShortcuts";
@@ -46,6 +46,7 @@ const ANALYTICS_PROMPTED_KEY = "orca-analytics-prompted";
 const initialSavedView = storageGet(SELECTED_VIEW_KEY);
 
 function App() {
+  const queryClient = useQueryClient();
   const savedView = useRef(initialSavedView);
   const initialRestoreDone = useRef(false);
 
@@ -71,6 +72,7 @@ function App() {
   const [dismissedIds, setDismissedIds] = useState<Set<string>>(new Set());
   const dismissedLoaded = useRef(false);
   const [remoteSession, setRemoteSession] = useState<RemoteSession | null>(null);
+  const [remoteGroup, setRemoteGroup] = useState<Group | null>(null);
   const [remotePassword, setRemotePassword] = useState<string>("");
   const [remoteServerUrl, setRemoteServerUrl] = useState<string>("");
 
@@ -203,6 +205,27 @@ function App() {
     };
   }, []);
 
+  // Refresh session data when the backend watcher sees agent-deck or JSONL changes
+  useEffect(() => {
+    const unlistenSessions = listen("session-updated", () => {
+      queryClient.invalidateQueries({ queryKey: ["sessions"] });
+    });
+    const unlistenAttention = listen("attention-changed", () => {
+      queryClient.invalidateQueries({ queryKey: queryKeys.attentionSessions });
+    });
+    // Session summaries only refresh here; the cards don't poll
+    const unlistenActivity = listen<string[]>("session-activity", (event) => {
+      for (const sessionId of event.payload) {
+        queryClient.invalidateQueries({ queryKey: queryKeys.summary(sessionId) });
+      }
+    });
+    return () => {
+      unlistenSessions.then((fn) => fn()).catch(() => {});
+      unlistenAttention.then((fn) => fn()).catch(() => {});
+      unlistenActivity.then((fn) => fn()).catch(() => {});
+    };
+  }, [queryClient]);
+
   // Check agent-deck version on mount
   useEffect(() => {
     invoke<{ supported: string; installed: string }>("check_agent_deck_version")
@@ -269,10 +292,11 @@ function App() {
     queryFn: () =>
       needsActionFilter
         ? invoke("get_attention_sessions")
-        : invoke("get_sessions", {
-            groupPath: selectedGroup?.path ?? undefined,
-          }),
-    refetchInterval: 3_000,
+        : selectedGroup
+          ? invoke("backend_list_sessions", { groupPath: selectedGroup.path })
+          : invoke("get_sessions"),
+    // Fallback only; the backend watcher pushes session-updated events.
+    refetchInterval: 30_000,
   });
 
   const {
@@ -292,8 +316,6 @@ function App() {
     refetchInterval: 5_000,
   });
 
-  const liveTmuxSet = useMemo(() => new Set(liveTmuxSessions ?? []), [liveTmuxSessions]);
-
   const groupNames = useMemo(() => {
     const map: Record<string, string> = {};
     for (const g of groups ?? []) {
@@ -302,6 +324,30 @@ function App() {
     return map;
   }, [groups]);
 
+  // Open the session a clicked desktop notification was about
+  const groupsRef = useRef(groups);
+  useEffect(() => {
+    groupsRef.current = groups;
+  });
+  useEffect(() => {
+    const unlisten = listen<FocusSessionEvent>("focus-session", async (event) => {
+      const { session_id, group_path } = event.payload;
+      const group = groupsRef.current?.find((g) => g.path === group_path);
+      if (group) {
+        setSelectedGroup(group);
+        setNeedsActionFilter(false);
+      }
+      const sessions = await invoke<Session[]>("backend_list_sessions", { groupPath: group_path });
+      const session = sessions.find((s) => s.id === session_id);
+      if (session) {
+        setSelectedSession(session);
+      }
+    });
+    return () => {
+      unlisten.then((fn) => fn()).catch(() => {});
+    };
+  }, []);
+
   // Derive up-to-date selectedGroup from latest groups query data
   // (e.g., after toggling github_issues_enabled in settings)
   const effectiveGroup = useMemo(() => {
@@ -309,6 +355,30 @@ function App() {
     return groups.find((g) => g.path === selectedGroup.path) ?? selectedGroup;
   }, [selectedGroup, groups]);
 
+  // Sessions of an SSH group live in the tmux server on its host
+  const sshGroupPath = effectiveGroup?.backend === "ssh" ? effectiveGroup.path : null;
+  const { data: sshTmuxSessions } = useQuery<string[]>({
+    queryKey: [...queryKeys.tmuxSessions, sshGroupPath],
+    queryFn: () => invoke("list_tmux_sessions", { groupPath: sshGroupPath }),
+    enabled: sshGroupPath !== null,
+    refetchInterval: 5_000,
+  });
+
+  // The watcher can't see transcripts on an SSH host, so their summaries poll
+  useEffect(() => {
+    if (!sshGroupPath) return;
+    const timer = setInterval(
+      () => queryClient.invalidateQueries({ queryKey: ["summary"] }),
+      10_000,
+    );
+    return () => clearInterval(timer);
+  }, [sshGroupPath, queryClient]);
+
+  const liveTmuxSet = useMemo(
+    () => new Set([...(liveTmuxSessions ?? []), ...(sshTmuxSessions ?? [])]),
+    [liveTmuxSessions, sshTmuxSessions],
+  );
+
   // Select a newly created group by refetching groups and finding it
   const handleGroupCreated = useCallback(
     async (groupName: string) => {
@@ -361,50 +431,76 @@ function App() {
   const terminalOpen = selectedSession !== null || remoteSession !== null;
   const isRemoteView = remoteSession !== null;
 
+  // Remote sessions open in the message stream, with their own group's server
+  // credentials (the All and Needs Action views have no selected group)
+  const openRemoteSession = useCallback(
+    async (session: RemoteSession, groupPath: string) => {
+      const group = groups?.find((g) => g.path === groupPath);
+      if (!group) throw new Error(`Unknown group: ${groupPath}`);
+      const [resolvedUrl, resolvedToken] = await invoke<[string | null, string | null]>(
+        "get_resolved_credentials",
+        { groupPath },
+      );
+      if (!resolvedUrl) throw new Error(`No server URL configured for ${group.name}`);
+      setRemoteSession(session);
+      setRemoteGroup(group);
+      setRemotePassword(resolvedToken ?? "");
+      setRemoteServerUrl(resolvedUrl);
+    },
+    [groups],
+  );
+
   const handleCreateRemoteSession = useCallback(
     async (title: string, prompt: string | null) => {
       if (!effectiveGroup) return;
       try {
-        const [resolvedUrl, resolvedToken] = await invoke<[string | null, string | null]>(
-          "get_resolved_credentials",
-          { groupPath: effectiveGroup.path },
-        );
-        if (!resolvedUrl) return;
-        if (effectiveGroup.backend === "claude-remote") {
-          const syntheticSession: RemoteSession = {
-            id: `cr-${Date.now()}`,
-            title: title || "Claude Remote",
-            status: "stable",
+        const sessionId = await invoke<string>("backend_create_session", {
+          params: {
+            group_path: effectiveGroup.path,
+            project_path: effectiveGroup.default_path,
+            title,
+            prompt,
+          },
+        });
+        queryClient.invalidateQueries({ queryKey: queryKeys.sessions(effectiveGroup.path) });
+        await openRemoteSession(
+          {
+            id: sessionId,
+            title,
+            status: prompt ? "running" : "idle",
             summary: null,
             created_at: Date.now(),
             last_accessed: Date.now(),
-          };
-          setRemoteSession(syntheticSession);
-          setRemotePassword(resolvedToken ?? "");
-          setRemoteServerUrl(resolvedUrl);
-          if (prompt) {
-            invoke("cr_send_message", {
-              serverUrl: resolvedUrl,
-              token: resolvedToken ?? "",
-              content: prompt,
-            }).catch((err) => console.error("Failed to send initial message:", err));
-          }
-        } else {
-          const session = await invoke<RemoteSession>("oc_create_session", {
-            serverUrl: resolvedUrl,
-            password: resolvedToken ?? "",
-            title,
-            initialMessage: prompt,
-          });
-          setRemoteSession(session);
-          setRemotePassword(resolvedToken ?? "");
-          setRemoteServerUrl(resolvedUrl);
-        }
+          },
+          effectiveGroup.path,
+        );
       } catch (err) {
         console.error("Failed to create remote session:", err);
       }
     },
-    [effectiveGroup],
+    [effectiveGroup, openRemoteSession, queryClient],
+  );
+
+  // Open a session: a terminal for agent-deck sessions, the message stream for remote ones
+  const openSession = useCallback(
+    (session: Session | null) => {
+      if (session && isRemoteSession(session)) {
+        openRemoteSession(
+          {
+            id: session.id,
+            title: session.title,
+            status: session.status,
+            summary: null,
+            created_at: session.created_at,
+            last_accessed: session.last_accessed,
+          },
+          session.group_path,
+        ).catch((err) => console.error("Failed to open remote session:", err));
+        return;
+      }
+      setSelectedSession(session);
+    },
+    [openRemoteSession],
   );
 
   // Wrap setFocusedIndex to also clear confirming remove state
@@ -417,7 +513,7 @@ function App() {
   const handleSelectSession = useCallback(
     (session: Session) => {
       trackEvent("session_opened");
-      setSelectedSession(session);
+      openSession(session);
       // Clear dismiss when user opens a session
       if (dismissedIds.has(session.id)) {
         handleUndismiss(session.id);
@@ -427,7 +523,7 @@ function App() {
         updateFocusedIndex(idx);
       }
     },
-    [filteredSessions, updateFocusedIndex, dismissedIds, handleUndismiss],
+    [filteredSessions, updateFocusedIndex, dismissedIds, handleUndismiss, openSession],
   );
 
   // When closing the terminal, focus the session that was just open
@@ -441,6 +537,7 @@ function App() {
     }
     setSelectedSession(null);
     setRemoteSession(null);
+    setRemoteGroup(null);
   }, [selectedSession, filteredSessions, updateFocusedIndex]);
 
   // Derive clamped focused index from session count
@@ -473,7 +570,7 @@ function App() {
     },
     {
       setFocusedIndex: updateFocusedIndex,
-      setSelectedSession,
+      setSelectedSession: openSession,
       setSelectedGroup,
       setNeedsActionFilter,
       setSearchQuery,
@@ -550,12 +647,13 @@ function App() {
       <div className="resize-handle" onMouseDown={handleMouseDown} />
       <div className="main-area">
         {terminalOpen ? (
-          isRemoteView && remoteSession && effectiveGroup ? (
+          isRemoteView && remoteSession && remoteGroup ? (
             <MessageStream
               session={remoteSession}
+              groupPath={remoteGroup.path}
               serverUrl={remoteServerUrl}
               serverPassword={remotePassword}
-              backend={effectiveGroup.backend as "opencode-remote" | "claude-remote"}
+              backend={remoteGroup.backend as "opencode-remote" | "claude-remote"}
               onClose={handleCloseTerminal}
             />
           ) : effectiveSession ? (
@@ -18,7 +18,7 @@ interface AddSessionBarProps {
   isGitRepo: boolean;
   worktreeCommand: string | null;
   componentDepth: number;
-  backend: "local" | "opencode-remote" | "claude-remote";
+  backend: "local" | "opencode-remote" | "claude-remote" | "ssh";
   createSession: (params: CreateSessionParams) => void;
   pendingCreations: Map<string, PendingCreation>;
   onCreateRemoteSession?: (title: string, prompt: string | null) => void;
@@ -0,0 +1,198 @@
+import { useState, useEffect } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import type { ApiSettings } from "../types";
+
+/** The local automation API: on/off, where it listens and its bearer token. */
+export function ApiSettingsSection() {
+  const [saved, setSaved] = useState<ApiSettings | null>(null);
+  const [settings, setSettings] = useState<ApiSettings | null>(null);
+  const [address, setAddress] = useState<string | null>(null);
+  const [token, setToken] = useState("");
+  const [showToken, setShowToken] = useState(false);
+  const [copied, setCopied] = useState(false);
+  const [confirmingRegenerate, setConfirmingRegenerate] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  const refreshAddress = () =>
+    invoke<string | null>("get_api_address")
+      .then(setAddress)
+      .catch((err) => console.warn("Failed to get API address:", err));
+
+  useEffect(() => {
+    invoke<ApiSettings>("get_api_settings")
+      .then((s) => {
+        setSaved(s);
+        setSettings(s);
+      })
+      .catch((err) => setError(String(err)));
+    invoke<string>("get_api_token")
+      .then(setToken)
+      .catch((err) => console.warn("Failed to get API token:", err));
+    invoke<string | null>("get_api_address")
+      .then(setAddress)
+      .catch((err) => console.warn("Failed to get API address:", err));
+  }, []);
+
+  if (!settings || !saved) {
+    return error ? <div className="wt-error">{error}</div> : null;
+  }
+
+  const save = async (next: ApiSettings) => {
+    setError(null);
+    try {
+      await invoke("set_api_settings", { settings: next });
+      setSaved(next);
+      setSettings(next);
+    } catch (err) {
+      setError(String(err));
+    }
+    refreshAddress();
+  };
+
+  const handleRegenerate = async () => {
+    setConfirmingRegenerate(false);
+    setError(null);
+    try {
+      setToken(await invoke<string>("regenerate_api_token"));
+      setCopied(false);
+    } catch (err) {
+      setError(String(err));
+    }
+    refreshAddress();
+  };
+
+  const handleCopy = () => {
+    navigator.clipboard
+      .writeText(token)
+      .then(() => setCopied(true))
+      .catch((err) => setError(`Failed to copy the token: ${err}`));
+  };
+
+  const changed =
+    settings.transport !== saved.transport ||
+    settings.port !== saved.port ||
+    (settings.socket_path ?? "") !== (saved.socket_path ?? "");
+
+  return (
+    <div className="app-settings-field">
+      <label className="app-settings-label">Automation API</label>
+      <label className="settings-toggle">
+        <input
+          type="checkbox"
+          checked={saved.enabled}
+          onChange={(e) => save({ ...saved, enabled: e.target.checked })}
+        />
+        <span className="settings-toggle-label">Enable the automation API</span>
+      </label>
+      <p className="settings-toggle-description">
+        A local HTTP API (and MCP server on /mcp) for scripts and agents, authenticated with the
+        token below. {address ? `Listening on ${address}.` : "Not running."}
+      </p>
+
+      <div className="settings-radio-group">
+        <span className="settings-radio-label">Listen on</span>
+        <label className="settings-radio-option">
+          <input
+            type="radio"
+            name="api-transport"
+            value="tcp"
+            checked={settings.transport === "tcp"}
+            onChange={() => setSettings({ ...settings, transport: "tcp" })}
+          />
+          <span className="settings-radio-text">
+            <strong>TCP</strong> — 127.0.0.1 on a port
+          </span>
+        </label>
+        <label className="settings-radio-option">
+          <input
+            type="radio"
+            name="api-transport"
+            value="unix"
+            checked={settings.transport === "unix"}
+            onChange={() => setSettings({ ...settings, transport: "unix" })}
+          />
+          <span className="settings-radio-text">
+            <strong>Unix socket</strong> — a file only you can open
+          </span>
+        </label>
+      </div>
+      {settings.transport === "tcp" ? (
+        <>
+          <label className="modal-label">Port</label>
+          <input
+            className="wt-input"
+            type="number"
+            min={1}
+            max={65535}
+            value={settings.port}
+            onChange={(e) => setSettings({ ...settings, port: parseInt(e.target.value) || 0 })}
+          />
+        </>
+      ) : (
+        <>
+          <label className="modal-label">Socket Path</label>
+          <input
+            className="wt-input"
+            type="text"
+            placeholder="Default: orca.sock in Orca's data directory"
+            value={settings.socket_path ?? ""}
+            onChange={(e) => setSettings({ ...settings, socket_path: e.target.value || null })}
+            spellCheck={false}
+          />
+        </>
+      )}
+      {changed && (
+        <div className="modal-actions">
+          <button className="wt-btn" onClick={() => setSettings(saved)}>
+            Revert
+          </button>
+          <button
+            className="wt-btn wt-btn-add"
+            onClick={() => save({ ...settings, enabled: saved.enabled })}
+          >
+            Apply
+          </button>
+        </div>
+      )}
+
+      <label className="modal-label" style={{ marginTop: 8 }}>
+        Token
+      </label>
+      <input
+        className="wt-input"
+        type={showToken ? "text" : "password"}
+        value={token}
+        readOnly
+        spellCheck={false}
+      />
+      <div className="modal-actions">
+        <button className="wt-btn" onClick={() => setShowToken((s) => !s)}>
+          {showToken ? "Hide" : "Show"}
+        </button>
+        <button className="wt-btn" onClick={handleCopy} disabled={!token}>
+          {copied ? "Copied" : "Copy"}
+        </button>
+        {confirmingRegenerate ? (
+          <>
+            <button className="wt-btn" onClick={() => setConfirmingRegenerate(false)}>
+              Cancel
+            </button>
+            <button className="wt-btn wt-btn-danger" onClick={handleRegenerate}>
+              Replace token
+            </button>
+          </>
+        ) : (
+          <button className="wt-btn" onClick={() => setConfirmingRegenerate(true)}>
+            Regenerate
+          </button>
+        )}
+      </div>
+      {confirmingRegenerate && (
+        <span className="settings-hint">
+          Scripts and agents using the current token will stop working until they get the new one.
+        </span>
+      )}
+      {error && <div className="wt-error">{error}</div>}
+    </div>
+  );
+}
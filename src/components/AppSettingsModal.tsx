@@ -7,6 +7,7 @@ import { setAnalyticsEnabled } from "../analytics";
 import { setSentryEnabled } from "../sentry";
 import { queryKeys } from "../queryKeys";
 import { Modal } from "./Modal";
+import { ApiSettingsSection } from "./ApiSettingsSection";
 
 interface AppSettingsModalProps {
   onClose: () => void;
@@ -158,6 +159,8 @@ export function AppSettingsModal({ onClose }: AppSettingsModalProps) {
         />
       </div>
 
+      <ApiSettingsSection />
+
       <div className="modal-actions">
         <button className="wt-btn" onClick={handleReset}>
           Reset to defaults
@@ -0,0 +1,177 @@
+import { useState } from "react";
+import { useMutation, useQuery, useQueryClient } from "@tanstack/react-query";
+import { invoke } from "@tauri-apps/api/core";
+import type { ConflictFile, ConflictReport, ResolveStrategy } from "../types";
+import { queryKeys } from "../queryKeys";
+import { Modal } from "./Modal";
+
+interface ConflictResolverModalProps {
+  worktreePath: string;
+  /** The session's group, whose SSH host has the worktree if it has one */
+  groupPath: string;
+  /** Branch names for git's "ours" and "theirs" sides */
+  oursLabel: string;
+  theirsLabel: string;
+  continueLabel: string;
+  onContinue: () => void;
+  continuePending: boolean;
+  /** git's output when continuing failed */
+  continueMessage: string | null;
+  onClose: () => void;
+}
+
+function fileMeta(file: ConflictFile, oursLabel: string, theirsLabel: string): string {
+  // Binary sides have no content either, so check that first
+  if (file.binary) return "binary";
+  if (file.ours === null) return `deleted on ${oursLabel}`;
+  if (file.theirs === null) return `deleted on ${theirsLabel}`;
+  return file.hunks.length === 1 ? "1 conflict" : `${file.hunks.length} conflicts`;
+}
+
+export function ConflictResolverModal({
+  worktreePath,
+  groupPath,
+  oursLabel,
+  theirsLabel,
+  continueLabel,
+  onContinue,
+  continuePending,
+  continueMessage,
+  onClose,
+}: ConflictResolverModalProps) {
+  const queryClient = useQueryClient();
+  const [editing, setEditing] = useState<{ path: string; content: string } | null>(null);
+
+  const { data: report, isLoading, error } = useQuery<ConflictReport>({
+    queryKey: queryKeys.conflicts(worktreePath),
+    queryFn: () => invoke("get_conflicts", { worktreePath, groupPath }),
+    staleTime: 0,
+  });
+
+  const resolveMutation = useMutation({
+    mutationFn: (args: { file: string; strategy: ResolveStrategy; content?: string }) =>
+      invoke("resolve_conflict", {
+        worktreePath,
+        file: args.file,
+        strategy: args.strategy,
+        content: args.content ?? null,
+        groupPath,
+      }),
+    onSuccess: () => {
+      setEditing(null);
+      queryClient.invalidateQueries({ queryKey: queryKeys.conflicts(worktreePath) });
+    },
+  });
+
+  const files = report?.files ?? [];
+  const busy = resolveMutation.isPending || continuePending;
+
+  return (
+    <Modal onClose={onClose} className="modal-content conflict-modal-content">
+      <h3 className="modal-title">Resolve Conflicts</h3>
+      {isLoading && (
+        <span className="loading-row">
+          <span className="spinner" />
+        </span>
+      )}
+      {report && files.length === 0 && (
+        <div className="conflict-empty">No conflicted files left.</div>
+      )}
+      {files.map((file) => (
+        <div key={file.path} className="conflict-file">
+          <div className="conflict-file-header">
+            <span className="conflict-file-path">{file.path}</span>
+            <span className="conflict-file-meta">{fileMeta(file, oursLabel, theirsLabel)}</span>
+            <button
+              className="wt-btn wt-btn-action"
+              onClick={() => resolveMutation.mutate({ file: file.path, strategy: "ours" })}
+              disabled={busy}
+              title={`Keep ${oursLabel}'s version of the whole file`}
+            >
+              Use {oursLabel}
+            </button>
+            <button
+              className="wt-btn wt-btn-action"
+              onClick={() => resolveMutation.mutate({ file: file.path, strategy: "theirs" })}
+              disabled={busy}
+              title={`Keep ${theirsLabel}'s version of the whole file`}
+            >
+              Use {theirsLabel}
+            </button>
+            {file.content !== null && (
+              <button
+                className="wt-btn wt-btn-action"
+                onClick={() => setEditing({ path: file.path, content: file.content ?? "" })}
+                disabled={busy || editing?.path === file.path}
+              >
+                Edit
+              </button>
+            )}
+          </div>
+          {editing?.path === file.path ? (
+            <>
+              <textarea
+                className="modal-input modal-textarea conflict-editor"
+                value={editing.content}
+                onChange={(e) => setEditing({ path: file.path, content: e.target.value })}
+                rows={14}
+                autoFocus
+              />
+              <div className="modal-actions">
+                <button
+                  className="wt-btn wt-btn-merge"
+                  onClick={() =>
+                    resolveMutation.mutate({
+                      file: file.path,
+                      strategy: "manual",
+                      content: editing.content,
+                    })
+                  }
+                  disabled={busy}
+                >
+                  Save & Stage
+                </button>
+                <button className="wt-btn wt-btn-cancel" onClick={() => setEditing(null)}>
+                  Cancel
+                </button>
+              </div>
+            </>
+          ) : (
+            file.hunks.map((hunk) => (
+              <div key={hunk.start_line} className="conflict-hunk">
+                <div className="conflict-hunk-range">Lines {hunk.start_line}-{hunk.end_line}</div>
+                <div className="conflict-side-label">{oursLabel}</div>
+                <pre className="conflict-side conflict-side-ours">{hunk.ours}</pre>
+                {hunk.base !== null && (
+                  <>
+                    <div className="conflict-side-label">base</div>
+                    <pre className="conflict-side">{hunk.base}</pre>
+                  </>
+                )}
+                <div className="conflict-side-label">{theirsLabel}</div>
+                <pre className="conflict-side conflict-side-theirs">{hunk.theirs}</pre>
+              </div>
+            ))
+          )}
+        </div>
+      ))}
+      {(error ?? resolveMutation.error) && (
+        <div className="wt-error">{String(error ?? resolveMutation.error)}</div>
+      )}
+      {continueMessage && <pre className="conflict-output">{continueMessage}</pre>}
+      <div className="modal-actions">
+        <button
+          className="wt-btn wt-btn-merge"
+          onClick={onContinue}
+          disabled={!report || files.length > 0 || busy}
+          title={files.length > 0 ? "Resolve every file first" : undefined}
+        >
+          {continueLabel}
+        </button>
+        <button className="wt-btn wt-btn-cancel" onClick={onClose}>
+          Close
+        </button>
+      </div>
+    </Modal>
+  );
+}
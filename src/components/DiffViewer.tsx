@@ -1,16 +1,23 @@
-import { useCallback, useRef, useState } from "react";
-import { useQuery } from "@tanstack/react-query";
+import { useCallback, useRef, useState, type ReactNode } from "react";
+import { useQuery, useQueryClient } from "@tanstack/react-query";
 import { invoke } from "@tauri-apps/api/core";
-import type { Session } from "../types";
-import type { DiffComment, DiffHunk } from "../utils";
-import { parseDiff, fileName, fileDir, formatCommentsAsPrompt } from "../utils";
+import type {
+  BranchDiff,
+  BranchDiffFile,
+  DiffHunk,
+  DiffLine,
+  DiffOptions,
+  FileDiff,
+  ReviewComment,
+  Session,
+} from "../types";
+import { fileName, fileDir } from "../utils";
 import { queryKeys } from "../queryKeys";
 import { useEscapeKey } from "../hooks/useEscapeKey";
 import { Modal } from "./Modal";
 
 interface DiffViewerProps {
   session: Session;
-  tmuxSession: string | null;
   onClose: () => void;
 }
 
@@ -24,18 +31,166 @@ interface LineSelection {
 const isMac = /Mac|iPhone|iPad|iPod/.test(navigator.userAgent);
 const MOD_KEY = isMac ? "\u2318" : "Ctrl";
 
-export function DiffViewer({ session, tmuxSession, onClose }: DiffViewerProps) {
-  const nextCommentId = useRef(1);
-  const { data, isLoading, error } = useQuery<string>({
-    queryKey: queryKeys.branchDiff(session.id),
+// Files whose hunks load with the list; larger ones wait for a click so a
+// huge diff doesn't freeze the viewer.
+const AUTO_EXPAND_FILES = 30;
+const AUTO_EXPAND_LINES = 1000;
+
+const STATUS_LABELS: Record<BranchDiffFile["status"], string> = {
+  added: "A",
+  modified: "M",
+  deleted: "D",
+  renamed: "R",
+  copied: "C",
+  type_changed: "T",
+  untracked: "U",
+};
+
+function autoExpanded(file: BranchDiffFile, index: number): boolean {
+  return (
+    !file.binary &&
+    index < AUTO_EXPAND_FILES &&
+    file.additions + file.deletions <= AUTO_EXPAND_LINES
+  );
+}
+
+/** A line's number on the side of the file a comment is anchored to. */
+function lineNumber(line: DiffLine, side: ReviewComment["side"]): number | null {
+  return (side === "old" ? line.old_line : line.new_line) ?? null;
+}
+
+/** Where a comment on `lines` is anchored: the changed file's lines if the
+ *  selection has any, otherwise the removed lines. */
+function commentAnchor(
+  lines: DiffLine[],
+): { side: ReviewComment["side"]; start: number; end: number } | null {
+  for (const side of ["new", "old"] as const) {
+    const numbers = lines.map((l) => lineNumber(l, side)).filter((n): n is number => n !== null);
+    if (numbers.length > 0) {
+      return { side, start: Math.min(...numbers), end: Math.max(...numbers) };
+    }
+  }
+  return null;
+}
+
+function LineContent({ line }: { line: DiffLine }) {
+  if (!line.segments) return <>{line.content}</>;
+  return (
+    <>
+      {line.content[0]}
+      {line.segments.map((segment, i) => (
+        <span key={i} className={`diff-word-${segment.type}`}>
+          {segment.text}
+        </span>
+      ))}
+    </>
+  );
+}
+
+interface DiffFileSectionProps {
+  session: Session;
+  base: string;
+  file: BranchDiffFile;
+  options: DiffOptions;
+  expanded: boolean;
+  onToggle: () => void;
+  renderHunks: (hunks: DiffHunk[]) => ReactNode;
+}
+
+/** One file of the diff; its hunks are fetched once it is expanded. */
+function DiffFileSection({
+  session,
+  base,
+  file,
+  options,
+  expanded,
+  onToggle,
+  renderHunks,
+}: DiffFileSectionProps) {
+  const { data, isLoading, error } = useQuery<FileDiff>({
+    queryKey: [...queryKeys.branchDiffFile(session.id, base, file.path), options],
     queryFn: () =>
-      invoke("get_branch_diff", {
+      invoke("get_branch_diff_file", {
         worktreePath: session.worktree_path,
-        branch: session.worktree_branch,
+        base,
+        file: {
+          path: file.path,
+          old_path: file.old_path,
+          untracked: file.status === "untracked",
+        },
+        options,
+        groupPath: session.group_path,
       }),
+    enabled: expanded && !file.binary,
   });
 
-  const [comments, setComments] = useState<DiffComment[]>([]);
+  return (
+    <>
+      <div className="diff-file-header diff-file-header-toggle" onClick={onToggle}>
+        <span className="diff-file-toggle">{expanded ? "\u25be" : "\u25b8"}</span>
+        <span className={`diff-file-status diff-file-status-${file.status}`}>
+          {STATUS_LABELS[file.status]}
+        </span>
+        {file.old_path ? `${file.old_path} \u2192 ${file.path}` : file.path}
+      </div>
+      {expanded &&
+        (file.binary ? (
+          <div className="diff-file-note">Binary file</div>
+        ) : (
+          <>
+            {isLoading && (
+              <div className="loading-row">
+                <span className="spinner" /> Loading...
+              </div>
+            )}
+            {error && <div className="error-row">{String(error)}</div>}
+            {data && renderHunks(data.hunks)}
+            {data?.truncated && (
+              <div className="diff-file-note">Diff truncated: the file has too many changes</div>
+            )}
+          </>
+        ))}
+      {!expanded && (
+        <div className="diff-file-note">
+          {file.binary
+            ? "Binary file"
+            : `${file.additions + file.deletions} changed lines hidden, click the header to load`}
+        </div>
+      )}
+    </>
+  );
+}
+
+export function DiffViewer({ session, onClose }: DiffViewerProps) {
+  const [options, setOptions] = useState<DiffOptions>({
+    scope: "committed",
+    ignore_whitespace: false,
+    word_diff: false,
+  });
+  const { data, isLoading, error } = useQuery<BranchDiff>({
+    queryKey: [...queryKeys.branchDiff(session.id), options.scope, options.ignore_whitespace],
+    queryFn: () =>
+      invoke("get_branch_diff_v2", {
+        worktreePath: session.worktree_path,
+        options,
+        groupPath: session.group_path,
+      }),
+  });
+  const [expandedOverrides, setExpandedOverrides] = useState<Map<string, boolean>>(new Map());
+
+  const queryClient = useQueryClient();
+  const worktreePath = session.worktree_path || null;
+  const { data: comments = [] } = useQuery<ReviewComment[]>({
+    queryKey: queryKeys.reviewComments(session.id),
+    queryFn: () =>
+      invoke("list_review_comments", {
+        sessionId: session.id,
+        worktreePath,
+        groupPath: session.group_path,
+      }),
+  });
+  const [commentError, setCommentError] = useState<string | null>(null);
+  const [sending, setSending] = useState(false);
   const [selection, setSelection] = useState<LineSelection | null>(null);
   const [activeCommentInput, setActiveCommentInput] = useState<{
     filePath: string;
@@ -45,15 +200,6 @@ export function DiffViewer({ session, tmuxSession, onClose }: DiffViewerProps) {
   const [commentText, setCommentText] = useState("");
   const [editingCommentId, setEditingCommentId] = useState<number | null>(null);
   const [editText, setEditText] = useState("");
-  const [confirmingClose, setConfirmingClose] = useState(false);
-
-  const guardedClose = useCallback(() => {
-    if (comments.length > 0) {
-      setConfirmingClose(true);
-    } else {
-      onClose();
-    }
-  }, [comments.length, onClose]);
 
   const cancelComment = () => {
     setSelection(null);
@@ -62,18 +208,41 @@ export function DiffViewer({ session, tmuxSession, onClose }: DiffViewerProps) {
   };
 
   useEscapeKey(() => {
-    if (confirmingClose) {
-      setConfirmingClose(false);
-    } else if (editingCommentId !== null) {
+    if (editingCommentId !== null) {
       setEditingCommentId(null);
     } else if (activeCommentInput) {
       cancelComment();
     } else {
-      guardedClose();
+      onClose();
     }
   });
 
-  const files = data ? parseDiff(data) : [];
+  // Comments are stored as soon as they are written, so closing loses nothing.
+  const updateComments = async (action: () => Promise<unknown>) => {
+    try {
+      await action();
+      setCommentError(null);
+    } catch (err) {
+      setCommentError(String(err));
+    }
+    queryClient.invalidateQueries({ queryKey: queryKeys.reviewComments(session.id) });
+  };
+
+  const files = data?.files ?? [];
+
+  const isExpanded = (file: BranchDiffFile, index: number): boolean =>
+    expandedOverrides.get(file.path) ?? autoExpanded(file, index);
+
+  const toggleFile = (file: BranchDiffFile, index: number) => {
+    setExpandedOverrides((prev) => new Map(prev).set(file.path, !isExpanded(file, index)));
+  };
+
+  const updateOptions = (changes: Partial<DiffOptions>) => {
+    // Hunk indices change with the options; comments keep their own lines.
+    setSelection(null);
+    setActiveCommentInput(null);
+    setOptions((prev) => ({ ...prev, ...changes }));
+  };
   const fileRefs = useRef<Map<string, HTMLDivElement>>(new Map());
 
   const scrollToFile = useCallback((path: string) => {
@@ -118,25 +287,32 @@ export function DiffViewer({ session, tmuxSession, onClose }: DiffViewerProps) {
     }
   };
 
-  const addComment = (hunk: DiffHunk) => {
+  const addComment = (file: BranchDiffFile, hunk: DiffHunk) => {
     if (!selection || selection.endLine === null || !commentText.trim()) return;
 
-    const start = selection.startLine;
-    const end = selection.endLine;
-    const lines = hunk.lines.slice(start, end + 1);
-
-    setComments((prev) => [
-      ...prev,
-      {
-        id: nextCommentId.current++,
-        filePath: selection.filePath,
-        hunkIndex: selection.hunkIndex,
-        startLine: start,
-        endLine: end,
-        text: commentText.trim(),
-        lines,
-      },
-    ]);
+    const lines = hunk.lines.slice(selection.startLine, selection.endLine + 1);
+    const anchor = commentAnchor(lines);
+    if (!anchor) return;
+    // The blob the line numbers refer to; the working tree is looked up by the backend.
+    const blobSha =
+      anchor.side === "old" ? file.old_blob : options.scope === "committed" ? file.new_blob : null;
+    const body = commentText.trim();
+    updateComments(() =>
+      invoke("add_review_comment", {
+        sessionId: session.id,
+        worktreePath,
+        comment: {
+          file_path: file.path,
+          side: anchor.side,
+          blob_sha: blobSha,
+          start_line: anchor.start,
+          end_line: anchor.end,
+          snippet: lines.map((l) => l.content).join("\n"),
+          body,
+        },
+        groupPath: session.group_path,
+      }),
+    );
 
     setSelection(null);
     setActiveCommentInput(null);
@@ -144,43 +320,48 @@ export function DiffViewer({ session, tmuxSession, onClose }: DiffViewerProps) {
   };
 
   const deleteComment = (commentId: number) => {
-    setComments((prev) => prev.filter((c) => c.id !== commentId));
+    updateComments(() => invoke("delete_review_comment", { id: commentId }));
     if (editingCommentId === commentId) setEditingCommentId(null);
   };
 
-  const startEditing = (comment: DiffComment) => {
+  const startEditing = (comment: ReviewComment) => {
     setEditingCommentId(comment.id);
-    setEditText(comment.text);
+    setEditText(comment.body);
   };
 
   const saveEdit = () => {
     if (editingCommentId === null) return;
-    const trimmed = editText.trim();
-    if (!trimmed) {
-      deleteComment(editingCommentId);
-    } else {
-      setComments((prev) =>
-        prev.map((c) => (c.id === editingCommentId ? { ...c, text: trimmed } : c)),
-      );
-    }
+    // An emptied comment is deleted.
+    const id = editingCommentId;
+    updateComments(() => invoke("update_review_comment", { id, body: editText }));
     setEditingCommentId(null);
   };
 
   const clearAllComments = () => {
-    setComments([]);
+    updateComments(() =>
+      Promise.all(comments.map((c) => invoke("delete_review_comment", { id: c.id }))),
+    );
     setSelection(null);
     setActiveCommentInput(null);
     setCommentText("");
   };
 
   const sendComments = async () => {
-    if (!tmuxSession || comments.length === 0) return;
-    const prompt = formatCommentsAsPrompt(comments);
+    if (comments.length === 0) return;
+    setSending(true);
     try {
-      await invoke("paste_to_tmux_pane", { tmuxSession, text: prompt, submit: true });
+      await invoke("send_review_comments", {
+        sessionId: session.id,
+        worktreePath,
+        groupPath: session.group_path,
+      });
       onClose();
     } catch (err) {
-      console.error("Failed to send comments to tmux:", err);
+      console.error("Failed to send review comments:", err);
+      setCommentError(String(err));
+    } finally {
+      setSending(false);
+      queryClient.invalidateQueries({ queryKey: queryKeys.reviewComments(session.id) });
     }
   };
 
@@ -191,38 +372,37 @@ export function DiffViewer({ session, tmuxSession, onClose }: DiffViewerProps) {
     return lineIndex >= selection.startLine && lineIndex <= selection.endLine;
   };
 
-  const isLineCommented = (filePath: string, hunkIndex: number, lineIndex: number): boolean => {
-    return comments.some(
-      (c) =>
-        c.filePath === filePath &&
-        c.hunkIndex === hunkIndex &&
-        lineIndex >= c.startLine &&
-        lineIndex <= c.endLine,
-    );
+  const isLineCommented = (filePath: string, line: DiffLine): boolean => {
+    return comments.some((c) => {
+      const n = lineNumber(line, c.side);
+      return (
+        c.file_path === filePath && !c.outdated && n !== null && n >= c.start_line && n <= c.end_line
+      );
+    });
   };
 
-  const getCommentsAfterLine = (
-    filePath: string,
-    hunkIndex: number,
-    lineIndex: number,
-  ): DiffComment[] => {
+  const getCommentsAfterLine = (filePath: string, line: DiffLine): ReviewComment[] => {
     return comments.filter(
-      (c) => c.filePath === filePath && c.hunkIndex === hunkIndex && c.endLine === lineIndex,
+      (c) => c.file_path === filePath && !c.outdated && lineNumber(line, c.side) === c.end_line,
     );
   };
 
   const fileCommentCount = (filePath: string): number => {
-    return comments.filter((c) => c.filePath === filePath).length;
+    return comments.filter((c) => c.file_path === filePath).length;
   };
 
+  const outdatedComments = comments.filter((c) => c.outdated);
+
   return (
-    <Modal onClose={guardedClose} className="diff-modal-content">
+    <Modal onClose={onClose} className="diff-modal-content">
       <div className="diff-header">
         <div className="diff-header-title">
           <span>Diff: {session.worktree_branch}</span>
-          {files.length > 0 && (
+          {data && files.length > 0 && (
             <span className="diff-file-count">
-              {files.length} file{files.length !== 1 ? "s" : ""}
+              {files.length} file{files.length !== 1 ? "s" : ""}{" "}
+              <span className="diff-stat-add">+{data.additions}</span>{" "}
+              <span className="diff-stat-del">-{data.deletions}</span>
             </span>
           )}
           {comments.length > 0 && (
@@ -232,37 +412,46 @@ export function DiffViewer({ session, tmuxSession, onClose }: DiffViewerProps) {
           )}
         </div>
         <div className="diff-header-actions">
-          {confirmingClose ? (
+          <div className="diff-options">
+            <select
+              value={options.scope}
+              onChange={(e) => updateOptions({ scope: e.target.value as DiffOptions["scope"] })}
+            >
+              <option value="committed">Committed</option>
+              <option value="uncommitted">Uncommitted</option>
+            </select>
+            <label>
+              <input
+                type="checkbox"
+                checked={options.ignore_whitespace}
+                onChange={(e) => updateOptions({ ignore_whitespace: e.target.checked })}
+              />
+              Ignore whitespace
+            </label>
+            <label>
+              <input
+                type="checkbox"
+                checked={options.word_diff}
+                onChange={(e) => updateOptions({ word_diff: e.target.checked })}
+              />
+              Word diff
+            </label>
+          </div>
+          {comments.length > 0 && (
             <>
-              <span className="diff-close-confirm-text">
-                Discard {comments.length} unsent comment{comments.length !== 1 ? "s" : ""}?
-              </span>
-              <button className="wt-btn wt-btn-danger" onClick={onClose}>
-                Discard
+              <button className="wt-btn wt-btn-add" onClick={sendComments} disabled={sending}>
+                {sending
+                  ? "Sending..."
+                  : `Send ${comments.length} comment${comments.length !== 1 ? "s" : ""} to the agent`}
               </button>
-              <button className="wt-btn" onClick={() => setConfirmingClose(false)}>
-                Cancel
-              </button>
-            </>
-          ) : (
-            <>
-              {comments.length > 0 && (
-                <>
-                  {tmuxSession && (
-                    <button className="wt-btn wt-btn-add" onClick={sendComments}>
-                      Send {comments.length} comment{comments.length !== 1 ? "s" : ""} to Claude
-                    </button>
-                  )}
-                  <button className="wt-btn wt-btn-danger" onClick={clearAllComments}>
-                    Clear All
-                  </button>
-                </>
-              )}
-              <button className="wt-btn" onClick={guardedClose}>
-                Close
+              <button className="wt-btn wt-btn-danger" onClick={clearAllComments}>
+                Clear All
               </button>
             </>
           )}
+          <button className="wt-btn" onClick={onClose}>
+            Close
+          </button>
         </div>
       </div>
       <div className="diff-layout">
@@ -275,6 +464,9 @@ export function DiffViewer({ session, tmuxSession, onClose }: DiffViewerProps) {
                 onClick={() => scrollToFile(file.path)}
                 title={file.path}
               >
+                <span className={`diff-file-status diff-file-status-${file.status}`}>
+                  {STATUS_LABELS[file.status]}
+                </span>
                 <span className="diff-file-list-name">
                   <span className="diff-file-list-dir">{fileDir(file.path)}</span>
                   {fileName(file.path)}
@@ -283,8 +475,14 @@ export function DiffViewer({ session, tmuxSession, onClose }: DiffViewerProps) {
                   {fileCommentCount(file.path) > 0 && (
                     <span className="diff-file-comment-badge">{fileCommentCount(file.path)}</span>
                   )}
-                  <span className="diff-stat-add">+{file.additions}</span>
-                  <span className="diff-stat-del">-{file.deletions}</span>
+                  {file.binary ? (
+                    <span className="diff-stat-binary">bin</span>
+                  ) : (
+                    <>
+                      <span className="diff-stat-add">+{file.additions}</span>
+                      <span className="diff-stat-del">-{file.deletions}</span>
+                    </>
+                  )}
                 </span>
               </button>
             ))}
@@ -297,140 +495,182 @@ export function DiffViewer({ session, tmuxSession, onClose }: DiffViewerProps) {
             </div>
           )}
           {error && <div className="error-row">{String(error)}</div>}
+          {commentError && <div className="error-row">{commentError}</div>}
+          {outdatedComments.length > 0 && (
+            <div className="diff-outdated-comments">
+              <div className="diff-outdated-title">
+                Outdated comments: the commented lines have changed
+              </div>
+              {outdatedComments.map((c) => (
+                <div key={c.id} className="diff-comment-display">
+                  <div className="diff-comment-text">
+                    <span className="diff-outdated-location">
+                      {c.file_path}:{c.start_line}
+                    </span>{" "}
+                    {c.body}
+                  </div>
+                  <button
+                    className="diff-comment-delete"
+                    onClick={() => deleteComment(c.id)}
+                    title="Delete comment"
+                  >
+                    x
+                  </button>
+                </div>
+              ))}
+            </div>
+          )}
           {data !== undefined && files.length === 0 && !isLoading && (
-            <div className="diff-empty">No changes compared to default branch</div>
+            <div className="diff-empty">
+              {options.scope === "committed"
+                ? "No changes compared to default branch"
+                : "No uncommitted changes"}
+            </div>
           )}
-          {files.map((file) => (
+          {files.map((file, fi) => (
             <div key={file.path} className="diff-file" ref={setFileRef(file.path)}>
-              <div className="diff-file-header">{file.path}</div>
-              {file.hunks.map((hunk, hi) => (
-                <div key={hi} className="diff-hunk">
-                  <div className="diff-hunk-header">{hunk.header}</div>
-                  {hunk.lines.map((line, li) => {
-                    const selected = isLineSelected(file.path, hi, li);
-                    const commented = isLineCommented(file.path, hi, li);
-                    const lineComments = getCommentsAfterLine(file.path, hi, li);
-                    const showInput =
-                      activeCommentInput &&
-                      activeCommentInput.filePath === file.path &&
-                      activeCommentInput.hunkIndex === hi &&
-                      activeCommentInput.endLine === li;
-
-                    return (
-                      <div key={li}>
-                        <div
-                          className={`diff-line diff-line-${line.type}${selected ? " diff-line-selected" : ""}${commented ? " diff-line-commented" : ""}`}
-                          onClick={() => handleLineClick(file.path, hi, li)}
-                        >
-                          <span className="diff-line-gutter">{commented ? "\u{1f4ac}" : ""}</span>
-                          <span className="diff-line-content">{line.content}</span>
-                        </div>
-                        {lineComments.map((c) => (
-                          <div key={c.id} className="diff-comment-display">
-                            {editingCommentId === c.id ? (
-                              <div className="diff-comment-edit-row">
+              <DiffFileSection
+                session={session}
+                base={data!.base}
+                file={file}
+                options={options}
+                expanded={isExpanded(file, fi)}
+                onToggle={() => toggleFile(file, fi)}
+                renderHunks={(hunks) =>
+                  hunks.map((hunk, hi) => (
+                    <div key={hi} className="diff-hunk">
+                      <div className="diff-hunk-header">{hunk.header}</div>
+                      {hunk.lines.map((line, li) => {
+                        const selected = isLineSelected(file.path, hi, li);
+                        const commented = isLineCommented(file.path, line);
+                        const lineComments = getCommentsAfterLine(file.path, line);
+                        const showInput =
+                          activeCommentInput &&
+                          activeCommentInput.filePath === file.path &&
+                          activeCommentInput.hunkIndex === hi &&
+                          activeCommentInput.endLine === li;
+
+                        return (
+                          <div key={li}>
+                            <div
+                              className={`diff-line diff-line-${line.type}${selected ? " diff-line-selected" : ""}${commented ? " diff-line-commented" : ""}`}
+                              onClick={() => handleLineClick(file.path, hi, li)}
+                            >
+                              <span className="diff-line-gutter">
+                                {commented ? "\u{1f4ac}" : ""}
+                              </span>
+                              <span className="diff-line-content">
+                                <LineContent line={line} />
+                              </span>
+                            </div>
+                            {lineComments.map((c) => (
+                              <div key={c.id} className="diff-comment-display">
+                                {editingCommentId === c.id ? (
+                                  <div className="diff-comment-edit-row">
+                                    <textarea
+                                      className="diff-comment-textarea"
+                                      value={editText}
+                                      onChange={(e) => setEditText(e.target.value)}
+                                      onKeyDown={(e) => {
+                                        if (e.key === "Escape") {
+                                          e.stopPropagation();
+                                          setEditingCommentId(null);
+                                        } else if (e.key === "Enter" && (e.metaKey || e.ctrlKey)) {
+                                          e.preventDefault();
+                                          saveEdit();
+                                        }
+                                      }}
+                                      autoFocus
+                                      rows={3}
+                                    />
+                                    <div className="diff-comment-input-actions">
+                                      <button
+                                        className="wt-btn wt-btn-add"
+                                        onClick={saveEdit}
+                                        disabled={!editText.trim()}
+                                      >
+                                        Save
+                                      </button>
+                                      <button
+                                        className="wt-btn"
+                                        onClick={() => setEditingCommentId(null)}
+                                      >
+                                        Cancel
+                                      </button>
+                                      <button
+                                        className="wt-btn wt-btn-danger"
+                                        onClick={() => deleteComment(c.id)}
+                                      >
+                                        Delete
+                                      </button>
+                                      <span className="diff-comment-shortcut-hint">
+                                        {MOD_KEY}+Enter to save
+                                      </span>
+                                    </div>
+                                  </div>
+                                ) : (
+                                  <>
+                                    <div
+                                      className="diff-comment-text"
+                                      onClick={() => startEditing(c)}
+                                      title="Click to edit"
+                                    >
+                                      {c.body}
+                                    </div>
+                                    <button
+                                      className="diff-comment-delete"
+                                      onClick={() => deleteComment(c.id)}
+                                      title="Delete comment"
+                                    >
+                                      x
+                                    </button>
+                                  </>
+                                )}
+                              </div>
+                            ))}
+                            {showInput && (
+                              <div className="diff-comment-input-row">
                                 <textarea
                                   className="diff-comment-textarea"
-                                  value={editText}
-                                  onChange={(e) => setEditText(e.target.value)}
+                                  value={commentText}
+                                  onChange={(e) => setCommentText(e.target.value)}
                                   onKeyDown={(e) => {
                                     if (e.key === "Escape") {
                                       e.stopPropagation();
-                                      setEditingCommentId(null);
+                                      cancelComment();
                                     } else if (e.key === "Enter" && (e.metaKey || e.ctrlKey)) {
                                       e.preventDefault();
-                                      saveEdit();
+                                      addComment(file, hunk);
                                     }
                                   }}
+                                  placeholder="Write a comment..."
                                   autoFocus
                                   rows={3}
                                 />
                                 <div className="diff-comment-input-actions">
                                   <button
                                     className="wt-btn wt-btn-add"
-                                    onClick={saveEdit}
-                                    disabled={!editText.trim()}
+                                    onClick={() => addComment(file, hunk)}
+                                    disabled={!commentText.trim()}
                                   >
-                                    Save
+                                    Add Comment
                                   </button>
-                                  <button
-                                    className="wt-btn"
-                                    onClick={() => setEditingCommentId(null)}
-                                  >
+                                  <button className="wt-btn" onClick={cancelComment}>
                                     Cancel
                                   </button>
-                                  <button
-                                    className="wt-btn wt-btn-danger"
-                                    onClick={() => deleteComment(c.id)}
-                                  >
-                                    Delete
-                                  </button>
                                   <span className="diff-comment-shortcut-hint">
-                                    {MOD_KEY}+Enter to save
+                                    {MOD_KEY}+Enter to submit
                                   </span>
                                 </div>
                               </div>
-                            ) : (
-                              <>
-                                <div
-                                  className="diff-comment-text"
-                                  onClick={() => startEditing(c)}
-                                  title="Click to edit"
-                                >
-                                  {c.text}
-                                </div>
-                                <button
-                                  className="diff-comment-delete"
-                                  onClick={() => deleteComment(c.id)}
-                                  title="Delete comment"
-                                >
-                                  x
-                                </button>
-                              </>
                             )}
                           </div>
-                        ))}
-                        {showInput && (
-                          <div className="diff-comment-input-row">
-                            <textarea
-                              className="diff-comment-textarea"
-                              value={commentText}
-                              onChange={(e) => setCommentText(e.target.value)}
-                              onKeyDown={(e) => {
-                                if (e.key === "Escape") {
-                                  e.stopPropagation();
-                                  cancelComment();
-                                } else if (e.key === "Enter" && (e.metaKey || e.ctrlKey)) {
-                                  e.preventDefault();
-                                  addComment(hunk);
-                                }
-                              }}
-                              placeholder="Write a comment..."
-                              autoFocus
-                              rows={3}
-                            />
-                            <div className="diff-comment-input-actions">
-                              <button
-                                className="wt-btn wt-btn-add"
-                                onClick={() => addComment(hunk)}
-                                disabled={!commentText.trim()}
-                              >
-                                Add Comment
-                              </button>
-                              <button className="wt-btn" onClick={cancelComment}>
-                                Cancel
-                              </button>
-                              <span className="diff-comment-shortcut-hint">
-                                {MOD_KEY}+Enter to submit
-                              </span>
-                            </div>
-                          </div>
-                        )}
-                      </div>
-                    );
-                  })}
-                </div>
-              ))}
+                        );
+                      })}
+                    </div>
+                  ))
+                }
+              />
             </div>
           ))}
         </div>
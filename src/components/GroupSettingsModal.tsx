@@ -16,9 +16,7 @@ export function GroupSettingsModal({ group, onClose, onGroupDeleted }: GroupSett
   const [mergeWorkflow, setMergeWorkflow] = useState(group.merge_workflow);
   const [worktreeCommand, setWorktreeCommand] = useState(group.worktree_command ?? "");
   const [componentDepth, setComponentDepth] = useState(group.component_depth);
-  const [backend, setBackend] = useState<"local" | "opencode-remote" | "claude-remote">(
-    group.backend,
-  );
+  const [backend, setBackend] = useState<Group["backend"]>(group.backend);
   const [serverUrl, setServerUrl] = useState(group.server_url ?? "");
   const [serverPassword, setServerPassword] = useState("");
   const isRemoteBackend = backend === "opencode-remote" || backend === "claude-remote";
@@ -195,7 +193,33 @@ export function GroupSettingsModal({ group, onClose, onGroupDeleted }: GroupSett
             onChange={() => setMergeWorkflow("merge")}
           />
           <span className="settings-radio-text">
-            <strong>Direct Merge</strong> — merge branches locally into main
+            <strong>Direct Merge</strong> — merge branches locally into main with a merge commit
+          </span>
+        </label>
+        <label className="settings-radio-option">
+          <input
+            type="radio"
+            name="mergeWorkflow"
+            value="squash"
+            checked={mergeWorkflow === "squash"}
+            onChange={() => setMergeWorkflow("squash")}
+          />
+          <span className="settings-radio-text">
+            <strong>Squash</strong> — squash branches into one commit on main, with an editable
+            message
+          </span>
+        </label>
+        <label className="settings-radio-option">
+          <input
+            type="radio"
+            name="mergeWorkflow"
+            value="rebase-ff"
+            checked={mergeWorkflow === "rebase-ff"}
+            onChange={() => setMergeWorkflow("rebase-ff")}
+          />
+          <span className="settings-radio-text">
+            <strong>Rebase & Fast-forward</strong> — rebase branches and fast-forward main,
+            without merge commits
           </span>
         </label>
         <label className="settings-radio-option">
@@ -263,6 +287,18 @@ export function GroupSettingsModal({ group, onClose, onGroupDeleted }: GroupSett
             <strong>Local</strong> — agent-deck + tmux (Claude, OpenCode, Shell)
           </span>
         </label>
+        <label className="settings-radio-option">
+          <input
+            type="radio"
+            name="backend"
+            value="ssh"
+            checked={backend === "ssh"}
+            onChange={() => setBackend("ssh")}
+          />
+          <span className="settings-radio-text">
+            <strong>SSH Host</strong> — agent-deck + tmux on another machine
+          </span>
+        </label>
         {showRemoteOptions && (
           <>
             <label className="settings-radio-option">
@@ -292,6 +328,23 @@ export function GroupSettingsModal({ group, onClose, onGroupDeleted }: GroupSett
           </>
         )}
       </div>
+      {backend === "ssh" && (
+        <div className="settings-section">
+          <label className="modal-label">SSH Host</label>
+          <input
+            className="wt-input"
+            type="text"
+            placeholder="user@devbox or ssh://user@devbox:2222"
+            value={serverUrl}
+            onChange={(e) => setServerUrl(e.target.value)}
+            spellCheck={false}
+          />
+          <span className="settings-hint">
+            Uses your SSH keys and ~/.ssh/config; the host needs agent-deck, tmux and sqlite3.
+            Add ?profile=name for an agent-deck profile other than the default.
+          </span>
+        </div>
+      )}
       {isRemoteBackend && (
         <div className="settings-section">
           <label className="modal-label">Server URL</label>
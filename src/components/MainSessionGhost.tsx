@@ -15,7 +15,7 @@ export function MainSessionGhost({ repoPath, groupPath, createSession }: MainSes
 
   const { data: defaultBranch } = useQuery<string>({
     queryKey: queryKeys.defaultBranch(repoPath),
-    queryFn: () => invoke("get_default_branch", { repoPath }),
+    queryFn: () => invoke("get_default_branch", { repoPath, groupPath }),
     staleTime: 5 * 60 * 1000,
   });
 
@@ -5,6 +5,7 @@ import type { RemoteMessage, RemoteSession } from "../types";
 
 interface MessageStreamProps {
   session: RemoteSession;
+  groupPath: string;
   serverUrl: string;
   serverPassword: string;
   backend: "opencode-remote" | "claude-remote";
@@ -13,6 +14,7 @@ interface MessageStreamProps {
 
 export function MessageStream({
   session,
+  groupPath,
   serverUrl,
   serverPassword,
   backend,
@@ -53,13 +55,12 @@ export function MessageStream({
       });
   }, [session.id, serverUrl, serverPassword, isClaude]);
 
-  // Start SSE subscription for claude-remote
+  // Start the group backend's SSE subscription
   useEffect(() => {
-    if (!isClaude) return;
-    invoke("cr_subscribe_events", { serverUrl, token: serverPassword }).catch((err) =>
+    invoke("backend_subscribe_events", { groupPath }).catch((err) =>
       console.error("Failed to subscribe to SSE:", err),
     );
-  }, [isClaude, serverUrl, serverPassword]);
+  }, [groupPath]);
 
   // Listen to SSE events
   useEffect(() => {
@@ -110,27 +111,14 @@ export function MessageStream({
     setSending(true);
     setInput("");
     try {
-      if (isClaude) {
-        await invoke("cr_send_message", {
-          serverUrl,
-          token: serverPassword,
-          content: text,
-        });
-      } else {
-        await invoke("oc_send_message", {
-          serverUrl,
-          password: serverPassword,
-          sessionId: session.id,
-          message: text,
-        });
-      }
+      await invoke("backend_send_message", { groupPath, sessionId: session.id, text });
     } catch (err) {
       setError(String(err));
     } finally {
       setSending(false);
       textareaRef.current?.focus();
     }
-  }, [input, sending, serverUrl, serverPassword, session.id, isClaude]);
+  }, [input, sending, groupPath, session.id]);
 
   const handleKeyDown = (e: React.KeyboardEvent) => {
     if (e.key === "Enter" && e.metaKey) {
@@ -1,7 +1,7 @@
 import { useState, useRef, useEffect } from "react";
 import { useQuery, useMutation, useQueryClient } from "@tanstack/react-query";
 import { invoke } from "@tauri-apps/api/core";
-import type { Session, SessionSummary, AttentionStatus } from "../types";
+import type { MergeWorkflow, Session, SessionSummary, AttentionStatus } from "../types";
 import { ATTENTION_CONFIG, fallbackAttention, formatPath, formatTime } from "../utils";
 import { queryKeys } from "../queryKeys";
 import { useWorktreeActions } from "../hooks/useWorktreeActions";
@@ -24,7 +24,7 @@ interface SessionCardProps {
   isDismissed?: boolean;
   onDismiss?: () => void;
   onUndismiss?: () => void;
-  mergeWorkflow?: "merge" | "pr";
+  mergeWorkflow?: MergeWorkflow;
 }
 
 export function SessionCard({
@@ -55,6 +55,7 @@ export function SessionCard({
     repoPath,
     onSelectSession,
     confirmingRemove: confirmingRemoveProp,
+    mergeWorkflow,
   });
 
   const prActions = usePrWorkflowActions({
@@ -84,14 +85,22 @@ export function SessionCard({
   const { data: summary } = useQuery<SessionSummary>({
     queryKey: queryKeys.summary(session.id),
     queryFn: () =>
-      invoke("get_session_summary", {
-        projectPath: session.project_path,
-        claudeSessionId: session.claude_session_id ?? "",
-        agentdeckStatus: session.status,
-        tmuxSession: session.tmux_session || null,
-      }),
-    refetchInterval: 10_000,
-    enabled: !!session.claude_session_id,
+      session.tool === "opencode"
+        ? invoke("get_opencode_session_summary", {
+            projectPath: session.project_path,
+            opencodeSessionId: session.opencode_session_id,
+            agentdeckStatus: session.status,
+            tmuxSession: session.tmux_session || null,
+            groupPath: session.group_path,
+          })
+        : invoke("get_session_summary", {
+            projectPath: session.project_path,
+            claudeSessionId: session.claude_session_id ?? "",
+            agentdeckStatus: session.status,
+            tmuxSession: session.tmux_session || null,
+            groupPath: session.group_path,
+          }),
+    enabled: !!session.claude_session_id || session.tool === "opencode",
   });
 
   const addWorktreeMutation = useMutation({
@@ -232,6 +241,14 @@ export function SessionCard({
             {(session.prompt || summary?.initial_prompt) && (
               <div className="session-summary">{session.prompt ?? summary?.initial_prompt}</div>
             )}
+            {summary?.subagents
+              .filter((a) => a.status === "running")
+              .map((a) => (
+                <div key={a.tool_use_id} className="session-subagent">
+                  Subagent: {a.description ?? a.subagent_type ?? "task"}
+                  {a.last_tool && <span className="session-subagent-tool"> · {a.last_tool}</span>}
+                </div>
+              ))}
             <div className="session-path">
               {groupName && <span className="session-group">{groupName}</span>}
               {formatPath(session.project_path)}
@@ -256,6 +273,7 @@ export function SessionCard({
             projectPath={session.project_path}
             worktreeBranch={session.worktree_branch}
             repoPath={repoPath}
+            groupPath={session.group_path}
             onShowDiff={() => setShowDiff(true)}
           />
         )}
@@ -264,7 +282,6 @@ export function SessionCard({
       {showDiff && (
         <DiffViewer
           session={session}
-          tmuxSession={session.tmux_session || null}
           onClose={() => setShowDiff(false)}
         />
       )}
@@ -1,5 +1,5 @@
 import { useState } from "react";
-import type { Session } from "../types";
+import type { MergeWorkflow, Session } from "../types";
 import type { PendingCreation, CreateSessionParams } from "../hooks/useSessionCreation";
 import { isMainSession } from "../utils";
 import { SessionCard } from "./SessionCard";
@@ -26,7 +26,7 @@ interface SessionListProps {
   pendingCreations?: Map<string, PendingCreation>;
   onDismissPending?: (creationId: string) => void;
   createSession?: (params: CreateSessionParams) => void;
-  mergeWorkflow?: "merge" | "pr";
+  mergeWorkflow?: MergeWorkflow;
 }
 
 function renderSessionCard(
@@ -43,7 +43,7 @@ function renderSessionCard(
     dismissedIds?: Set<string>;
     onDismiss?: (sessionId: string) => void;
     onUndismiss?: (sessionId: string) => void;
-    mergeWorkflow?: "merge" | "pr";
+    mergeWorkflow?: MergeWorkflow;
   },
 ) {
   return (
@@ -48,7 +48,7 @@ export function Sidebar({
   const { data: attentionSessions } = useQuery<Session[]>({
     queryKey: queryKeys.attentionSessions,
     queryFn: () => invoke("get_attention_sessions"),
-    refetchInterval: 5_000,
+    refetchInterval: 30_000,
   });
 
   // Compute counts and per-group dots from attention sessions, excluding dismissed
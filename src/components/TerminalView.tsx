@@ -124,7 +124,9 @@ export function TerminalView({ session, onClose }: TerminalViewProps) {
       let tmuxReady = false;
       for (let attempt = 0; attempt < 8; attempt++) {
         try {
-          const liveSessions = await invoke<string[]>("list_tmux_sessions");
+          const liveSessions = await invoke<string[]>("list_tmux_sessions", {
+            groupPath: session.group_path,
+          });
           if (liveSessions.includes(session.tmux_session!)) {
             tmuxReady = true;
             break;
@@ -152,6 +154,7 @@ export function TerminalView({ session, onClose }: TerminalViewProps) {
         cols,
         rows,
         onOutput,
+        groupPath: session.group_path,
       });
 
       terminal.focus();
@@ -190,6 +193,7 @@ export function TerminalView({ session, onClose }: TerminalViewProps) {
 
     // Intercept key events that xterm.js doesn't handle correctly.
     const tmuxSession = session.tmux_session!;
+    const groupPath = session.group_path;
     terminal.attachCustomKeyEventHandler((event) => {
       // Shift+Enter: paste a newline via tmux bracketed paste.
       // Can't use CSI u (\e[13;2u) because Claude Code negotiates the kitty
@@ -206,7 +210,7 @@ export function TerminalView({ session, onClose }: TerminalViewProps) {
         !event.metaKey
       ) {
         if (event.type === "keydown") {
-          invoke("paste_to_tmux_pane", { tmuxSession, text: "\n" }).catch(() => {});
+          invoke("paste_to_tmux_pane", { tmuxSession, text: "\n", groupPath }).catch(() => {});
         }
         return false;
       }
@@ -253,7 +257,7 @@ export function TerminalView({ session, onClose }: TerminalViewProps) {
       const speed = parseFloat(storageGet(SCROLL_SPEED_KEY) ?? "1.0");
       const raw = e.deltaMode === 1 ? Math.abs(e.deltaY) : Math.abs(e.deltaY) / 20;
       const lines = Math.max(1, Math.round(raw * speed));
-      invoke("scroll_tmux_pane", { tmuxSession, direction, lines }).catch(() => {});
+      invoke("scroll_tmux_pane", { tmuxSession, direction, lines, groupPath }).catch(() => {});
     };
     container.addEventListener("wheel", handleWheel, { passive: false, capture: true });
 
@@ -286,12 +290,13 @@ export function TerminalView({ session, onClose }: TerminalViewProps) {
         cleanupRef.current = null;
       }
     };
-  }, [session.id, session.tmux_session]);
+  }, [session.id, session.tmux_session, session.group_path]);
 
   // Handle file drag-and-drop: paste file paths into tmux session
   useEffect(() => {
     if (!session.tmux_session) return;
     const tmuxSession = session.tmux_session;
+    const groupPath = session.group_path;
 
     // Shell-escape a path by backslash-escaping special characters
     const shellEscape = (path: string) => path.replace(/([ \\'"()&;|<>$`!#*?[\]{}~^])/g, "\\$1");
@@ -314,7 +319,7 @@ export function TerminalView({ session, onClose }: TerminalViewProps) {
         setIsDragOver(false);
         if (isOverContainer(payload.position)) {
           const text = payload.paths.map(shellEscape).join(" ");
-          invoke("paste_to_tmux_pane", { tmuxSession, text }).catch(() => {});
+          invoke("paste_to_tmux_pane", { tmuxSession, text, groupPath }).catch(() => {});
           // Refocus the terminal after drop
           terminalRef.current?.focus();
         }
@@ -327,12 +332,15 @@ export function TerminalView({ session, onClose }: TerminalViewProps) {
       cleaned = true;
       unlisten.then((fn) => fn()).catch(() => {});
     };
-  }, [session.tmux_session]);
+  }, [session.tmux_session, session.group_path]);
 
   const handleRestart = useCallback(async () => {
     setRestarting(true);
     try {
-      await invoke("restart_session", { sessionId: session.id });
+      await invoke("restart_session", {
+        sessionId: session.id,
+        groupPath: session.group_path,
+      });
       // Invalidate and wait for refetch; the parent syncs selectedSession
       // from query data, which re-triggers our setup effect with the new tmux_session
       await queryClient.invalidateQueries({ queryKey: queryKeys.sessions() });
@@ -341,7 +349,7 @@ export function TerminalView({ session, onClose }: TerminalViewProps) {
     } finally {
       setRestarting(false);
     }
-  }, [session.id, queryClient]);
+  }, [session.id, session.group_path, queryClient]);
 
   if (!session.tmux_session) {
     return (
@@ -379,7 +387,6 @@ export function TerminalView({ session, onClose }: TerminalViewProps) {
         {showDiff && (
           <DiffViewer
             session={session}
-            tmuxSession={session.tmux_session ?? null}
             onClose={() => {
               setShowDiff(false);
               terminalRef.current?.focus();
@@ -428,7 +435,6 @@ export function TerminalView({ session, onClose }: TerminalViewProps) {
       {showDiff && (
         <DiffViewer
           session={session}
-          tmuxSession={session.tmux_session ?? null}
           onClose={() => {
             setShowDiff(false);
             terminalRef.current?.focus();
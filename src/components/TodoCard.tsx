@@ -2,7 +2,13 @@ import { useState, useRef, useEffect } from "react";
 import { useQuery, useMutation, useQueryClient } from "@tanstack/react-query";
 import { invoke } from "@tauri-apps/api/core";
 import { openUrl } from "@tauri-apps/plugin-opener";
-import type { GitHubIssue, Session, SessionSummary, AttentionStatus } from "../types";
+import type {
+  GitHubIssue,
+  MergeWorkflow,
+  Session,
+  SessionSummary,
+  AttentionStatus,
+} from "../types";
 import { ATTENTION_CONFIG, fallbackAttention, formatTime, labelStyle } from "../utils";
 import { queryKeys } from "../queryKeys";
 import { useWorktreeActions } from "../hooks/useWorktreeActions";
@@ -15,13 +21,13 @@ interface TodoCardProps {
   issue: GitHubIssue;
   session?: Session;
   repoPath: string;
-  backend?: "local" | "opencode-remote" | "claude-remote";
+  backend?: "local" | "opencode-remote" | "claude-remote" | "ssh";
   onSelectSession?: (session: Session) => void;
   onStartIssue?: (issue: GitHubIssue, tool?: string) => void;
   onEditIssue?: (issue: GitHubIssue) => void;
   liveTmuxSessions?: Set<string>;
   isFocused?: boolean;
-  mergeWorkflow?: "merge" | "pr";
+  mergeWorkflow?: MergeWorkflow;
   isDismissed?: boolean;
   onDismiss?: () => void;
   onUndismiss?: () => void;
@@ -59,7 +65,7 @@ function TodoCardInProgress({
   onSelectSession?: (session: Session) => void;
   liveTmuxSessions?: Set<string>;
   isFocused?: boolean;
-  mergeWorkflow?: "merge" | "pr";
+  mergeWorkflow?: MergeWorkflow;
   isDismissed?: boolean;
   onDismiss?: () => void;
   onUndismiss?: () => void;
@@ -86,14 +92,22 @@ function TodoCardInProgress({
   const { data: summary } = useQuery<SessionSummary>({
     queryKey: queryKeys.summary(session.id),
     queryFn: () =>
-      invoke("get_session_summary", {
-        projectPath: session.project_path,
-        claudeSessionId: session.claude_session_id ?? "",
-        agentdeckStatus: session.status,
-        tmuxSession: session.tmux_session || null,
-      }),
-    refetchInterval: 10_000,
-    enabled: !!session.claude_session_id,
+      session.tool === "opencode"
+        ? invoke("get_opencode_session_summary", {
+            projectPath: session.project_path,
+            opencodeSessionId: session.opencode_session_id,
+            agentdeckStatus: session.status,
+            tmuxSession: session.tmux_session || null,
+            groupPath: session.group_path,
+          })
+        : invoke("get_session_summary", {
+            projectPath: session.project_path,
+            claudeSessionId: session.claude_session_id ?? "",
+            agentdeckStatus: session.status,
+            tmuxSession: session.tmux_session || null,
+            groupPath: session.group_path,
+          }),
+    enabled: !!session.claude_session_id || session.tool === "opencode",
   });
 
   const sessionRepoPath = session.worktree_repo || session.project_path || repoPath;
@@ -103,6 +117,7 @@ function TodoCardInProgress({
     repoPath: sessionRepoPath,
     onSelectSession,
     extraInvalidateKeys: [["issues", repoPath]],
+    mergeWorkflow,
   });
 
   const prActions = usePrWorkflowActions({
@@ -208,6 +223,7 @@ function TodoCardInProgress({
             projectPath={session.project_path}
             worktreeBranch={session.worktree_branch}
             repoPath={sessionRepoPath}
+            groupPath={session.group_path}
             onShowDiff={() => setShowDiff(true)}
           />
         )}
@@ -253,7 +269,6 @@ function TodoCardInProgress({
       {showDiff && (
         <DiffViewer
           session={session}
-          tmuxSession={session.tmux_session || null}
           onClose={() => setShowDiff(false)}
         />
       )}
@@ -1,13 +1,22 @@
+import { useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
-import type { WorktreeActionsResult } from "../hooks/useWorktreeActions";
+import type { MergeStrategy, WorktreeActionsResult } from "../hooks/useWorktreeActions";
 import { extractIssueNumber } from "../utils";
 import { CloseIssueButton } from "./CloseIssueButton";
+import { ConflictResolverModal } from "./ConflictResolverModal";
+
+const MERGE_LABELS: Record<MergeStrategy, string> = {
+  merge: "Rebase & merge into",
+  squash: "Rebase & squash into",
+  "rebase-ff": "Rebase & fast-forward",
+};
 
 interface WorktreeActionsProps {
   actions: WorktreeActionsResult;
   projectPath: string;
   worktreeBranch: string;
   repoPath: string;
+  groupPath: string;
   onShowDiff: () => void;
 }
 
@@ -16,6 +25,7 @@ export function WorktreeActions({
   projectPath,
   worktreeBranch,
   repoPath,
+  groupPath,
   onShowDiff,
 }: WorktreeActionsProps) {
   const {
@@ -26,6 +36,11 @@ export function WorktreeActions({
     setConfirmingRemove,
     mergeState,
     setMergeState,
+    conflictPath,
+    mergeStrategy,
+    squashMessage,
+    setSquashMessage,
+    squashMessageLoading,
     worktreeStatus,
     statusLoading,
     mergeWarnings,
@@ -34,12 +49,18 @@ export function WorktreeActions({
     removeMutation,
     mergeCleanupMutation,
     conflictSessionMutation,
+    continueRebaseMutation,
+    continueMergeMutation,
     abortRebaseMutation,
     abortMergeMutation,
     startRebase,
     startMerge,
     isPending,
   } = actions;
+  const [showConflicts, setShowConflicts] = useState(false);
+  const isRebaseConflict = mergeState === "rebase_conflict";
+  const continueMutation = isRebaseConflict ? continueRebaseMutation : continueMergeMutation;
+  const continueResult = continueMutation.data;
 
   return (
     <div className="session-wt-actions">
@@ -76,7 +97,7 @@ export function WorktreeActions({
                   setMergeState("confirming");
                 }}
                 disabled={isPending}
-                title={`Rebase & merge ${worktreeBranch} into ${defaultBranch ?? "main"}`}
+                title={`${MERGE_LABELS[mergeStrategy]} ${defaultBranch ?? "main"}`}
               >
                 Merge
               </button>
@@ -100,14 +121,30 @@ export function WorktreeActions({
               ))}
             </div>
           )}
-          <span className="merge-label">Rebase & merge into {defaultBranch ?? "main"}?</span>
+          <span className="merge-label">
+            {MERGE_LABELS[mergeStrategy]} {defaultBranch ?? "main"}?
+          </span>
+          {mergeStrategy === "squash" && (
+            <textarea
+              className="merge-message-input"
+              value={squashMessage}
+              onChange={(e) => setSquashMessage(e.target.value)}
+              onClick={(e) => e.stopPropagation()}
+              placeholder={squashMessageLoading ? "Generating commit message..." : "Commit message"}
+              rows={4}
+            />
+          )}
           <button
             className="wt-btn wt-btn-merge"
             onClick={(e) => {
               e.stopPropagation();
               startMerge();
             }}
-            disabled={isPending || statusLoading}
+            disabled={
+              isPending ||
+              statusLoading ||
+              (mergeStrategy === "squash" && (squashMessageLoading || !squashMessage.trim()))
+            }
           >
             {hasMergeWarnings ? "Merge Anyway" : "Confirm"}
           </button>
@@ -140,6 +177,17 @@ export function WorktreeActions({
           >
             Abort
           </button>
+          <button
+            className="wt-btn wt-btn-action"
+            onClick={(e) => {
+              e.stopPropagation();
+              setShowConflicts(true);
+            }}
+            disabled={abortRebaseMutation.isPending || conflictSessionMutation.isPending}
+            title="Resolve the conflicted files here"
+          >
+            Resolve
+          </button>
           <button
             className="wt-btn wt-btn-merge"
             onClick={(e) => {
@@ -211,6 +259,17 @@ export function WorktreeActions({
           >
             Abort
           </button>
+          <button
+            className="wt-btn wt-btn-action"
+            onClick={(e) => {
+              e.stopPropagation();
+              setShowConflicts(true);
+            }}
+            disabled={abortMergeMutation.isPending || conflictSessionMutation.isPending}
+            title="Resolve the conflicted files here"
+          >
+            Resolve
+          </button>
           <button
             className="wt-btn wt-btn-merge"
             onClick={(e) => {
@@ -286,6 +345,28 @@ export function WorktreeActions({
           </button>
         </>
       )}
+      {showConflicts && conflictPath && (
+        <ConflictResolverModal
+          worktreePath={conflictPath}
+          groupPath={groupPath}
+          oursLabel={defaultBranch ?? "main"}
+          theirsLabel={worktreeBranch}
+          continueLabel={isRebaseConflict ? "Continue Rebase" : "Commit Merge"}
+          onContinue={() => {
+            // Close once the rebase or merge lands; stay open on the next conflicts
+            const onSuccess = (result: { success: boolean }) => {
+              if (result.success) setShowConflicts(false);
+            };
+            if (isRebaseConflict) continueRebaseMutation.mutate(undefined, { onSuccess });
+            else continueMergeMutation.mutate(undefined, { onSuccess });
+          }}
+          continuePending={continueMutation.isPending}
+          continueMessage={
+            continueResult && !continueResult.success ? continueResult.conflict_message : null
+          }
+          onClose={() => setShowConflicts(false)}
+        />
+      )}
     </div>
   );
 }
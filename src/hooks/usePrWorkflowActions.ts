@@ -119,6 +119,7 @@ export function usePrWorkflowActions({
         repoPath,
         worktreePath: session.worktree_path,
         branch: session.worktree_branch,
+        groupPath: session.group_path,
       }),
     enabled: enabled && (prState === "confirming" || confirmingRemove) && isWorktree,
     staleTime: 5_000,
@@ -202,6 +203,7 @@ export function usePrWorkflowActions({
     invoke<PushResult>("update_main_branch", {
       repoPath,
       mainBranch: defaultBranch ?? "main",
+      groupPath: session.group_path,
     })
       .then((result) => {
         if (!result.success) {
@@ -211,7 +213,7 @@ export function usePrWorkflowActions({
       .catch((err) => {
         setMainUpdateWarning(String(err));
       });
-  }, [prStatusData, prState, session.id, repoPath, defaultBranch]);
+  }, [prStatusData, prState, session.id, session.group_path, repoPath, defaultBranch]);
 
   // Remove session mutation (background)
   const removeMutation = useMutation({
@@ -222,6 +224,7 @@ export function usePrWorkflowActions({
         sessionId: session.id,
         repoPath: isWorktree ? repoPath : null,
         worktreePath: isWorktree ? session.worktree_path : null,
+        groupPath: session.group_path,
       });
     },
   });
@@ -233,6 +236,7 @@ export function usePrWorkflowActions({
         worktreePath: session.worktree_path,
         mainBranch: defaultBranch ?? "main",
         useRemote: true,
+        groupPath: session.group_path,
       }),
     onSuccess: (result) => {
       if (result.success) {
@@ -253,6 +257,7 @@ export function usePrWorkflowActions({
       invoke<PushResult>("push_branch", {
         worktreePath: session.worktree_path,
         branch: session.worktree_branch,
+        groupPath: session.group_path,
       }),
     onSuccess: (result) => {
       if (result.success) {
@@ -300,6 +305,7 @@ export function usePrWorkflowActions({
         worktreePath: session.worktree_path,
         mainBranch: defaultBranch ?? "main",
         useRemote: true,
+        groupPath: session.group_path,
       });
       if (!rebaseResult.success) {
         setPrState("rebase_conflict");
@@ -308,6 +314,7 @@ export function usePrWorkflowActions({
       const pushResult = await invoke<PushResult>("force_push_branch", {
         worktreePath: session.worktree_path,
         branch: session.worktree_branch,
+        groupPath: session.group_path,
       });
       if (!pushResult.success) {
         throw new Error(pushResult.message || "Push failed");
@@ -330,6 +337,7 @@ export function usePrWorkflowActions({
     mutationFn: (): Promise<void> =>
       invoke<void>("abort_rebase", {
         worktreePath: session.worktree_path,
+        groupPath: session.group_path,
       }),
     onSuccess: () => {
       // Return to pr_open if we were rebasing for an existing PR, else idle
@@ -404,6 +412,7 @@ export function usePrWorkflowActions({
           sessionId: session.id,
           repoPath,
           worktreePath: session.worktree_path,
+          groupPath: session.group_path,
         });
         return;
       } else if (mode === "remove_worktree") {
@@ -411,6 +420,7 @@ export function usePrWorkflowActions({
           await invoke("remove_worktree", {
             repoPath,
             worktreePath: session.worktree_path,
+            groupPath: session.group_path,
           });
         } catch {
           // Worktree may already be gone
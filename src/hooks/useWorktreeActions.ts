@@ -8,9 +8,18 @@ import {
 } from "@tanstack/react-query";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
-import type { Session, WorktreeStatus, MergeResult, RebaseResult } from "../types";
+import type {
+  ConflictReport,
+  MergeWorkflow,
+  Session,
+  SessionSummary,
+  WorktreeStatus,
+  MergeResult,
+  RebaseResult,
+} from "../types";
 import { queryKeys } from "../queryKeys";
 import { trackEvent } from "../analytics";
+import { isRemoteSession } from "../utils";
 
 export type MergeState =
   | "idle"
@@ -21,6 +30,9 @@ export type MergeState =
   | "success"
   | "conflict";
 
+/** How the local merge workflow lands a branch (`try_merge_branch`'s strategy) */
+export type MergeStrategy = "merge" | "squash" | "rebase-ff";
+
 /** The return type of useWorktreeActions, with setters widened for prop compatibility */
 export interface WorktreeActionsResult {
   isWorktree: boolean;
@@ -31,6 +43,13 @@ export interface WorktreeActionsResult {
   mergeState: MergeState;
   setMergeState: (value: MergeState) => void;
   mergeResult: MergeResult | null;
+  /** The worktree with the in-progress rebase or merge, while in a conflict state */
+  conflictPath: string | undefined;
+  mergeStrategy: MergeStrategy;
+  /** The squash commit message, generated and then edited in the confirm step */
+  squashMessage: string;
+  setSquashMessage: (value: string) => void;
+  squashMessageLoading: boolean;
   worktreeStatus: WorktreeStatus | undefined;
   statusLoading: boolean;
   mergeWarnings: string[];
@@ -41,6 +60,8 @@ export interface WorktreeActionsResult {
   mergeMutation: UseMutationResult<MergeResult, Error, void>;
   mergeCleanupMutation: UseMutationResult<void, Error, "remove_all" | "remove_worktree" | "keep">;
   conflictSessionMutation: UseMutationResult<string, Error, void>;
+  continueRebaseMutation: UseMutationResult<RebaseResult, Error, void>;
+  continueMergeMutation: UseMutationResult<MergeResult, Error, void>;
   abortRebaseMutation: UseMutationResult<void, Error, void>;
   abortMergeMutation: UseMutationResult<void, Error, void>;
   isRemoving: boolean;
@@ -58,6 +79,7 @@ interface UseWorktreeActionsParams {
   /** When confirmingRemove state is lifted to a parent, pass the effective value here
    *  so the worktree status query fires even though the hook's internal state isn't set. */
   confirmingRemove?: boolean;
+  mergeWorkflow?: MergeWorkflow;
 }
 
 export function useWorktreeActions({
@@ -66,6 +88,7 @@ export function useWorktreeActions({
   onSelectSession,
   extraInvalidateKeys,
   confirmingRemove: externalConfirmingRemove,
+  mergeWorkflow,
 }: UseWorktreeActionsParams) {
   const queryClient = useQueryClient();
   const [confirmingRemove, setConfirmingRemove] = useState(false);
@@ -74,9 +97,18 @@ export function useWorktreeActions({
   const [removingSessionId, setRemovingSessionId] = useState<string | null>(null);
   const [removalError, setRemovalError] = useState<Error | null>(null);
   const mergeAfterRebase = useRef(false);
+  const [editedSquashMessage, setSquashMessage] = useState<string | null>(null);
+  const mergeStrategy: MergeStrategy =
+    mergeWorkflow === "squash" || mergeWorkflow === "rebase-ff" ? mergeWorkflow : "merge";
 
   const isWorktree = !!session.worktree_branch;
   const isRemoving = removingSessionId === session.id;
+  const conflictPath =
+    mergeState === "rebase_conflict"
+      ? session.worktree_path
+      : mergeState === "conflict"
+        ? mergeResult?.main_worktree_path
+        : undefined;
 
   const { data: worktreeStatus, isFetching: statusLoading } = useQuery<WorktreeStatus>({
     queryKey: queryKeys.worktreeStatus(session.worktree_path ?? ""),
@@ -85,6 +117,7 @@ export function useWorktreeActions({
         repoPath,
         worktreePath: session.worktree_path,
         branch: session.worktree_branch,
+        groupPath: session.group_path,
       }),
     enabled:
       (confirmingRemove || externalConfirmingRemove || mergeState === "confirming") && isWorktree,
@@ -136,43 +169,82 @@ export function useWorktreeActions({
   const removeMutation = useMutation({
     mutationFn: async () => {
       setRemovalError(null);
+      if (isRemoteSession(session)) {
+        // Remote backends delete on their server, with no worktree to clean up
+        await invoke("backend_delete_session", {
+          groupPath: session.group_path,
+          sessionId: session.id,
+        });
+        invalidateAll();
+        return;
+      }
       setRemovingSessionId(session.id);
       await invoke("remove_session_background", {
         sessionId: session.id,
         repoPath: isWorktree ? repoPath : null,
         worktreePath: isWorktree ? session.worktree_path : null,
+        groupPath: session.group_path,
       });
     },
   });
 
   const { data: defaultBranch } = useQuery<string>({
     queryKey: queryKeys.defaultBranch(repoPath),
-    queryFn: () => invoke("get_default_branch", { repoPath }),
+    queryFn: () => invoke("get_default_branch", { repoPath, groupPath: session.group_path }),
     staleTime: 5 * 60 * 1000,
     enabled: isWorktree,
   });
 
+  const { data: generatedSquashMessage, isFetching: squashMessageLoading } = useQuery<string>({
+    queryKey: queryKeys.squashMessage(session.id),
+    queryFn: () =>
+      invoke("get_squash_message", {
+        repoPath,
+        branch: session.worktree_branch,
+        mainBranch: defaultBranch ?? "main",
+        prompt: session.prompt,
+        summary:
+          queryClient.getQueryData<SessionSummary>(queryKeys.summary(session.id))?.summary ?? null,
+        groupPath: session.group_path,
+      }),
+    enabled: mergeStrategy === "squash" && mergeState === "confirming" && isWorktree,
+    staleTime: 0,
+  });
+  const squashMessage = editedSquashMessage ?? generatedSquashMessage ?? "";
+
   const isFeatureBranch =
     isWorktree &&
     session.worktree_branch !== "main" &&
     session.worktree_branch !== "master" &&
     session.worktree_branch !== defaultBranch;
 
+  // After a clean rebase, go on to the merge when the rebase was its first step
+  const onRebased = () => {
+    if (mergeAfterRebase.current) {
+      mergeAfterRebase.current = false;
+      setMergeState("merging");
+      mergeMutation.mutate();
+    } else {
+      setMergeState("idle");
+    }
+  };
+
+  // A merge landed, with or without stopping on conflicts first
+  const onMerged = () => {
+    setSquashMessage(null);
+    trackEvent("worktree_merged");
+  };
+
   const rebaseMutation = useMutation({
     mutationFn: () =>
       invoke<RebaseResult>("rebase_branch", {
         worktreePath: session.worktree_path,
         mainBranch: defaultBranch ?? "main",
+        groupPath: session.group_path,
       }),
     onSuccess: (result) => {
       if (result.success) {
-        if (mergeAfterRebase.current) {
-          mergeAfterRebase.current = false;
-          setMergeState("merging");
-          mergeMutation.mutate();
-        } else {
-          setMergeState("idle");
-        }
+        onRebased();
       } else {
         setMergeState("rebase_conflict");
       }
@@ -184,18 +256,25 @@ export function useWorktreeActions({
   });
 
   const mergeMutation = useMutation({
-    mutationFn: () =>
-      invoke<MergeResult>("try_merge_branch", {
+    mutationFn: async () => {
+      const result = await invoke<MergeResult>("try_merge_branch", {
         repoPath,
         branch: session.worktree_branch,
         mainBranch: defaultBranch ?? "main",
-      }),
+        strategy: mergeStrategy,
+        commitMessage: mergeStrategy === "squash" ? squashMessage : null,
+        groupPath: session.group_path,
+      });
+      // Rolled back, so there's nothing to resolve
+      if (!result.success && !result.conflicted) {
+        throw new Error(result.conflict_message ?? "Merge failed");
+      }
+      return result;
+    },
     onSuccess: (result) => {
       setMergeResult(result);
       setMergeState(result.success ? "success" : "conflict");
-      if (result.success) {
-        trackEvent("worktree_merged");
-      }
+      if (result.success) onMerged();
       // Clear cached worktree status so the remove dialog doesn't show stale "not merged" data
       queryClient.removeQueries({
         queryKey: queryKeys.worktreeStatus(session.worktree_path ?? ""),
@@ -217,6 +296,7 @@ export function useWorktreeActions({
           sessionId: session.id,
           repoPath,
           worktreePath: session.worktree_path,
+          groupPath: session.group_path,
         });
         return;
       } else if (mode === "remove_worktree") {
@@ -224,6 +304,7 @@ export function useWorktreeActions({
           await invoke("remove_worktree", {
             repoPath,
             worktreePath: session.worktree_path,
+            groupPath: session.group_path,
           });
         } catch {
           // Worktree may already be gone
@@ -249,13 +330,29 @@ export function useWorktreeActions({
       if (!targetPath) throw new Error("No worktree path for conflict resolution");
 
       const branch = defaultBranch ?? "main";
-      const prompt = isRebaseConflict
+      // Name the conflicted files and line ranges (null if reading them fails)
+      const report = await invoke<ConflictReport>("get_conflicts", {
+        worktreePath: targetPath,
+        groupPath: session.group_path,
+      }).catch(() => null);
+      const fallbackPrompt = isRebaseConflict
         ? `A \`git rebase ${branch}\` is in progress but hit conflicts. Please resolve them:\n1. Run \`git status\` to see which files have conflicts\n2. Open each conflicted file and resolve the conflict markers (<<<<<<< ======= >>>>>>>)\n3. Stage each resolved file with \`git add <file>\`\n4. Run \`git rebase --continue\`\n5. If more conflicts appear, repeat from step 1\n\nDo NOT use \`git merge\` — this is a rebase, not a merge.`
-        : `There are merge conflicts from merging '${session.worktree_branch}' into ${branch}. Please resolve all conflicts, then commit the merge.`;
+        : mergeStrategy === "squash"
+          ? `There are conflicts from squash-merging '${session.worktree_branch}' into ${branch}. Please resolve all conflicts, stage the files, then commit with this message:\n\n${squashMessage}`
+          : `There are merge conflicts from merging '${session.worktree_branch}' into ${branch}. Please resolve all conflicts, then commit the merge.`;
+      const prompt = !report?.files.length
+        ? fallbackPrompt
+        : !isRebaseConflict && mergeStrategy === "squash"
+          ? `${report.description}\n\nCommit with this message:\n\n${squashMessage}`
+          : report.description;
       const title = isRebaseConflict
         ? `rebase-${session.worktree_branch}`
         : `merge-${session.worktree_branch}`;
 
+      // The resolver runs in the conflicted worktree itself, which for a merge is
+      // the main one. Its attention hooks go in .claude/settings.local.json there;
+      // install_hooks lists that file in .git/info/exclude, so main stays clean for
+      // the merge commit, and the hooks go when the last session there is deleted.
       const creationId = crypto.randomUUID();
       await invoke("create_session", {
         creationId,
@@ -307,10 +404,46 @@ export function useWorktreeActions({
     },
   });
 
+  const continueRebaseMutation = useMutation({
+    mutationFn: () =>
+      invoke<RebaseResult>("continue_rebase", {
+        worktreePath: session.worktree_path,
+        groupPath: session.group_path,
+      }),
+    onSuccess: (result) => {
+      queryClient.invalidateQueries({
+        queryKey: queryKeys.conflicts(session.worktree_path ?? ""),
+      });
+      // On failure the rebase stopped on the next commit's conflicts
+      if (result.success) onRebased();
+    },
+  });
+
+  const continueMergeMutation = useMutation({
+    mutationFn: () => {
+      const mainPath = mergeResult?.main_worktree_path;
+      if (!mainPath) throw new Error("No main worktree path");
+      return invoke<MergeResult>("continue_merge", {
+        worktreePath: mainPath,
+        message: mergeStrategy === "squash" ? squashMessage : null,
+        groupPath: session.group_path,
+      });
+    },
+    onSuccess: (result) => {
+      queryClient.invalidateQueries({ queryKey: queryKeys.conflicts(result.main_worktree_path) });
+      if (result.success) {
+        setMergeResult(result);
+        setMergeState("success");
+        onMerged();
+      }
+    },
+  });
+
   const abortRebaseMutation = useMutation({
     mutationFn: (): Promise<void> =>
       invoke<void>("abort_rebase", {
         worktreePath: session.worktree_path,
+        groupPath: session.group_path,
       }),
     onSuccess: () => {
       setMergeState("idle");
@@ -321,7 +454,7 @@ export function useWorktreeActions({
     mutationFn: (): Promise<void> => {
       const mainPath = mergeResult?.main_worktree_path;
       if (!mainPath) throw new Error("No main worktree path");
-      return invoke<void>("abort_merge", { worktreePath: mainPath });
+      return invoke<void>("abort_merge", { worktreePath: mainPath, groupPath: session.group_path });
     },
     onSuccess: () => {
       setMergeState("idle");
@@ -336,6 +469,8 @@ export function useWorktreeActions({
     mergeMutation.isPending ||
     mergeCleanupMutation.isPending ||
     conflictSessionMutation.isPending ||
+    continueRebaseMutation.isPending ||
+    continueMergeMutation.isPending ||
     abortRebaseMutation.isPending ||
     abortMergeMutation.isPending;
   const mutationError =
@@ -345,6 +480,8 @@ export function useWorktreeActions({
     mergeMutation.error ??
     mergeCleanupMutation.error ??
     conflictSessionMutation.error ??
+    continueRebaseMutation.error ??
+    continueMergeMutation.error ??
     abortRebaseMutation.error ??
     abortMergeMutation.error;
 
@@ -369,6 +506,11 @@ export function useWorktreeActions({
     mergeState,
     setMergeState,
     mergeResult,
+    conflictPath,
+    mergeStrategy,
+    squashMessage,
+    setSquashMessage,
+    squashMessageLoading,
     worktreeStatus,
     statusLoading,
     mergeWarnings,
@@ -379,6 +521,8 @@ export function useWorktreeActions({
     mergeMutation,
     mergeCleanupMutation,
     conflictSessionMutation,
+    continueRebaseMutation,
+    continueMergeMutation,
     abortRebaseMutation,
     abortMergeMutation,
     isRemoving,
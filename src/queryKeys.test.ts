@@ -30,10 +30,31 @@ describe("queryKeys", () => {
     expect(queryKeys.branchDiff("sess-1")).toEqual(["branch-diff", "sess-1"]);
   });
 
+  it("branchDiffFile nests under the session's branch diff", () => {
+    expect(queryKeys.branchDiffFile("sess-1", "abc", "src/a.ts")).toEqual([
+      "branch-diff",
+      "sess-1",
+      "abc",
+      "src/a.ts",
+    ]);
+  });
+
+  it("reviewComments includes session id", () => {
+    expect(queryKeys.reviewComments("sess-1")).toEqual(["reviewComments", "sess-1"]);
+  });
+
   it("summary includes session id", () => {
     expect(queryKeys.summary("sess-1")).toEqual(["summary", "sess-1"]);
   });
 
+  it("squashMessage includes session id", () => {
+    expect(queryKeys.squashMessage("sess-1")).toEqual(["squashMessage", "sess-1"]);
+  });
+
+  it("conflicts includes worktree path", () => {
+    expect(queryKeys.conflicts("/repo/wt")).toEqual(["conflicts", "/repo/wt"]);
+  });
+
   it("issues includes repo path", () => {
     expect(queryKeys.issues("/repo")).toEqual(["issues", "/repo"]);
   });
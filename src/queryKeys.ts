@@ -8,7 +8,12 @@ export const queryKeys = {
   worktrees: (repoPath: string) => ["worktrees", repoPath] as const,
   defaultBranch: (repoPath: string) => ["defaultBranch", repoPath] as const,
   branchDiff: (sessionId: string) => ["branch-diff", sessionId] as const,
+  branchDiffFile: (sessionId: string, base: string, path: string) =>
+    ["branch-diff", sessionId, base, path] as const,
+  reviewComments: (sessionId: string) => ["reviewComments", sessionId] as const,
   summary: (sessionId: string) => ["summary", sessionId] as const,
+  squashMessage: (sessionId: string) => ["squashMessage", sessionId] as const,
+  conflicts: (worktreePath: string) => ["conflicts", worktreePath] as const,
   issues: (repoPath: string) => ["issues", repoPath] as const,
   githubUsername: (repoPath: string) => ["githubUsername", repoPath] as const,
   prStatus: (repoPath: string, branch: string) => ["prStatus", repoPath, branch] as const,
@@ -1,3 +1,6 @@
+/** `merge`, `squash` and `rebase-ff` merge locally; `pr` goes through GitHub */
+export type MergeWorkflow = "merge" | "squash" | "rebase-ff" | "pr";
+
 export interface Group {
   path: string;
   name: string;
@@ -6,10 +9,10 @@ export interface Group {
   default_path: string;
   github_issues_enabled: boolean;
   is_git_repo: boolean;
-  merge_workflow: "merge" | "pr";
+  merge_workflow: MergeWorkflow;
   worktree_command: string | null;
   component_depth: number;
-  backend: "local" | "opencode-remote" | "claude-remote";
+  backend: "local" | "opencode-remote" | "claude-remote" | "ssh";
   server_url: string | null;
 }
 
@@ -54,14 +57,38 @@ export interface Session {
   worktree_path: string;
   worktree_repo: string;
   worktree_branch: string;
+  tool: string;
   claude_session_id: string | null;
+  opencode_session_id: string | null;
   prompt: string | null;
   pr_url: string | null;
   pr_number: number | null;
   pr_state: string | null;
 }
 
-export type AttentionStatus = "needs_input" | "error" | "running" | "idle" | "stale" | "unknown";
+/** Patterns are plain text, or `$marker` to match the end of the last non-empty line. */
+export interface ToolProfile {
+  name: string;
+  binary: string;
+  version_args: string[];
+  required: boolean;
+  ready_patterns: string[];
+  waiting_patterns: string[];
+  permission_patterns: string[];
+  transcript_location: string | null;
+  builtin: boolean;
+}
+
+export type AttentionStatus =
+  | "needs_input"
+  | "awaiting_permission"
+  | "rate_limited"
+  | "compacting"
+  | "error"
+  | "running"
+  | "idle"
+  | "stale"
+  | "unknown";
 
 export interface SessionSummary {
   summary: string | null;
@@ -69,6 +96,201 @@ export interface SessionSummary {
   attention: AttentionStatus;
   last_tool: string | null;
   last_text: string | null;
+  subagents: Subagent[];
+}
+
+export interface Subagent {
+  tool_use_id: string;
+  description: string | null;
+  subagent_type: string | null;
+  status: "running" | "completed" | "failed";
+  started_at: string | null;
+  last_tool: string | null;
+}
+
+export type TranscriptBlock =
+  | { type: "text"; text: string }
+  | { type: "thinking"; text: string }
+  | { type: "tool_use"; id: string; name: string; input: unknown }
+  | {
+      type: "tool_result";
+      tool_use_id: string;
+      output: string;
+      is_error: boolean;
+      truncated: boolean;
+    }
+  | { type: "image" };
+
+export interface TranscriptEntry {
+  uuid: string | null;
+  parent_uuid: string | null;
+  role: "user" | "assistant" | "system";
+  timestamp: string | null;
+  model: string | null;
+  is_sidechain: boolean;
+  agent_id: string | null;
+  blocks: TranscriptBlock[];
+}
+
+export interface TranscriptPage {
+  entries: TranscriptEntry[];
+  next_cursor: number;
+  has_more: boolean;
+}
+
+export type FileAction = "read" | "write" | "edit" | "delete";
+
+export interface FileTouch {
+  path: string;
+  relative_path: string | null;
+  action: FileAction;
+  tool: string;
+  tool_use_id: string;
+  timestamp: string | null;
+  edits: { old_string: string; new_string: string }[];
+  command: string | null;
+  reason: string | null;
+  failed: boolean;
+  is_sidechain: boolean;
+}
+
+export interface FileTimeline {
+  events: FileTouch[];
+  files: {
+    path: string;
+    relative_path: string | null;
+    reads: number;
+    writes: number;
+    edits: number;
+    deletes: number;
+    first_touched: string | null;
+    last_touched: string | null;
+  }[];
+}
+
+export interface SearchHit {
+  session_id: string;
+  group_path: string;
+  title: string | null;
+  timestamp: string | null;
+  kind: "prompt" | "assistant" | "tool";
+  snippet: string;
+}
+
+export type ExportFormat = "markdown" | "html" | "json";
+
+export interface TranscriptExport {
+  file_name: string;
+  mime_type: string;
+  content: string;
+}
+
+export interface UsageTotals {
+  input_tokens: number;
+  output_tokens: number;
+  cache_creation_tokens: number;
+  cache_read_tokens: number;
+  cost_usd: number;
+}
+
+export interface UsageReport {
+  total: UsageTotals;
+  sessions: (UsageTotals & { session_id: string; group_path: string; title: string | null })[];
+  groups: (UsageTotals & { group_path: string })[];
+  days: (UsageTotals & { day: string })[];
+  unpriced_models: string[];
+}
+
+export interface ModelPrice {
+  model: string;
+  input_per_mtok: number;
+  output_per_mtok: number;
+  cache_write_per_mtok: number;
+  cache_read_per_mtok: number;
+}
+
+export interface AttentionThresholds {
+  stale_after_secs: number;
+  idle_after_secs: number | null;
+}
+
+export type WebhookEventName =
+  | "session.needs_input"
+  | "session.error"
+  | "session.finished"
+  | "pr.created"
+  | "pr.merged";
+
+export interface Webhook {
+  id: number | null;
+  name: string;
+  url: string;
+  events: WebhookEventName[];
+  body_template: string | null;
+  enabled: boolean;
+}
+
+export interface WebhookDelivery {
+  id: number;
+  webhook_id: number;
+  event: WebhookEventName;
+  delivered: boolean;
+  attempts: number;
+  status_code: number | null;
+  error: string | null;
+  created_at: number;
+}
+
+export interface ApiSettings {
+  enabled: boolean;
+  transport: "tcp" | "unix";
+  port: number;
+  socket_path: string | null;
+}
+
+export interface NotificationSettings {
+  enabled: boolean;
+  sound: boolean;
+  quiet_start: string | null;
+  quiet_end: string | null;
+}
+
+export interface FocusSessionEvent {
+  session_id: string;
+  group_path: string;
+}
+
+export type BudgetAction = "warn" | "interrupt" | "stop";
+
+export interface GroupBudget {
+  max_tokens: number | null;
+  max_usd: number | null;
+  max_runtime_mins: number | null;
+  warn_percent: number;
+  action: BudgetAction;
+}
+
+export interface BudgetCheck {
+  kind: "tokens" | "usd" | "runtime";
+  used: number;
+  limit: number;
+  percent: number;
+  level: "ok" | "warning" | "exhausted";
+}
+
+export interface BudgetStatus {
+  group_path: string;
+  day: string;
+  budget: GroupBudget;
+  usage: { tokens: number; cost_usd: number; runtime_secs: number };
+  checks: BudgetCheck[];
+}
+
+export interface BudgetEvent {
+  group_path: string;
+  check: BudgetCheck;
+  action: BudgetAction;
+  affected_sessions: string[];
 }
 
 export interface Worktree {
@@ -82,6 +304,8 @@ export interface MergeResult {
   success: boolean;
   main_worktree_path: string;
   conflict_message: string | null;
+  /** Git stopped with conflicts in main_worktree_path; false when the merge was rolled back. */
+  conflicted: boolean;
 }
 
 export interface WorktreeStatus {
@@ -102,6 +326,37 @@ export interface RebaseResult {
   conflict_message: string | null;
 }
 
+export type ConflictOperation = "merge" | "rebase" | "cherry_pick" | "other" | "none";
+
+/** One conflicted region, between the `<<<<<<<` and `>>>>>>>` markers (1-based lines) */
+export interface ConflictHunk {
+  start_line: number;
+  end_line: number;
+  ours: string;
+  base: string | null;
+  theirs: string;
+}
+
+export interface ConflictFile {
+  path: string;
+  base: string | null;
+  ours: string | null;
+  theirs: string | null;
+  /** The working-tree file, with conflict markers */
+  content: string | null;
+  binary: boolean;
+  hunks: ConflictHunk[];
+}
+
+export interface ConflictReport {
+  operation: ConflictOperation;
+  files: ConflictFile[];
+  /** The conflicts described as a prompt for an agent session */
+  description: string;
+}
+
+export type ResolveStrategy = "ours" | "theirs" | "manual";
+
 export interface PushResult {
   success: boolean;
   message: string;
@@ -123,3 +378,75 @@ export interface GitHubLabel {
   name: string;
   color: string;
 }
+
+export type DiffScope = "committed" | "uncommitted";
+
+export interface DiffOptions {
+  scope: DiffScope;
+  ignore_whitespace: boolean;
+  word_diff: boolean;
+}
+
+export interface BranchDiffFile {
+  path: string;
+  old_path: string | null;
+  status: "added" | "modified" | "deleted" | "renamed" | "copied" | "type_changed" | "untracked";
+  binary: boolean;
+  additions: number;
+  deletions: number;
+  old_blob: string | null;
+  new_blob: string | null;
+}
+
+export interface BranchDiff {
+  scope: DiffScope;
+  base: string;
+  files: BranchDiffFile[];
+  additions: number;
+  deletions: number;
+}
+
+export interface DiffSegment {
+  type: "addition" | "deletion" | "context";
+  text: string;
+}
+
+export interface DiffLine {
+  type: "addition" | "deletion" | "context";
+  content: string;
+  /** Line numbers and word-diff segments, from `get_branch_diff_file` */
+  old_line?: number | null;
+  new_line?: number | null;
+  segments?: DiffSegment[] | null;
+}
+
+export interface DiffHunk {
+  header: string;
+  lines: DiffLine[];
+}
+
+export interface FileDiff {
+  path: string;
+  binary: boolean;
+  hunks: DiffHunk[];
+  truncated: boolean;
+}
+
+/** A stored review comment on a session's diff, from `list_review_comments` */
+export interface ReviewComment {
+  id: number;
+  session_id: string;
+  branch: string;
+  file_path: string;
+  side: "new" | "old";
+  blob_sha: string | null;
+  start_line: number;
+  end_line: number;
+  snippet: string;
+  body: string;
+  status: "pending" | "sent";
+  created_at: number;
+  sent_at: number | null;
+  /** The commented lines are no longer in the file */
+  outdated: boolean;
+}
@@ -6,8 +6,6 @@ import {
   fallbackAttention,
   extractIssueNumber,
   issueToSlug,
-  formatCommentsAsPrompt,
-  parseDiff,
   fileName,
   fileDir,
   isMainSession,
@@ -15,7 +13,6 @@ import {
   storageGet,
   storageSet,
 } from "./utils";
-import type { DiffComment } from "./utils";
 
 describe("formatPath", () => {
   it("replaces /Users/<user>/ with ~/", () => {
@@ -96,80 +93,6 @@ describe("fallbackAttention", () => {
   });
 });
 
-describe("parseDiff", () => {
-  it("returns empty array for empty input", () => {
-    expect(parseDiff("")).toEqual([]);
-  });
-
-  it("parses a single file with additions and deletions", () => {
-    const raw = `diff --git a/src/foo.ts b/src/foo.ts
-index abc..def 100644
---- a/src/foo.ts
-+++ b/src/foo.ts
-@@ -1,3 +1,3 @@
- line1
--old line
-+new line
- line3`;
-    const files = parseDiff(raw);
-    expect(files).toHaveLength(1);
-    expect(files[0].path).toBe("src/foo.ts");
-    expect(files[0].additions).toBe(1);
-    expect(files[0].deletions).toBe(1);
-    expect(files[0].hunks).toHaveLength(1);
-    expect(files[0].hunks[0].lines).toHaveLength(4);
-    expect(files[0].hunks[0].lines[0]).toEqual({ type: "context", content: " line1" });
-    expect(files[0].hunks[0].lines[1]).toEqual({ type: "deletion", content: "-old line" });
-    expect(files[0].hunks[0].lines[2]).toEqual({ type: "addition", content: "+new line" });
-    expect(files[0].hunks[0].lines[3]).toEqual({ type: "context", content: " line3" });
-  });
-
-  it("parses multiple files", () => {
-    const raw = `diff --git a/a.ts b/a.ts
-@@ -1 +1 @@
--old
-+new
-diff --git a/b.ts b/b.ts
-@@ -1 +1,2 @@
- existing
-+added`;
-    const files = parseDiff(raw);
-    expect(files).toHaveLength(2);
-    expect(files[0].path).toBe("a.ts");
-    expect(files[0].additions).toBe(1);
-    expect(files[0].deletions).toBe(1);
-    expect(files[1].path).toBe("b.ts");
-    expect(files[1].additions).toBe(1);
-    expect(files[1].deletions).toBe(0);
-  });
-
-  it("handles multiple hunks in one file", () => {
-    const raw = `diff --git a/file.ts b/file.ts
-@@ -1,2 +1,2 @@
--a
-+b
-@@ -10,2 +10,2 @@
--c
-+d`;
-    const files = parseDiff(raw);
-    expect(files).toHaveLength(1);
-    expect(files[0].hunks).toHaveLength(2);
-    expect(files[0].additions).toBe(2);
-    expect(files[0].deletions).toBe(2);
-  });
-
-  it("handles context-only hunks", () => {
-    const raw = `diff --git a/file.ts b/file.ts
-@@ -1,2 +1,2 @@
- line1
- line2`;
-    const files = parseDiff(raw);
-    expect(files[0].additions).toBe(0);
-    expect(files[0].deletions).toBe(0);
-    expect(files[0].hunks[0].lines).toHaveLength(2);
-  });
-});
-
 describe("fileName", () => {
   it("extracts filename from path", () => {
     expect(fileName("src/components/App.tsx")).toBe("App.tsx");
@@ -229,7 +152,10 @@ describe("ATTENTION_CONFIG", () => {
     expect(keys).toContain("idle");
     expect(keys).toContain("stale");
     expect(keys).toContain("unknown");
-    expect(keys).toHaveLength(6);
+    expect(keys).toContain("awaiting_permission");
+    expect(keys).toContain("rate_limited");
+    expect(keys).toContain("compacting");
+    expect(keys).toHaveLength(9);
   });
 
   it("each entry has a label and className", () => {
@@ -293,59 +219,6 @@ describe("issueToSlug", () => {
   });
 });
 
-describe("formatCommentsAsPrompt", () => {
-  it("formats a single comment", () => {
-    const comments: DiffComment[] = [
-      {
-        id: 1,
-        filePath: "src/foo.ts",
-        hunkIndex: 0,
-        startLine: 1,
-        endLine: 2,
-        text: "Please fix this",
-        lines: [
-          { type: "addition", content: "+new line" },
-          { type: "deletion", content: "-old line" },
-        ],
-      },
-    ];
-    const result = formatCommentsAsPrompt(comments);
-    expect(result).toContain("review comments");
-    expect(result).toContain("## Comment 1: src/foo.ts");
-    expect(result).toContain("+new line");
-    expect(result).toContain("-old line");
-    expect(result).toContain("Please fix this");
-  });
-
-  it("formats multiple comments", () => {
-    const comments: DiffComment[] = [
-      {
-        id: 1,
-        filePath: "a.ts",
-        hunkIndex: 0,
-        startLine: 1,
-        endLine: 1,
-        text: "Comment A",
-        lines: [{ type: "context", content: " line" }],
-      },
-      {
-        id: 2,
-        filePath: "b.ts",
-        hunkIndex: 0,
-        startLine: 1,
-        endLine: 1,
-        text: "Comment B",
-        lines: [{ type: "addition", content: "+added" }],
-      },
-    ];
-    const result = formatCommentsAsPrompt(comments);
-    expect(result).toContain("## Comment 1: a.ts");
-    expect(result).toContain("## Comment 2: b.ts");
-    expect(result).toContain("Comment A");
-    expect(result).toContain("Comment B");
-  });
-});
-
 describe("storageGet", () => {
   it("returns value from localStorage", () => {
     const original = globalThis.localStorage;
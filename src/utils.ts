@@ -1,8 +1,11 @@
 import type React from "react";
-import type { AttentionStatus } from "./types";
+import type { AttentionStatus, Session } from "./types";
 
 export const ATTENTION_CONFIG: Record<AttentionStatus, { label: string; className: string }> = {
   needs_input: { label: "Needs Input", className: "status-needs-input" },
+  awaiting_permission: { label: "Needs Permission", className: "status-needs-input" },
+  rate_limited: { label: "Rate Limited", className: "status-error" },
+  compacting: { label: "Compacting", className: "status-running" },
   error: { label: "Error", className: "status-error" },
   running: { label: "Running", className: "status-running" },
   idle: { label: "Idle", className: "status-idle" },
@@ -51,6 +54,11 @@ export function formatTime(epoch: number): string {
   return `${diffDays}d ago`;
 }
 
+/** Check if a session belongs to a remote backend (no agent-deck row: no project path or tmux). */
+export function isRemoteSession(session: Session): boolean {
+  return !session.project_path && !session.tmux_session;
+}
+
 /** Check if a session is on the main/master branch (or has no worktree branch). */
 export function isMainSession(worktreeBranch?: string): boolean {
   return !worktreeBranch || worktreeBranch === "main" || worktreeBranch === "master";
@@ -74,79 +82,6 @@ export function issueToSlug(number: number, title: string): string {
   return `${number}-${slug}`;
 }
 
-export interface DiffLine {
-  type: "addition" | "deletion" | "context";
-  content: string;
-}
-
-export interface DiffHunk {
-  header: string;
-  lines: DiffLine[];
-}
-
-export interface DiffComment {
-  id: number;
-  filePath: string;
-  hunkIndex: number;
-  startLine: number;
-  endLine: number;
-  text: string;
-  /** The actual diff lines covered by this comment */
-  lines: DiffLine[];
-}
-
-export function formatCommentsAsPrompt(comments: DiffComment[]): string {
-  const parts = ["I have review comments on the current diff. Please address each one:"];
-  comments.forEach((c, i) => {
-    const diffBlock = c.lines.map((l) => l.content).join("\n");
-    parts.push(`\n## Comment ${i + 1}: ${c.filePath}\n\`\`\`diff\n${diffBlock}\n\`\`\`\n${c.text}`);
-  });
-  return parts.join("\n");
-}
-
-export interface DiffFile {
-  path: string;
-  hunks: DiffHunk[];
-  additions: number;
-  deletions: number;
-}
-
-export function parseDiff(raw: string): DiffFile[] {
-  const files: DiffFile[] = [];
-  let currentFile: DiffFile | null = null;
-  let currentHunk: DiffHunk | null = null;
-
-  for (const line of raw.split("\n")) {
-    if (line.startsWith("diff --git")) {
-      // Extract path from "diff --git a/path b/path"
-      const match = line.match(/^diff --git a\/.+ b\/(.+)$/);
-      currentFile = {
-        path: match?.[1] ?? line,
-        hunks: [],
-        additions: 0,
-        deletions: 0,
-      };
-      currentHunk = null;
-      files.push(currentFile);
-    } else if (line.startsWith("@@") && currentFile) {
-      currentHunk = { header: line, lines: [] };
-      currentFile.hunks.push(currentHunk);
-    } else if (currentHunk) {
-      if (line.startsWith("+")) {
-        currentHunk.lines.push({ type: "addition", content: line });
-        if (currentFile) currentFile.additions++;
-      } else if (line.startsWith("-")) {
-        currentHunk.lines.push({ type: "deletion", content: line });
-        if (currentFile) currentFile.deletions++;
-      } else {
-        currentHunk.lines.push({ type: "context", content: line });
-      }
-    }
-  }
-
-  return files;
-}
-
 export function fileName(path: string): string {
   const i = path.lastIndexOf("/");
   return i === -1 ? path : path.slice(i + 1);